@@ -1,2 +1,4 @@
-pub mod cal;
+pub use tlc_core as cal;
+pub use tlc_core::awsl;
+
 pub mod view;
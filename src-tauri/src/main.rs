@@ -8,6 +8,17 @@ use std::sync::mpsc;
 use tlc::view::{handle::init, request::Request};
 
 fn main() {
+    // 日志写入缓存目录下的tlc.log，找不到缓存目录时不记录
+    let _guard = tlc::cal::paths::cache_dir("").ok().map(|cache_dir| {
+        let (writer, guard) =
+            tracing_appender::non_blocking(tracing_appender::rolling::never(cache_dir, "tlc.log"));
+        tracing_subscriber::fmt()
+            .with_writer(writer)
+            .with_ansi(false)
+            .init();
+        guard
+    });
+
     let (tx, rx) = mpsc::sync_channel(3);
     init(rx);
 
@@ -4,19 +4,22 @@ use std::{collections::HashMap, sync::mpsc::Receiver};
 use ndarray::ArrayView2;
 use tauri::WebviewMut;
 
-use super::request::{Request, Value};
+use super::job::JobManager;
+use super::request::{Callback, Request, Value};
 use crate::awsl;
-use crate::cal::{error::TLCResult, *};
+use crate::cal::{error::TLCResult, preprocess::TCStatus, *};
 
 macro_rules! register {
     (@$hm:expr, $($f:expr),* $(,)*) => {
         $($hm.insert(
             snake_to_camel(stringify!($f)),
-            &$f as &(dyn Fn(&mut TLCData, Request) -> TLCResult<String>)
+            &$f as Handler
         );)*
     };
 }
 
+pub type Handler = &'static (dyn Fn(&mut TLCData, Request) -> TLCResult<Callback> + Sync);
+
 fn snake_to_camel(snake: &str) -> String {
     let mut flag = false;
     let mut arr = Vec::with_capacity(snake.len());
@@ -41,52 +44,192 @@ pub fn init(rx: Receiver<(WebviewMut, Request)>) {
             load_default_config,
             load_config,
             save_config,
+            get_config_schema,
+            get_pipeline,
             set_save_dir,
+            set_output_layout,
+            set_tags,
+            query_catalog,
             set_video_path,
             set_daq_path,
+            set_daq_sheet,
+            set_daq_sample_rate,
+            set_daq_resample,
+            set_daq_time_column,
             set_start_frame,
             set_start_row,
             set_peak_temp,
             set_solid_thermal_conductivity,
             set_solid_thermal_diffusivity,
+            set_solid_thermal_conductivity_curve,
+            set_solid_thermal_diffusivity_curve,
+            set_model_thickness,
+            check_penetration,
+            get_h_traces,
+            export_h_traces,
             set_air_thermal_conductivity,
             set_characteristic_length,
             set_regulator,
+            set_nu_correlation,
+            save_nu_ratio,
+            save_profile,
+            save_radial_profile,
+            probe_line,
+            save_region_stats,
+            get_nu_histogram,
+            set_colormap,
+            set_nu_smoothing,
+            set_pixel_size,
+            set_export_options,
+            set_plot_options,
+            set_post_scripts,
+            save_nu_figure,
+            save_peak_time_map,
+            save_amplitude_map,
+            get_setup_overlay,
+            calibrate_regulator,
             set_filter_method,
+            set_drift_correction,
+            set_reference_patch,
+            set_defect_frames,
+            set_peak_method,
+            set_events,
+            get_heating_events,
+            solve_events,
+            solve_binned,
+            quick_look,
+            set_threads,
+            set_memory_budget,
+            get_memory_usage,
+            get_defect_pixels,
+            set_daq_filter,
             set_interp_method,
+            infer_interp_method,
+            refine_thermocouples,
+            set_lazy_interp,
+            set_extrapolation_limit,
+            get_extrapolation,
+            set_filter_in_place,
             set_iteration_method,
+            set_coarse_stride,
+            set_t0_estimation,
+            set_mask,
+            get_mask_ratio,
             set_region,
             set_thermocouples,
             get_frame,
             get_daq,
+            get_daq_channels,
+            get_daq_sheet_names,
             synchronize,
             get_interp_single_frame,
+            get_tc_status,
+            validate_interp,
+            export_interp,
+            export_arrays,
+            export_hdf5,
+            export_vtk,
+            export_filtered_video,
+            export_xlsx,
+            export_bundle,
+            save_report,
+            run_post_scripts,
+            verify_reproducibility,
+            run_batch,
+            stitch_views,
+            sweep,
+            convergence,
+            plan,
+            save_state,
+            load_state,
+            validate_config,
+            save_preset,
+            list_presets,
+            load_preset,
+            relocate,
+            history,
+            undo,
+            get_timings,
+            get_quality_summary,
+            set_lang,
+            verify_inputs,
+            start_daq_stream,
+            stop_daq_stream,
+            poll_daq_stream,
             try_drop_video,
             get_green_history,
+            compare_filters,
+            tune_filter,
             get_point_nu,
+            get_point_history,
+            get_frame_times,
             set_color_range,
         );
 
-        let mut tlc_data = TLCData::new();
+        let mut jobs = JobManager::new(TLCData::new());
 
         loop {
             let (mut wm, req) = rx.recv().unwrap();
-            let f = hm[&req.cmd];
-            let callback_string = match tlc_data.as_mut() {
-                Ok(tlc_data) => f(tlc_data, req),
-                Err(err) => {
-                    let res: Result<(), String> = Err(err.to_string());
+            // 任务列表的查询与取消不涉及TLCData，不必排队
+            let callback = match (req.cmd.as_str(), &req.body) {
+                ("listJobs", _) => {
+                    let res: Result<_, String> = Ok(jobs.list());
+                    Request::format_callback(res, req.callback, req.error)
+                }
+                ("watchInputs", &Value::Bool(auto_rerun)) => {
+                    jobs.watch(Some(auto_rerun), wm, req);
+                    continue;
+                }
+                ("watchInputs", _) => {
+                    let res: Result<(), _> = Err(awsl!(req.body));
+                    Request::format_callback(res, req.callback, req.error)
+                }
+                ("unwatchInputs", _) => {
+                    jobs.watch(None, wm, req);
+                    continue;
+                }
+                ("cancelJob", &Value::Uint(id)) => {
+                    let res = jobs.cancel(id, &mut wm);
+                    Request::format_callback(res, req.callback, req.error)
+                }
+                ("cancelJob", _) => {
+                    let res: Result<(), _> = Err(awsl!(req.body));
                     Request::format_callback(res, req.callback, req.error)
                 }
+                (cmd, _) => match hm.get(cmd) {
+                    Some(&f) => {
+                        jobs.submit(f, wm, req);
+                        continue;
+                    }
+                    None => {
+                        let res: Result<(), _> =
+                            Err(awsl!(HandleError, format!("未知的请求{}", cmd)));
+                        Request::format_callback(res, req.callback, req.error)
+                    }
+                },
             };
-            if let Ok(callback_string) = callback_string {
-                let _ = wm.dispatch(move |w| w.eval(callback_string.as_str()));
+            if let Ok(callback) = callback {
+                eval(&mut wm, callback.script);
             }
         }
     });
 }
 
-fn load_default_config(data: &mut TLCData, req: Request) -> TLCResult<String> {
+pub fn call(f: Handler, tlc_data: &mut TLCResult<TLCData>, req: Request) -> TLCResult<Callback> {
+    match tlc_data.as_mut() {
+        Ok(tlc_data) => f(tlc_data, req),
+        Err(err) => {
+            let res: Result<(), _> = Err(err.report());
+            Request::format_callback(res, req.callback, req.error)
+        }
+    }
+}
+
+pub fn eval(wm: &mut WebviewMut, script: String) {
+    let _ = wm.dispatch(move |w| w.eval(script.as_str()));
+}
+
+fn load_default_config(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
     let res = TLCData::new().map(|new_data| {
         *data = new_data;
         data.get_config()
@@ -95,7 +238,7 @@ fn load_default_config(data: &mut TLCData, req: Request) -> TLCResult<String> {
     Request::format_callback(res, req.callback, req.error)
 }
 
-fn load_config(data: &mut TLCData, req: Request) -> TLCResult<String> {
+fn load_config(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
     let res = match req.body {
         Value::String(config_path) => TLCData::from_path(config_path).map(|new_data| {
             *data = new_data;
@@ -107,7 +250,7 @@ fn load_config(data: &mut TLCData, req: Request) -> TLCResult<String> {
     Request::format_callback(res, req.callback, req.error)
 }
 
-fn save_config(data: &mut TLCData, req: Request) -> TLCResult<String> {
+fn save_config(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
     Request::format_callback(
         data.save_config().map(|data| data.get_config()),
         req.callback,
@@ -115,7 +258,19 @@ fn save_config(data: &mut TLCData, req: Request) -> TLCResult<String> {
     )
 }
 
-fn set_save_dir(data: &mut TLCData, req: Request) -> TLCResult<String> {
+fn get_config_schema(_: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    Request::format_callback(schema::config_schema(), req.callback, req.error)
+}
+
+fn get_pipeline(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    Request::format_callback(
+        TLCResult::Ok(data.pipeline().status()),
+        req.callback,
+        req.error,
+    )
+}
+
+fn set_save_dir(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
     let res = match req.body {
         Value::String(save_dir) => data.set_save_dir(save_dir).map(|data| data.get_config()),
         _ => Err(awsl!(req.body)),
@@ -124,7 +279,36 @@ fn set_save_dir(data: &mut TLCData, req: Request) -> TLCResult<String> {
     Request::format_callback(res, req.callback, req.error)
 }
 
-fn set_video_path(data: &mut TLCData, req: Request) -> TLCResult<String> {
+fn set_output_layout(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Layout(output_layout) => data
+            .set_output_layout(output_layout)
+            .map(|data| data.get_config()),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn set_tags(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Tags(tags) => Ok(data.set_tags(tags).get_config()),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn query_catalog(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Catalog(query) => data.query_catalog(&query),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn set_video_path(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
     let res = match req.body {
         Value::String(video_path) => data
             .set_video_path(video_path)
@@ -135,7 +319,7 @@ fn set_video_path(data: &mut TLCData, req: Request) -> TLCResult<String> {
     Request::format_callback(res, req.callback, req.error)
 }
 
-fn set_daq_path(data: &mut TLCData, req: Request) -> TLCResult<String> {
+fn set_daq_path(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
     let res = match req.body {
         Value::String(daq_path) => data.set_daq_path(daq_path).map(|data| data.get_config()),
         _ => Err(awsl!(req.body)),
@@ -144,7 +328,45 @@ fn set_daq_path(data: &mut TLCData, req: Request) -> TLCResult<String> {
     Request::format_callback(res, req.callback, req.error)
 }
 
-fn set_start_frame(data: &mut TLCData, req: Request) -> TLCResult<String> {
+fn set_daq_sheet(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::DAQSheet(daq_sheet, daq_cell_offset) => data
+            .set_daq_sheet(daq_sheet, daq_cell_offset)
+            .map(|data| data.get_config()),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn set_daq_sample_rate(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Float(daq_sample_rate) => Ok(data.set_daq_sample_rate(daq_sample_rate).get_config()),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn set_daq_resample(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Resample(daq_resample) => Ok(data.set_daq_resample(daq_resample).get_config()),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn set_daq_time_column(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::OptionUint(column) => Ok(data.set_daq_time_column(column).get_config()),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn set_start_frame(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
     let res = match req.body {
         Value::Uint(start_frame) => data
             .set_start_frame(start_frame)
@@ -155,7 +377,7 @@ fn set_start_frame(data: &mut TLCData, req: Request) -> TLCResult<String> {
     Request::format_callback(res, req.callback, req.error)
 }
 
-fn set_start_row(data: &mut TLCData, req: Request) -> TLCResult<String> {
+fn set_start_row(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
     let res = match req.body {
         Value::Uint(start_row) => data.set_start_row(start_row).map(|data| data.get_config()),
         _ => Err(awsl!(req.body)),
@@ -164,7 +386,7 @@ fn set_start_row(data: &mut TLCData, req: Request) -> TLCResult<String> {
     Request::format_callback(res, req.callback, req.error)
 }
 
-fn set_peak_temp(data: &mut TLCData, req: Request) -> TLCResult<String> {
+fn set_peak_temp(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
     let res = match req.body {
         Value::Float(peak_temp) => Ok(data.set_peak_temp(peak_temp).get_config()),
         _ => Err(awsl!(req.body)),
@@ -173,7 +395,7 @@ fn set_peak_temp(data: &mut TLCData, req: Request) -> TLCResult<String> {
     Request::format_callback(res, req.callback, req.error)
 }
 
-fn set_solid_thermal_conductivity(data: &mut TLCData, req: Request) -> TLCResult<String> {
+fn set_solid_thermal_conductivity(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
     let res = match req.body {
         Value::Float(solid_thermal_conductivity) => Ok(data
             .set_solid_thermal_conductivity(solid_thermal_conductivity)
@@ -184,7 +406,7 @@ fn set_solid_thermal_conductivity(data: &mut TLCData, req: Request) -> TLCResult
     Request::format_callback(res, req.callback, req.error)
 }
 
-fn set_solid_thermal_diffusivity(data: &mut TLCData, req: Request) -> TLCResult<String> {
+fn set_solid_thermal_diffusivity(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
     let res = match req.body {
         Value::Float(solid_thermal_diffusivity) => Ok(data
             .set_solid_thermal_diffusivity(solid_thermal_diffusivity)
@@ -195,7 +417,68 @@ fn set_solid_thermal_diffusivity(data: &mut TLCData, req: Request) -> TLCResult<
     Request::format_callback(res, req.callback, req.error)
 }
 
-fn set_air_thermal_conductivity(data: &mut TLCData, req: Request) -> TLCResult<String> {
+fn set_solid_thermal_conductivity_curve(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::PropertyCurve(curve) => Ok(data
+            .set_solid_thermal_conductivity_curve(curve)
+            .get_config()),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn set_solid_thermal_diffusivity_curve(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::PropertyCurve(curve) => {
+            Ok(data.set_solid_thermal_diffusivity_curve(curve).get_config())
+        }
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn set_model_thickness(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Float(model_thickness) => Ok(data.set_model_thickness(model_thickness).get_config()),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn check_penetration(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Nothing => data.check_penetration(),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn get_h_traces(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::HTraces(targets, options) => targets
+            .into_iter()
+            .map(|target| data.reconstruct_h_trace(target, options))
+            .collect::<TLCResult<Vec<_>>>(),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn export_h_traces(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::HTraces(targets, options) => data.export_h_traces(targets, options),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn set_air_thermal_conductivity(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
     let res = match req.body {
         Value::Float(air_thermal_conductivity) => Ok(data
             .set_air_thermal_conductivity(air_thermal_conductivity)
@@ -206,7 +489,7 @@ fn set_air_thermal_conductivity(data: &mut TLCData, req: Request) -> TLCResult<S
     Request::format_callback(res, req.callback, req.error)
 }
 
-fn set_characteristic_length(data: &mut TLCData, req: Request) -> TLCResult<String> {
+fn set_characteristic_length(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
     let res = match req.body {
         Value::Float(characteristic_length) => Ok(data
             .set_characteristic_length(characteristic_length)
@@ -217,7 +500,7 @@ fn set_characteristic_length(data: &mut TLCData, req: Request) -> TLCResult<Stri
     Request::format_callback(res, req.callback, req.error)
 }
 
-fn set_regulator(data: &mut TLCData, req: Request) -> TLCResult<String> {
+fn set_regulator(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
     let res = match req.body {
         Value::FloatVec(regulator) => Ok(data.set_regulator(regulator).get_config()),
         _ => Err(awsl!(req.body)),
@@ -226,59 +509,51 @@ fn set_regulator(data: &mut TLCData, req: Request) -> TLCResult<String> {
     Request::format_callback(res, req.callback, req.error)
 }
 
-fn set_filter_method(data: &mut TLCData, req: Request) -> TLCResult<String> {
+fn calibrate_regulator(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
     let res = match req.body {
-        Value::Filter(filter_method) => Ok(data.set_filter_method(filter_method).get_config()),
+        Value::Uint(frame) => data
+            .calibrate_regulator(frame)
+            .map(|data| data.get_config()),
         _ => Err(awsl!(req.body)),
     };
 
     Request::format_callback(res, req.callback, req.error)
 }
 
-fn set_interp_method(data: &mut TLCData, req: Request) -> TLCResult<String> {
+fn set_nu_correlation(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
     let res = match req.body {
-        Value::Interp(interp_method) => data
-            .set_interp_method(interp_method)
-            .map(|data| data.get_config()),
+        Value::Correlation(nu_correlation, reynolds, prandtl) => Ok(data
+            .set_nu_correlation(nu_correlation, reynolds, prandtl)
+            .get_config()),
         _ => Err(awsl!(req.body)),
     };
 
     Request::format_callback(res, req.callback, req.error)
 }
 
-fn set_iteration_method(data: &mut TLCData, req: Request) -> TLCResult<String> {
-    fn f(data: &mut TLCData, body: Value) -> TLCResult<(String, f32)> {
-        match body {
-            Value::Iteration(iteration_method) => {
-                data.set_iteration_method(iteration_method)
-                    .solve()?
-                    .save_nu()?;
-                let nu2d_string = data.get_nu_img(None)?;
-                let nu_nan_mean = data.get_nu_nan_mean()?;
-                Ok((nu2d_string, nu_nan_mean))
-            }
-            _ => Err(awsl!(body)),
-        }
+/// 保存Nu/Nu0并返回其平均值
+fn save_nu_ratio(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    fn f(data: &mut TLCData) -> TLCResult<f32> {
+        let ratio = data.save_nu_ratio()?.get_nu_ratio()?;
+        Ok(postprocess::cal_nan_mean(ratio.view()))
     }
 
-    Request::format_callback(f(data, req.body), req.callback, req.error)
+    Request::format_callback(f(data), req.callback, req.error)
 }
 
-fn set_region(data: &mut TLCData, req: Request) -> TLCResult<String> {
+fn save_profile(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
     let res = match req.body {
-        Value::UintVec(region) if region.len() == 4 => Ok(data
-            .set_region((region[0], region[1]), (region[2], region[3]))
-            .get_config()),
+        Value::Uint(axis) => data.save_profile(axis),
         _ => Err(awsl!(req.body)),
     };
 
     Request::format_callback(res, req.callback, req.error)
 }
 
-fn set_thermocouples(data: &mut TLCData, req: Request) -> TLCResult<String> {
+fn save_radial_profile(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
     let res = match req.body {
-        Value::Thermocouples(thermocouples) => {
-            Ok(data.set_thermocouples(thermocouples).get_config())
+        Value::Radial(center, diameter, bin_width) => {
+            data.save_radial_profile(center, diameter, bin_width)
         }
         _ => Err(awsl!(req.body)),
     };
@@ -286,66 +561,833 @@ fn set_thermocouples(data: &mut TLCData, req: Request) -> TLCResult<String> {
     Request::format_callback(res, req.callback, req.error)
 }
 
-fn get_frame(data: &mut TLCData, req: Request) -> TLCResult<String> {
+fn probe_line(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
     let res = match req.body {
-        Value::Uint(frame_index) => data.get_frame(frame_index),
+        Value::Probe(p0, p1, n) => data
+            .probe_line(p0, p1, n)
+            .map(|(probe, png)| (probe, base64::encode(png))),
         _ => Err(awsl!(req.body)),
     };
 
     Request::format_callback(res, req.callback, req.error)
 }
 
-fn get_daq(data: &mut TLCData, req: Request) -> TLCResult<String> {
-    fn f(data: &mut TLCData) -> TLCResult<ArrayView2<f32>> {
-        if data.get_daq().is_err() {
-            data.read_daq()?;
+fn save_region_stats(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Polygons(ref polygons) => data.save_region_stats(polygons),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn get_nu_histogram(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Histogram(bins, render) => data
+            .get_nu_histogram(bins, render)
+            .map(|(hist, png)| (hist, png.map(base64::encode))),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn set_colormap(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Colormap(colormap, reverse) => data
+            .set_colormap(colormap, reverse)
+            .map(|data| data.get_config()),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn set_pixel_size(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Float(pixel_size) => Ok(data.set_pixel_size(pixel_size).get_config()),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn set_export_options(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::ExportOptions(export_options) => {
+            Ok(data.set_export_options(export_options).get_config())
         }
-        data.get_daq()
-    }
+        _ => Err(awsl!(req.body)),
+    };
 
-    Request::format_callback(f(data), req.callback, req.error)
+    Request::format_callback(res, req.callback, req.error)
 }
 
-fn synchronize(data: &mut TLCData, req: Request) -> TLCResult<String> {
+fn set_plot_options(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
     let res = match req.body {
-        Value::UintVec(arr) => {
-            let (frame_index, row_index) = (arr[0], arr[1]);
-            Ok(data.synchronize(frame_index, row_index).get_config())
+        Value::PlotOptions(plot_options) => Ok(data.set_plot_options(plot_options).get_config()),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn set_post_scripts(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::PostScripts(post_scripts) => data
+            .set_post_scripts(post_scripts)
+            .map(|data| data.get_config()),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn save_nu_figure(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::FloatVec(range) if range.len() == 2 => {
+            data.save_nu_figure(Some((range[0], range[1])))
         }
+        Value::Nothing => data.save_nu_figure(None),
         _ => Err(awsl!(req.body)),
     };
 
     Request::format_callback(res, req.callback, req.error)
 }
 
-fn get_interp_single_frame(data: &mut TLCData, req: Request) -> TLCResult<String> {
+fn save_peak_time_map(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
     let res = match req.body {
-        Value::Uint(frame_index) => data.interp_single_frame(frame_index),
+        Value::Nothing => data.save_peak_time_map(),
         _ => Err(awsl!(req.body)),
     };
 
     Request::format_callback(res, req.callback, req.error)
 }
 
-/// 如果当前Green矩阵已存在，则说明不需要重新解码视频，可以将视频缓存数据包和解码相关内存析构
-fn try_drop_video(data: &mut TLCData, req: Request) -> TLCResult<String> {
-    if let Ok(_) = data.get_raw_g2d() {
-        data.drop_video();
-    }
+fn save_amplitude_map(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Nothing => data.save_amplitude_map(),
+        _ => Err(awsl!(req.body)),
+    };
 
-    Request::format_callback(TLCResult::Ok(()), req.callback, req.error)
+    Request::format_callback(res, req.callback, req.error)
 }
 
-fn get_green_history(data: &mut TLCData, req: Request) -> TLCResult<String> {
+fn get_setup_overlay(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
     let res = match req.body {
-        Value::Uint(pos) => data.filtering_single_point(pos),
+        Value::Overlay(frame_index, contour_levels) => data
+            .get_setup_overlay(frame_index, &contour_levels)
+            .map(base64::encode),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn set_nu_smoothing(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Smoothing(nu_smoothing) => data
+            .set_nu_smoothing(nu_smoothing)
+            .map(|data| data.get_config()),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn export_arrays(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::ExportArrays(format) => data.export_arrays(format),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn export_hdf5(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Bool(with_green) => data.export_hdf5(with_green),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn export_vtk(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Bool(with_series) => data.export_vtk(with_series),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn export_filtered_video(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Nothing => data.export_filtered_video(),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn export_xlsx(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Polygons(polygons) => data.export_xlsx(&polygons),
+        Value::Nothing => data.export_xlsx(&[]),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn export_bundle(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Nothing => data.export_bundle(),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn save_report(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Nothing => data.save_report(),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn run_post_scripts(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Nothing => data.run_post_scripts(),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn verify_reproducibility(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::String(manifest_path) => reproduce::ReproManifest::from_path(manifest_path)
+            .and_then(|manifest| data.verify_reproducibility(&manifest)),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn run_batch(_: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::String(manifest_path) => {
+            batch::TLCBatch::from_path(manifest_path).and_then(|batch| batch.run())
+        }
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn stitch_views(_: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::String(manifest_path) => {
+            multiview::TLCMultiView::from_path(manifest_path).and_then(|views| views.run())
+        }
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn sweep(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Sweep(param, values) => data.sweep(param, &values),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn convergence(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Convergence(fractions, points) => data.convergence(&fractions, &points),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn plan(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Overrides(overrides) => data
+            .get_config()
+            .with_overrides(overrides)
+            .map(|config| data.plan(&config)),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn save_state(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Nothing => data.save_state(),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn load_state(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Nothing => data.load_state(),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn validate_config(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Nothing => Ok(data.validate()),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn save_preset(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::String(name) => data.save_preset(&name),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn list_presets(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Nothing => data.list_presets(),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn load_preset(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::String(name) => data.new_from_preset(&name).map(|new_data| {
+            *data = new_data;
+            data.get_config()
+        }),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn relocate(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::String(media_dir) => data.relocate(media_dir).map(|data| data.get_config()),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn history(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Nothing => Ok(data.history()),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn undo(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Nothing => data.undo().map(|data| data.get_config()),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn get_timings(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Nothing => Ok(data.get_timings()),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn get_quality_summary(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Nothing => data.quality_summary(),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn set_lang(_: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Lang(lang) => {
+            error::set_lang(lang);
+            Ok(())
+        }
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn verify_inputs(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Nothing => Ok(data.verify_inputs()),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn start_daq_stream(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::DaqSource(source) => data.start_daq_stream(source).map(|data| data.get_config()),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn stop_daq_stream(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Nothing => Ok(data.stop_daq_stream().get_config()),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn poll_daq_stream(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Nothing => data.poll_daq_stream(),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn set_filter_method(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Filter(filter_method) => Ok(data.set_filter_method(filter_method).get_config()),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn set_drift_correction(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Drift(drift_correction) => {
+            Ok(data.set_drift_correction(drift_correction).get_config())
+        }
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn set_reference_patch(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::ReferencePatch(patch) => Ok(data.set_reference_patch(patch).get_config()),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn set_defect_frames(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Uint(defect_frames) => Ok(data.set_defect_frames(defect_frames).get_config()),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn get_defect_pixels(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Nothing => data.get_defect_pixels(),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn set_peak_method(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Peak(peak_method) => Ok(data.set_peak_method(peak_method).get_config()),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn set_events(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Events(events) => Ok(data.set_events(events).get_config()),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn get_heating_events(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Nothing => data.heating_events(),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn solve_events(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Nothing => data.solve_events(),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn solve_binned(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Binned(bin, upsample) => data.solve_binned(bin, upsample),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn quick_look(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::QuickLook(options) => data.quick_look(options),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn set_threads(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Threads(threads) => Ok(data.set_threads(threads).get_config()),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn set_memory_budget(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Uint(memory_budget) => Ok(data.set_memory_budget(memory_budget).get_config()),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn get_memory_usage(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Nothing => Ok(data.get_memory_usage()),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn set_daq_filter(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::DAQFilter(daq_filter) => Ok(data.set_daq_filter(daq_filter).get_config()),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn set_interp_method(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Interp(interp_method) => data
+            .set_interp_method(interp_method)
+            .map(|data| data.get_config()),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn refine_thermocouples(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Refine(radius, apply) => data.refine_thermocouples(radius, apply),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn set_extrapolation_limit(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Float(limit) => Ok(data.set_extrapolation_limit(limit).get_config()),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn get_extrapolation(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Nothing => data.extrapolation(),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn set_lazy_interp(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Bool(lazy_interp) => Ok(data.set_lazy_interp(lazy_interp).get_config()),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn set_filter_in_place(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Bool(filter_in_place) => Ok(data.set_filter_in_place(filter_in_place).get_config()),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn infer_interp_method(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Nothing => Ok(data.infer_interp_method()),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn set_iteration_method(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    fn f(data: &mut TLCData, body: Value) -> TLCResult<(String, f32)> {
+        match body {
+            Value::Iteration(iteration_method) => {
+                data.set_iteration_method(iteration_method)
+                    .solve()?
+                    .save_nu()?;
+                let nu2d_string = base64::encode(data.get_nu_img(None)?);
+                let nu_nan_mean = data.get_nu_nan_mean()?;
+                Ok((nu2d_string, nu_nan_mean))
+            }
+            _ => Err(awsl!(body)),
+        }
+    }
+
+    Request::format_callback(f(data, req.body), req.callback, req.error)
+}
+
+fn set_t0_estimation(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::T0(t0_estimation) => Ok(data.set_t0_estimation(t0_estimation).get_config()),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn set_coarse_stride(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Uint(coarse_stride) => Ok(data.set_coarse_stride(coarse_stride).get_config()),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn set_mask(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Mask(mask) => data.set_mask(mask).map(|data| data.get_config()),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn get_mask_ratio(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Nothing => data.get_mask_ratio(),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn set_region(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::UintVec(region) if region.len() == 4 => Ok(data
+            .set_region((region[0], region[1]), (region[2], region[3]))
+            .get_config()),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn set_thermocouples(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Thermocouples(thermocouples) => {
+            Ok(data.set_thermocouples(thermocouples).get_config())
+        }
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn get_frame(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Uint(frame_index) => data.get_frame(frame_index).map(base64::encode),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn get_daq(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    fn f(data: &mut TLCData) -> TLCResult<ArrayView2<f32>> {
+        if data.get_daq().is_err() {
+            data.read_daq()?;
+        }
+        data.get_daq()
+    }
+
+    Request::format_callback(f(data), req.callback, req.error)
+}
+
+fn get_daq_channels(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    Request::format_callback(data.get_config().daq_channels(), req.callback, req.error)
+}
+
+fn get_daq_sheet_names(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    Request::format_callback(data.get_config().daq_sheet_names(), req.callback, req.error)
+}
+
+fn synchronize(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::UintVec(arr) => {
+            let (frame_index, row_index) = (arr[0], arr[1]);
+            Ok(data.synchronize(frame_index, row_index).get_config())
+        }
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn get_interp_single_frame(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Uint(frame_index) => data.interp_single_frame(frame_index),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn get_tc_status(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    fn f(data: &mut TLCData) -> TLCResult<&Vec<TCStatus>> {
+        if data.get_tc_status().is_err() {
+            data.init_t2d()?;
+        }
+        data.get_tc_status()
+    }
+
+    Request::format_callback(f(data), req.callback, req.error)
+}
+
+fn validate_interp(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    Request::format_callback(data.validate_interp(), req.callback, req.error)
+}
+
+fn export_interp(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::ExportInterp(frames, format) => data.export_interp(frames, format),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+/// 如果当前Green矩阵已存在，则说明不需要重新解码视频，可以将视频缓存数据包和解码相关内存析构
+fn try_drop_video(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    if let Ok(_) = data.get_raw_g2d() {
+        data.drop_video();
+    }
+
+    Request::format_callback(TLCResult::Ok(()), req.callback, req.error)
+}
+
+fn get_green_history(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Uint(pos) => data.filtering_single_point(pos),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn compare_filters(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::CompareFilters(pos, filter_methods) => data.compare_filters(pos, &filter_methods),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn tune_filter(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Filters(candidates) => data.tune_filter(&candidates),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn get_point_history(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Uint(pos) => data.get_point_history(pos),
+        _ => Err(awsl!(req.body)),
+    };
+
+    Request::format_callback(res, req.callback, req.error)
+}
+
+fn get_frame_times(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
+    let res = match req.body {
+        Value::Nothing => data
+            .init_frame_times()
+            .and_then(|data| data.get_frame_times().cloned()),
         _ => Err(awsl!(req.body)),
     };
 
     Request::format_callback(res, req.callback, req.error)
 }
 
-fn get_point_nu(data: &mut TLCData, req: Request) -> TLCResult<String> {
+fn get_point_nu(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
     let res = match req.body {
         Value::UintVec(pos) => data.get_nu2d().map(|nu2d| nu2d.row(pos[0])[pos[1]]),
         _ => Err(awsl!(req.body)),
@@ -354,9 +1396,11 @@ fn get_point_nu(data: &mut TLCData, req: Request) -> TLCResult<String> {
     Request::format_callback(res, req.callback, req.error)
 }
 
-fn set_color_range(data: &mut TLCData, req: Request) -> TLCResult<String> {
+fn set_color_range(data: &mut TLCData, req: Request) -> TLCResult<Callback> {
     let res = match req.body {
-        Value::FloatVec(range) => data.get_nu_img(Some((range[0], range[1]))),
+        Value::FloatVec(range) => data
+            .get_nu_img(Some((range[0], range[1])))
+            .map(base64::encode),
         _ => Err(awsl!(req.body)),
     };
 
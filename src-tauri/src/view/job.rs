@@ -0,0 +1,357 @@
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use tauri::WebviewMut;
+
+use super::handle::{call, eval, Handler};
+use super::request::{Callback, Request};
+use crate::awsl;
+use crate::cal::error::{ErrorReport, TLCResult};
+use crate::cal::{notify::StateEvent, watch::InputWatcher, Stage, TLCData};
+
+/// 后台任务提交后立即返回给前端的句柄
+#[derive(Debug, Serialize)]
+pub struct JobHandle {
+    pub id: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum JobState {
+    Queued,
+    Running,
+    Done,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct JobInfo {
+    pub id: usize,
+    pub cmd: String,
+    pub state: JobState,
+    /// 提交时间，unix毫秒
+    pub submitted_at: u128,
+    /// 运行耗时（秒），尚未结束时为None
+    pub elapsed: Option<f32>,
+}
+
+/// 执行线程中的一个请求，后台任务带有编号
+struct Task {
+    job: Option<usize>,
+    f: Handler,
+    wm: WebviewMut,
+    req: Request,
+}
+
+/// 发给执行线程的消息，监视相关的操作需要执行线程自己的发送端，不经过`Handler`
+enum Msg {
+    Task(Task),
+    /// `auto_rerun`为None时停止监视
+    Watch {
+        auto_rerun: Option<bool>,
+        wm: WebviewMut,
+        req: Request,
+    },
+    InputChanged {
+        input: Stage,
+        auto_rerun: bool,
+        wm: WebviewMut,
+    },
+}
+
+/// 输入文件更新后通过window上的"tlc-inputs-changed"事件通知前端：
+///
+/// `{ input, stages, error, rerun }`，`rerun`为自动重新求解的结果，未开启时为null
+#[derive(Serialize)]
+struct InputChangedEvent {
+    input: Stage,
+    /// 被清空、需要重新计算的阶段
+    stages: Vec<Stage>,
+    /// 重新读取文件信息失败（如文件正在被写入）
+    error: Option<ErrorReport>,
+    rerun: Option<Result<f32, ErrorReport>>,
+}
+
+/// 所有修改`TLCData`的请求都在同一个执行线程中按提交顺序执行，互不冲突
+///
+/// 分发线程只负责排队，后台任务立即返回`JobHandle`，前台请求在轮到时返回结果，
+/// 任务列表的查询与取消由分发线程直接处理，不需要等待正在运行的任务
+pub struct JobManager {
+    jobs: Arc<Mutex<Vec<JobInfo>>>,
+    tx: Sender<Msg>,
+    next_id: usize,
+}
+
+/// 定义任务完成时调用的两个回调，把结果转发为window上的"tlc-job"事件：
+///
+/// `{ id, cmd, ok: true, result }`或`{ id, cmd, ok: false, error }`
+fn job_callbacks(id: usize, cmd: &str) -> (String, String, String) {
+    let (ok_name, err_name) = callback_names(id);
+    let cmd = serde_json::to_string(cmd).unwrap_or_default();
+    let define = |name: &str, ok: bool, key: &str| {
+        format!(
+            "window[\"{name}\"]=function(v){{delete window[\"{ok_name}\"];delete window[\"{err_name}\"];\
+             window.dispatchEvent(new CustomEvent(\"tlc-job\",\
+             {{detail:{{id:{id},cmd:{cmd},ok:{ok},{key}:v}}}}))}};",
+            name = name,
+            ok_name = ok_name,
+            err_name = err_name,
+            id = id,
+            cmd = cmd,
+            ok = ok,
+            key = key,
+        )
+    };
+    let script = define(&ok_name, true, "result") + define(&err_name, false, "error").as_str();
+
+    (script, ok_name, err_name)
+}
+
+/// 清空受影响的阶段，按需重新求解，返回派发事件的脚本
+fn input_changed(data: &mut TLCData, input: Stage, auto_rerun: bool) -> String {
+    let (stages, error) = match data.input_changed(input) {
+        Ok(stages) => (stages, None),
+        Err(err) => (Vec::new(), Some(err.report())),
+    };
+    let rerun = if auto_rerun && error.is_none() {
+        let res = data
+            .solve()
+            .and_then(|data| data.get_nu_nan_mean())
+            .map_err(|err| err.report());
+        Some(res)
+    } else {
+        None
+    };
+    let event = InputChangedEvent {
+        input,
+        stages,
+        error,
+        rerun,
+    };
+    let detail = serde_json::to_string(&event).unwrap_or_default();
+
+    format!(
+        "window.dispatchEvent(new CustomEvent(\"tlc-inputs-changed\",{{detail:{}}}))",
+        detail
+    )
+}
+
+/// 将`TLCData`的状态变化转发为window上的"tlc-state"事件，`detail`为`StateEvent`，
+/// 发往最近一个请求所在的窗口
+fn forward_events(events: Receiver<StateEvent>, wm: Arc<Mutex<Option<WebviewMut>>>) {
+    thread::spawn(move || {
+        for event in events {
+            let detail = serde_json::to_string(&event).unwrap_or_default();
+            let script = format!(
+                "window.dispatchEvent(new CustomEvent(\"tlc-state\",{{detail:{}}}))",
+                detail
+            );
+            if let Some(mut wm) = wm.lock().ok().and_then(|wm| wm.clone()) {
+                eval(&mut wm, script);
+            }
+        }
+    });
+}
+
+fn callback_names(id: usize) -> (String, String) {
+    (format!("tlcJob{}Ok", id), format!("tlcJob{}Err", id))
+}
+
+fn now_millis() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis())
+}
+
+fn update(jobs: &Mutex<Vec<JobInfo>>, id: usize, f: impl FnOnce(&mut JobInfo)) {
+    if let Ok(mut jobs) = jobs.lock() {
+        if let Some(job) = jobs.iter_mut().find(|job| job.id == id) {
+            f(job);
+        }
+    }
+}
+
+impl JobManager {
+    pub fn new(mut tlc_data: TLCResult<TLCData>) -> Self {
+        let jobs = Arc::new(Mutex::new(Vec::new()));
+        let (tx, rx) = channel::<Msg>();
+
+        let jobs_ref = Arc::clone(&jobs);
+        let watch_tx = tx.clone();
+        let (event_tx, event_rx) = channel();
+        let event_wm = Arc::new(Mutex::new(None));
+        forward_events(event_rx, Arc::clone(&event_wm));
+        thread::spawn(move || {
+            let mut watcher: Option<InputWatcher> = None;
+            for msg in rx {
+                // 加载配置会替换整个TLCData，每次都重新设置接收端
+                if let Ok(data) = tlc_data.as_mut() {
+                    data.set_listener(event_tx.clone());
+                }
+                let Task {
+                    job,
+                    f,
+                    mut wm,
+                    req,
+                } = match msg {
+                    Msg::Task(task) => task,
+                    Msg::Watch {
+                        auto_rerun,
+                        mut wm,
+                        req,
+                    } => {
+                        let res = match (auto_rerun, tlc_data.as_ref()) {
+                            (None, _) => {
+                                watcher = None;
+                                Ok(())
+                            }
+                            (Some(auto_rerun), Ok(data)) => {
+                                let tx = watch_tx.clone();
+                                let event_wm = wm.clone();
+                                data.watch_inputs(move |input| {
+                                    let _ = tx.send(Msg::InputChanged {
+                                        input,
+                                        auto_rerun,
+                                        wm: event_wm.clone(),
+                                    });
+                                })
+                                .map(|w| watcher = Some(w))
+                                .map_err(|err| err.report())
+                            }
+                            (Some(_), Err(err)) => Err(err.report()),
+                        };
+                        if let Ok(callback) = Request::format_callback(res, req.callback, req.error)
+                        {
+                            eval(&mut wm, callback.script);
+                        }
+                        continue;
+                    }
+                    Msg::InputChanged {
+                        input,
+                        auto_rerun,
+                        mut wm,
+                    } => {
+                        if let Ok(data) = tlc_data.as_mut() {
+                            eval(&mut wm, input_changed(data, input, auto_rerun));
+                        }
+                        continue;
+                    }
+                };
+
+                let mut cancelled = false;
+                if let Some(id) = job {
+                    update(&jobs_ref, id, |job| match job.state {
+                        JobState::Queued => job.state = JobState::Running,
+                        _ => cancelled = true,
+                    });
+                }
+                if cancelled {
+                    continue;
+                }
+
+                if let Ok(mut event_wm) = event_wm.lock() {
+                    *event_wm = Some(wm.clone());
+                }
+                let t0 = Instant::now();
+                let cmd = req.cmd.clone();
+                let callback = call(f, &mut tlc_data, req);
+                let failed = !matches!(callback, Ok(Callback { ok: true, .. }));
+                if failed {
+                    let _ = event_tx.send(StateEvent::Failed { cmd });
+                }
+                if let Some(id) = job {
+                    update(&jobs_ref, id, |job| {
+                        job.state = if failed {
+                            JobState::Failed
+                        } else {
+                            JobState::Done
+                        };
+                        job.elapsed = Some(t0.elapsed().as_secs_f32());
+                    });
+                }
+                if let Ok(callback) = callback {
+                    eval(&mut wm, callback.script);
+                }
+            }
+        });
+
+        Self {
+            jobs,
+            tx,
+            next_id: 1,
+        }
+    }
+
+    /// 后台任务登记后立即以`JobHandle`答复，前台请求直接排队
+    pub fn submit(&mut self, f: Handler, mut wm: WebviewMut, mut req: Request) {
+        let job = if req.background {
+            let id = self.next_id;
+            self.next_id += 1;
+            if let Ok(mut jobs) = self.jobs.lock() {
+                jobs.push(JobInfo {
+                    id,
+                    cmd: req.cmd.clone(),
+                    state: JobState::Queued,
+                    submitted_at: now_millis(),
+                    elapsed: None,
+                });
+            }
+
+            let (script, ok_name, err_name) = job_callbacks(id, &req.cmd);
+            let callback = std::mem::replace(&mut req.callback, ok_name);
+            let error = std::mem::replace(&mut req.error, err_name);
+            let res: Result<_, String> = Ok(JobHandle { id });
+            if let Ok(callback) = Request::format_callback(res, callback, error) {
+                eval(&mut wm, script + callback.script.as_str());
+            }
+            Some(id)
+        } else {
+            None
+        };
+
+        let _ = self.tx.send(Msg::Task(Task { job, f, wm, req }));
+    }
+
+    /// 开始或停止（`auto_rerun`为None）监视视频与数采文件，监视器由执行线程持有
+    pub fn watch(&self, auto_rerun: Option<bool>, wm: WebviewMut, req: Request) {
+        let _ = self.tx.send(Msg::Watch {
+            auto_rerun,
+            wm,
+            req,
+        });
+    }
+
+    pub fn list(&self) -> Vec<JobInfo> {
+        self.jobs
+            .lock()
+            .map(|jobs| jobs.clone())
+            .unwrap_or_default()
+    }
+
+    /// 只能取消排队中的任务，被取消任务的Promise以错误结束
+    pub fn cancel(&self, id: usize, wm: &mut WebviewMut) -> TLCResult<JobInfo> {
+        let mut jobs = self.jobs.lock().map_err(|err| awsl!(err.to_string()))?;
+        let job = jobs
+            .iter_mut()
+            .find(|job| job.id == id)
+            .ok_or(awsl!(HandleError, format!("任务{}不存在", id)))?;
+        if job.state != JobState::Queued {
+            return Err(awsl!(
+                HandleError,
+                format!("任务{}已开始或已结束，无法取消", id)
+            ));
+        }
+        job.state = JobState::Cancelled;
+
+        let (ok_name, err_name) = callback_names(id);
+        let res: Result<(), _> = Err(awsl!(HandleError, "任务已取消"));
+        if let Ok(callback) = Request::format_callback(res, ok_name, err_name) {
+            eval(wm, callback.script);
+        }
+
+        Ok(job.clone())
+    }
+}
@@ -1,2 +1,3 @@
 pub mod handle;
+pub mod job;
 pub mod request;
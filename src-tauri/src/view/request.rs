@@ -1,9 +1,28 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value as Json};
 use tauri::api::rpc::format_callback_result;
 
 use crate::awsl;
-use crate::cal::preprocess::{FilterMethod, InterpMethod};
-use crate::cal::{error::TLCResult, solve::IterationMethod, Thermocouple};
+use crate::cal::catalog::CatalogQuery;
+use crate::cal::colormap::Colormap;
+use crate::cal::error::{ErrorReport, Lang, TLCResult};
+use crate::cal::events::EventOptions;
+use crate::cal::io::{ArrayFormat, DAQSheet, ExportOptions, FieldFormat};
+use crate::cal::mask::Mask;
+use crate::cal::paths::OutputLayout;
+use crate::cal::postprocess::{NuCorrelation, NuSmoothing, PlotOptions};
+use crate::cal::preprocess::{
+    DAQFilter, DAQResample, DriftCorrection, FilterMethod, InterpMethod, PeakMethod,
+};
+use crate::cal::quicklook::QuickLook;
+use crate::cal::script::PostScript;
+use crate::cal::solve::{IterationMethod, PropertyCurve, SweepParam, T0Estimation};
+use crate::cal::stream::DaqSource;
+use crate::cal::threads::ThreadOptions;
+use crate::cal::transient::{TraceTarget, TransientOptions};
+use crate::cal::Thermocouple;
 
 /// body数据类型
 #[derive(Debug, Deserialize)]
@@ -11,13 +30,74 @@ pub enum Value {
     Nothing,
     String(String),
     Uint(usize),
+    /// None为不设置
+    OptionUint(Option<usize>),
     Float(f32),
+    Bool(bool),
     UintVec(Vec<usize>),
     FloatVec(Vec<f32>),
     Thermocouples(Vec<Thermocouple>),
     Interp(InterpMethod),
     Filter(FilterMethod),
+    /// 像素位置，候选的滤波方法
+    CompareFilters(usize, Vec<FilterMethod>),
+    /// 候选的滤波方法
+    Filters(Vec<FilterMethod>),
+    Drift(DriftCorrection),
+    Events(EventOptions),
+    Peak(PeakMethod),
+    /// 分块尺寸（像素），是否上采样回原分辨率
+    Binned(usize, bool),
+    /// 热电偶位置的搜索半径（像素），是否修改热电偶位置
+    Refine(usize, bool),
+    QuickLook(QuickLook),
+    Threads(ThreadOptions),
+    /// 参考区域左上角(y, x)与尺寸(高, 宽)，None为不归一化
+    ReferencePatch(Option<((usize, usize), (usize, usize))>),
     Iteration(IterationMethod),
+    T0(T0Estimation),
+    DAQSheet(DAQSheet, (usize, usize)),
+    Resample(DAQResample),
+    DAQFilter(DAQFilter),
+    ExportInterp((usize, usize), FieldFormat),
+    ExportArrays(ArrayFormat),
+    ExportOptions(ExportOptions),
+    /// 关联式，雷诺数，普朗特数
+    Correlation(NuCorrelation, f32, f32),
+    /// 驻点(y, x)，射流直径（像素），区间宽度（r/D）
+    Radial((f32, f32), f32, f32),
+    /// 起点(y, x)，终点(y, x)，采样点数
+    Probe((f32, f32), (f32, f32), usize),
+    /// 多边形顶点(y, x)
+    Polygons(Vec<Vec<(f32, f32)>>),
+    /// 区间数，是否绘图
+    Histogram(usize, bool),
+    /// colormap，是否反转
+    Colormap(Colormap, bool),
+    PlotOptions(PlotOptions),
+    Smoothing(NuSmoothing),
+    /// 扫描的参数及取值
+    Sweep(SweepParam, Vec<f32>),
+    /// 帧数比例，关注的nu2d坐标(行, 列)
+    Convergence(Vec<f32>, Vec<(usize, usize)>),
+    /// 帧数，Nu等值线取值
+    Overlay(usize, Vec<f32>),
+    /// 覆盖的配置项，键与配置文件一致
+    Overrides(Map<String, Json>),
+    /// 错误信息的语言
+    Lang(Lang),
+    DaqSource(DaqSource),
+    /// None为取消遮罩
+    Mask(Option<Mask>),
+    Layout(OutputLayout),
+    /// 标签名与值
+    Tags(BTreeMap<String, String>),
+    Catalog(CatalogQuery),
+    /// None为使用固定值
+    PropertyCurve(Option<PropertyCurve>),
+    /// 重建h(t)的对象与参数
+    HTraces(Vec<TraceTarget>, TransientOptions),
+    PostScripts(Vec<PostScript>),
 }
 
 #[derive(Debug, Deserialize)]
@@ -31,6 +111,9 @@ pub struct Request {
     pub callback: String,
     /// catch
     pub error: String,
+    /// 在后台执行，立即返回`JobHandle`，完成后通过window上的"tlc-job"事件返回结果
+    #[serde(default)]
+    pub background: bool,
 }
 
 impl Default for Value {
@@ -39,13 +122,23 @@ impl Default for Value {
     }
 }
 
+/// 请求的结果：调用前端回调的脚本，以及调用的是哪一个回调
+pub struct Callback {
+    pub script: String,
+    pub ok: bool,
+}
+
 impl Request {
-    pub fn format_callback<T: Serialize, E: ToString>(
+    /// 错误以`ErrorReport`返回，前端根据其中的错误码给出提示
+    pub fn format_callback<T: Serialize, E: Into<ErrorReport>>(
         result: Result<T, E>,
         callback: String,
         error: String,
-    ) -> TLCResult<String> {
-        format_callback_result(result.map_err(|err| err.to_string()), callback, error)
-            .map_err(|err| awsl!(err))
+    ) -> TLCResult<Callback> {
+        let ok = result.is_ok();
+        let script = format_callback_result(result.map_err(Into::into), callback, error)
+            .map_err(|err| awsl!(err))?;
+
+        Ok(Callback { script, ok })
     }
 }
@@ -0,0 +1,250 @@
+//! 无界面的命令行入口，用于在服务器上批量处理
+//!
+//! ```text
+//! tlc-cli run config.json -s filter_method='{"Median":10}' -s peak_temp=35.48
+//! tlc-cli preview config.json --frame 100 -o frame.jpg
+//! tlc-cli export config.json --format npz
+//! tlc-cli batch manifest.json
+//! tlc-cli stitch views.json
+//! tlc-cli watch config.json --rerun
+//! tlc-cli schema > schema.json
+//! ```
+
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::mpsc::channel;
+
+use serde_json::{Map, Value as Json};
+use structopt::StructOpt;
+
+use tlc_core::awsl;
+use tlc_core::multiview::TLCMultiView;
+use tlc_core::pipeline::PipelineStep;
+use tlc_core::reproduce::ReproManifest;
+use tlc_core::{batch::TLCBatch, error::TLCResult, io::ArrayFormat, TLCConfig, TLCData};
+
+#[derive(Debug, StructOpt)]
+#[structopt(name = "tlc-cli", about = "瞬态液晶实验数据处理")]
+enum Command {
+    /// 求解并保存Nu矩阵与云图
+    Run {
+        #[structopt(flatten)]
+        case: CaseArgs,
+    },
+    /// 将视频中的一帧保存为.jpg，用于检查计算区域与起始帧
+    Preview {
+        #[structopt(flatten)]
+        case: CaseArgs,
+        /// 帧序号，从0开始
+        #[structopt(long, default_value = "0")]
+        frame: usize,
+        /// 输出路径
+        #[structopt(short, long, default_value = "preview.jpg")]
+        output: PathBuf,
+    },
+    /// 求解后按指定格式导出
+    Export {
+        #[structopt(flatten)]
+        case: CaseArgs,
+        /// npy、npz、vtk、bundle或hdf5
+        #[structopt(long)]
+        format: ExportFormat,
+    },
+    /// 按清单批量处理，汇总结果写入清单旁的.csv
+    Batch { manifest: PathBuf },
+    /// 按多视角清单分别求解各视角，并按物理坐标拼接为一张Nu分布
+    Stitch { manifest: PathBuf },
+    /// 监视视频与数采文件，文件更新后清空受影响的缓存，Ctrl+C退出
+    Watch {
+        #[structopt(flatten)]
+        case: CaseArgs,
+        /// 文件更新后自动重新求解并保存Nu矩阵
+        #[structopt(long)]
+        rerun: bool,
+    },
+    /// 按复现清单重新求解其中抽取的像素，核对配置、输入文件与Nu是否一致
+    Verify {
+        #[structopt(flatten)]
+        case: CaseArgs,
+        /// 保存Nu时写出的`{case_name}_manifest.json`
+        manifest: PathBuf,
+    },
+    /// 输出配置项的JSON Schema（类型、单位、取值范围与中英文名称）
+    Schema,
+}
+
+#[derive(Debug, StructOpt)]
+struct CaseArgs {
+    /// 配置文件路径，支持.json/.toml/.yaml
+    config: PathBuf,
+    /// 覆盖配置文件中的配置项，形如`key=value`，value按json解析，解析失败时视为字符串
+    #[structopt(short = "s", long = "set", parse(try_from_str = parse_override))]
+    overrides: Vec<(String, Json)>,
+}
+
+#[derive(Debug)]
+enum ExportFormat {
+    Arrays(ArrayFormat),
+    Vtk,
+    Bundle,
+    Hdf5,
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "npy" => ExportFormat::Arrays(ArrayFormat::Npy),
+            "npz" => ExportFormat::Arrays(ArrayFormat::Npz),
+            "vtk" => ExportFormat::Vtk,
+            "bundle" => ExportFormat::Bundle,
+            "hdf5" => ExportFormat::Hdf5,
+            _ => return Err(format!("不支持的导出格式{}", s)),
+        })
+    }
+}
+
+fn parse_override(s: &str) -> Result<(String, Json), String> {
+    let (key, value) = s
+        .split_once('=')
+        .ok_or(format!("{}应为key=value的形式", s))?;
+    let value = serde_json::from_str(value).unwrap_or_else(|_| Json::String(value.to_owned()));
+
+    Ok((key.to_owned(), value))
+}
+
+impl CaseArgs {
+    fn load(self) -> TLCResult<TLCData> {
+        let overrides: Map<_, _> = self.overrides.into_iter().collect();
+        let config = TLCConfig::from_path_with_overrides(self.config, overrides)?;
+        for mismatch in config.verify_inputs() {
+            eprintln!(
+                "警告：{}与上次求解时的文件不一致，结果可能来自不同的输入",
+                mismatch.path
+            );
+        }
+
+        Ok(TLCData::from_config(config))
+    }
+}
+
+fn run(command: Command) -> TLCResult<()> {
+    match command {
+        Command::Run { case } => {
+            let mut data = case.load()?;
+            data.pipeline().check(PipelineStep::Solve)?;
+            data.solve()?.save_nu()?;
+            let plots_path = data.save_nu_figure(None)?;
+            println!("Nu平均值：{}", data.get_nu_nan_mean()?);
+            println!("云图已保存至{}", plots_path);
+            // 未设置模型厚度时不检查
+            if let Ok(report) = data.check_penetration() {
+                if report.invalid_pixels > 0 {
+                    eprintln!(
+                        "警告：{:.1}%的像素不满足半无限大假设，有效加热时间不超过{:.1}s（第{}帧）",
+                        report.invalid_ratio * 100.,
+                        report.max_valid_secs,
+                        report.max_valid_frame
+                    );
+                }
+            }
+        }
+        Command::Preview {
+            case,
+            frame,
+            output,
+        } => {
+            let mut data = case.load()?;
+            let jpeg = data.get_frame(frame)?;
+            std::fs::write(&output, jpeg).map_err(|err| awsl!(DataSaveError, err, output))?;
+            println!("第{}帧已保存至{:?}", frame, output);
+        }
+        Command::Export { case, format } => {
+            let mut data = case.load()?;
+            data.pipeline().check(PipelineStep::Solve)?;
+            data.solve()?;
+            let export_path = match format {
+                ExportFormat::Arrays(array_format) => data.export_arrays(array_format)?,
+                ExportFormat::Vtk => data.export_vtk(false)?,
+                ExportFormat::Bundle => data.export_bundle()?,
+                ExportFormat::Hdf5 => data.export_hdf5(false)?,
+            };
+            println!("已导出至{}", export_path);
+        }
+        Command::Batch { manifest } => {
+            let summaries = TLCBatch::from_path(manifest)?.run()?;
+            for summary in summaries {
+                match summary.error {
+                    Some(err) => println!("{}: 失败，{}", summary.case_name, err),
+                    None => println!("{}: Nu平均值{}", summary.case_name, summary.nu_nan_mean),
+                }
+            }
+        }
+        Command::Stitch { manifest } => {
+            let result = TLCMultiView::from_path(manifest)?.run()?;
+            for view in result.views {
+                match view.error {
+                    Some(err) => println!("{}: 失败，{}", view.name, err),
+                    None => println!("{}: Nu平均值{}", view.name, view.nu_nan_mean),
+                }
+            }
+            println!(
+                "拼接结果已保存至{}，Nu平均值{}",
+                result.nu_path, result.nu_nan_mean
+            );
+        }
+        Command::Watch { case, rerun } => {
+            let mut data = case.load()?;
+            let (tx, rx) = channel();
+            let _watcher = data.watch_inputs(move |input| {
+                let _ = tx.send(input);
+            })?;
+            println!("正在监视输入文件，按Ctrl+C退出");
+            // 单次更新出错不退出，等待下一次更新
+            for input in rx {
+                match data.input_changed(input) {
+                    Ok(stages) => println!("{:?}已更新，清空{:?}", input, stages),
+                    Err(err) => {
+                        eprintln!("{}", err);
+                        continue;
+                    }
+                }
+                if rerun {
+                    match data.solve().and_then(|data| data.save_nu()) {
+                        Ok(data) => println!("Nu平均值：{}", data.get_nu_nan_mean()?),
+                        Err(err) => eprintln!("{}", err),
+                    }
+                }
+            }
+        }
+        Command::Verify { case, manifest } => {
+            let manifest = ReproManifest::from_path(manifest)?;
+            let mut data = case.load()?;
+            let report = data.verify_reproducibility(&manifest)?;
+            let json = serde_json::to_string_pretty(&report).map_err(|err| awsl!(err))?;
+            println!("{}", json);
+            if !report.reproducible {
+                return Err(awsl!(HandleError, report.mismatches.join("\n")));
+            }
+        }
+        Command::Schema => {
+            let schema = tlc_core::schema::config_schema()?;
+            let schema = serde_json::to_string_pretty(&schema).map_err(|err| awsl!(err))?;
+            println!("{}", schema);
+        }
+    }
+
+    Ok(())
+}
+
+fn main() {
+    // 各阶段的耗时等日志输出到stderr，不影响stdout上的结果
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .init();
+    if let Err(err) = run(Command::from_args()) {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,93 @@
+//! 各计算热点的基准测试，`cargo bench --features bench`
+//!
+//! 输入为`tlc_core::bench::Synthetic`合成的数据，尺寸与常见实验相当
+
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use tlc_core::bench::{Synthetic, TC_SHAPE};
+use tlc_core::preprocess::{FilterMethod, InterpMethod, WaveletBoundary};
+use tlc_core::solve::IterationMethod;
+
+/// 计算区域（高，宽）与帧数
+const REGION_SHAPE: (usize, usize) = (256, 320);
+const FRAME_NUM: usize = 1000;
+/// 提取Green值所用的帧数
+const EXTRACT_FRAMES: usize = 200;
+
+fn synthetic() -> Synthetic {
+    Synthetic::new(REGION_SHAPE, FRAME_NUM).unwrap()
+}
+
+fn green_extraction(c: &mut Criterion) {
+    let synthetic = synthetic();
+    let frames = synthetic.rgb_frames(EXTRACT_FRAMES).unwrap();
+    c.bench_function("extract_green", |b| {
+        b.iter(|| synthetic.extract_green(&frames))
+    });
+}
+
+fn filtering(c: &mut Criterion) {
+    let mut synthetic = synthetic();
+    let mut group = c.benchmark_group("filtering");
+    group.sample_size(10);
+    for (name, filter_method) in [
+        ("median", FilterMethod::Median(20)),
+        (
+            "wavelet",
+            FilterMethod::Wavelet {
+                threshold_ratio: 0.5,
+                boundary: WaveletBoundary::Symmetric,
+            },
+        ),
+    ] {
+        group.bench_function(name, |b| {
+            b.iter(|| synthetic.filtering(filter_method.clone()).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn interp(c: &mut Criterion) {
+    let mut synthetic = synthetic();
+    let mut group = c.benchmark_group("interp");
+    group.sample_size(10);
+    for (name, interp_method) in [
+        ("bilinear", InterpMethod::Bilinear(TC_SHAPE)),
+        ("bilinear_extra", InterpMethod::BilinearExtra(TC_SHAPE)),
+    ] {
+        group.bench_function(name, |b| {
+            b.iter(|| synthetic.interp(interp_method).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn solve(c: &mut Criterion) {
+    let mut synthetic = synthetic();
+    let mut group = c.benchmark_group("solve");
+    group.sample_size(10);
+    for (name, iteration_method) in [
+        (
+            "newton_tangent",
+            IterationMethod::NewtonTangent {
+                h0: 50.,
+                max_iter_num: 10,
+            },
+        ),
+        (
+            "newton_down",
+            IterationMethod::NewtonDown {
+                h0: 50.,
+                max_iter_num: 10,
+            },
+        ),
+    ] {
+        group.bench_function(name, |b| {
+            b.iter(|| synthetic.solve(iteration_method.clone()).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, green_extraction, filtering, interp, solve);
+criterion_main!(benches);
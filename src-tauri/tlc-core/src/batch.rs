@@ -0,0 +1,216 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+use csv::WriterBuilder;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value as Json};
+
+use super::{error::TLCResult, postprocess, TLCConfig, TLCData};
+use crate::awsl;
+
+/// 批处理清单
+#[derive(Debug, Deserialize)]
+pub struct BatchManifest {
+    /// 模板配置文件，`Overrides`形式的工况在此基础上覆盖
+    #[serde(default)]
+    pub template: Option<String>,
+    pub cases: Vec<BatchCase>,
+    /// 各工况并行处理，内存占用随之成倍增加
+    #[serde(default)]
+    pub parallel: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum BatchCase {
+    /// 完整的配置文件路径
+    Config(String),
+    /// 覆盖模板中的部分配置，键与配置文件一致，如`{"video_path": "..."}`
+    Overrides(Map<String, Json>),
+}
+
+/// 单个工况的处理结果，汇总为一行
+#[derive(Debug, Serialize)]
+pub struct CaseSummary {
+    pub case_name: String,
+    /// 处理失败时的错误信息
+    pub error: Option<String>,
+    pub nu_nan_mean: f32,
+    pub nu_std: f32,
+    pub nu_min: f32,
+    pub nu_max: f32,
+    /// 有效（非NaN）像素占比
+    pub valid_ratio: f32,
+    /// 5%、50%、95%百分位
+    pub percentiles: [f32; 3],
+}
+
+pub struct TLCBatch {
+    manifest: BatchManifest,
+    /// 清单中的相对路径以清单所在目录为基准
+    base_dir: PathBuf,
+    summary_path: PathBuf,
+}
+
+impl TLCBatch {
+    pub fn from_path<P: AsRef<Path>>(manifest_path: P) -> TLCResult<Self> {
+        let manifest_path = manifest_path.as_ref();
+        let file =
+            File::open(manifest_path).map_err(|err| awsl!(ConfigIOError, err, manifest_path))?;
+        let manifest =
+            serde_json::from_reader(BufReader::new(file)).map_err(|err| awsl!(ConfigError, err))?;
+        let base_dir = manifest_path
+            .parent()
+            .ok_or(awsl!(ConfigIOError, "清单路径无效", manifest_path))?
+            .to_owned();
+        let stem = manifest_path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or("batch");
+        let summary_path = base_dir.join(format!("{}_summary.csv", stem));
+
+        Ok(Self {
+            manifest,
+            base_dir,
+            summary_path,
+        })
+    }
+
+    /// 依次（或并行）处理所有工况，单个工况失败不影响其余工况，汇总结果写入清单旁的.csv
+    pub fn run(&self) -> TLCResult<Vec<CaseSummary>> {
+        let template = match &self.manifest.template {
+            Some(template) => {
+                let template_path = self.base_dir.join(template);
+                let file = File::open(&template_path)
+                    .map_err(|err| awsl!(ConfigIOError, err, template_path))?;
+                let json: Json = serde_json::from_reader(BufReader::new(file))
+                    .map_err(|err| awsl!(ConfigError, err))?;
+                Some(json)
+            }
+            None => None,
+        };
+
+        let run_case = |(i, case): (usize, &BatchCase)| {
+            let case_name = match case {
+                BatchCase::Config(config_path) => config_path.clone(),
+                BatchCase::Overrides(_) => format!("case_{}", i),
+            };
+            self.load_config(case, template.as_ref())
+                .and_then(|config| run_pipeline(TLCData::from_config(config)))
+                .unwrap_or_else(|err| CaseSummary::failed(case_name, err.to_string()))
+        };
+        let cases = self.manifest.cases.iter().enumerate();
+        let summaries: Vec<_> = if self.manifest.parallel {
+            cases
+                .collect::<Vec<_>>()
+                .into_par_iter()
+                .map(run_case)
+                .collect()
+        } else {
+            cases.map(run_case).collect()
+        };
+        self.save_summary(&summaries)?;
+
+        Ok(summaries)
+    }
+
+    fn load_config(&self, case: &BatchCase, template: Option<&Json>) -> TLCResult<TLCConfig> {
+        match case {
+            BatchCase::Config(config_path) => TLCConfig::from_path(self.base_dir.join(config_path)),
+            BatchCase::Overrides(overrides) => {
+                let mut json = template
+                    .ok_or(awsl!(ConfigError, "使用覆盖配置时必须提供模板"))?
+                    .clone();
+                let object = json
+                    .as_object_mut()
+                    .ok_or(awsl!(ConfigError, "模板格式错误"))?;
+                object.extend(overrides.clone());
+                TLCConfig::from_json(json)
+            }
+        }
+    }
+
+    fn save_summary(&self, summaries: &[CaseSummary]) -> TLCResult<()> {
+        let summary_path = &self.summary_path;
+        let mut wtr = WriterBuilder::new()
+            .from_path(summary_path)
+            .map_err(|err| awsl!(DataSaveError, err, summary_path))?;
+        let header = [
+            "case_name",
+            "error",
+            "nu_nan_mean",
+            "nu_std",
+            "nu_min",
+            "nu_max",
+            "valid_ratio",
+            "nu_p5",
+            "nu_p50",
+            "nu_p95",
+        ];
+        wtr.write_record(&header)
+            .map_err(|err| awsl!(DataSaveError, err, summary_path))?;
+        for s in summaries {
+            let stats = [s.nu_nan_mean, s.nu_std, s.nu_min, s.nu_max, s.valid_ratio];
+            let record: Vec<_> = [s.case_name.clone(), s.error.clone().unwrap_or_default()]
+                .iter()
+                .cloned()
+                .chain(
+                    stats
+                        .iter()
+                        .chain(s.percentiles.iter())
+                        .map(|v| v.to_string()),
+                )
+                .collect();
+            wtr.write_record(&record)
+                .map_err(|err| awsl!(DataSaveError, err, summary_path))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl CaseSummary {
+    fn failed(case_name: String, error: String) -> Self {
+        tracing::warn!(%case_name, %error, "case failed");
+        Self {
+            case_name,
+            error: Some(error),
+            nu_nan_mean: f32::NAN,
+            nu_std: f32::NAN,
+            nu_min: f32::NAN,
+            nu_max: f32::NAN,
+            valid_ratio: f32::NAN,
+            percentiles: [f32::NAN; 3],
+        }
+    }
+}
+
+/// 完整处理流程：求解、保存Nu矩阵与云图
+fn run_pipeline(mut data: TLCData) -> TLCResult<CaseSummary> {
+    data.solve()?.save_nu()?;
+    data.save_nu_figure(None)?;
+
+    let nu2d = data.get_smoothed_nu2d()?;
+    let valid: Vec<_> = nu2d.iter().copied().filter(|nu| !nu.is_nan()).collect();
+    let (_, nu_std) = postprocess::nan_mean_std(valid.iter().copied());
+    // 全为NaN时直方图无法统计，百分位记为NaN
+    let hist = postprocess::histogram(nu2d, 1).ok();
+    let percentile = |p: f32| {
+        hist.as_ref()
+            .and_then(|hist| hist.percentiles.iter().find(|&&(q, _)| q == p))
+            .map_or(f32::NAN, |&(_, nu)| nu)
+    };
+
+    Ok(CaseSummary {
+        case_name: data.get_config().case_name.clone(),
+        error: None,
+        nu_nan_mean: data.get_nu_nan_mean()?,
+        nu_std,
+        nu_min: valid.iter().copied().fold(f32::NAN, f32::min),
+        nu_max: valid.iter().copied().fold(f32::NAN, f32::max),
+        valid_ratio: valid.len() as f32 / nu2d.len() as f32,
+        percentiles: [percentile(5.), percentile(50.), percentile(95.)],
+    })
+}
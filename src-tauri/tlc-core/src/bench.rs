@@ -0,0 +1,93 @@
+//! 基准测试的入口，只在`bench`特性下编译，`cargo bench --features bench`运行
+//!
+//! 输入为`testing::SyntheticCase`合成的数据，不依赖视频与数采文件
+
+use ndarray::parallel::prelude::*;
+use ndarray::prelude::*;
+
+use super::{
+    error::TLCResult,
+    io,
+    preprocess::{FilterMethod, InterpMethod},
+    solve::IterationMethod,
+    testing::SyntheticCase,
+    TLCConfig, TLCData,
+};
+
+pub use super::testing::TC_SHAPE;
+
+/// 已完成滤波、峰值检测与插值的合成工况，各热点可以单独重复运行
+pub struct Synthetic {
+    case: SyntheticCase,
+    data: TLCData,
+}
+
+impl Synthetic {
+    pub fn new(region_shape: (usize, usize), frame_num: usize) -> TLCResult<Self> {
+        let case = SyntheticCase::new(region_shape, frame_num)?;
+        let mut data = case.data()?;
+        data.filtering()?.detect_peak()?.init_t2d()?.interp()?;
+
+        Ok(Self { case, data })
+    }
+
+    /// 由raw_g2d合成`n`帧视频
+    pub fn rgb_frames(&self, n: usize) -> TLCResult<Vec<Vec<u8>>> {
+        Ok(self
+            .data
+            .get_raw_g2d()?
+            .axis_iter(Axis(0))
+            .take(n)
+            .map(|green| self.case.rgb_frame(green))
+            .collect())
+    }
+
+    /// 与读取视频时相同，逐帧并行取出计算区域的Green值
+    pub fn extract_green(&self, frames: &[Vec<u8>]) -> Array2<u8> {
+        let TLCConfig {
+            video_shape: (_, video_w),
+            top_left_pos: (tl_y, tl_x),
+            region_shape: (cal_h, cal_w),
+            ..
+        } = self.data.config;
+        let offsets: Vec<_> = (0..cal_h * cal_w)
+            .map(|pos| {
+                let (y, x) = (tl_y + pos / cal_w, tl_x + pos % cal_w);
+                (pos, (y * video_w + x) * 3 + 1)
+            })
+            .collect();
+
+        let mut g2d = Array2::zeros((frames.len(), cal_h * cal_w));
+        frames
+            .par_iter()
+            .zip(g2d.axis_iter_mut(Axis(0)).into_par_iter())
+            .for_each(|(rgb, row)| io::extract_green(rgb, &offsets, row));
+
+        g2d
+    }
+
+    pub fn filtering(&mut self, filter_method: FilterMethod) -> TLCResult<()> {
+        self.data.config.filter_method = filter_method;
+        self.data.filtered_g2d = None;
+        self.data.filtering()?;
+
+        Ok(())
+    }
+
+    pub fn interp(&mut self, interp_method: InterpMethod) -> TLCResult<()> {
+        self.data.config.interp_method = interp_method;
+        self.data.interp = None;
+        self.data.interp()?;
+
+        Ok(())
+    }
+
+    /// 只计时迭代求解，峰值帧与参考温度沿用构造时的结果
+    pub fn solve(&mut self, iteration_method: IterationMethod) -> TLCResult<()> {
+        self.data.config.iteration_method = iteration_method;
+        self.data.nu2d = None;
+        self.data.solve_nu(0)?;
+
+        Ok(())
+    }
+}
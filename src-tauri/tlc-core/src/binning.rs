@@ -0,0 +1,176 @@
+use std::path::Path;
+
+use ndarray::parallel::prelude::*;
+use ndarray::prelude::*;
+use serde::Serialize;
+
+use super::{error::TLCResult, io, postprocess, preprocess::Interp, TLCData};
+use crate::awsl;
+
+/// 分块求解的结果
+#[derive(Debug, Serialize)]
+pub struct BinnedNu {
+    pub bin: usize,
+    /// 分块后的尺寸（高，宽），不能整除时边缘的块较小
+    pub binned_shape: (usize, usize),
+    pub nu_nan_mean: f32,
+    /// 保存的Nu矩阵，上采样时与计算区域同尺寸
+    pub nu_path: String,
+}
+
+/// 以各块中心为节点双线性插值回`shape`，只对有效（非NaN）的节点加权，四个节点均无效时为NaN
+fn upsample(binned: ArrayView2<f32>, bin: usize, shape: (usize, usize)) -> Array2<f32> {
+    let (bh, bw) = binned.dim();
+    let center = (bin as f32 - 1.) / 2.;
+    let locate = |p: usize, n: usize| {
+        let f = ((p as f32 - center) / bin as f32)
+            .max(0.)
+            .min((n - 1) as f32);
+        let i0 = f.floor() as usize;
+        (i0, (i0 + 1).min(n - 1), f - i0 as f32)
+    };
+
+    Array2::from_shape_fn(shape, |(y, x)| {
+        let (y0, y1, ty) = locate(y, bh);
+        let (x0, x1, tx) = locate(x, bw);
+        let corners = [
+            (binned[(y0, x0)], (1. - ty) * (1. - tx)),
+            (binned[(y0, x1)], (1. - ty) * tx),
+            (binned[(y1, x0)], ty * (1. - tx)),
+            (binned[(y1, x1)], ty * tx),
+        ];
+        let (sum, weight) = corners
+            .iter()
+            .filter(|(nu, _)| !nu.is_nan())
+            .fold((0., 0.), |(sum, weight), &(nu, w)| {
+                (sum + nu * w, weight + w)
+            });
+        if weight > 0. {
+            sum / weight
+        } else {
+            f32::NAN
+        }
+    })
+}
+
+impl TLCData {
+    /// 将`bin`×`bin`像素的Green值历史平均后作为一个点求解，计算量减少为1/bin²，
+    /// 用于大区域的快速预览，保存Nu矩阵；`upsample_nu`为true时双线性插值回计算区域的分辨率
+    ///
+    /// 不影响当前的求解结果
+    pub fn solve_binned(&mut self, bin: usize, upsample_nu: bool) -> TLCResult<BinnedNu> {
+        let mut nu2d = self.binned_nu2d(bin)?;
+        let binned_shape = nu2d.dim();
+        let mut meta = self.data_meta("Nu", true)?;
+        if upsample_nu {
+            // 插值在未翻转的坐标下进行，边缘不完整的块位于最下方与最右侧
+            nu2d.invert_axis(Axis(0));
+            nu2d = upsample(nu2d.view(), bin, self.config.region_shape);
+            nu2d.invert_axis(Axis(0));
+        } else {
+            meta.pixel_size *= bin as f32;
+        }
+
+        let nu_path = Path::new(&self.config.data_path)
+            .with_file_name(format!("{}_binned{}_nu.csv", self.config.case_name, bin));
+        io::save_data(nu2d.view(), &nu_path, Some(&meta))?;
+
+        Ok(BinnedNu {
+            bin,
+            binned_shape,
+            nu_nan_mean: postprocess::cal_nan_mean(nu2d.view()),
+            nu_path: nu_path.to_str().ok_or(awsl!(nu_path))?.to_owned(),
+        })
+    }
+
+    /// 分块求解的Nu矩阵，与nu2d一样上下翻转；参考温度取各块中心处的值
+    pub(crate) fn binned_nu2d(&mut self, bin: usize) -> TLCResult<Array2<f32>> {
+        if bin == 0 {
+            return Err(awsl!(HandleError, "分块尺寸应大于0"));
+        }
+        if self.filtered_g2d.is_none() {
+            self.filtering()?;
+        }
+        if self.interp.is_none() {
+            self.interp()?;
+        }
+        let (start, end) = self.event_window()?;
+        let region_shape = self.config.region_shape;
+        let (cal_h, cal_w) = region_shape;
+        let binned_shape = ((cal_h + bin - 1) / bin, (cal_w + bin - 1) / bin);
+        let mask = self.config.mask_pixels()?;
+        let included = |pos: usize| mask.as_ref().map_or(true, |mask| mask[pos]);
+        let peak_method = self.config.peak_method;
+        let filtered_g2d = self.get_filtered_g2d()?;
+        let interp = self.get_interp()?;
+
+        let bins: Vec<_> = (0..binned_shape.0 * binned_shape.1)
+            .into_par_iter()
+            .map(|b| {
+                let (y0, x0) = (b / binned_shape.1 * bin, b % binned_shape.1 * bin);
+                let (y1, x1) = ((y0 + bin).min(cal_h), (x0 + bin).min(cal_w));
+                let center = (y0 + y1) / 2 * cal_w + (x0 + x1) / 2;
+                let temps = interp.interp_single_point(center, region_shape).to_vec();
+                let pixels: Vec<_> = (y0..y1)
+                    .flat_map(|y| (x0..x1).map(move |x| y * cal_w + x))
+                    .filter(|&pos| included(pos))
+                    .collect();
+                // 块内没有参与处理的像素时峰值帧记为0，求解时视为无效
+                if pixels.is_empty() {
+                    return (0, temps);
+                }
+                let mut sum = Array1::<u32>::zeros(end - start);
+                for &pos in &pixels {
+                    let green = filtered_g2d.slice(s![start..end, pos]);
+                    sum.zip_mut_with(&green, |s, &g| *s += g as u32);
+                }
+                let n = pixels.len() as u32;
+                let green = sum.mapv(|s| ((s + n / 2) / n) as u8);
+
+                (
+                    peak_method.locate(green.view()).map_or(0, |i| start + i),
+                    temps,
+                )
+            })
+            .collect();
+        let frame_num = bins.first().map_or(0, |(_, temps)| temps.len());
+        let (peaks, temps): (Vec<_>, Vec<_>) = bins.into_iter().unzip();
+        let temps = Array2::from_shape_vec((peaks.len(), frame_num), temps.concat())
+            .map_err(|err| awsl!(err))?;
+
+        // 临时换成分块后的区域、峰值帧与参考温度，求解后恢复
+        let snapshot = self.take_solution();
+        let interp = self.interp.replace(Interp::Eager(temps));
+        let region_shape = std::mem::replace(&mut self.config.region_shape, binned_shape);
+        let mask = self.config.mask.take();
+        self.peak_frames = Some(peaks);
+        let nu2d = self
+            .solve_nu(start)
+            .and_then(|data| Ok(data.get_nu2d()?.to_owned()));
+        self.config.region_shape = region_shape;
+        self.config.mask = mask;
+        self.interp = interp;
+        self.restore_solution(snapshot);
+
+        nu2d
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn upsample_linear() {
+        // 2×2的块，中心在0.5、2.5处，线性场插值后保持线性
+        let binned = array![[0., 2.], [4., 6.]];
+        let full = upsample(binned.view(), 2, (4, 4));
+        assert_eq!(full[(0, 0)], 0.);
+        assert_eq!(full[(3, 3)], 6.);
+        assert!((full[(1, 1)] - 1.5).abs() < 1e-6);
+        // 只由无效节点决定的像素仍为NaN
+        let with_nan = upsample(array![[0., f32::NAN], [4., 6.]].view(), 2, (4, 4));
+        assert!(with_nan[(0, 3)].is_nan());
+        assert!(!with_nan[(1, 2)].is_nan());
+    }
+}
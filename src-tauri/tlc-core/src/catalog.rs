@@ -0,0 +1,189 @@
+//! `save_dir`下所有已保存工况的索引，每次保存Nu时更新，用于事后按标签与参数查找工况
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value as Json};
+
+use super::{error::TLCResult, TLCData};
+use crate::awsl;
+
+const CATALOG_FILE: &str = "catalog.json";
+
+/// 批处理并行保存时逐个读写索引文件
+static CATALOG_LOCK: Mutex<()> = Mutex::new(());
+
+/// 一个已保存的工况
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogEntry {
+    pub case_name: String,
+    /// 保存时间
+    pub saved_at: String,
+    pub config_path: String,
+    /// Nu矩阵路径，同一路径只保留最近一次保存
+    pub data_path: String,
+    /// 用户定义的标签，如涂层、试验件编号
+    pub tags: BTreeMap<String, String>,
+    /// 主要配置参数
+    pub params: Map<String, Json>,
+    pub nu_nan_mean: f32,
+    /// 有效（非NaN）像素占比（%）
+    pub valid_percent: f32,
+    pub nan_count: usize,
+}
+
+/// 查找条件，各项同时满足，为空的项不限制
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CatalogQuery {
+    /// 工况名包含该字符串
+    pub case_name: String,
+    /// 标签相等
+    pub tags: BTreeMap<String, String>,
+    /// 主要配置参数相等，数值参数在`tolerance`的相对误差内视为相等
+    pub params: Map<String, Json>,
+    pub tolerance: f32,
+}
+
+impl CatalogQuery {
+    fn matches(&self, entry: &CatalogEntry) -> bool {
+        let param_eq = |expected: &Json, actual: Option<&Json>| match (expected, actual) {
+            (Json::Number(a), Some(Json::Number(b))) => match (a.as_f64(), b.as_f64()) {
+                (Some(a), Some(b)) => (a - b).abs() <= self.tolerance as f64 * a.abs(),
+                _ => false,
+            },
+            (expected, actual) => Some(expected) == actual,
+        };
+
+        entry.case_name.contains(&self.case_name)
+            && self
+                .tags
+                .iter()
+                .all(|(key, value)| entry.tags.get(key) == Some(value))
+            && self
+                .params
+                .iter()
+                .all(|(key, value)| param_eq(value, entry.params.get(key)))
+    }
+}
+
+fn catalog_path(save_dir: &str) -> PathBuf {
+    Path::new(save_dir).join(CATALOG_FILE)
+}
+
+/// 读取索引，文件不存在时为空
+fn read_catalog(path: &Path) -> TLCResult<Vec<CatalogEntry>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let file = File::open(path).map_err(|err| awsl!(DataReadError, err, path))?;
+
+    serde_json::from_reader(BufReader::new(file)).map_err(|err| awsl!(DataReadError, err, path))
+}
+
+/// 替换同一Nu矩阵路径的旧记录，按保存时间排序
+fn upsert(entries: &mut Vec<CatalogEntry>, entry: CatalogEntry) {
+    entries.retain(|e| e.data_path != entry.data_path);
+    entries.push(entry);
+    entries.sort_by(|a, b| a.saved_at.cmp(&b.saved_at));
+}
+
+/// 在`save_dir`的索引中查找
+pub fn query_catalog(save_dir: &str, query: &CatalogQuery) -> TLCResult<Vec<CatalogEntry>> {
+    let _guard = CATALOG_LOCK.lock().map_err(|err| awsl!(err))?;
+    let entries = read_catalog(&catalog_path(save_dir))?;
+
+    Ok(entries.into_iter().filter(|e| query.matches(e)).collect())
+}
+
+impl TLCData {
+    /// 将当前工况写入`save_dir`下的索引，已求解后调用
+    pub fn update_catalog(&self) -> TLCResult<&Self> {
+        let config = &self.config;
+        let stats = self.get_solve_stats()?;
+        let total = self.get_nu2d()?.len();
+        let params = serde_json::from_value(json!({
+            "reynolds": config.reynolds,
+            "peak_temp": config.peak_temp,
+            "characteristic_length": config.characteristic_length,
+            "frame_rate": config.frame_rate,
+            "filter_method": config.filter_method,
+            "interp_method": config.interp_method,
+            "iteration_method": config.iteration_method,
+        }))
+        .map_err(|err| awsl!(err))?;
+        let entry = CatalogEntry {
+            case_name: config.case_name.clone(),
+            saved_at: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            config_path: config.config_path.clone(),
+            data_path: config.data_path.clone(),
+            tags: config.tags.clone(),
+            params,
+            nu_nan_mean: self.get_nu_nan_mean()?,
+            valid_percent: (total - stats.nan_count) as f32 / total.max(1) as f32 * 100.,
+            nan_count: stats.nan_count,
+        };
+
+        let _guard = CATALOG_LOCK.lock().map_err(|err| awsl!(err))?;
+        let path = catalog_path(&config.save_dir);
+        let mut entries = read_catalog(&path)?;
+        upsert(&mut entries, entry);
+        let file = File::create(&path).map_err(|err| awsl!(DataSaveError, err, path))?;
+        serde_json::to_writer_pretty(BufWriter::new(file), &entries).map_err(|err| awsl!(err))?;
+
+        Ok(self)
+    }
+
+    /// 在当前`save_dir`的索引中查找
+    pub fn query_catalog(&self, query: &CatalogQuery) -> TLCResult<Vec<CatalogEntry>> {
+        query_catalog(&self.config.save_dir, query)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn entry(case_name: &str, saved_at: &str, coating: &str, reynolds: f32) -> CatalogEntry {
+        CatalogEntry {
+            case_name: case_name.to_owned(),
+            saved_at: saved_at.to_owned(),
+            config_path: String::new(),
+            data_path: format!("data/{}.csv", case_name),
+            tags: std::iter::once(("coating".to_owned(), coating.to_owned())).collect(),
+            params: serde_json::from_value(json!({ "reynolds": reynolds })).unwrap(),
+            nu_nan_mean: 100.,
+            valid_percent: 100.,
+            nan_count: 0,
+        }
+    }
+
+    #[test]
+    fn upsert_and_query() {
+        let mut entries = Vec::new();
+        upsert(&mut entries, entry("run1", "2024-01-02", "R30C", 30000.));
+        upsert(&mut entries, entry("run2", "2024-01-01", "R30C", 20000.));
+        upsert(&mut entries, entry("run1", "2024-01-03", "R35C", 30000.));
+        let names: Vec<_> = entries.iter().map(|e| e.case_name.as_str()).collect();
+        assert_eq!(names, ["run2", "run1"]);
+
+        let query: CatalogQuery = serde_json::from_value(json!({
+            "tags": { "coating": "R35C" },
+            "params": { "reynolds": 30100 },
+            "tolerance": 0.01,
+        }))
+        .unwrap();
+        let found: Vec<_> = entries.iter().filter(|e| query.matches(e)).collect();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].saved_at, "2024-01-03");
+        assert!(!CatalogQuery {
+            case_name: "run3".to_owned(),
+            ..Default::default()
+        }
+        .matches(&entries[0]));
+    }
+}
@@ -1,3 +1,10 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::error::TLCResult;
+use crate::awsl;
+
 /// jet colormap from Matlab
 
 pub const JET: &[[f32; 3]; 256] = &[
@@ -258,3 +265,165 @@ pub const JET: &[[f32; 3]; 256] = &[
     [0.515625000000000, 0., 0.],
     [0.500000000000000, 0., 0.],
 ];
+
+/// 查色表长度
+pub const LUT_LEN: usize = 256;
+
+/// 绘制Nu分布所用的colormap
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Colormap {
+    Jet,
+    Viridis,
+    Plasma,
+    Inferno,
+    Turbo,
+    /// 蓝-白-红的发散型colormap，适合Nu/Nu0等以1为中心的量
+    CoolWarm,
+    /// 用户提供的查色表文件，每行一个颜色`r g b`（或以逗号分隔），取值为0~1或0~255
+    Custom(String),
+}
+
+impl Default for Colormap {
+    fn default() -> Self {
+        Colormap::Jet
+    }
+}
+
+/// viridis/plasma/inferno的6阶多项式拟合系数，按`[r, g, b]`排列
+const VIRIDIS: [[f32; 3]; 7] = [
+    [0.277727327, 0.005407345, 0.334099805],
+    [0.105093043, 1.404613530, 1.384590163],
+    [-0.330861829, 0.214847559, 0.095095163],
+    [-4.634230499, -5.799100973, -19.332440956],
+    [6.228269936, 14.179933367, 56.690552601],
+    [4.776384998, -13.745145378, -65.353032633],
+    [-5.435455856, 4.645852612, 26.312435250],
+];
+
+const PLASMA: [[f32; 3]; 7] = [
+    [0.058732344, 0.023336709, 0.543340183],
+    [2.176514634, 0.238383417, 0.753960460],
+    [-2.689460476, -7.455851136, 3.110799940],
+    [6.130348346, 42.346188148, -28.518854653],
+    [-11.107436191, -82.666311094, 60.139847674],
+    [10.023065576, 71.413617701, -54.072186556],
+    [-3.658713843, -22.931534655, 18.191907785],
+];
+
+const INFERNO: [[f32; 3]; 7] = [
+    [0.000218940, 0.001651005, -0.019480898],
+    [0.106513419, 0.563956437, 3.932712389],
+    [11.602493082, -3.972853966, -15.942394106],
+    [-41.703996131, 17.436398882, 44.354145199],
+    [77.162935699, -33.402358942, -81.807309257],
+    [-71.319428245, 32.626064264, 73.209519858],
+    [25.131126225, -12.242668952, -23.070325003],
+];
+
+/// Google turbo的5阶多项式拟合系数
+const TURBO: [[f32; 3]; 6] = [
+    [0.13572138, 0.09140261, 0.10667330],
+    [4.61539260, 2.19418839, 12.64194608],
+    [-42.66032258, 4.84296658, -60.58204836],
+    [132.13108234, -14.18503333, 110.36276771],
+    [-152.94239396, 4.27729857, -89.90310912],
+    [59.28637943, 2.82956604, 27.34824973],
+];
+
+/// Moreland cool-warm的控制点，控制点之间线性插值
+const COOL_WARM: [[f32; 3]; 9] = [
+    [0.230, 0.299, 0.754],
+    [0.384, 0.510, 0.918],
+    [0.553, 0.690, 0.996],
+    [0.722, 0.816, 0.976],
+    [0.867, 0.867, 0.867],
+    [0.961, 0.769, 0.678],
+    [0.957, 0.604, 0.482],
+    [0.871, 0.376, 0.302],
+    [0.706, 0.016, 0.150],
+];
+
+impl Colormap {
+    /// 长度为`LUT_LEN`的查色表，`reversed`为true时首尾颠倒
+    pub fn lut(&self, reversed: bool) -> TLCResult<Vec<[f32; 3]>> {
+        use Colormap::*;
+        let mut lut = match self {
+            Jet => JET.to_vec(),
+            Viridis => polynomial(&VIRIDIS),
+            Plasma => polynomial(&PLASMA),
+            Inferno => polynomial(&INFERNO),
+            Turbo => polynomial(&TURBO),
+            CoolWarm => resample(&COOL_WARM),
+            Custom(lut_path) => resample(&read_lut(lut_path)?),
+        };
+        if reversed {
+            lut.reverse();
+        }
+
+        Ok(lut)
+    }
+}
+
+fn polynomial(coefs: &[[f32; 3]]) -> Vec<[f32; 3]> {
+    (0..LUT_LEN)
+        .map(|i| {
+            let t = i as f32 / (LUT_LEN - 1) as f32;
+            let mut rgb = [0.; 3];
+            for (c, v) in rgb.iter_mut().enumerate() {
+                *v = coefs.iter().rev().fold(0., |acc, coef| acc * t + coef[c]);
+                *v = v.max(0.).min(1.);
+            }
+            rgb
+        })
+        .collect()
+}
+
+/// 将任意长度（至少两个）的控制点线性插值为`LUT_LEN`长度
+fn resample(points: &[[f32; 3]]) -> Vec<[f32; 3]> {
+    let last = (points.len() - 1) as f32;
+    (0..LUT_LEN)
+        .map(|i| {
+            let pos = i as f32 / (LUT_LEN - 1) as f32 * last;
+            let lo = (pos.floor() as usize).min(points.len() - 2);
+            let t = pos - lo as f32;
+            let mut rgb = [0.; 3];
+            for (c, v) in rgb.iter_mut().enumerate() {
+                *v = points[lo][c] * (1. - t) + points[lo + 1][c] * t;
+            }
+            rgb
+        })
+        .collect()
+}
+
+fn read_lut<P: AsRef<Path>>(lut_path: P) -> TLCResult<Vec<[f32; 3]>> {
+    let lut_path = lut_path.as_ref();
+    let content =
+        std::fs::read_to_string(lut_path).map_err(|err| awsl!(DataReadError, err, lut_path))?;
+    let mut lut = Vec::new();
+    for line in content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+    {
+        let rgb: Vec<f32> = line
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|s| !s.is_empty())
+            .map(|s| s.parse())
+            .collect::<Result<_, _>>()
+            .map_err(|err| awsl!(DataReadError, err, lut_path))?;
+        if rgb.len() != 3 {
+            let context = format!("查色表每行须为3个数: {}", line);
+            return Err(awsl!(DataReadError, context, lut_path));
+        }
+        lut.push([rgb[0], rgb[1], rgb[2]]);
+    }
+    if lut.len() < 2 {
+        return Err(awsl!(DataReadError, "查色表至少需要两个颜色", lut_path));
+    }
+    // 0~255的查色表归一化
+    if lut.iter().flatten().any(|&v| v > 1.) {
+        lut.iter_mut().flatten().for_each(|v| *v /= 255.);
+    }
+
+    Ok(lut)
+}
@@ -0,0 +1,878 @@
+use std::fmt;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Rust的std::io::Error不会包含错误路径，需要自己封装
+///
+/// >The number one problem with `std::io::Error` is that, when a file-system operation
+/// fails, you don’t know which path it has failed for! This is understandable —
+/// Rust is a systems language, so it shouldn’t add much fat over what OS provides
+/// natively. OS returns an integer return code, and coupling that with a
+///heap-allocated PathBuf could be an unacceptable overhead!
+///
+/// 错误信息按`lang()`的语言显示，前端通过`ErrorReport`中的错误码给出针对性的提示
+#[derive(Error, Debug)]
+pub enum TLCError {
+    ConfigIOError {
+        raw_err: Text,
+        context: Text,
+    },
+
+    ConfigError(Text),
+
+    VideoIOError(Text),
+
+    VideoError {
+        raw_err: Text,
+        context: Text,
+    },
+
+    DAQIOError {
+        raw_err: Text,
+        context: Text,
+    },
+
+    DAQError {
+        raw_err: Text,
+        context: Text,
+    },
+
+    CreateDirError {
+        raw_err: Text,
+        context: Text,
+    },
+
+    DataSaveError {
+        raw_err: Text,
+        context: Text,
+    },
+
+    DataReadError {
+        raw_err: Text,
+        context: Text,
+    },
+
+    PlotError(Text),
+
+    /// 帧序号超出范围
+    FrameError {
+        frame: usize,
+        frame_num: usize,
+    },
+
+    /// 热电偶对应的列超出数采文件，`index`为热电偶序号
+    ThermocoupleError {
+        index: usize,
+        column_num: usize,
+        daq_columns: usize,
+    },
+
+    HandleError(Text),
+
+    UnKnown(Text),
+}
+
+pub type TLCResult<T> = Result<T, TLCError>;
+
+/// 错误中的一段文字
+///
+/// `awsl!`中直接写出的提示信息（字符串字面量或`format!`）以中文模板为key、参数另存，
+/// 显示时按语言选取模板再依次填入参数；底层错误、路径等其他内容以Debug格式原样保存
+#[derive(Debug, Clone, PartialEq)]
+pub enum Text {
+    Message {
+        key: &'static str,
+        args: Vec<String>,
+    },
+    Raw(String),
+}
+
+impl Text {
+    pub fn message(key: &'static str, args: Vec<String>) -> Self {
+        Text::Message { key, args }
+    }
+
+    pub fn debug<T: fmt::Debug + ?Sized>(context: &T) -> Self {
+        Text::Raw(format!("{:?}", context))
+    }
+
+    pub fn render(&self, lang: Lang) -> String {
+        match self {
+            Text::Message { key, args } => {
+                let template = match lang {
+                    Lang::Zh => key,
+                    Lang::En => english(key).unwrap_or(key),
+                };
+                fill(template, args)
+            }
+            // 字符串的Debug格式带有引号，显示时去掉
+            Text::Raw(raw) => serde_json::from_str(raw).unwrap_or_else(|_| raw.clone()),
+        }
+    }
+}
+
+/// 错误信息的语言
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Lang {
+    Zh,
+    En,
+}
+
+const LANG_UNSET: u8 = 0;
+const LANG_ZH: u8 = 1;
+const LANG_EN: u8 = 2;
+
+static LANG: AtomicU8 = AtomicU8::new(LANG_UNSET);
+
+pub fn set_lang(lang: Lang) {
+    let lang = match lang {
+        Lang::Zh => LANG_ZH,
+        Lang::En => LANG_EN,
+    };
+    LANG.store(lang, Ordering::Relaxed);
+}
+
+/// 未通过`set_lang`设置时依次参考`TLC_LANG`与`LANG`环境变量，都没有设置时为中文
+pub fn lang() -> Lang {
+    match LANG.load(Ordering::Relaxed) {
+        LANG_ZH => Lang::Zh,
+        LANG_EN => Lang::En,
+        _ => {
+            let lang = match std::env::var("TLC_LANG").or_else(|_| std::env::var("LANG")) {
+                Ok(env) if !env.is_empty() && !env.starts_with("zh") => Lang::En,
+                _ => Lang::Zh,
+            };
+            set_lang(lang);
+            lang
+        }
+    }
+}
+
+/// 返回给前端的错误，`message`已按当前语言本地化
+#[derive(Debug, Serialize)]
+pub struct ErrorReport {
+    /// 稳定的错误码，不随语言变化
+    pub code: &'static str,
+    pub message: String,
+    pub path: Option<String>,
+    pub frame: Option<usize>,
+    /// 热电偶序号
+    pub thermocouple: Option<usize>,
+}
+
+impl TLCError {
+    pub fn code(&self) -> &'static str {
+        use TLCError::*;
+        match self {
+            ConfigIOError { .. } => "config_io",
+            ConfigError(_) => "config",
+            VideoIOError(_) => "video_io",
+            VideoError { .. } => "video",
+            DAQIOError { .. } => "daq_io",
+            DAQError { .. } => "daq",
+            CreateDirError { .. } => "create_dir",
+            DataSaveError { .. } => "data_save",
+            DataReadError { .. } => "data_read",
+            PlotError(_) => "plot",
+            FrameError { .. } => "frame",
+            ThermocoupleError { .. } => "thermocouple",
+            HandleError(_) => "handle",
+            UnKnown(_) => "unknown",
+        }
+    }
+
+    /// 出错的文件路径，只有文件读写相关的错误才有
+    pub fn path(&self) -> Option<String> {
+        use TLCError::*;
+        let context = match self {
+            ConfigIOError { context, .. }
+            | DAQIOError { context, .. }
+            | DAQError { context, .. }
+            | CreateDirError { context, .. }
+            | DataSaveError { context, .. }
+            | DataReadError { context, .. }
+            | VideoIOError(context) => context,
+            _ => return None,
+        };
+        // 路径经`awsl!`格式化后带引号，不带引号的（如TDMS段的偏移）不是路径
+        match context {
+            Text::Raw(raw) => serde_json::from_str(raw).ok(),
+            Text::Message { .. } => None,
+        }
+    }
+
+    pub fn frame(&self) -> Option<usize> {
+        match *self {
+            TLCError::FrameError { frame, .. } => Some(frame),
+            _ => None,
+        }
+    }
+
+    pub fn thermocouple(&self) -> Option<usize> {
+        match *self {
+            TLCError::ThermocoupleError { index, .. } => Some(index),
+            _ => None,
+        }
+    }
+
+    pub fn message(&self, lang: Lang) -> String {
+        use Lang::*;
+        use TLCError::*;
+        let t = |s: &Text| s.render(lang);
+        match (self, lang) {
+            (ConfigIOError { raw_err, context }, Zh) => format!(
+                "配置文件读写失败：{}\n请检查配置文件路径：{}",
+                t(raw_err),
+                t(context)
+            ),
+            (ConfigIOError { raw_err, context }, En) => format!(
+                "Failed to read or write the config file: {}\nPlease check the config path: {}",
+                t(raw_err),
+                t(context)
+            ),
+            (ConfigError(err), Zh) => format!("配置文件错误: {}", t(err)),
+            (ConfigError(err), En) => format!("Config error: {}", t(err)),
+            (VideoIOError(path), Zh) => format!("请检查视频文件路径：{}", t(path)),
+            (VideoIOError(path), En) => format!("Please check the video path: {}", t(path)),
+            (VideoError { raw_err, context }, Zh) => {
+                format!("视频文件错误: {}\n{}", t(raw_err), t(context))
+            }
+            (VideoError { raw_err, context }, En) => {
+                format!("Video error: {}\n{}", t(raw_err), t(context))
+            }
+            (DAQIOError { raw_err, context }, Zh) => format!(
+                "数据采集文件读取失败：{}\n请检查数据采集文件路径：{}",
+                t(raw_err),
+                t(context)
+            ),
+            (DAQIOError { raw_err, context }, En) => format!(
+                "Failed to read the DAQ file: {}\nPlease check the DAQ path: {}",
+                t(raw_err),
+                t(context)
+            ),
+            (DAQError { raw_err, context }, Zh) => format!(
+                "数据采集文件解析错误：{}\n请检查数据采集文件：{}",
+                t(raw_err),
+                t(context)
+            ),
+            (DAQError { raw_err, context }, En) => format!(
+                "Failed to parse the DAQ file: {}\nPlease check the DAQ file: {}",
+                t(raw_err),
+                t(context)
+            ),
+            (CreateDirError { raw_err, context }, Zh) => {
+                format!("创建保存结果的子目录{}失败: {}\n", t(context), t(raw_err))
+            }
+            (CreateDirError { raw_err, context }, En) => {
+                format!(
+                    "Failed to create directory {}: {}\n",
+                    t(context),
+                    t(raw_err)
+                )
+            }
+            (DataSaveError { raw_err, context }, Zh) => format!(
+                "矩阵数据保存失败：{}\n请检查文件是否被占用以及保存路径：{}",
+                t(raw_err),
+                t(context)
+            ),
+            (DataSaveError { raw_err, context }, En) => format!(
+                "Failed to save data: {}\nPlease check whether the file is in use \
+                 and the save path: {}",
+                t(raw_err),
+                t(context)
+            ),
+            (DataReadError { raw_err, context }, Zh) => format!(
+                "矩阵数据读取失败：{}\n请检查矩阵路径及文件：{}",
+                t(raw_err),
+                t(context)
+            ),
+            (DataReadError { raw_err, context }, En) => format!(
+                "Failed to read data: {}\nPlease check the path and the file: {}",
+                t(raw_err),
+                t(context)
+            ),
+            (PlotError(err), Zh) => format!("画图失败: {}", t(err)),
+            (PlotError(err), En) => format!("Plotting failed: {}", t(err)),
+            (FrameError { frame, frame_num }, Zh) => {
+                format!("帧数{}超出范围，共{}帧", frame, frame_num)
+            }
+            (FrameError { frame, frame_num }, En) => {
+                format!("Frame {} is out of range ({} frames)", frame, frame_num)
+            }
+            (
+                ThermocoupleError {
+                    index,
+                    column_num,
+                    daq_columns,
+                },
+                Zh,
+            ) => format!(
+                "第{}个热电偶的列数{}超出数采文件列数{}",
+                index + 1,
+                column_num,
+                daq_columns
+            ),
+            (
+                ThermocoupleError {
+                    index,
+                    column_num,
+                    daq_columns,
+                },
+                En,
+            ) => format!(
+                "Column {} of thermocouple #{} exceeds the {} columns of the DAQ file",
+                column_num,
+                index + 1,
+                daq_columns
+            ),
+            (HandleError(err), _) => t(err),
+            (UnKnown(err), Zh) => format!("未知错误，可能原因：{}", t(err)),
+            (UnKnown(err), En) => format!("Unknown error, possible cause: {}", t(err)),
+        }
+    }
+
+    pub fn report(&self) -> ErrorReport {
+        ErrorReport {
+            code: self.code(),
+            message: self.message(lang()),
+            path: self.path(),
+            frame: self.frame(),
+            thermocouple: self.thermocouple(),
+        }
+    }
+}
+
+impl fmt::Display for TLCError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message(lang()))
+    }
+}
+
+impl From<TLCError> for ErrorReport {
+    fn from(err: TLCError) -> Self {
+        err.report()
+    }
+}
+
+/// 不经过`TLCError`的错误（如任务管理），只有文字信息
+impl From<String> for ErrorReport {
+    fn from(message: String) -> Self {
+        ErrorReport {
+            code: "handle",
+            message,
+            path: None,
+            frame: None,
+            thermocouple: None,
+        }
+    }
+}
+
+impl From<&str> for ErrorReport {
+    fn from(message: &str) -> Self {
+        message.to_owned().into()
+    }
+}
+
+/// 各处`awsl!`中直接写出的中文提示信息模板及其英文模板，`{}`为按顺序填入的参数
+const MESSAGES: &[(&str, &str)] = &[
+    ("路径有误", "invalid path"),
+    (
+        "只支持.lvm、.xlsx或.tdms格式",
+        "only .lvm, .xlsx and .tdms are supported",
+    ),
+    ("配置文件格式错误", "malformed config file"),
+    ("模板格式错误", "malformed template"),
+    (
+        "使用覆盖配置时必须提供模板",
+        "a template is required when using overrides",
+    ),
+    ("清单路径无效", "invalid manifest path"),
+    (
+        "平滑核尺寸必须为正奇数",
+        "smoothing kernel size must be a positive odd number",
+    ),
+    ("各场尺寸不一致", "fields have different shapes"),
+    ("矩阵为空", "empty matrix"),
+    (
+        "查色表至少需要两个颜色",
+        "a lookup table needs at least two colors",
+    ),
+    (
+        "暂不支持大端存储的TDMS文件",
+        "big-endian TDMS files are not supported",
+    ),
+    (
+        "暂不支持DAQmx原始数据格式",
+        "DAQmx raw data is not supported",
+    ),
+    ("不支持的TDMS数据类型", "unsupported TDMS data type"),
+    ("TDMS段标识错误", "invalid TDMS segment tag"),
+    ("TDMS段长度错误", "invalid TDMS segment length"),
+    (
+        "TDMS原始数据偏移超出段范围",
+        "TDMS raw data offset exceeds the segment",
+    ),
+    ("TDMS原始数据索引错误", "invalid TDMS raw data index"),
+    (
+        "暂不支持含字符串通道的交错存储TDMS文件",
+        "interleaved TDMS files with string channels are not supported",
+    ),
+    (
+        "ffmpeg初始化错误，建议重装",
+        "ffmpeg failed to initialize, try reinstalling",
+    ),
+    ("颜色转换错误", "color conversion failed"),
+    ("接受数据帧错误", "failed to receive frame"),
+    ("发送数据包错误", "failed to send packet"),
+    ("帧数超出范围", "frame out of range"),
+    (
+        "起始帧数超过视频总帧数",
+        "start frame exceeds the total frames of the video",
+    ),
+    (
+        "起始行数超过数采文件总行数",
+        "start row exceeds the total rows of the DAQ file",
+    ),
+    (
+        "根据同步结果推算出的起始行数非正值",
+        "the start row derived from synchronization is not positive",
+    ),
+    (
+        "根据同步结果推算出的起始行数超过数采文件总行数",
+        "the start row derived from synchronization exceeds the total rows of the DAQ file",
+    ),
+    (
+        "根据同步结果推算出的起始帧数非正值",
+        "the start frame derived from synchronization is not positive",
+    ),
+    (
+        "根据同步结果推算出的起始帧数超过视频总帧数",
+        "the start frame derived from synchronization exceeds the total frames of the video",
+    ),
+    ("没有可用的热电偶", "no thermocouple available"),
+    (
+        "可用热电偶少于两个，无法插值",
+        "at least two thermocouples are required to interpolate",
+    ),
+    (
+        "薄板样条插值至少需要三个热电偶",
+        "thin plate spline interpolation requires at least three thermocouples",
+    ),
+    (
+        "热电偶共线，无法进行薄板样条插值",
+        "thermocouples are collinear, thin plate spline interpolation is impossible",
+    ),
+    ("没有有效的Nu或区间数为0", "no valid Nu or zero bins"),
+    ("没有可撤销的修改", "nothing to undo"),
+    (
+        "求解设置发生变化，需要重新求解",
+        "solve settings changed, please solve again",
+    ),
+    (
+        "未设置雷诺数或普朗特数",
+        "Reynolds or Prandtl number is not set",
+    ),
+    ("未设置保存根目录", "save directory is not set"),
+    (
+        "射流直径与区间宽度必须为正",
+        "jet diameter and bin width must be positive",
+    ),
+    ("axis只能为0或1", "axis must be 0 or 1"),
+    (
+        "找不到系统应用数据目录，请在配置中指定app_dir",
+        "cannot find the system app data directory, please set app_dir in the config",
+    ),
+    ("任务已取消", "job cancelled"),
+    ("任务{}不存在", "job {} does not exist"),
+    (
+        "任务{}已开始或已结束，无法取消",
+        "job {} has already started or finished and cannot be cancelled",
+    ),
+    ("未知的请求{}", "unknown command {}"),
+    ("TDMS文件意外结束", "unexpected end of the TDMS file"),
+    ("候选滤波方法为空", "the list of candidate filters is empty"),
+    ("没有可用于评估的像素", "no pixel available for evaluation"),
+    ("{}下找不到{}", "directory {} does not contain {}"),
+    (
+        "抽帧间隔与分块尺寸应大于0",
+        "frame step and block size must be greater than 0",
+    ),
+    (
+        "抽帧间隔{}不能整除帧率{}",
+        "frame step {} does not divide the frame rate {}",
+    ),
+    (
+        "未设置模型厚度，无法检查半无限大假设",
+        "model thickness is not set, cannot check the semi-infinite assumption",
+    ),
+    (
+        "路径模板{}中的花括号未闭合",
+        "unclosed brace in path template {}",
+    ),
+    (
+        "路径模板{}中的{{{}}}不是工况名、日期或单值配置项",
+        "path template {} contains {{{}}}, which is not the case name, the date or a config field",
+    ),
+    (
+        "参考区域(左上角({}, {})，尺寸{}×{})超出视频画面{}×{}",
+        "reference patch (top left ({}, {}), size {}×{}) exceeds the {}×{} video frame",
+    ),
+    (
+        "参考区域(左上角({}, {})，尺寸{}×{})超出计算区域{}×{}",
+        "reference patch (top left ({}, {}), size {}×{}) exceeds the {}×{} region",
+    ),
+    (
+        "区域(左上角({}, {})，尺寸{}×{})超出计算区域{}×{}",
+        "area (top left ({}, {}), size {}×{}) exceeds the {}×{} region",
+    ),
+    ("找不到视频流", "no video stream found"),
+    ("视频只有{}帧", "the video has only {} frames"),
+    ("找不到视频编码器", "video encoder not found"),
+    ("读取数据包的线程出错", "the packet reader thread failed"),
+    (
+        "视频从起始帧起只有{}帧",
+        "the video has only {} frames from the start frame",
+    ),
+    (
+        "当前版本未启用hdf5导出，请使用`--features hdf5`重新编译",
+        "hdf5 export is not enabled in this build, please rebuild with `--features hdf5`",
+    ),
+    ("数采文件为空", "the DAQ file is empty"),
+    ("找不到worksheet{}", "worksheet {} not found"),
+    (
+        "数据采集文件中不应当有数字以外的格式",
+        "the DAQ file should contain numbers only",
+    ),
+    ("无法识别的配置文件版本{}", "unrecognized config version {}"),
+    (
+        "配置文件版本{}高于当前程序支持的版本{}，请更新程序",
+        "config version {} is newer than the supported version {}, please update the program",
+    ),
+    (
+        "temp_column_num与thermocouple_pos长度不一致",
+        "temp_column_num and thermocouple_pos have different lengths",
+    ),
+    (
+        "temp_column_num与thermocouple_pos必须同时存在",
+        "temp_column_num and thermocouple_pos must be given together",
+    ),
+    ("无法进行{}：\n{}", "cannot run {}:\n{}"),
+    ("峰值检测出错", "peak detection failed"),
+    (
+        "时间列{}超出数采文件的列数{}",
+        "time column {} exceeds the {} columns of the DAQ file",
+    ),
+    (
+        "热电偶{}不可用，且所在行列可用热电偶不足",
+        "thermocouple {} is unavailable and its row and column lack usable thermocouples",
+    ),
+    (
+        "参考温度插值错误",
+        "failed to interpolate the reference temperature",
+    ),
+    (
+        "参考温度矩阵形状转换失败",
+        "failed to reshape the reference temperature matrix",
+    ),
+    (
+        "未设置两次加热的最小间隔",
+        "minimum gap between heating events is not set",
+    ),
+    (
+        "只检测到{}次加热，没有第{}次",
+        "only {} heating events detected, there is no event #{}",
+    ),
+    ("没有检测到加热", "no heating detected"),
+    ("预设名称\"{}\"不合法", "invalid preset name \"{}\""),
+    (
+        "清单中的像素({}, {})超出计算区域{}×{}",
+        "pixel ({}, {}) in the manifest exceeds the {}×{} region",
+    ),
+    (
+        "雷诺数{}超出关联式适用范围",
+        "Reynolds number {} is outside the range of the correlation",
+    ),
+    (
+        "热电偶行数({})列数({})之积不等于热电偶数量",
+        "thermocouple rows ({}) times columns ({}) does not equal the number of thermocouples",
+    ),
+    (
+        "搜索半径{}应在1到{}之间",
+        "search radius {} should be between 1 and {}",
+    ),
+    (
+        "配置应序列化为对象",
+        "the config should serialize to an object",
+    ),
+    ("未知的配置项{}", "unknown config field {}"),
+    ("{}（{}）应为{}", "{} ({}) should be {}"),
+    (
+        "{}（{}）为{}，应在{}到{}之间",
+        "{} ({}) is {}, should be between {} and {}",
+    ),
+    (
+        "后处理脚本{}运行失败：{}",
+        "post-processing script {} failed: {}",
+    ),
+    ("未注册名为{}的求解器", "no solver registered as {}"),
+    (
+        "视频与数采文件均不存在，无法监视",
+        "neither the video nor the DAQ file exists, nothing to watch",
+    ),
+    (
+        "只有视频与数采文件可以监视",
+        "only the video and DAQ files can be watched",
+    ),
+    ("分块尺寸应大于0", "block size must be greater than 0"),
+    (
+        "遮罩尺寸({}, {})与计算区域({}, {})及视频({}, {})均不一致",
+        "mask shape ({}, {}) matches neither the region ({}, {}) nor the video ({}, {})",
+    ),
+    ("没有正在接收的数采数据", "no DAQ stream is running"),
+    ("清单中没有视角", "the manifest has no views"),
+    ("所有视角均处理失败", "all views failed"),
+    (
+        "拼接网格间距必须为正",
+        "stitching grid spacing must be positive",
+    ),
+    (
+        "视频帧率为0，没有时间基准",
+        "the video frame rate is 0, there is no time base",
+    ),
+    (
+        "以数采时间列为时间基准，需要先读取热电偶温度",
+        "the DAQ time column is the time base, read the thermocouple temperatures first",
+    ),
+    (
+        "后处理脚本名称{}只能包含字母、数字、_与-",
+        "post-processing script name {} may only contain letters, digits, _ and -",
+    ),
+];
+
+fn english(key: &str) -> Option<&'static str> {
+    MESSAGES
+        .iter()
+        .find(|(zh, _)| *zh == key)
+        .map(|(_, en)| *en)
+}
+
+/// 依次以`args`替换模板中的`{}`，`{{`与`}}`为花括号本身
+fn fill(template: &str, args: &[String]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut args = args.iter();
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('{', Some('{')) | ('}', Some('}')) => {
+                chars.next();
+                out.push(c);
+            }
+            ('{', Some('}')) => {
+                chars.next();
+                out.extend(args.next().map(|arg| arg.as_str()));
+            }
+            _ => out.push(c),
+        }
+    }
+
+    out
+}
+
+/// 字符串字面量与`format!`作为提示信息保存，可按语言显示；其他表达式以Debug格式保存
+///
+/// `format!`中只能使用`{}`
+#[macro_export]
+macro_rules! awsl {
+    (@text format!($fmt:literal $(, $arg:expr)* $(,)?) $(,)?) => {
+        $crate::error::Text::message($fmt, vec![$($arg.to_string()),*])
+    };
+    (@text $msg:literal $(,)?) => {
+        $crate::error::Text::message($msg, Vec::new())
+    };
+    (@text $context:expr $(,)?) => {
+        $crate::error::Text::debug(&$context)
+    };
+    () => {
+        $crate::error::TLCError::UnKnown($crate::error::Text::Raw("bakana!".to_owned()))
+    };
+    ($msg:literal) => {
+        $crate::error::TLCError::UnKnown($crate::awsl!(@text $msg))
+    };
+    ($context:expr) => {
+        $crate::error::TLCError::UnKnown($crate::awsl!(@text $context))
+    };
+    ($member:tt, format!($($fmt:tt)*), $($context:tt)+) => {
+        $crate::error::TLCError::$member {
+            raw_err: $crate::awsl!(@text format!($($fmt)*)),
+            context: $crate::awsl!(@text $($context)+),
+        }
+    };
+    ($member:tt, format!($($fmt:tt)*) $(,)?) => {
+        $crate::error::TLCError::$member($crate::awsl!(@text format!($($fmt)*)))
+    };
+    ($member:tt, $raw_err:literal, $($context:tt)+) => {
+        $crate::error::TLCError::$member {
+            raw_err: $crate::awsl!(@text $raw_err),
+            context: $crate::awsl!(@text $($context)+),
+        }
+    };
+    ($member:tt, $msg:literal $(,)?) => {
+        $crate::error::TLCError::$member($crate::awsl!(@text $msg))
+    };
+    ($member:tt, $raw_err:expr, $($context:tt)+) => {
+        $crate::error::TLCError::$member {
+            raw_err: $crate::awsl!(@text $raw_err),
+            context: $crate::awsl!(@text $($context)+),
+        }
+    };
+    ($member:tt, $context:expr $(,)?) => {
+        $crate::error::TLCError::$member($crate::awsl!(@text $context))
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::{Path, PathBuf};
+
+    use super::*;
+
+    fn is_cjk(c: char) -> bool {
+        ('\u{4e00}'..='\u{9fff}').contains(&c)
+    }
+
+    /// 模板中`{}`的个数，`{`后不是`}`的（如`{:?}`）返回None
+    fn placeholders(template: &str) -> Option<usize> {
+        let mut num = 0;
+        let mut chars = template.chars().peekable();
+        while let Some(c) = chars.next() {
+            match (c, chars.peek()) {
+                ('{', Some('{')) | ('}', Some('}')) => {
+                    chars.next();
+                }
+                ('{', Some('}')) => {
+                    chars.next();
+                    num += 1;
+                }
+                ('{', _) => return None,
+                _ => {}
+            }
+        }
+
+        Some(num)
+    }
+
+    fn rs_files(dir: &Path, files: &mut Vec<PathBuf>) {
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                rs_files(&path, files);
+            } else if path.extension().map_or(false, |ext| ext == "rs") {
+                files.push(path);
+            }
+        }
+    }
+
+    /// `awsl!(...)`中包含中文的字符串字面量
+    fn awsl_literals(src: &str) -> Vec<String> {
+        let mut literals = Vec::new();
+        for (start, _) in src.match_indices("awsl!(") {
+            let mut chars = src[start + 6..].chars();
+            let mut depth = 1;
+            while depth > 0 {
+                match chars.next() {
+                    Some('(') => depth += 1,
+                    Some(')') => depth -= 1,
+                    Some('"') => {
+                        let mut literal = String::new();
+                        while let Some(c) = chars.next() {
+                            match c {
+                                '"' => break,
+                                '\\' => match chars.next() {
+                                    Some('n') => literal.push('\n'),
+                                    Some(c) => literal.push(c),
+                                    None => {}
+                                },
+                                c => literal.push(c),
+                            }
+                        }
+                        if literal.chars().any(is_cjk) {
+                            literals.push(literal);
+                        }
+                    }
+                    Some(_) => {}
+                    None => break,
+                }
+            }
+        }
+
+        literals
+    }
+
+    #[test]
+    fn messages_translated() {
+        for (zh, en) in MESSAGES {
+            assert!(placeholders(zh).is_some(), "{}", zh);
+            assert_eq!(placeholders(zh), placeholders(en), "{}", zh);
+        }
+
+        // 各crate源码中`awsl!`直接写出的提示信息都应有英文模板
+        let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let mut files = Vec::new();
+        rs_files(&root.join("src"), &mut files);
+        for dir in &[
+            "../src",
+            "../tlc-cli/src",
+            "../tlc-server/src",
+            "../tlc-py/src",
+        ] {
+            let dir = root.join(dir);
+            if dir.is_dir() {
+                rs_files(&dir, &mut files);
+            }
+        }
+        let mut missing = Vec::new();
+        for file in &files {
+            let src = std::fs::read_to_string(file).unwrap();
+            for literal in awsl_literals(&src) {
+                if english(&literal).is_none() {
+                    missing.push(format!("{}: {}", file.display(), literal));
+                }
+            }
+        }
+        assert!(missing.is_empty(), "缺少英文模板：\n{}", missing.join("\n"));
+        assert!(english("没有可撤销的修改").is_some());
+    }
+
+    #[test]
+    fn fill_template() {
+        let args = ["a".to_owned(), "b".to_owned()];
+        assert_eq!(fill("{}与{{{}}}", &args), "a与{b}");
+        let err = awsl!(HandleError, format!("任务{}不存在", 3));
+        assert_eq!(err.message(Lang::Zh), "任务3不存在");
+        assert_eq!(err.message(Lang::En), "job 3 does not exist");
+    }
+
+    #[test]
+    fn report_context() {
+        let path = std::path::Path::new("C:\\data\\case1.lvm");
+        let err = awsl!(DAQIOError, "路径有误", path);
+        assert_eq!(err.code(), "daq_io");
+        assert_eq!(err.path().as_deref(), Some("C:\\data\\case1.lvm"));
+        assert!(err
+            .message(Lang::En)
+            .starts_with("Failed to read the DAQ file: invalid path"));
+
+        let err = TLCError::FrameError {
+            frame: 10,
+            frame_num: 10,
+        };
+        assert_eq!(err.frame(), Some(10));
+        assert_eq!(err.path(), None);
+        assert_eq!(
+            awsl!(HandleError, "没有可撤销的修改").message(Lang::Zh),
+            "没有可撤销的修改"
+        );
+    }
+}
@@ -0,0 +1,234 @@
+use std::path::Path;
+
+use ndarray::parallel::prelude::*;
+use ndarray::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{error::TLCResult, io, postprocess, transient, TLCData};
+use crate::awsl;
+
+/// 一次实验中重复多次加热时的划分方式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct EventOptions {
+    /// 相邻两次加热峰值的最小间隔（帧），为0时视为只加热一次
+    pub min_separation: usize,
+    /// 区域平均Green值的峰值须比前后`min_separation`帧内的最小值高出的量
+    pub min_prominence: u8,
+    /// 单独求解的加热序号（从0开始），None时使用全部帧
+    pub selected: Option<usize>,
+}
+
+impl Default for EventOptions {
+    fn default() -> Self {
+        Self {
+            min_separation: 0,
+            min_prominence: 10,
+            selected: None,
+        }
+    }
+}
+
+/// 一次加热所占的帧`[start, end)`，求解时以`start`为加热开始的时刻
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct HeatingEvent {
+    pub start: usize,
+    pub end: usize,
+    /// 区域平均Green值的峰值帧
+    pub peak: usize,
+}
+
+/// 逐次求解后的平均结果
+#[derive(Debug, Serialize)]
+pub struct EventAverage {
+    pub events: Vec<HeatingEvent>,
+    /// 各次加热的Nu平均值
+    pub nu_nan_means: Vec<f32>,
+    /// 平均Nu分布的平均值
+    pub nu_nan_mean: f32,
+    /// 各像素在多次加热间标准差的平均值
+    pub std_nan_mean: f32,
+    pub mean_path: String,
+    pub std_path: String,
+}
+
+/// 相邻两个峰值之间区域平均Green值最低的帧作为后一次加热的开始，
+/// 因此两次加热之间壁面须充分冷却，液晶回到显色范围以下
+fn split_events(mean_green: &[u8], options: &EventOptions) -> Vec<HeatingEvent> {
+    let peaks = transient::find_events(mean_green, options.min_prominence, options.min_separation);
+    let mut starts = vec![0];
+    for pair in peaks.windows(2) {
+        let trough = (pair[0]..pair[1])
+            .min_by_key(|&i| mean_green[i])
+            .unwrap_or(pair[0]);
+        starts.push(trough);
+    }
+
+    peaks
+        .iter()
+        .enumerate()
+        .map(|(i, &peak)| HeatingEvent {
+            start: starts[i],
+            end: starts.get(i + 1).copied().unwrap_or(mean_green.len()),
+            peak,
+        })
+        .collect()
+}
+
+impl TLCData {
+    /// 按区域平均Green值划分各次加热
+    pub fn heating_events(&mut self) -> TLCResult<Vec<HeatingEvent>> {
+        let options = self.config.events;
+        if options.min_separation == 0 {
+            return Err(awsl!(ConfigError, "未设置两次加热的最小间隔"));
+        }
+        if self.filtered_g2d.is_none() {
+            self.filtering()?;
+        }
+        let mask = self.config.mask_pixels()?;
+        let filtered_g2d = self.get_filtered_g2d()?;
+        let mean_green: Vec<_> = filtered_g2d
+            .axis_iter(Axis(0))
+            .into_par_iter()
+            .map(|frame| {
+                let (sum, n) = frame
+                    .iter()
+                    .enumerate()
+                    .filter(|&(pos, _)| mask.as_ref().map_or(true, |mask| mask[pos]))
+                    .fold((0u64, 0u64), |(sum, n), (_, &g)| (sum + g as u64, n + 1));
+                (sum as f32 / n.max(1) as f32).round() as u8
+            })
+            .collect();
+
+        Ok(split_events(&mean_green, &options))
+    }
+
+    /// 峰值检测与求解所用的帧`[start, end)`，未选择单次加热时为全部帧
+    pub(crate) fn event_window(&mut self) -> TLCResult<(usize, usize)> {
+        let index = match self.config.events.selected {
+            Some(index) => index,
+            None => return Ok((0, self.config.frame_num)),
+        };
+        let events = self.heating_events()?;
+        let event = events.get(index).ok_or(awsl!(
+            ConfigError,
+            format!("只检测到{}次加热，没有第{}次", events.len(), index + 1)
+        ))?;
+
+        Ok((event.start, event.end))
+    }
+
+    /// 逐次求解各次加热，保存各像素的平均Nu与多次加热间的标准差，结束后恢复原来的结果
+    pub fn solve_events(&mut self) -> TLCResult<EventAverage> {
+        let events = self.heating_events()?;
+        if events.is_empty() {
+            return Err(awsl!(HandleError, "没有检测到加热"));
+        }
+        if self.peak_frames.is_none() {
+            self.detect_peak()?;
+        }
+        if self.interp.is_none() {
+            self.interp()?;
+        }
+        let mask = self.config.mask_pixels()?;
+        let included = |pos: usize| mask.as_ref().map_or(true, |mask| mask[pos]);
+        let peak_method = self.config.peak_method;
+        let filtered_g2d = self.get_filtered_g2d()?;
+        // 与`detect_peak`一致，遮罩排除的像素峰值帧记为0
+        let event_peaks: Vec<Vec<usize>> = events
+            .iter()
+            .map(|event| {
+                filtered_g2d
+                    .slice(s![event.start..event.end, ..])
+                    .axis_iter(Axis(1))
+                    .into_par_iter()
+                    .enumerate()
+                    .map(|(pos, col)| {
+                        if !included(pos) {
+                            return 0;
+                        }
+                        peak_method.locate(col).map_or(0, |i| event.start + i)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let snapshot = self.take_solution();
+        let run = || -> TLCResult<Vec<Array2<f32>>> {
+            let mut nu2ds = Vec::with_capacity(events.len());
+            for (event, peaks) in events.iter().zip(event_peaks) {
+                self.peak_frames = Some(peaks);
+                self.solve_nu(event.start)?;
+                nu2ds.push(self.get_nu2d()?.to_owned());
+            }
+
+            Ok(nu2ds)
+        };
+        let nu2ds = run();
+        self.restore_solution(snapshot);
+        let nu2ds = nu2ds?;
+
+        let shape = nu2ds[0].raw_dim();
+        let mut mean = Array2::from_elem(shape.clone(), f32::NAN);
+        let mut std = Array2::from_elem(shape, f32::NAN);
+        for ((idx, m), s) in mean.indexed_iter_mut().zip(std.iter_mut()) {
+            let valid: Vec<_> = nu2ds
+                .iter()
+                .map(|nu2d| nu2d[idx])
+                .filter(|nu| !nu.is_nan())
+                .collect();
+            if valid.is_empty() {
+                continue;
+            }
+            let n = valid.len() as f32;
+            *m = valid.iter().sum::<f32>() / n;
+            if valid.len() > 1 {
+                let var = valid.iter().map(|nu| (nu - *m).powi(2)).sum::<f32>() / (n - 1.);
+                *s = var.sqrt();
+            }
+        }
+
+        let meta = self.data_meta("Nu", true)?;
+        let data_path = Path::new(&self.config.data_path);
+        let mean_path =
+            data_path.with_file_name(format!("{}_events_mean_nu.csv", self.config.case_name));
+        let std_path =
+            data_path.with_file_name(format!("{}_events_std_nu.csv", self.config.case_name));
+        io::save_data(mean.view(), &mean_path, Some(&meta))?;
+        io::save_data(std.view(), &std_path, Some(&meta))?;
+
+        Ok(EventAverage {
+            events,
+            nu_nan_means: nu2ds
+                .iter()
+                .map(|nu2d| postprocess::cal_nan_mean(nu2d.view()))
+                .collect(),
+            nu_nan_mean: postprocess::cal_nan_mean(mean.view()),
+            std_nan_mean: postprocess::cal_nan_mean(std.view()),
+            mean_path: mean_path.to_str().ok_or(awsl!(mean_path))?.to_owned(),
+            std_path: std_path.to_str().ok_or(awsl!(std_path))?.to_owned(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn split_two_events() {
+        let bump = |t: f32, c: f32| 20. + 100. * (-((t - c) / 10.).powi(2)).exp();
+        let green: Vec<_> = (0..200)
+            .map(|t| bump(t as f32, 50.).max(bump(t as f32, 150.)) as u8)
+            .collect();
+        let options = EventOptions {
+            min_separation: 30,
+            ..Default::default()
+        };
+        let events = split_events(&green, &options);
+        assert_eq!(events.len(), 2);
+        assert_eq!((events[0].start, events[0].peak), (0, 50));
+        assert_eq!((events[1].end, events[1].peak), (200, 150));
+        assert!(events[1].start > 60 && events[1].start < 140);
+        assert_eq!(events[0].end, events[1].start);
+    }
+}
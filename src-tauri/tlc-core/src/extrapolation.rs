@@ -0,0 +1,120 @@
+use ndarray::prelude::*;
+use serde::Serialize;
+
+use super::preprocess::{InterpMethod, TCStatus};
+use super::{error::TLCResult, TLCConfig, TLCData};
+
+/// 外插的参考温度超出热电偶范围的程度
+#[derive(Debug, Serialize)]
+pub struct Extrapolation {
+    /// 计算区域超出最外侧热电偶的像素数（上，下，左，右），不外插的方向为0
+    pub extents: (usize, usize, usize, usize),
+    /// 各帧计算区域内参考温度超出该帧热电偶温度范围的最大值（°C），未超出为0
+    pub overshoots: Vec<f32>,
+    pub max_overshoot: f32,
+    /// 超出量大于`extrapolation_limit`的帧数
+    pub exceeded_frames: usize,
+}
+
+/// `lo`到`hi`的区间超出`[min, max]`的部分（前，后）
+fn beyond(lo: i32, hi: i32, min: i32, max: i32) -> (usize, usize) {
+    ((min - lo).max(0) as usize, (hi - max).max(0) as usize)
+}
+
+/// 计算区域边界上的像素，线性与双线性外插的极值都在边界上取得
+fn boundary(region_shape: (usize, usize)) -> Vec<usize> {
+    let (cal_h, cal_w) = region_shape;
+    let mut positions: Vec<_> = (0..cal_w)
+        .flat_map(|x| [x, (cal_h - 1) * cal_w + x])
+        .chain((0..cal_h).flat_map(|y| [y * cal_w, y * cal_w + cal_w - 1]))
+        .collect();
+    positions.sort_unstable();
+    positions.dedup();
+
+    positions
+}
+
+impl TLCData {
+    /// 统计`*Extra`插值方法外插的范围与超出热电偶温度范围的程度，其他插值方法返回None
+    pub fn extrapolation(&mut self) -> TLCResult<Option<Extrapolation>> {
+        use InterpMethod::*;
+        let TLCConfig {
+            interp_method,
+            top_left_pos: (y0, x0),
+            region_shape: (cal_h, cal_w),
+            extrapolation_limit,
+            ..
+        } = self.config;
+        let (vertical, horizontal) = match interp_method {
+            HorizontalExtra => (false, true),
+            VerticalExtra => (true, false),
+            BilinearExtra(_) => (true, true),
+            _ => return Ok(None),
+        };
+        if self.interp.is_none() {
+            self.interp()?;
+        }
+        let tc_status = self.get_tc_status()?;
+        let active: Vec<_> = (0..tc_status.len())
+            .filter(|&i| tc_status[i] == TCStatus::Ok)
+            .collect();
+        let tcs = &self.config.thermocouples;
+        // 双线性插值补齐了不可用的网格节点，外插从整个网格的边缘开始
+        let positions: Vec<_> = match interp_method {
+            BilinearExtra(_) => tcs.iter().map(|tc| tc.pos).collect(),
+            _ => active.iter().map(|&i| tcs[i].pos).collect(),
+        };
+        let extent = |coord: fn(&(i32, i32)) -> i32, start: usize, len: usize| {
+            let min = positions.iter().map(coord).min().unwrap_or(0);
+            let max = positions.iter().map(coord).max().unwrap_or(0);
+            beyond(start as i32, (start + len) as i32 - 1, min, max)
+        };
+        let (top, bottom) = if vertical {
+            extent(|pos| pos.0, y0, cal_h)
+        } else {
+            (0, 0)
+        };
+        let (left, right) = if horizontal {
+            extent(|pos| pos.1, x0, cal_w)
+        } else {
+            (0, 0)
+        };
+
+        let t2d = self.get_t2d()?.select(Axis(0), &active);
+        let tc_min = t2d.fold_axis(Axis(0), f32::INFINITY, |&m, &t| m.min(t));
+        let tc_max = t2d.fold_axis(Axis(0), f32::NEG_INFINITY, |&m, &t| m.max(t));
+        let interp = self.get_interp()?;
+        let mut overshoots = vec![0.; t2d.ncols()];
+        for pos in boundary((cal_h, cal_w)) {
+            let temps = interp.interp_single_point(pos, (cal_h, cal_w));
+            for (frame, overshoot) in overshoots.iter_mut().enumerate() {
+                let t = temps[frame];
+                *overshoot = f32::max(*overshoot, (t - tc_max[frame]).max(tc_min[frame] - t));
+            }
+        }
+        let max_overshoot = overshoots.iter().copied().fold(0., f32::max);
+        let exceeded_frames = overshoots
+            .iter()
+            .filter(|&&overshoot| overshoot > extrapolation_limit)
+            .count();
+
+        Ok(Some(Extrapolation {
+            extents: (top, bottom, left, right),
+            overshoots,
+            max_overshoot,
+            exceeded_frames,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn boundary_pixels() {
+        assert_eq!(beyond(10, 29, 15, 40), (5, 0));
+        assert_eq!(boundary((3, 4)), vec![0, 1, 2, 3, 4, 7, 8, 9, 10, 11]);
+        assert_eq!(boundary((1, 2)), vec![0, 1]);
+    }
+}
@@ -0,0 +1,180 @@
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+
+use serde::Serialize;
+use serde_json::{Map, Value as Json};
+
+use super::{error::TLCResult, TLCConfig, TLCData};
+use crate::awsl;
+
+/// 一个配置项的一次修改
+#[derive(Debug, Clone, Serialize)]
+pub struct HistoryEntry {
+    /// 同一次设置引起的多个配置项变化（如更换视频后帧数、保存路径等一起变化）序号相同，撤销时一起恢复
+    pub step: usize,
+    pub parameter: String,
+    pub old: Json,
+    pub new: Json,
+    pub timestamp: String,
+}
+
+/// 当前工况的配置修改记录
+#[derive(Debug, Default)]
+pub struct History {
+    entries: Vec<HistoryEntry>,
+    /// 上一次记录时的配置，用于比较出本次修改的配置项
+    snapshot: Map<String, Json>,
+    step: usize,
+}
+
+impl History {
+    pub fn new(config: &TLCConfig) -> Self {
+        Self {
+            snapshot: config_map(config),
+            ..Default::default()
+        }
+    }
+
+    /// 不经过用户设置的配置变化（如输入文件被更新后重新读取的帧数）直接计入快照
+    pub(super) fn resync(&mut self, config: &TLCConfig) {
+        self.snapshot = config_map(config);
+    }
+}
+
+/// 输入文件校验和由求解记录，不是用户的设置
+fn config_map(config: &TLCConfig) -> Map<String, Json> {
+    match serde_json::to_value(config) {
+        Ok(Json::Object(mut map)) => {
+            map.remove("input_hashes");
+            map
+        }
+        _ => Map::new(),
+    }
+}
+
+/// 追加到配置文件旁的.history.jsonl，每行一条
+fn append_log<'a>(
+    config_path: &str,
+    entries: impl Iterator<Item = &'a HistoryEntry>,
+) -> TLCResult<()> {
+    if config_path.is_empty() {
+        return Ok(());
+    }
+    let log_path = Path::new(config_path).with_extension("history.jsonl");
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&log_path)
+        .map_err(|err| awsl!(DataSaveError, err, log_path))?;
+    for entry in entries {
+        let line = serde_json::to_string(entry).map_err(|err| awsl!(err))?;
+        writeln!(file, "{}", line).map_err(|err| awsl!(DataSaveError, err, log_path))?;
+    }
+
+    Ok(())
+}
+
+fn now() -> String {
+    chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+impl TLCData {
+    /// 与上一次记录时的配置比较，记下所有变化的配置项，每次设置后调用，
+    /// 需要重新计算的设置经由`invalidate`调用
+    pub(super) fn record_history(&mut self) {
+        let current = config_map(&self.config);
+        let history = &mut self.history;
+        let step = history.step + 1;
+        let timestamp = now();
+        let entries: Vec<_> = current
+            .iter()
+            .filter(|&(parameter, new)| history.snapshot.get(parameter) != Some(new))
+            .map(|(parameter, new)| HistoryEntry {
+                step,
+                parameter: parameter.clone(),
+                old: history
+                    .snapshot
+                    .get(parameter)
+                    .cloned()
+                    .unwrap_or(Json::Null),
+                new: new.clone(),
+                timestamp: timestamp.clone(),
+            })
+            .collect();
+        history.snapshot = current;
+        if entries.is_empty() {
+            return;
+        }
+        history.step = step;
+        // 记录文件写入失败不影响设置本身
+        let _ = append_log(&self.config.config_path, entries.iter());
+        history.entries.extend(entries);
+    }
+
+    pub fn history(&self) -> &[HistoryEntry] {
+        &self.history.entries
+    }
+
+    /// 撤销最近一次设置，恢复其修改的所有配置项，已缓存的数据按需失效
+    pub fn undo(&mut self) -> TLCResult<&mut Self> {
+        let entries = &self.history.entries;
+        let step = entries
+            .last()
+            .ok_or(awsl!(HandleError, "没有可撤销的修改"))?
+            .step;
+        let start = entries
+            .iter()
+            .rposition(|e| e.step != step)
+            .map_or(0, |i| i + 1);
+        let overrides = entries[start..]
+            .iter()
+            .map(|e| (e.parameter.clone(), e.old.clone()))
+            .collect();
+        self.config = self.config.restored(overrides)?;
+
+        let timestamp = now();
+        let undone: Vec<_> = self
+            .history
+            .entries
+            .split_off(start)
+            .into_iter()
+            .map(|e| HistoryEntry {
+                old: e.new,
+                new: e.old,
+                timestamp: timestamp.clone(),
+                ..e
+            })
+            .collect();
+        let _ = append_log(&self.config.config_path, undone.iter());
+        // 撤销本身不进入记录，否则连续撤销会在两个状态之间来回切换
+        self.history.snapshot = config_map(&self.config);
+        self.invalidate();
+
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn undo_setting_without_invalidation() {
+        let config = TLCConfig::from_json(json!({ "peak_temp": 35. })).unwrap();
+        let mut data = TLCData::from_config(config);
+        data.set_peak_temp(36.);
+        data.set_pixel_size(0.5);
+        let last = data.history().last().unwrap();
+        assert_eq!(last.parameter, "pixel_size");
+        assert_eq!(last.step, 2);
+
+        data.undo().unwrap();
+        assert_eq!(data.config.pixel_size, 0.);
+        assert_eq!(data.config.peak_temp, 36.);
+        assert_eq!(data.history().len(), 1);
+        assert_eq!(data.history()[0].parameter, "peak_temp");
+    }
+}
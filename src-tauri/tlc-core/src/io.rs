@@ -0,0 +1,3033 @@
+use std::cell::Ref;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::{create_dir_all, File};
+use std::io::{BufRead, BufReader, Write};
+use std::ops::{Deref, DerefMut};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc::sync_channel, Mutex};
+use std::{cell::RefCell, io::BufWriter};
+
+use calamine::{open_workbook, DataType, Range, Reader, Xlsx};
+use csv::{ReaderBuilder, WriterBuilder};
+use ffmpeg::software::scaling::flag::Flags;
+use ffmpeg::util::frame::video::Video;
+use ffmpeg::{codec, codec::Context, encoder, media::Type};
+use ffmpeg::{format, Packet};
+use ffmpeg_next as ffmpeg;
+use ndarray::parallel::prelude::*;
+use ndarray::prelude::*;
+use rust_xlsxwriter::Workbook;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, to_writer_pretty, Map};
+use sha2::{Digest, Sha256};
+use thread_local::ThreadLocal;
+use zip::{write::FileOptions, CompressionMethod, ZipWriter};
+
+use super::preprocess::{DAQResample, Interp, PeakSignal, TCStatus};
+use super::{
+    error::{TLCError, TLCResult},
+    paths::{self, OutputLayout},
+    postprocess,
+    provenance::{sha256_bytes, sha256_hex, FileHash, InputHashes},
+    schema,
+    tdms::{TdmsFile, TdmsValue},
+};
+use super::{Stage, TLCConfig, TLCData, Thermocouple};
+use crate::awsl;
+
+/// 视频帧压缩后发送给前端
+const COMPRESSION_RATIO: u32 = 2;
+
+/// 处理时每次读取的数据包数，读取与解码交替进行，内存中至多同时有两块
+const PACKET_CHUNK: usize = 256;
+
+/// 导出滤波后视频的码率（bit/像素/帧），灰度画面在该码率下几乎看不出压缩损失
+const FILTERED_VIDEO_BITS_PER_PIXEL: usize = 1;
+
+/// ffmpeg跳转所用的时间单位（AV_TIME_BASE）
+const SEEK_TIME_BASE: i64 = 1_000_000;
+
+/// wrap `Context` to pass between threads(because of the raw pointer)
+struct SendCtx(ffmpeg::software::scaling::Context);
+
+unsafe impl Send for SendCtx {}
+
+impl Deref for SendCtx {
+    type Target = ffmpeg::software::scaling::Context;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for SendCtx {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+/// .xlsx数采文件中的worksheet，可按序号或名称指定
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum DAQSheet {
+    Index(usize),
+    Name(String),
+}
+
+impl Default for DAQSheet {
+    fn default() -> Self {
+        DAQSheet::Index(0)
+    }
+}
+
+impl fmt::Display for DAQSheet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DAQSheet::Index(index) => write!(f, "#{}", index),
+            DAQSheet::Name(name) => write!(f, "\"{}\"", name),
+        }
+    }
+}
+
+/// 参考温度场导出格式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum FieldFormat {
+    /// 每帧一个.csv文件，存放在同一目录下
+    CsvStack,
+    /// 单个(帧数, 高, 宽)的.npy文件
+    Npy,
+    /// 单个.h5文件，`interp`为(帧数, 高, 宽)，`frames`与`frame_times`为各帧的帧数与实验时间
+    #[cfg(feature = "hdf5")]
+    Hdf5,
+}
+
+/// 主要矩阵的导出格式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum ArrayFormat {
+    /// 每个矩阵一个.npy文件，存放在同一目录下
+    Npy,
+    /// 打包为单个.npz文件，`np.load`后按名称取出
+    Npz,
+}
+
+/// 矩阵.csv的附加内容，默认均关闭，与原先的纯矩阵格式一致
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct ExportOptions {
+    /// 文件开头以`#`注释写入工况名、配置哈希、日期与单位
+    #[serde(default)]
+    pub header: bool,
+    /// 首行写入x坐标、首列写入y坐标，有空间标定时单位为mm，否则为像素
+    #[serde(default)]
+    pub coordinates: bool,
+    /// 小数位数，为None时写入能精确还原的最短表示
+    #[serde(default)]
+    pub precision: Option<usize>,
+}
+
+/// 配置文件格式，由扩展名决定，无法识别时按.json处理
+///
+/// .toml与.yaml支持注释，便于手动编辑
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConfigFormat {
+    Json,
+    Toml,
+    Yaml,
+}
+
+impl Default for ConfigFormat {
+    fn default() -> Self {
+        ConfigFormat::Json
+    }
+}
+
+impl ConfigFormat {
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Self {
+        match path.as_ref().extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => ConfigFormat::Toml,
+            Some("yaml") | Some("yml") => ConfigFormat::Yaml,
+            _ => ConfigFormat::Json,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ConfigFormat::Json => "json",
+            ConfigFormat::Toml => "toml",
+            ConfigFormat::Yaml => "yaml",
+        }
+    }
+
+    pub fn parse<T: DeserializeOwned>(self, content: &str) -> TLCResult<T> {
+        match self {
+            ConfigFormat::Json => {
+                serde_json::from_str(content).map_err(|err| awsl!(ConfigError, err))
+            }
+            // 与写入时一样经由json转换，toml对枚举的表示与json不一致
+            ConfigFormat::Toml => {
+                let value: toml::Value =
+                    toml::from_str(content).map_err(|err| awsl!(ConfigError, err))?;
+                let json = serde_json::to_value(value).map_err(|err| awsl!(ConfigError, err))?;
+                serde_json::from_value(json).map_err(|err| awsl!(ConfigError, err))
+            }
+            ConfigFormat::Yaml => {
+                serde_yaml::from_str(content).map_err(|err| awsl!(ConfigError, err))
+            }
+        }
+    }
+
+    pub fn to_string<T: Serialize>(self, value: &T) -> TLCResult<String> {
+        match self {
+            ConfigFormat::Json => {
+                serde_json::to_string_pretty(value).map_err(|err| awsl!(ConfigError, err))
+            }
+            // 经由json转为toml::Value，使普通值排在表之前
+            // 先写成字符串再解析，f32按最短表示转为f64，避免出现0.02759999968之类的值
+            ConfigFormat::Toml => {
+                let json: serde_json::Value = serde_json::to_string(value)
+                    .and_then(|json| serde_json::from_str(&json))
+                    .map_err(|err| awsl!(ConfigError, err))?;
+                toml::Value::try_from(json)
+                    .and_then(|value| toml::to_string_pretty(&value))
+                    .map_err(|err| awsl!(ConfigError, err))
+            }
+            ConfigFormat::Yaml => {
+                serde_yaml::to_string(value).map_err(|err| awsl!(ConfigError, err))
+            }
+        }
+    }
+}
+
+/// 写入矩阵文件头的信息
+pub struct DataMeta {
+    pub options: ExportOptions,
+    pub case_name: String,
+    pub config_hash: String,
+    /// 求解所用视频与数采文件的校验和
+    pub input_hashes: InputHashes,
+    /// 矩阵数值的单位
+    pub unit: &'static str,
+    pub pixel_size: f32,
+    /// 矩阵是否上下翻转过（nu2d第0行对应计算区域最下方）
+    pub flipped: bool,
+}
+
+/// 修改计算区域前的raw_g2d，新区域与之重叠的像素直接复制，不必重新提取
+pub(crate) struct PreviousG2d {
+    pub(crate) g2d: Array2<u8>,
+    top_left_pos: (usize, usize),
+    region_shape: (usize, usize),
+    /// 参考区域的逐帧平均值，参考区域不变时复用
+    reference_g: Option<Vec<f32>>,
+    reference_patch: Option<((usize, usize), (usize, usize))>,
+    // 以下任一项变化后不可复用
+    start_frame: usize,
+    frame_num: usize,
+    video_hash: u64,
+}
+
+/// 新计算区域各像素在旧区域raw_g2d中的列号，不在旧区域内的为None
+fn overlap_columns(
+    (old_tl, (old_h, old_w)): ((usize, usize), (usize, usize)),
+    ((tl_y, tl_x), (cal_h, cal_w)): ((usize, usize), (usize, usize)),
+) -> Vec<Option<usize>> {
+    (0..cal_h * cal_w)
+        .map(|pos| {
+            let (y, x) = (tl_y + pos / cal_w, tl_x + pos % cal_w);
+            let (y, x) = (y.checked_sub(old_tl.0)?, x.checked_sub(old_tl.1)?);
+            if y < old_h && x < old_w {
+                Some(y * old_w + x)
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// 参考区域各像素在帧数据中的字节序号
+fn reference_offsets(
+    ((ref_y, ref_x), (ref_h, ref_w)): ((usize, usize), (usize, usize)),
+    video_shape: (usize, usize),
+) -> TLCResult<Vec<usize>> {
+    if ref_h == 0 || ref_w == 0 || ref_y + ref_h > video_shape.0 || ref_x + ref_w > video_shape.1 {
+        return Err(awsl!(
+            ConfigError,
+            format!(
+                "参考区域(左上角({}, {})，尺寸{}×{})超出视频画面{}×{}",
+                ref_y, ref_x, ref_h, ref_w, video_shape.0, video_shape.1
+            )
+        ));
+    }
+    let real_w = video_shape.1 * 3;
+
+    Ok((ref_y..ref_y + ref_h)
+        .flat_map(|y| (ref_x..ref_x + ref_w).map(move |x| y * real_w + x * 3 + 1))
+        .collect())
+}
+
+/// 从一帧中取出各像素的Green值，`offsets`为（在g2d中的列号，在帧数据中的字节序号）
+pub(crate) fn extract_green(rgb: &[u8], offsets: &[(usize, usize)], mut row: ArrayViewMut1<u8>) {
+    // the data of each frame store in one u8 array:
+    // ||r g b r g b...r g b|......|r g b r g b...r g b||
+    // ||.......row_0.......|......|.......row_n.......||
+    for &(pos, j) in offsets {
+        row[pos] = unsafe { *rgb.get_unchecked(j) };
+    }
+}
+
+/// 坐标行首个单元格，读取时据此识别带坐标的矩阵
+const COORDINATE_MARK: &str = "y\\x";
+
+/// 保存矩阵时每块序列化的行数，各块并行序列化后按顺序写入
+const SERIALIZE_ROWS: usize = 64;
+
+/// 保存矩阵所用写缓冲的大小
+const WRITE_BUFFER_SIZE: usize = 1 << 20;
+
+/// `save_state`保存的内容，借用数据避免复制大数组
+#[derive(Serialize)]
+struct StateRef<'a> {
+    stage_hashes: &'a HashMap<Stage, u64>,
+    /// 保存时视频与数采文件的校验和，同一路径的文件被替换后缓存失效
+    inputs: InputHashes,
+    filtered_g2d: Option<&'a Array2<u8>>,
+    defects: Option<&'a Vec<bool>>,
+    peak_frames: Option<&'a Vec<usize>>,
+    peak_signals: Option<&'a Vec<PeakSignal>>,
+    t2d: Option<&'a Array2<f32>>,
+    tc_status: Option<&'a Vec<TCStatus>>,
+    interp: Option<&'a Interp>,
+}
+
+/// 与`StateRef`格式一致
+#[derive(Deserialize)]
+struct State {
+    stage_hashes: HashMap<Stage, u64>,
+    inputs: InputHashes,
+    filtered_g2d: Option<Array2<u8>>,
+    defects: Option<Vec<bool>>,
+    peak_frames: Option<Vec<usize>>,
+    peak_signals: Option<Vec<PeakSignal>>,
+    t2d: Option<Array2<f32>>,
+    tc_status: Option<Vec<TCStatus>>,
+    interp: Option<Interp>,
+}
+
+/// 导出.npy时逐个矩阵获取写入目标
+enum NpySink {
+    Dir(PathBuf),
+    Npz(ZipWriter<BufWriter<File>>),
+}
+
+impl NpySink {
+    fn entry(&mut self, name: &str) -> TLCResult<Box<dyn Write + '_>> {
+        match self {
+            NpySink::Dir(dir) => {
+                let npy_path = dir.join(format!("{}.npy", name));
+                let file =
+                    File::create(&npy_path).map_err(|err| awsl!(DataSaveError, err, npy_path))?;
+                Ok(Box::new(BufWriter::new(file)))
+            }
+            NpySink::Npz(zip) => {
+                // 与np.savez一致，不压缩
+                let options = FileOptions::default().compression_method(CompressionMethod::Stored);
+                zip.start_file(format!("{}.npy", name), options)
+                    .map_err(|err| awsl!(DataSaveError, err, name))?;
+                Ok(Box::new(zip))
+            }
+        }
+    }
+}
+
+/// 数采文件中的一个通道
+#[derive(Debug, Serialize)]
+pub struct DAQChannel {
+    /// 对应数采矩阵的列数
+    pub column_num: usize,
+    /// .tdms为`group/channel`，其余格式按列编号
+    pub name: String,
+    /// 通道属性
+    pub properties: Vec<(String, String)>,
+}
+
+pub struct VideoCtx(Mutex<Context>);
+
+impl Deref for VideoCtx {
+    type Target = Mutex<Context>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+pub struct Decoder {
+    decoder: RefCell<ffmpeg::decoder::Video>,
+    sws_ctx: RefCell<SendCtx>,
+    src_frame: RefCell<Video>,
+    dst_frame: RefCell<Video>,
+}
+
+impl Decoder {
+    fn new(video_ctx: &VideoCtx, compress: bool) -> TLCResult<Self> {
+        let decoder = video_ctx
+            .lock()
+            .map_err(|err| awsl!(err))?
+            .clone()
+            .decoder()
+            .video()
+            .map_err(|err| awsl!(VideoError, err, ""))?;
+        let (h, w) = (decoder.height(), decoder.width());
+        let sws_ctx = ffmpeg::software::scaling::Context::get(
+            decoder.format(),
+            w,
+            h,
+            format::Pixel::RGB24,
+            if compress { w / COMPRESSION_RATIO } else { w },
+            if compress { h / COMPRESSION_RATIO } else { h },
+            Flags::FAST_BILINEAR,
+        )
+        .map_err(|err| awsl!(VideoError, err, ""))?;
+
+        Ok(Self {
+            decoder: RefCell::new(decoder),
+            sws_ctx: RefCell::new(SendCtx(sws_ctx)),
+            src_frame: RefCell::new(Video::empty()),
+            dst_frame: RefCell::new(Video::empty()),
+        })
+    }
+
+    fn decode(&self, packet: &Packet) -> TLCResult<Ref<Video>> {
+        let mut decoder = self.decoder.borrow_mut();
+        let mut sws_ctx = self.sws_ctx.borrow_mut();
+        let mut src_frame = self.src_frame.borrow_mut();
+        let mut dst_frame = self.dst_frame.borrow_mut();
+
+        decoder
+            .send_packet(packet)
+            .map_err(|err| awsl!(VideoError, err, "发送数据包错误"))?;
+        decoder
+            .receive_frame(&mut src_frame)
+            .map_err(|err| awsl!(VideoError, err, "接受数据帧错误"))?;
+        sws_ctx
+            .run(&src_frame, &mut dst_frame)
+            .map_err(|err| awsl!(VideoError, err, "颜色转换错误"))?;
+        drop(dst_frame);
+
+        Ok(self.dst_frame.borrow())
+    }
+
+    /// 从关键帧开始依次解码`packets`，返回显示时间戳为`target`的一帧，
+    /// `target`为None时返回最后一帧；帧间编码的视频预览时使用
+    fn decode_seek(&self, packets: &[Packet], target: Option<i64>) -> TLCResult<Ref<Video>> {
+        let mut decoder = self.decoder.borrow_mut();
+        let mut sws_ctx = self.sws_ctx.borrow_mut();
+        let mut src_frame = self.src_frame.borrow_mut();
+        let mut dst_frame = self.dst_frame.borrow_mut();
+
+        let mut frame = Video::empty();
+        let mut found = false;
+        let mut sent = Ok(());
+        'send: for packet in packets.iter().map(Some).chain(std::iter::once(None)) {
+            if let Err(err) = match packet {
+                Some(packet) => decoder.send_packet(packet),
+                None => decoder.send_eof(),
+            } {
+                sent = Err(awsl!(VideoError, err, "发送数据包错误"));
+                break;
+            }
+            while decoder.receive_frame(&mut frame).is_ok() {
+                std::mem::swap(&mut *src_frame, &mut frame);
+                found = true;
+                if let (Some(target), Some(pts)) = (target, src_frame.pts()) {
+                    if pts >= target {
+                        break 'send;
+                    }
+                }
+            }
+        }
+        // 出错时也要清空解码器内部缓存的帧，下次从新的关键帧开始
+        decoder.flush();
+        sent?;
+        if !found {
+            return Err(awsl!(VideoError, "", "接受数据帧错误"));
+        }
+        sws_ctx
+            .run(&src_frame, &mut dst_frame)
+            .map_err(|err| awsl!(VideoError, err, "颜色转换错误"))?;
+        drop(dst_frame);
+
+        Ok(self.dst_frame.borrow())
+    }
+}
+
+/// 按解码顺序读取视频流的数据包，不缓存
+struct PacketReader {
+    video_path: String,
+    input: format::context::Input,
+    stream_index: usize,
+}
+
+impl PacketReader {
+    fn open(video_path: &str) -> TLCResult<Self> {
+        ffmpeg::init().map_err(|err| awsl!(VideoError, err, "ffmpeg初始化错误，建议重装"))?;
+        let input = format::input(&video_path).map_err(|_| awsl!(VideoIOError, video_path))?;
+        let stream_index = input
+            .streams()
+            .best(Type::Video)
+            .ok_or(awsl!(VideoError, "找不到视频流", video_path))?
+            .index();
+
+        Ok(Self {
+            video_path: video_path.to_owned(),
+            input,
+            stream_index,
+        })
+    }
+
+    fn codec(&self) -> TLCResult<Context> {
+        let stream = self.input.stream(self.stream_index).ok_or(awsl!())?;
+
+        Ok(stream.codec())
+    }
+
+    /// 读取至多`n`个数据包，读到文件末尾时少于`n`个
+    fn read(&mut self, n: usize) -> Vec<Packet> {
+        let index = self.stream_index;
+        self.input
+            .packets()
+            .filter(|(stream, _)| stream.index() == index)
+            .map(|(_, packet)| packet)
+            .take(n)
+            .collect()
+    }
+
+    /// 跳过`n`个数据包，只读取不解码
+    fn skip(&mut self, n: usize) -> usize {
+        let index = self.stream_index;
+        self.input
+            .packets()
+            .filter(|(stream, _)| stream.index() == index)
+            .take(n)
+            .count()
+    }
+
+    /// 跳转到第`frame`帧之前最近的关键帧，读取到第`frame`帧为止，同时返回该帧的时间戳
+    ///
+    /// 视频没有时间戳时从头读取`frame + 1`个数据包，时间戳为None
+    fn seek(mut self, frame: usize) -> TLCResult<(Vec<Packet>, Option<i64>)> {
+        let stream = self.input.stream(self.stream_index).ok_or(awsl!())?;
+        let (time_base, frame_rate) = (stream.time_base(), stream.avg_frame_rate());
+        // 首帧的时间戳不一定为0，未知时为AV_NOPTS_VALUE
+        let start_time = match stream.start_time() {
+            i64::MIN => 0,
+            start_time => start_time,
+        };
+        let (tb_num, tb_den) = (time_base.numerator() as i64, time_base.denominator() as i64);
+        let (fr_num, fr_den) = (
+            frame_rate.numerator() as i64,
+            frame_rate.denominator() as i64,
+        );
+        if tb_num > 0 && fr_num > 0 {
+            let target = start_time + frame as i64 * fr_den * tb_den / (fr_num * tb_num);
+            let position = target * tb_num * SEEK_TIME_BASE / tb_den;
+            if self.input.seek(position, ..=position).is_ok() {
+                let index = self.stream_index;
+                let mut packets = Vec::new();
+                for (stream, packet) in self.input.packets() {
+                    if stream.index() != index || (packets.is_empty() && !packet.is_key()) {
+                        continue;
+                    }
+                    match packet.dts().or_else(|| packet.pts()) {
+                        Some(ts) if ts <= target => packets.push(packet),
+                        Some(_) => break,
+                        None => {
+                            packets.clear();
+                            break;
+                        }
+                    }
+                }
+                if !packets.is_empty() {
+                    return Ok((packets, Some(target)));
+                }
+            }
+        }
+
+        let packets = Self::open(&self.video_path)?.read(frame + 1);
+        if packets.len() <= frame {
+            return Err(awsl!(
+                VideoError,
+                format!("视频只有{}帧", packets.len()),
+                self.video_path
+            ));
+        }
+
+        Ok((packets, None))
+    }
+}
+
+/// 逐帧编码并写出视频，帧的时间单位为1/帧率
+pub(crate) struct VideoWriter {
+    output: format::context::Output,
+    encoder: encoder::video::Encoder,
+    stream_index: usize,
+    time_base: (i32, i32),
+    stream_time_base: ffmpeg::Rational,
+    frame: Video,
+    frame_index: i64,
+    video_path: PathBuf,
+}
+
+impl VideoWriter {
+    /// `bit_rate`为None时使用编码器的默认码率，rawvideo等无损编码忽略码率
+    pub(crate) fn create<P: AsRef<Path>>(
+        video_path: P,
+        codec_id: codec::Id,
+        pixel: format::Pixel,
+        (height, width): (usize, usize),
+        frame_rate: usize,
+        bit_rate: Option<usize>,
+    ) -> TLCResult<Self> {
+        let video_path = video_path.as_ref().to_owned();
+        let err = |err| awsl!(VideoError, err, video_path);
+        let time_base = (1, frame_rate as i32);
+
+        ffmpeg::init().map_err(|err| awsl!(VideoError, err, "ffmpeg初始化错误，建议重装"))?;
+        let mut output = format::output(&video_path).map_err(err)?;
+        let codec =
+            encoder::find(codec_id).ok_or(awsl!(VideoError, codec_id, "找不到视频编码器"))?;
+        let global_header = output
+            .format()
+            .flags()
+            .contains(format::flag::Flags::GLOBAL_HEADER);
+        let mut stream = output.add_stream(codec).map_err(err)?;
+        let mut encoder = stream.codec().encoder().video().map_err(err)?;
+        encoder.set_width(width as u32);
+        encoder.set_height(height as u32);
+        encoder.set_format(pixel);
+        encoder.set_time_base(time_base);
+        encoder.set_frame_rate(Some((frame_rate as i32, 1)));
+        if let Some(bit_rate) = bit_rate {
+            encoder.set_bit_rate(bit_rate);
+        }
+        // mp4等封装格式要求编码参数写在文件头中
+        if global_header {
+            encoder.set_flags(codec::flag::Flags::GLOBAL_HEADER);
+        }
+        let encoder = encoder.open_as(codec).map_err(err)?;
+        stream.set_parameters(&encoder);
+        let stream_index = stream.index();
+        output.write_header().map_err(err)?;
+        // 封装格式可能修改流的时间单位
+        let stream_time_base = output.stream(stream_index).ok_or(awsl!())?.time_base();
+
+        Ok(Self {
+            output,
+            encoder,
+            stream_index,
+            time_base,
+            stream_time_base,
+            frame: Video::new(pixel, width as u32, height as u32),
+            frame_index: 0,
+            video_path,
+        })
+    }
+
+    /// 下一帧的缓冲，填充后调用`write_frame`
+    pub(crate) fn frame_mut(&mut self) -> &mut Video {
+        &mut self.frame
+    }
+
+    pub(crate) fn write_frame(&mut self) -> TLCResult<()> {
+        self.frame.set_pts(Some(self.frame_index));
+        self.frame_index += 1;
+        self.encoder
+            .send_frame(&self.frame)
+            .map_err(|err| awsl!(VideoError, err, self.video_path))?;
+
+        self.write_packets()
+    }
+
+    fn write_packets(&mut self) -> TLCResult<()> {
+        let mut packet = Packet::empty();
+        while self.encoder.receive_packet(&mut packet).is_ok() {
+            packet.set_stream(self.stream_index);
+            packet.rescale_ts(self.time_base, self.stream_time_base);
+            packet
+                .write_interleaved(&mut self.output)
+                .map_err(|err| awsl!(VideoError, err, self.video_path))?;
+        }
+
+        Ok(())
+    }
+
+    /// 写出编码器中剩余的帧与文件尾
+    pub(crate) fn finish(mut self) -> TLCResult<()> {
+        self.encoder
+            .send_eof()
+            .map_err(|err| awsl!(VideoError, err, self.video_path))?;
+        self.write_packets()?;
+
+        self.output
+            .write_trailer()
+            .map_err(|err| awsl!(VideoError, err, self.video_path))
+    }
+}
+
+impl TLCData {
+    /// 压缩后的视频帧，编码为.jpg
+    pub fn get_frame(&mut self, frame_index: usize) -> TLCResult<Vec<u8>> {
+        let total_frames = self.config.total_frames;
+        if total_frames > 0 && frame_index >= total_frames {
+            return Err(TLCError::FrameError {
+                frame: frame_index,
+                frame_num: total_frames,
+            });
+        }
+        if self.video_ctx.is_none() {
+            self.video_ctx.insert(self.create_video_ctx()?);
+            self.computed(Stage::Video);
+        }
+        if self.decoder_tool.is_none() {
+            self.decoder_tool
+                .insert(Decoder::new(self.get_video_ctx()?, true)?);
+        }
+
+        // 只读取目标帧所在的一段，不必等待整个视频读完
+        let (packets, target) = PacketReader::open(&self.config.video_path)?.seek(frame_index)?;
+        let decoder = self.get_decoder()?;
+        let dst_frame = decoder.decode_seek(&packets, target)?;
+
+        let (src_h, src_w) = self.get_config().video_shape;
+        let dst_h = src_h as u32 / COMPRESSION_RATIO;
+        let dst_w = src_w as u32 / COMPRESSION_RATIO;
+        let mut buf = Vec::with_capacity((dst_h * dst_w >> 2) as usize);
+
+        let mut jpeg_encoder = image::jpeg::JpegEncoder::new(&mut buf);
+        jpeg_encoder
+            .encode(dst_frame.data(0), dst_w, dst_h, image::ColorType::Rgb8)
+            .map_err(|err| awsl!(err))?;
+
+        Ok(buf)
+    }
+
+    /// 修改计算区域前调用，保留当前的raw_g2d供新区域复用
+    pub(crate) fn keep_raw_g2d(&mut self) {
+        if let Some(g2d) = self.raw_g2d.take() {
+            self.previous_g2d = Some(PreviousG2d {
+                g2d,
+                top_left_pos: self.config.top_left_pos,
+                region_shape: self.config.region_shape,
+                reference_g: self.reference_g.take(),
+                reference_patch: self.config.reference_patch,
+                start_frame: self.config.start_frame,
+                frame_num: self.config.frame_num,
+                video_hash: self.config.stage_hashes()[&Stage::Video],
+            });
+        }
+    }
+
+    pub fn read_video(&mut self) -> TLCResult<&mut Self> {
+        self.in_pool(Self::decode_g2d)?
+    }
+
+    fn decode_g2d(&mut self) -> TLCResult<&mut Self> {
+        let timer = self.start_stage("decode");
+        let TLCConfig {
+            top_left_pos,
+            region_shape,
+            start_frame,
+            frame_num,
+            video_shape,
+            reference_patch,
+            ..
+        } = self.config;
+
+        // 左上角坐标
+        let (tl_y, tl_x) = top_left_pos;
+        // 区域尺寸
+        let (cal_h, cal_w) = region_shape;
+        // 总像素点数
+        let pix_num = cal_h * cal_w;
+        // 视频帧一行实际字节数
+        let real_w = (video_shape.1 * 3) as usize;
+
+        // 重叠部分要用到修改区域前的raw_g2d
+        self.reserve_memory(frame_num * pix_num, &[Stage::RawG2d]);
+        let mut g2d = Array2::zeros((frame_num, pix_num));
+        // 需要从视频中提取的像素（在g2d中的列号，在帧数据中的字节序号）
+        let mut offsets = Vec::with_capacity(pix_num);
+        let video_hash = self.config.stage_hashes()[&Stage::Video];
+        let previous = self.previous_g2d.take().filter(|p| {
+            (p.start_frame, p.frame_num, p.video_hash) == (start_frame, frame_num, video_hash)
+        });
+        let columns = match &previous {
+            Some(p) => overlap_columns(
+                (p.top_left_pos, p.region_shape),
+                (top_left_pos, region_shape),
+            ),
+            None => vec![None; pix_num],
+        };
+        for (pos, column) in columns.into_iter().enumerate() {
+            match (&previous, column) {
+                (Some(p), Some(column)) => g2d.column_mut(pos).assign(&p.g2d.column(column)),
+                _ => {
+                    let (y, x) = (tl_y + pos / cal_w, tl_x + pos % cal_w);
+                    // 每个像素依次为r g b，取g
+                    offsets.push((pos, y * real_w + x * 3 + 1));
+                }
+            }
+        }
+        let mut reference_g = previous
+            .as_ref()
+            .filter(|p| p.reference_patch == reference_patch)
+            .and_then(|p| p.reference_g.clone());
+        let reference_offsets = match (reference_patch, &reference_g) {
+            (Some(patch), None) => reference_offsets(patch, video_shape)?,
+            _ => Vec::new(),
+        };
+        drop(previous);
+        // 新区域完全在旧区域内且参考区域不变时不必解码
+        if offsets.is_empty() && reference_offsets.is_empty() {
+            self.raw_g2d = Some(g2d);
+            self.reference_g = reference_g;
+            self.computed(Stage::RawG2d);
+            self.finish(timer);
+            return Ok(self);
+        }
+
+        let mut reference_row = vec![0.; frame_num];
+        let mut outputs: Vec<_> = g2d
+            .axis_iter_mut(Axis(0))
+            .zip(reference_row.iter_mut())
+            .collect();
+        self.decode_frames(1, &mut outputs, |rgb, (row, reference)| {
+            extract_green(rgb, &offsets, row.view_mut());
+            if !reference_offsets.is_empty() {
+                let sum: u32 = reference_offsets.iter().map(|&j| rgb[j] as u32).sum();
+                **reference = sum as f32 / reference_offsets.len() as f32;
+            }
+        })?;
+        drop(outputs);
+        if !reference_offsets.is_empty() {
+            reference_g = Some(reference_row);
+        }
+        self.raw_g2d.insert(g2d);
+        self.reference_g = reference_g;
+        self.computed(Stage::RawG2d);
+        self.drop_video();
+        self.finish(timer);
+
+        Ok(self)
+    }
+
+    /// 从起始帧开始每隔`frame_step`帧解码一帧，`groups`中每组像素（计算区域内的序号）
+    /// 取平均Green值作为一列，同时返回参考区域的平均Green值；不改变raw_g2d，用于快速预览
+    ///
+    /// 空的组各帧均为0
+    pub(crate) fn read_grouped(
+        &mut self,
+        frame_step: usize,
+        groups: &[Vec<usize>],
+    ) -> TLCResult<(Array2<u8>, Option<Vec<f32>>)> {
+        let TLCConfig {
+            top_left_pos: (tl_y, tl_x),
+            region_shape: (_, cal_w),
+            frame_num,
+            video_shape,
+            reference_patch,
+            ..
+        } = self.config;
+        let real_w = video_shape.1 * 3;
+        let offsets: Vec<Vec<usize>> = groups
+            .iter()
+            .map(|group| {
+                group
+                    .iter()
+                    .map(|&pos| (tl_y + pos / cal_w) * real_w + (tl_x + pos % cal_w) * 3 + 1)
+                    .collect()
+            })
+            .collect();
+        let reference_offsets = match reference_patch {
+            Some(patch) => reference_offsets(patch, video_shape)?,
+            None => Vec::new(),
+        };
+        let frames = (frame_num + frame_step - 1) / frame_step;
+
+        let mut g2d = Array2::zeros((frames, groups.len()));
+        let mut reference_row = vec![0.; frames];
+        let mut outputs: Vec<_> = g2d
+            .axis_iter_mut(Axis(0))
+            .zip(reference_row.iter_mut())
+            .collect();
+        self.decode_frames(frame_step, &mut outputs, |rgb, (row, reference)| {
+            for (g, group) in row.iter_mut().zip(&offsets) {
+                if !group.is_empty() {
+                    let sum: u32 = group.iter().map(|&j| rgb[j] as u32).sum();
+                    *g = ((sum + group.len() as u32 / 2) / group.len() as u32) as u8;
+                }
+            }
+            if !reference_offsets.is_empty() {
+                let sum: u32 = reference_offsets.iter().map(|&j| rgb[j] as u32).sum();
+                **reference = sum as f32 / reference_offsets.len() as f32;
+            }
+        })?;
+        drop(outputs);
+
+        Ok((g2d, reference_patch.map(|_| reference_row)))
+    }
+
+    /// 从起始帧开始分块读取数据包并逐块并行解码，每隔`frame_step`帧取一帧，
+    /// 按顺序交给`f`写入`outputs`中的一项；读取在单独的线程中与解码交替进行
+    fn decode_frames<T, F>(&mut self, frame_step: usize, outputs: &mut [T], f: F) -> TLCResult<()>
+    where
+        T: Send,
+        F: Fn(&[u8], &mut T) + Sync,
+    {
+        if self.video_ctx.is_none() {
+            self.video_ctx = Some(self.create_video_ctx()?);
+            self.computed(Stage::Video);
+        }
+        let TLCConfig {
+            ref video_path,
+            start_frame,
+            frame_num,
+            ..
+        } = self.config;
+        let mut reader = PacketReader::open(video_path)?;
+        let (sender, receiver) = sync_channel(1);
+        let reading = std::thread::spawn(move || {
+            reader.skip(start_frame);
+            let mut remaining = frame_num;
+            while remaining > 0 {
+                let chunk = reader.read(remaining.min(PACKET_CHUNK));
+                let len = chunk.len();
+                // 解码出错时接收端提前析构，发送失败即停止读取
+                if len == 0 || sender.send(chunk).is_err() {
+                    break;
+                }
+                remaining -= len;
+            }
+        });
+
+        let ctx_mutex = self.get_video_ctx()?;
+        let tls = ThreadLocal::new();
+        let (mut frame, mut decoded) = (0, 0);
+        for chunk in receiver {
+            let picked: Vec<_> = chunk
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| (frame + i) % frame_step == 0)
+                .map(|(_, packet)| packet)
+                .collect();
+            let end = (decoded + picked.len()).min(outputs.len());
+            outputs[decoded..end]
+                .par_iter_mut()
+                .zip(picked.into_par_iter())
+                .try_for_each(|(output, packet)| -> TLCResult<()> {
+                    let dst_frame = tls
+                        .get_or_try(|| Decoder::new(ctx_mutex, false))?
+                        .decode(packet)?;
+                    f(dst_frame.data(0), output);
+
+                    Ok(())
+                })?;
+            frame += chunk.len();
+            decoded = end;
+        }
+        // 确保thread local析构
+        tls.into_iter().for_each(|v| drop(v));
+        reading
+            .join()
+            .map_err(|_| awsl!(VideoError, "读取数据包的线程出错", video_path))?;
+        if decoded < outputs.len() {
+            return Err(awsl!(
+                VideoError,
+                format!("视频从起始帧起只有{}帧", frame),
+                video_path
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub fn read_daq(&mut self) -> TLCResult<&mut Self> {
+        let timer = self.start_stage("daq");
+        self.daq.insert(self.config.read_daq()?);
+        self.computed(Stage::Daq);
+        self.finish(timer);
+
+        Ok(self)
+    }
+
+    pub fn drop_video(&mut self) {
+        // 解码相关内存析构
+        self.video_ctx.take();
+        self.decoder_tool.take();
+    }
+
+    /// Nu云图，编码为.png
+    pub fn get_nu_img(&mut self, range: Option<(f32, f32)>) -> TLCResult<Vec<u8>> {
+        let timer = self.start_stage("plot");
+        self.config.refresh_paths()?;
+        let (vmin, vmax) = match range {
+            Some(range) => range,
+            None => {
+                let nu_nan_mean = self.get_nu_nan_mean()?;
+                (nu_nan_mean * 0.6, nu_nan_mean * 2.)
+            }
+        };
+        let colormap = self.config.colormap.lut(self.config.reverse_colormap)?;
+        postprocess::plot_area(
+            &self.config.plots_path,
+            self.get_smoothed_nu2d()?,
+            vmin,
+            vmax,
+            &colormap,
+        )?;
+        self.finish(timer);
+
+        std::fs::read(&self.config.plots_path).map_err(|err| awsl!(err))
+    }
+
+    /// 在完整分辨率的视频帧上画出计算区域、热电偶位置及Nu等值线，用于检查配置中的坐标
+    ///
+    /// `contour_levels`为空时不画等值线，也不需要已求解的Nu分布，返回.png
+    pub fn get_setup_overlay(
+        &mut self,
+        frame_index: usize,
+        contour_levels: &[f32],
+    ) -> TLCResult<Vec<u8>> {
+        if self.video_ctx.is_none() {
+            self.video_ctx.insert(self.create_video_ctx()?);
+            self.computed(Stage::Video);
+        }
+
+        let (packets, target) = PacketReader::open(&self.config.video_path)?.seek(frame_index)?;
+        // 预览用的decoder_tool是压缩过的，这里单独解码一帧原始尺寸的图像
+        let decoder = Decoder::new(self.get_video_ctx()?, false)?;
+        let dst_frame = decoder.decode_seek(&packets, target)?;
+        let (h, w) = self.config.video_shape;
+        let stride = dst_frame.stride(0);
+        let mut rgb = Vec::with_capacity(h * w * 3);
+        dst_frame
+            .data(0)
+            .chunks(stride)
+            .take(h)
+            .for_each(|row| rgb.extend_from_slice(&row[..w * 3]));
+        drop(dst_frame);
+
+        let thermocouples = self
+            .config
+            .thermocouples
+            .iter()
+            .enumerate()
+            .map(|(i, tc)| (tc.describe(i), tc.pos))
+            .collect();
+        let contours = if contour_levels.is_empty() {
+            None
+        } else {
+            Some((self.get_smoothed_nu2d()?, contour_levels))
+        };
+        let overlay = postprocess::Overlay {
+            top_left_pos: self.config.top_left_pos,
+            region_shape: self.config.region_shape,
+            thermocouples,
+            contours,
+        };
+
+        let overlay_path = Path::new(&self.config.plots_path)
+            .with_file_name(format!("{}_setup.png", self.config.case_name));
+        postprocess::plot_overlay(&overlay_path, rgb, (h, w), &overlay)?;
+
+        std::fs::read(&overlay_path).map_err(|err| awsl!(err))
+    }
+
+    /// 导出`[start, end)`帧完整分辨率的参考温度场，返回导出路径
+    ///
+    /// 各帧的实验时间另存为同名的`_times.csv`，逐帧.csv时保存在目录中，.h5时写入同一文件
+    pub fn export_interp(
+        &mut self,
+        (start, end): (usize, usize),
+        format: FieldFormat,
+    ) -> TLCResult<String> {
+        if self.interp.is_none() {
+            self.interp()?;
+        }
+        if self.frame_times.is_none() {
+            self.init_frame_times()?;
+        }
+        if start >= end {
+            return Err(awsl!(HandleError, "帧数超出范围"));
+        }
+        if end > self.config.frame_num {
+            return Err(TLCError::FrameError {
+                frame: end,
+                frame_num: self.config.frame_num,
+            });
+        }
+
+        let region_shape = self.config.region_shape;
+        let interp = self.get_interp()?;
+        let data_path = Path::new(&self.config.data_path);
+        let export_path = data_path.with_file_name(format!("{}_interp", self.config.case_name));
+        let frames: Vec<_> = (start..end).map(|frame| frame as f32).collect();
+        let times = &self.get_frame_times()?[start..end];
+        let times_header = ["frame", "time"];
+
+        let export_path = match format {
+            FieldFormat::CsvStack => {
+                let meta = self.data_meta("°C", false)?;
+                create_dir_all(&export_path)
+                    .map_err(|err| awsl!(CreateDirError, err, export_path))?;
+                for frame in start..end {
+                    let field = interp.frame_field(frame, region_shape)?;
+                    let csv_path = export_path.join(format!("{}.csv", frame));
+                    save_data(field.view(), csv_path, Some(&meta))?;
+                }
+                let times_path = export_path.join("times.csv");
+                save_table(times_path, &times_header, &[&frames, times])?;
+                export_path
+            }
+            FieldFormat::Npy => {
+                let (cal_h, cal_w) = region_shape;
+                let mut fields = Array3::zeros((end - start, cal_h, cal_w));
+                for (frame, mut field) in (start..end).zip(fields.outer_iter_mut()) {
+                    field.assign(&interp.frame_field(frame, region_shape)?);
+                }
+                let npy_path = export_path.with_extension("npy");
+                save_npy(fields.view(), &npy_path)?;
+                let times_path = export_path
+                    .with_file_name(format!("{}_interp_times.csv", self.config.case_name));
+                save_table(times_path, &times_header, &[&frames, times])?;
+                npy_path
+            }
+            #[cfg(feature = "hdf5")]
+            FieldFormat::Hdf5 => {
+                let h5_path = export_path.with_extension("h5");
+                let save_err = |err: hdf5::Error| awsl!(DataSaveError, err, h5_path);
+                let file = hdf5::File::create(&h5_path).map_err(save_err)?;
+                write_interp_dataset(&file, interp, (start, end), region_shape, &h5_path)?;
+                let frames = ArrayView1::from(&frames);
+                write_dataset(&file, "frames", frames).map_err(save_err)?;
+                let times = ArrayView1::from(times);
+                write_dataset(&file, "frame_times", times).map_err(save_err)?;
+                h5_path
+            }
+        };
+
+        Ok(export_path.to_str().ok_or(awsl!(export_path))?.to_owned())
+    }
+
+    /// `save_data`所需的文件头信息
+    pub fn data_meta(&self, unit: &'static str, flipped: bool) -> TLCResult<DataMeta> {
+        Ok(DataMeta {
+            options: self.config.export_options,
+            case_name: self.config.case_name.clone(),
+            config_hash: self.config.hash()?,
+            input_hashes: self.config.input_hashes.clone(),
+            unit,
+            pixel_size: self.config.pixel_size,
+            flipped,
+        })
+    }
+
+    /// 图片与数据目录下以工况名开头的文件及按模板命名的Nu云图与Nu矩阵，返回（"plots"或"data"，路径）
+    pub(crate) fn case_outputs(&self) -> TLCResult<Vec<(&'static str, PathBuf)>> {
+        let case_name = &self.config.case_name;
+        let dirs = [
+            ("plots", &self.config.plots_path),
+            ("data", &self.config.data_path),
+        ];
+        let mut outputs = Vec::new();
+        for &(dir_name, path_str) in dirs.iter() {
+            let dir = Path::new(path_str).parent().ok_or(awsl!(path_str))?;
+            let entries = std::fs::read_dir(dir).map_err(|err| awsl!(DataReadError, err, dir))?;
+            for entry in entries.filter_map(|entry| entry.ok()) {
+                let path = entry.path();
+                let name = entry.file_name().to_string_lossy().into_owned();
+                if path.is_file()
+                    && (name.starts_with(case_name.as_str()) || path == Path::new(path_str))
+                {
+                    outputs.push((dir_name, path));
+                }
+            }
+        }
+        outputs.sort();
+
+        Ok(outputs)
+    }
+
+    /// 将配置、Nu与h矩阵、本工况的图片与表格、结果摘要及输入文件校验和打包为一个.zip，
+    /// 保存在`save_dir`下，返回保存路径
+    pub fn export_bundle(&self) -> TLCResult<String> {
+        let TLCConfig {
+            characteristic_length,
+            air_thermal_conductivity,
+            ref case_name,
+            ..
+        } = self.config;
+        let bundle_path =
+            Path::new(&self.config.save_dir).join(format!("{}_bundle.zip", case_name));
+        let file =
+            File::create(&bundle_path).map_err(|err| awsl!(DataSaveError, err, bundle_path))?;
+        let mut zip = ZipWriter::new(BufWriter::new(file));
+        let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+
+        zip.start_file("config.json", options)
+            .map_err(|err| awsl!(DataSaveError, err, bundle_path))?;
+        to_writer_pretty(&mut zip, &self.config).map_err(|err| awsl!(ConfigError, err))?;
+
+        // 打包的矩阵总是带文件头，保证脱离原目录后仍可追溯
+        let nu2d = self.get_nu2d()?;
+        let h2d = nu2d.mapv(|nu| nu * air_thermal_conductivity / characteristic_length);
+        for (name, data, unit) in [("nu.csv", nu2d, "Nu"), ("h.csv", h2d.view(), "W/(m^2*K)")] {
+            let mut meta = self.data_meta(unit, true)?;
+            meta.options.header = true;
+            zip.start_file(name, options)
+                .map_err(|err| awsl!(DataSaveError, err, bundle_path))?;
+            write_data(&mut zip, data, Some(&meta))
+                .map_err(|err| awsl!(DataSaveError, err, bundle_path))?;
+        }
+
+        // 本工况已保存的图片与表格，大体积的矩阵导出不打包
+        for (dir_name, path) in self.case_outputs()? {
+            let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
+            if !["png", "svg", "csv", "xlsx"].contains(&extension) {
+                continue;
+            }
+            let name = path.file_name().ok_or(awsl!(path))?.to_string_lossy();
+            let buf = std::fs::read(&path).map_err(|err| awsl!(DataReadError, err, path))?;
+            zip.start_file(format!("{}/{}", dir_name, name), options)
+                .map_err(|err| awsl!(DataSaveError, err, bundle_path))?;
+            zip.write_all(&buf)
+                .map_err(|err| awsl!(DataSaveError, err, bundle_path))?;
+        }
+
+        let tc_status = self.tc_status.as_ref();
+        let summary = serde_json::json!({
+            "case_name": case_name,
+            "config_hash": self.config.hash()?,
+            "input_hashes": self.config.input_hashes,
+            "date": chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            "nu_nan_mean": self.get_nu_nan_mean()?,
+            "valid_pixels": nu2d.iter().filter(|nu| !nu.is_nan()).count(),
+            "total_pixels": nu2d.len(),
+            "thermocouples": tc_status.map(|status| {
+                let names = self.config.thermocouples.iter().enumerate();
+                let tcs = names.zip(status).map(|((i, tc), s)| {
+                    serde_json::json!({
+                        "name": tc.name(i),
+                        "location": tc.location,
+                        "status": s,
+                    })
+                });
+                tcs.collect::<Vec<_>>()
+            }),
+        });
+        zip.start_file("summary.json", options)
+            .map_err(|err| awsl!(DataSaveError, err, bundle_path))?;
+        to_writer_pretty(&mut zip, &summary).map_err(|err| awsl!(err))?;
+
+        let manifest =
+            sha256_line(&self.config.video_path)? + sha256_line(&self.config.daq_path)?.as_str();
+        zip.start_file("manifest.sha256", options)
+            .map_err(|err| awsl!(DataSaveError, err, bundle_path))?;
+        zip.write_all(manifest.as_bytes())
+            .map_err(|err| awsl!(DataSaveError, err, bundle_path))?;
+
+        zip.finish()
+            .map_err(|err| awsl!(DataSaveError, err, bundle_path))?;
+
+        Ok(bundle_path.to_str().ok_or(awsl!(bundle_path))?.to_owned())
+    }
+
+    /// 将Nu、滤波后的绿色通道、参考温度及插值温度场导出为.npy或.npz，尚未计算的数据跳过，
+    /// 返回导出路径
+    pub fn export_arrays(&self, format: ArrayFormat) -> TLCResult<String> {
+        let data_path = Path::new(&self.config.data_path);
+        let export_path = data_path.with_file_name(format!("{}_arrays", self.config.case_name));
+        let (export_path, mut sink) = match format {
+            ArrayFormat::Npy => {
+                create_dir_all(&export_path)
+                    .map_err(|err| awsl!(CreateDirError, err, export_path))?;
+                (export_path.clone(), NpySink::Dir(export_path))
+            }
+            ArrayFormat::Npz => {
+                let npz_path = export_path.with_extension("npz");
+                let file =
+                    File::create(&npz_path).map_err(|err| awsl!(DataSaveError, err, npz_path))?;
+                (npz_path, NpySink::Npz(ZipWriter::new(BufWriter::new(file))))
+            }
+        };
+        let save_err = |err| awsl!(DataSaveError, err, export_path);
+
+        if let Some(nu2d) = &self.nu2d {
+            write_npy(&mut sink.entry("nu2d")?, nu2d.view()).map_err(save_err)?;
+        }
+        if let Some(filtered_g2d) = &self.filtered_g2d {
+            write_npy(&mut sink.entry("filtered_g2d")?, filtered_g2d.view()).map_err(save_err)?;
+        }
+        if let Some(t2d) = &self.t2d {
+            write_npy(&mut sink.entry("t2d")?, t2d.view()).map_err(save_err)?;
+        }
+        if let Some(frame_times) = &self.frame_times {
+            let frame_times = ArrayView1::from(frame_times);
+            write_npy(&mut sink.entry("frame_times")?, frame_times).map_err(save_err)?;
+        }
+        if let Some(interp) = &self.interp {
+            // 逐帧写入(帧数, 高, 宽)，不在内存中展开完整的温度场
+            let TLCConfig {
+                frame_num,
+                region_shape,
+                ..
+            } = self.config;
+            let mut writer = sink.entry("interp")?;
+            let shape = [frame_num, region_shape.0, region_shape.1];
+            write_npy_header(&mut writer, f32::DESCR, &shape).map_err(save_err)?;
+            for frame in 0..frame_num {
+                for &t in interp.frame_field(frame, region_shape)?.iter() {
+                    t.write_le(&mut writer).map_err(save_err)?;
+                }
+            }
+            writer.flush().map_err(save_err)?;
+        }
+        if let NpySink::Npz(mut zip) = sink {
+            zip.finish()
+                .map_err(|err| awsl!(DataSaveError, err, export_path))?;
+        }
+
+        Ok(export_path.to_str().ok_or(awsl!(export_path))?.to_owned())
+    }
+
+    /// 以filtered_g2d为灰度重新生成计算区域的视频，保存为`{case_name}_filtered.mp4`，
+    /// 求解前逐帧确认滤波没有破坏显色过程，返回保存路径
+    pub fn export_filtered_video(&mut self) -> TLCResult<String> {
+        if self.filtered_g2d.is_none() {
+            self.filtering()?;
+        }
+        let TLCConfig {
+            region_shape: (cal_h, cal_w),
+            frame_rate,
+            ..
+        } = self.config;
+        // yuv420p要求宽高为偶数，为奇数时重复最后一行或一列
+        let (video_h, video_w) = ((cal_h + 1) / 2 * 2, (cal_w + 1) / 2 * 2);
+        let video_path = Path::new(&self.config.plots_path)
+            .with_file_name(format!("{}_filtered.mp4", self.config.case_name));
+        let bit_rate = video_h * video_w * frame_rate * FILTERED_VIDEO_BITS_PER_PIXEL;
+        let mut writer = VideoWriter::create(
+            &video_path,
+            codec::Id::MPEG4,
+            format::Pixel::YUV420P,
+            (video_h, video_w),
+            frame_rate,
+            Some(bit_rate),
+        )?;
+        // 色度取中间值即为灰度
+        for plane in 1..3 {
+            writer.frame_mut().data_mut(plane).fill(128);
+        }
+        for green in self.get_filtered_g2d()?.axis_iter(Axis(0)) {
+            let frame = writer.frame_mut();
+            let stride = frame.stride(0);
+            for (y, row) in frame
+                .data_mut(0)
+                .chunks_mut(stride)
+                .take(video_h)
+                .enumerate()
+            {
+                let src = &green.as_slice().ok_or(awsl!())?[y.min(cal_h - 1) * cal_w..][..cal_w];
+                row[..cal_w].copy_from_slice(src);
+                row[cal_w..video_w].fill(src[cal_w - 1]);
+            }
+            writer.write_frame()?;
+        }
+        writer.finish()?;
+
+        Ok(video_path.to_str().ok_or(awsl!(video_path))?.to_owned())
+    }
+
+    /// 导出ParaView可读的.vti，像素间距按空间标定换算为米（未标定时为1像素），返回导出路径
+    ///
+    /// `with_series`为true时另将各帧插值温度场导出为.vti序列，并生成按实验时间索引的.pvd
+    pub fn export_vtk(&self, with_series: bool) -> TLCResult<String> {
+        let TLCConfig {
+            pixel_size,
+            frame_num,
+            region_shape,
+            ..
+        } = self.config;
+        let spacing = if pixel_size > 0. {
+            pixel_size / 1000.
+        } else {
+            1.
+        };
+        let data_path = Path::new(&self.config.data_path);
+
+        let nu2d = self.get_nu2d()?;
+        let mut fields = vec![("Nu", nu2d)];
+        if let Some(smoothed_nu2d) = &self.smoothed_nu2d {
+            fields.push(("Nu_smoothed", smoothed_nu2d.view()));
+        }
+        let vti_path = data_path.with_file_name(format!("{}.vti", self.config.case_name));
+        save_vti(&vti_path, spacing, &fields)?;
+
+        if with_series {
+            let interp = self.get_interp()?;
+            let frame_times = self.get_frame_times()?;
+            let series_dir = data_path.with_file_name(format!("{}_vtk", self.config.case_name));
+            create_dir_all(&series_dir).map_err(|err| awsl!(CreateDirError, err, series_dir))?;
+
+            let mut datasets = String::new();
+            for frame in 0..frame_num {
+                // 插值温度场与nu2d保持相同的上下方向
+                let mut field = interp.frame_field(frame, region_shape)?;
+                field.invert_axis(Axis(0));
+                let file_name = format!("interp_{:05}.vti", frame);
+                save_vti(series_dir.join(&file_name), spacing, &[("T", field.view())])?;
+                datasets.push_str(&format!(
+                    "    <DataSet timestep=\"{}\" file=\"{}\"/>\n",
+                    frame_times[frame], file_name,
+                ));
+            }
+            let pvd = format!(
+                "<?xml version=\"1.0\"?>\n\
+                 <VTKFile type=\"Collection\" version=\"0.1\" byte_order=\"LittleEndian\">\n\
+                 <Collection>\n{}</Collection>\n</VTKFile>\n",
+                datasets,
+            );
+            let pvd_path = series_dir.join("interp.pvd");
+            std::fs::write(&pvd_path, pvd).map_err(|err| awsl!(DataSaveError, err, pvd_path))?;
+        }
+
+        Ok(vti_path.to_str().ok_or(awsl!(vti_path))?.to_owned())
+    }
+
+    /// 导出多页Excel报告：配置、Nu矩阵、子区域统计与热电偶温度曲线，返回保存路径
+    ///
+    /// `polygons`为空时统计整个计算区域
+    pub fn export_xlsx(&self, polygons: &[Vec<(f32, f32)>]) -> TLCResult<String> {
+        use serde_json::Value as Json;
+
+        let xlsx_path = Path::new(&self.config.data_path)
+            .with_file_name(format!("{}.xlsx", self.config.case_name));
+        let save_err = |err| awsl!(DataSaveError, err, xlsx_path);
+        let mut workbook = Workbook::new();
+
+        let sheet = workbook
+            .add_worksheet()
+            .set_name("config")
+            .map_err(save_err)?;
+        let config = serde_json::to_value(&self.config).map_err(|err| awsl!(err))?;
+        let items = config.as_object().ok_or(awsl!(config))?;
+        for (row, (key, value)) in items.iter().enumerate() {
+            let row = row as u32;
+            sheet.write_string(row, 0, key).map_err(save_err)?;
+            match value {
+                Json::Bool(b) => sheet.write_boolean(row, 1, *b),
+                Json::Number(n) => sheet.write_number(row, 1, n.as_f64().unwrap_or(f64::NAN)),
+                Json::String(s) => sheet.write_string(row, 1, s),
+                _ => sheet.write_string(row, 1, &value.to_string()),
+            }
+            .map_err(save_err)?;
+        }
+
+        // 与save_nu保存的矩阵方向一致，NaN留空
+        let nu2d = self.get_nu2d()?;
+        let sheet = workbook.add_worksheet().set_name("Nu").map_err(save_err)?;
+        for ((row, col), &nu) in nu2d.indexed_iter() {
+            if !nu.is_nan() {
+                sheet
+                    .write_number(row as u32, col as u16, nu as f64)
+                    .map_err(save_err)?;
+            }
+        }
+
+        let whole_region;
+        let polygons = if polygons.is_empty() {
+            let (h, w) = (nu2d.nrows() as f32 - 0.5, nu2d.ncols() as f32 - 0.5);
+            whole_region = [vec![(-0.5, -0.5), (-0.5, w), (h, w), (h, -0.5)]];
+            &whole_region[..]
+        } else {
+            polygons
+        };
+        let stats = postprocess::polygon_stats(self.get_smoothed_nu2d()?, polygons);
+        let sheet = workbook
+            .add_worksheet()
+            .set_name("regions")
+            .map_err(save_err)?;
+        let header = [
+            "region", "nu_mean", "nu_std", "nu_min", "nu_max", "area", "valid",
+        ];
+        for (col, title) in header.iter().enumerate() {
+            sheet
+                .write_string(0, col as u16, *title)
+                .map_err(save_err)?;
+        }
+        for (i, s) in stats.iter().enumerate() {
+            let values = [s.mean, s.std, s.min, s.max, s.area as f32, s.valid as f32];
+            let row = i as u32 + 1;
+            sheet.write_number(row, 0, i as f64).map_err(save_err)?;
+            for (col, &v) in (1..).zip(values.iter()) {
+                if !v.is_nan() {
+                    sheet.write_number(row, col, v as f64).map_err(save_err)?;
+                }
+            }
+        }
+
+        // 每个热电偶一列，首列为时间（s）
+        if let Some(t2d) = &self.t2d {
+            let sheet = workbook
+                .add_worksheet()
+                .set_name("thermocouples")
+                .map_err(save_err)?;
+            sheet.write_string(0, 0, "time").map_err(save_err)?;
+            for (row, &t) in (1..).zip(self.get_frame_times()?) {
+                sheet.write_number(row, 0, t as f64).map_err(save_err)?;
+            }
+            let tcs = self.config.thermocouples.iter().enumerate();
+            for ((i, tc), (col, temps)) in tcs.zip((1..).zip(t2d.outer_iter())) {
+                sheet
+                    .write_string(0, col, &tc.describe(i))
+                    .map_err(save_err)?;
+                for (row, &t) in (1..).zip(temps.iter()) {
+                    sheet.write_number(row, col, t as f64).map_err(save_err)?;
+                }
+            }
+        }
+
+        workbook.save(&xlsx_path).map_err(save_err)?;
+
+        Ok(xlsx_path.to_str().ok_or(awsl!(xlsx_path))?.to_owned())
+    }
+
+    /// 将已有的中间结果、Nu、h与完整配置写入同一个.h5文件，尚未计算的数据跳过，返回保存路径
+    ///
+    /// 绿色通道矩阵体积很大，`with_green`为true时才写入
+    #[cfg(feature = "hdf5")]
+    pub fn export_hdf5(&self, with_green: bool) -> TLCResult<String> {
+        use hdf5::types::VarLenUnicode;
+        use serde_json::Value as Json;
+
+        let h5_path = Path::new(&self.config.data_path)
+            .with_file_name(format!("{}.h5", self.config.case_name));
+        let save_err = |err: hdf5::Error| awsl!(DataSaveError, err, h5_path);
+        let file = hdf5::File::create(&h5_path).map_err(save_err)?;
+
+        if with_green {
+            if let Some(raw_g2d) = &self.raw_g2d {
+                write_dataset(&file, "raw_g2d", raw_g2d.view()).map_err(save_err)?;
+            }
+            if let Some(filtered_g2d) = &self.filtered_g2d {
+                write_dataset(&file, "filtered_g2d", filtered_g2d.view()).map_err(save_err)?;
+            }
+        }
+        if let Some(peak_frames) = &self.peak_frames {
+            let peak_frames: Array1<u64> = peak_frames.iter().map(|&p| p as u64).collect();
+            write_dataset(&file, "peak_frames", peak_frames.view()).map_err(save_err)?;
+        }
+        if let Some(t2d) = &self.t2d {
+            write_dataset(&file, "t2d", t2d.view()).map_err(save_err)?;
+        }
+        if let Some(frame_times) = &self.frame_times {
+            let frame_times = ArrayView1::from(frame_times);
+            write_dataset(&file, "frame_times", frame_times).map_err(save_err)?;
+        }
+        if let Some(interp) = &self.interp {
+            let frames = (0, self.config.frame_num);
+            write_interp_dataset(&file, interp, frames, self.config.region_shape, &h5_path)?;
+        }
+        if let Some(nu2d) = &self.nu2d {
+            let TLCConfig {
+                characteristic_length,
+                air_thermal_conductivity,
+                ..
+            } = self.config;
+            let h2d = nu2d.mapv(|nu| nu * air_thermal_conductivity / characteristic_length);
+            write_dataset(&file, "nu2d", nu2d.view()).map_err(save_err)?;
+            write_dataset(&file, "h2d", h2d.view()).map_err(save_err)?;
+        }
+        if let Some(smoothed_nu2d) = &self.smoothed_nu2d {
+            write_dataset(&file, "smoothed_nu2d", smoothed_nu2d.view()).map_err(save_err)?;
+        }
+
+        // 每项配置单独作为根组的属性，完整的json另存一份便于读回
+        let to_h5_str = |s: &str| {
+            s.parse::<VarLenUnicode>()
+                .map_err(|err| awsl!(DataSaveError, err, h5_path))
+        };
+        let config = serde_json::to_value(&self.config).map_err(|err| awsl!(err))?;
+        let attrs = config.as_object().ok_or(awsl!(config))?;
+        for (key, value) in attrs {
+            let builder = file.new_attr_builder();
+            match value {
+                Json::Bool(b) => builder.with_data(&arr0(*b)).create(key.as_str()),
+                Json::Number(n) => builder
+                    .with_data(&arr0(n.as_f64().unwrap_or(f64::NAN)))
+                    .create(key.as_str()),
+                Json::String(s) => builder.with_data(&arr0(to_h5_str(s)?)).create(key.as_str()),
+                _ => builder
+                    .with_data(&arr0(to_h5_str(&value.to_string())?))
+                    .create(key.as_str()),
+            }
+            .map_err(save_err)?;
+        }
+        file.new_attr_builder()
+            .with_data(&arr0(to_h5_str(&config.to_string())?))
+            .create("config")
+            .map_err(save_err)?;
+
+        Ok(h5_path.to_str().ok_or(awsl!(h5_path))?.to_owned())
+    }
+
+    #[cfg(not(feature = "hdf5"))]
+    pub fn export_hdf5(&self, _with_green: bool) -> TLCResult<String> {
+        Err(awsl!(
+            HandleError,
+            "当前版本未启用hdf5导出，请使用`--features hdf5`重新编译"
+        ))
+    }
+
+    /// 将滤波后的Green值与坏点、峰值帧数与峰值信号、热电偶温度与插值结果保存到`save_dir/cache`，
+    /// 返回保存路径
+    ///
+    /// 重新打开同一工况时用`load_state`恢复，免去重新解码与滤波
+    pub fn save_state(&self) -> TLCResult<String> {
+        let cache_dir = Path::new(&self.config.save_dir).join("cache");
+        create_dir_all(&cache_dir).map_err(|err| awsl!(CreateDirError, err, cache_dir))?;
+        let state_path = self.state_path();
+        let state = StateRef {
+            stage_hashes: &self.stage_hashes,
+            inputs: self.config.current_inputs(),
+            filtered_g2d: self.filtered_g2d.as_ref(),
+            defects: self.defects.as_ref(),
+            peak_frames: self.peak_frames.as_ref(),
+            peak_signals: self.peak_signals.as_ref(),
+            t2d: self.t2d.as_ref(),
+            tc_status: self.tc_status.as_ref(),
+            interp: self.interp.as_ref(),
+        };
+        let file =
+            File::create(&state_path).map_err(|err| awsl!(DataSaveError, err, state_path))?;
+        bincode::serialize_into(BufWriter::new(file), &state)
+            .map_err(|err| awsl!(DataSaveError, err, state_path))?;
+
+        Ok(state_path.to_str().ok_or(awsl!(state_path))?.to_owned())
+    }
+
+    /// 恢复`save_state`保存的数据，只恢复计算时的配置与当前配置一致、且输入文件内容未变的阶段，
+    /// 返回恢复的阶段
+    pub fn load_state(&mut self) -> TLCResult<Vec<Stage>> {
+        let state_path = self.state_path();
+        let file = File::open(&state_path).map_err(|err| awsl!(DataReadError, err, state_path))?;
+        let State {
+            stage_hashes,
+            inputs,
+            filtered_g2d,
+            defects,
+            peak_frames,
+            peak_signals,
+            t2d,
+            tc_status,
+            interp,
+        } = bincode::deserialize_from(BufReader::new(file))
+            .map_err(|err| awsl!(DataReadError, err, state_path))?;
+
+        // 按内容比较，路径相同而文件已重新录制时不恢复
+        let current = self.config.current_inputs();
+        let same = |saved: &Option<FileHash>, current: &Option<FileHash>| match (saved, current) {
+            (Some(saved), Some(current)) => saved.sha256 == current.sha256,
+            _ => false,
+        };
+        let (same_video, same_daq) = (
+            same(&inputs.video, &current.video),
+            same(&inputs.daq, &current.daq),
+        );
+        let hashes = self.config.stage_hashes();
+        let valid = |stage| stage_hashes.get(&stage) == hashes.get(&stage);
+        let mut restored = Vec::new();
+        if let (true, true, Some(filtered_g2d), Some(defects)) =
+            (same_video, valid(Stage::FilteredG2d), filtered_g2d, defects)
+        {
+            self.filtered_g2d = Some(filtered_g2d);
+            self.defects = Some(defects);
+            restored.push(Stage::FilteredG2d);
+        }
+        if let (true, true, Some(peak_frames), Some(peak_signals)) = (
+            same_video,
+            valid(Stage::PeakFrames),
+            peak_frames,
+            peak_signals,
+        ) {
+            self.peak_frames = Some(peak_frames);
+            self.peak_signals = Some(peak_signals);
+            restored.push(Stage::PeakFrames);
+        }
+        if let (true, true, Some(t2d), Some(tc_status)) =
+            (same_daq, valid(Stage::T2d), t2d, tc_status)
+        {
+            self.t2d = Some(t2d);
+            self.tc_status = Some(tc_status);
+            self.init_frame_times()?;
+            restored.push(Stage::T2d);
+        }
+        if let (true, true, Some(interp)) = (same_daq, valid(Stage::Interp), interp) {
+            self.interp = Some(interp);
+            restored.push(Stage::Interp);
+        }
+        for &stage in &restored {
+            self.computed(stage);
+        }
+
+        Ok(restored)
+    }
+
+    fn state_path(&self) -> PathBuf {
+        Path::new(&self.config.save_dir)
+            .join("cache")
+            .join(format!("{}.state", self.config.case_name))
+    }
+
+    /// 数据包在用到时再读取，不缓存整个视频
+    pub fn create_video_ctx(&self) -> TLCResult<VideoCtx> {
+        let reader = PacketReader::open(&self.config.video_path)?;
+
+        Ok(VideoCtx(Mutex::new(reader.codec()?)))
+    }
+}
+
+impl TLCConfig {
+    /// 按扩展名识别.json/.toml/.yaml格式，旧版本的配置自动升级，相对路径按配置文件位置还原
+    pub fn from_path<P: AsRef<Path>>(config_path: P) -> TLCResult<Self> {
+        Self::from_path_with_overrides(config_path, Map::new())
+    }
+
+    /// 读取配置文件后覆盖其中部分配置项再初始化，用于命令行逐项指定参数
+    pub fn from_path_with_overrides<P: AsRef<Path>>(
+        config_path: P,
+        overrides: Map<String, serde_json::Value>,
+    ) -> TLCResult<Self> {
+        let config_path = config_path.as_ref();
+        let config_format = ConfigFormat::from_path(config_path);
+        let content = std::fs::read_to_string(config_path)
+            .map_err(|err| awsl!(ConfigIOError, err, config_path))?;
+        let mut json = migrate(config_format.parse(&content)?)?;
+        for (key, value) in &overrides {
+            schema::check_field(key, value)?;
+        }
+        json.as_object_mut()
+            .ok_or(awsl!(ConfigError, "配置文件格式错误"))?
+            .extend(overrides);
+        let mut cfg: Self = serde_json::from_value(json).map_err(|err| awsl!(ConfigError, err))?;
+        cfg.config_format = config_format;
+        cfg.resolve_paths(config_path)?;
+
+        Ok(cfg.init())
+    }
+
+    /// 由json构造，用于批处理时在模板上覆盖部分配置
+    pub fn from_json(json: serde_json::Value) -> TLCResult<Self> {
+        Ok(Self::migrated(json)?.init())
+    }
+
+    /// 升级到当前版本后再反序列化
+    fn migrated(json: serde_json::Value) -> TLCResult<Self> {
+        serde_json::from_value(migrate(json)?).map_err(|err| awsl!(ConfigError, err))
+    }
+
+    fn init(mut self) -> Self {
+        let _ = self.init_video_metadata();
+        let _ = self.init_daq_metadata();
+        let _ = self.init_path();
+        if self.frame_num == 0 {
+            self.init_frame_num();
+        }
+        self.init_regulator();
+
+        self
+    }
+
+    /// 影响Nu的配置的SHA-256，用于追溯结果文件对应的配置
+    ///
+    /// 只包含Nu及其上游各阶段的配置，不含路径，工况名、绘图等配置不同时哈希相同；
+    /// 输入文件由`input_hashes`按内容核对
+    pub fn hash(&self) -> TLCResult<String> {
+        let mut upstream = vec![Stage::Nu];
+        let mut i = 0;
+        while i < upstream.len() {
+            for &dep in upstream[i].deps() {
+                if !upstream.contains(&dep) {
+                    upstream.push(dep);
+                }
+            }
+            i += 1;
+        }
+        let configs: Vec<_> = Stage::ALL
+            .iter()
+            .filter(|stage| upstream.contains(stage))
+            .map(|&stage| json!([stage, self.result_config(stage)]))
+            .collect();
+        let json = serde_json::to_string(&configs).map_err(|err| awsl!(err))?;
+
+        Ok(sha256_bytes(json.as_bytes()))
+    }
+
+    /// 该阶段影响结果的配置，即去掉输入文件路径的`stage_config`
+    fn result_config(&self, stage: Stage) -> serde_json::Value {
+        match stage {
+            Stage::Video => json!([]),
+            Stage::Daq => json!([self.daq_sheet, self.daq_cell_offset]),
+            _ => self.stage_config(stage),
+        }
+    }
+
+    /// 各阶段的哈希，由该阶段直接相关的配置与所依赖阶段的哈希共同决定
+    ///
+    /// 取SHA-256的前8字节，不随编译器版本变化，可以与`save_state`保存的哈希比较
+    pub fn stage_hashes(&self) -> HashMap<Stage, u64> {
+        let mut hashes: HashMap<Stage, u64> = HashMap::new();
+        for &stage in Stage::ALL.iter() {
+            let mut hasher = Sha256::new();
+            hasher.update(self.stage_config(stage).to_string());
+            for dep in stage.deps() {
+                hasher.update(hashes[dep].to_le_bytes());
+            }
+            let mut bytes = [0; 8];
+            bytes.copy_from_slice(&hasher.finalize()[..8]);
+            hashes.insert(stage, u64::from_le_bytes(bytes));
+        }
+
+        hashes
+    }
+
+    /// 与该阶段直接相关的配置，上游的配置通过依赖关系传递
+    fn stage_config(&self, stage: Stage) -> serde_json::Value {
+        use Stage::*;
+        match stage {
+            Video => json!([self.video_path]),
+            Daq => json!([self.daq_path, self.daq_sheet, self.daq_cell_offset]),
+            // frame_num由起始帧数、起始行数与数采频率共同决定
+            RawG2d => json!([
+                self.start_frame,
+                self.frame_num,
+                self.top_left_pos,
+                self.region_shape,
+                self.reference_patch,
+            ]),
+            // 陷波滤波按帧率换算闪烁频率
+            FilteredG2d => json!([
+                self.filter_method,
+                self.frame_rate,
+                self.drift_correction,
+                self.defect_frames,
+                self.mask,
+            ]),
+            PeakFrames => json!([self.peak_method, self.events]),
+            // 热电偶的名称与安装位置只用于显示
+            T2d => json!([
+                self.thermocouples
+                    .iter()
+                    .map(|tc| {
+                        json!([
+                            tc.column_num,
+                            tc.pos,
+                            tc.unit,
+                            tc.enabled,
+                            tc.calibration_offset,
+                        ])
+                    })
+                    .collect::<Vec<_>>(),
+                self.start_row,
+                self.frame_num,
+                self.frame_rate,
+                self.daq_sample_rate,
+                self.daq_resample,
+                self.daq_time_column,
+                self.start_frame,
+                self.regulator,
+                self.daq_filter,
+            ]),
+            Interp => json!([
+                self.interp_method,
+                self.top_left_pos,
+                self.region_shape,
+                self.lazy_interp,
+            ]),
+            Nu => json!([
+                self.interp_method,
+                self.top_left_pos,
+                self.region_shape,
+                self.iteration_method,
+                self.coarse_stride,
+                self.t0_estimation,
+                self.frame_rate,
+                self.peak_temp,
+                self.solid_thermal_conductivity,
+                self.solid_thermal_diffusivity,
+                self.solid_thermal_conductivity_curve,
+                self.solid_thermal_diffusivity_curve,
+                self.characteristic_length,
+                self.air_thermal_conductivity,
+            ]),
+            SmoothedNu => json!([self.nu_smoothing]),
+        }
+    }
+
+    /// 在当前配置上覆盖部分配置项，用于预估修改配置的影响
+    ///
+    /// 覆盖项按原样写入，不做起始帧数与起始行数等的联动
+    pub fn with_overrides(&self, overrides: Map<String, serde_json::Value>) -> TLCResult<Self> {
+        let mut json = serde_json::to_value(self).map_err(|err| awsl!(err))?;
+        json.as_object_mut().ok_or(awsl!())?.extend(overrides);
+
+        serde_json::from_value(json).map_err(|err| awsl!(ConfigError, err))
+    }
+
+    /// 撤销修改时恢复部分配置项，保留配置文件格式并重新生成各保存路径
+    pub(super) fn restored(&self, overrides: Map<String, serde_json::Value>) -> TLCResult<Self> {
+        let mut cfg = self.with_overrides(overrides)?;
+        cfg.config_format = self.config_format;
+        cfg.config_path = self.config_path.clone();
+        cfg.data_path = self.data_path.clone();
+        cfg.plots_path = self.plots_path.clone();
+        let _ = cfg.init_path();
+
+        Ok(cfg)
+    }
+
+    pub(crate) fn init_video_metadata(&mut self) -> TLCResult<&mut Self> {
+        ffmpeg::init().map_err(|err| awsl!(VideoError, err, "ffmpeg初始化错误，建议重装"))?;
+
+        let input =
+            format::input(&self.video_path).map_err(|_| awsl!(VideoIOError, &self.video_path))?;
+        let video_stream = input.streams().best(Type::Video).ok_or(awsl!(
+            VideoError,
+            "找不到视频流",
+            &self.video_path,
+        ))?;
+        let rational = video_stream.avg_frame_rate();
+        self.frame_rate =
+            (rational.numerator() as f64 / rational.denominator() as f64).round() as usize;
+        self.total_frames =
+            (input.duration() as f64 * self.frame_rate as f64 / 1_000_000 as f64).floor() as usize;
+
+        let decoder = video_stream
+            .codec()
+            .decoder()
+            .video()
+            .map_err(|err| awsl!(VideoError, err, ""))?;
+        if self.video_shape == (0, 0) {
+            self.video_shape = (decoder.height() as usize, decoder.width() as usize);
+        }
+
+        Ok(self)
+    }
+
+    pub(crate) fn init_daq_metadata(&mut self) -> TLCResult<&mut Self> {
+        let daq_path = Path::new(&self.daq_path);
+        self.total_rows = match daq_path
+            .extension()
+            .ok_or(awsl!(DAQIOError, "路径有误", daq_path))?
+            .to_str()
+            .ok_or(awsl!(DAQIOError, "路径有误", daq_path))?
+        {
+            "lvm" => {
+                let (rows, header) = read_lvm(daq_path)?;
+                if let Some(delta_x) = header.delta_x {
+                    self.daq_sample_rate = 1. / delta_x;
+                }
+                rows.len()
+            }
+            "xlsx" => self
+                .open_daq_sheet()?
+                .height()
+                .saturating_sub(self.daq_cell_offset.0),
+            "tdms" => {
+                let tdms = TdmsFile::from_path(daq_path)?;
+                let wf_increment =
+                    tdms.data_channels()
+                        .find_map(|c| match c.properties.get("wf_increment") {
+                            Some(TdmsValue::Float(dt)) if *dt > 0. => Some(*dt),
+                            _ => None,
+                        });
+                if let Some(dt) = wf_increment {
+                    self.daq_sample_rate = (1. / dt) as f32;
+                }
+                tdms.data_channels()
+                    .map(|c| c.data.len())
+                    .max()
+                    .unwrap_or(0)
+            }
+            _ => Err(awsl!(DAQIOError, "只支持.lvm、.xlsx或.tdms格式", daq_path))?,
+        };
+
+        Ok(self)
+    }
+
+    pub(crate) fn init_frame_num(&mut self) -> &mut Self {
+        let rows_left = self.total_rows.saturating_sub(self.start_row);
+        let frames_by_rows = if rows_left == 0 {
+            0
+        } else {
+            ((rows_left - 1) as f32 / self.daq_rows_per_frame()).floor() as usize + 1
+        };
+        self.frame_num = self
+            .total_frames
+            .saturating_sub(self.start_frame)
+            .min(frames_by_rows);
+
+        self
+    }
+
+    /// 每帧对应的数采行数，不重采样时为1
+    pub fn daq_rows_per_frame(&self) -> f32 {
+        match self.daq_resample {
+            DAQResample::No => 1.,
+            _ if self.daq_sample_rate <= 0. || self.frame_rate == 0 => 1.,
+            _ => self.daq_sample_rate / self.frame_rate as f32,
+        }
+    }
+
+    /// Nu矩阵的保存路径
+    pub fn data_path(&self) -> &str {
+        &self.data_path
+    }
+
+    fn init_path(&mut self) -> TLCResult<&mut Self> {
+        if self.save_dir == "" {
+            return Err(awsl!(HandleError, "未设置保存根目录"));
+        }
+        if self.video_path != "video_path" {
+            self.case_name = Path::new(&self.video_path)
+                .file_stem()
+                .ok_or(awsl!(VideoIOError, &self.video_path))?
+                .to_str()
+                .ok_or(awsl!(VideoIOError, &self.video_path))?
+                .to_owned();
+        }
+        let save_dir = Path::new(&self.save_dir);
+        let layout = &self.output_layout;
+        let config_dir = save_dir.join(self.render_path(&layout.config_dir)?);
+        let data_dir = save_dir.join(self.render_path(&layout.data_dir)?);
+        let plots_dir = save_dir.join(self.render_path(&layout.plots_dir)?);
+
+        create_dir_all(&config_dir).map_err(|err| awsl!(CreateDirError, err, config_dir))?;
+        create_dir_all(&data_dir).map_err(|err| awsl!(CreateDirError, err, data_dir))?;
+        create_dir_all(&plots_dir).map_err(|err| awsl!(CreateDirError, err, plots_dir))?;
+
+        if self.video_path == "video_path" {
+            return Ok(self);
+        }
+
+        // 文件名中可能有小数点，不能用with_extension
+        let file_name = self.render_path(&layout.file_name)?;
+        let config_path =
+            config_dir.join(format!("{}.{}", file_name, self.config_format.extension()));
+        self.config_path = config_path.to_str().ok_or(awsl!(config_path))?.to_owned();
+        let data_path = data_dir.join(format!("{}.csv", file_name));
+        self.data_path = data_path.to_str().ok_or(awsl!(data_path))?.to_owned();
+        let plots_path = plots_dir.join(format!("{}.png", file_name));
+        self.plots_path = plots_path.to_str().ok_or(awsl!(plots_path))?.to_owned();
+
+        Ok(self)
+    }
+
+    /// 按当前配置展开`output_layout`中的模板
+    fn render_path(&self, template: &str) -> TLCResult<String> {
+        let json = serde_json::to_value(self).map_err(|err| awsl!(err))?;
+        let date = chrono::Local::now().format("%Y-%m-%d").to_string();
+        paths::render_template(template, |name| match name {
+            "case" => Some(self.case_name.clone()),
+            "date" => Some(date.clone()),
+            _ => match json.get(name)? {
+                serde_json::Value::String(s) => Some(s.clone()),
+                serde_json::Value::Bool(b) => Some(b.to_string()),
+                // 浮点配置项都是f32，按f32输出以免出现35.47999954这样的位数
+                serde_json::Value::Number(n) => match n.as_i64() {
+                    Some(i) => Some(i.to_string()),
+                    None => n.as_f64().map(|f| (f as f32).to_string()),
+                },
+                _ => None,
+            },
+        })
+    }
+
+    /// 按当前配置重新生成保存路径，文件名模板引用的配置项可能已修改，未设置保存根目录时不变
+    pub(crate) fn refresh_paths(&mut self) -> TLCResult<&mut Self> {
+        if !self.save_dir.is_empty() {
+            self.init_path()?;
+        }
+
+        Ok(self)
+    }
+
+    fn init_regulator(&mut self) -> &mut Self {
+        if self.thermocouples.len() != self.regulator.len() {
+            self.regulator = vec![1.; self.thermocouples.len()];
+        }
+
+        self
+    }
+
+    pub fn set_save_dir(&mut self, save_dir: String) -> TLCResult<&mut Self> {
+        self.save_dir = save_dir;
+        self.init_path()?;
+
+        Ok(self)
+    }
+
+    /// 模板无法展开时保持原来的目录结构
+    pub fn set_output_layout(&mut self, output_layout: OutputLayout) -> TLCResult<&mut Self> {
+        let previous = std::mem::replace(&mut self.output_layout, output_layout);
+        if let Err(err) = self.refresh_paths() {
+            self.output_layout = previous;
+            return Err(err);
+        }
+
+        Ok(self)
+    }
+
+    pub fn set_video_path(&mut self, video_path: String) -> TLCResult<&mut Self> {
+        self.video_path = video_path;
+        self.init_video_metadata()?.init_frame_num().init_path()?;
+
+        Ok(self)
+    }
+
+    pub fn set_daq_path(&mut self, daq_path: String) -> TLCResult<&mut Self> {
+        self.daq_path = daq_path;
+        self.init_daq_metadata()?.init_frame_num();
+
+        Ok(self)
+    }
+
+    pub fn set_start_frame(&mut self, start_frame: usize) -> TLCResult<&mut Self> {
+        if start_frame >= self.total_frames {
+            return Err(TLCError::FrameError {
+                frame: start_frame,
+                frame_num: self.total_frames,
+            });
+        }
+        let start_row = self.start_row as f32
+            + (start_frame as f32 - self.start_frame as f32) * self.daq_rows_per_frame();
+        if start_row < 0. {
+            return Err(awsl!(HandleError, "根据同步结果推算出的起始行数非正值"));
+        }
+        let start_row = start_row.round() as usize;
+        if start_row >= self.total_rows {
+            return Err(awsl!(
+                HandleError,
+                "根据同步结果推算出的起始行数超过数采文件总行数"
+            ));
+        }
+        self.start_frame = start_frame;
+        self.start_row = start_row;
+        self.init_frame_num();
+
+        Ok(self)
+    }
+
+    pub fn set_start_row(&mut self, start_row: usize) -> TLCResult<&mut Self> {
+        if start_row >= self.total_rows {
+            return Err(awsl!(HandleError, "起始行数超过数采文件总行数"));
+        }
+        let start_frame = self.start_frame as f32
+            + (start_row as f32 - self.start_row as f32) / self.daq_rows_per_frame();
+        if start_frame < 0. {
+            return Err(awsl!(HandleError, "根据同步结果推算出的起始帧数非正值"));
+        }
+        let start_frame = start_frame.round() as usize;
+        if start_frame >= self.total_frames {
+            return Err(awsl!(
+                HandleError,
+                "根据同步结果推算出的起始帧数超过视频总帧数"
+            ));
+        }
+        self.start_row = start_row;
+        self.start_frame = start_frame;
+        self.init_frame_num();
+
+        Ok(self)
+    }
+
+    pub fn set_daq_sample_rate(&mut self, daq_sample_rate: f32) -> &mut Self {
+        self.daq_sample_rate = daq_sample_rate;
+        self.init_frame_num();
+
+        self
+    }
+
+    pub fn set_daq_resample(&mut self, daq_resample: DAQResample) -> &mut Self {
+        self.daq_resample = daq_resample;
+        self.init_frame_num();
+
+        self
+    }
+
+    pub fn set_thermocouples(&mut self, thermocouples: Vec<Thermocouple>) -> &mut Self {
+        self.thermocouples = thermocouples;
+        self.init_regulator();
+
+        self
+    }
+
+    /// 视频第`frame_index`帧与数采文件第`row_index`行为同一时刻
+    ///
+    /// 采样频率不同时起始行数取整，同步误差不超过半个数采周期
+    pub fn synchronize(&mut self, frame_index: usize, row_index: usize) -> &mut Self {
+        let ratio = self.daq_rows_per_frame();
+        let rows_before = frame_index as f32 * ratio;
+        if rows_before <= row_index as f32 {
+            self.start_frame = 0;
+            self.start_row = (row_index as f32 - rows_before).round() as usize;
+        } else {
+            self.start_row = 0;
+            self.start_frame = (frame_index as f32 - row_index as f32 / ratio).round() as usize;
+        }
+        self.init_frame_num();
+
+        self
+    }
+
+    /// 读取参考温度(.lvm or .xlsx)
+    pub fn read_daq(&self) -> TLCResult<Array2<f32>> {
+        let daq_path = Path::new(&self.daq_path);
+        let daq = match daq_path
+            .extension()
+            .ok_or(awsl!(DAQIOError, "路径有误", daq_path))?
+            .to_str()
+            .ok_or(awsl!(DAQIOError, "路径有误", daq_path))?
+        {
+            "lvm" => self.read_daq_from_lvm(),
+            "xlsx" => self.read_daq_from_excel(),
+            "tdms" => self.read_daq_from_tdms(),
+            _ => Err(awsl!(DAQIOError, "只支持.lvm、.xlsx或.tdms格式", daq_path))?,
+        }?;
+
+        Ok(daq)
+    }
+
+    /// 列出数采文件中的所有通道，供前端选择热电偶对应列
+    pub fn daq_channels(&self) -> TLCResult<Vec<DAQChannel>> {
+        let daq_path = Path::new(&self.daq_path);
+        if daq_path.extension().and_then(|ext| ext.to_str()) == Some("tdms") {
+            let tdms = TdmsFile::from_path(daq_path)?;
+            let channels = tdms
+                .data_channels()
+                .enumerate()
+                .map(|(column_num, c)| DAQChannel {
+                    column_num,
+                    name: format!("{}/{}", c.group, c.name),
+                    properties: c
+                        .properties
+                        .iter()
+                        .map(|(k, v)| (k.clone(), v.to_string()))
+                        .collect(),
+                })
+                .collect();
+
+            return Ok(channels);
+        }
+
+        let channels = (0..self.read_daq()?.ncols())
+            .map(|column_num| DAQChannel {
+                column_num,
+                name: format!("第{}列", column_num),
+                properties: Vec::new(),
+            })
+            .collect();
+
+        Ok(channels)
+    }
+
+    fn read_daq_from_lvm(&self) -> TLCResult<Array2<f32>> {
+        let (rows, _) = read_lvm(&self.daq_path)?;
+        let total_columns = rows.iter().map(|row| row.len()).max().ok_or(awsl!(
+            DAQError,
+            "数采文件为空",
+            self.daq_path
+        ))?;
+
+        let mut daq = Array2::from_elem((self.total_rows, total_columns), f32::NAN);
+        for (row, mut daq_row) in rows.into_iter().zip(daq.rows_mut()) {
+            for (lvm_val, daq_val) in row.into_iter().zip(daq_row.iter_mut()) {
+                *daq_val = lvm_val;
+            }
+        }
+
+        Ok(daq)
+    }
+
+    /// 打开`daq_sheet`指定的worksheet
+    fn open_daq_sheet(&self) -> TLCResult<Range<DataType>> {
+        let daq_path = Path::new(&self.daq_path);
+        let mut excel: Xlsx<_> =
+            open_workbook(daq_path).map_err(|err| awsl!(DAQIOError, err, daq_path))?;
+        let sheet = match &self.daq_sheet {
+            DAQSheet::Index(index) => excel.worksheet_range_at(*index),
+            DAQSheet::Name(name) => excel.worksheet_range(name),
+        };
+
+        sheet
+            .ok_or(awsl!(
+                DAQError,
+                format!("找不到worksheet{}", self.daq_sheet),
+                daq_path
+            ))?
+            .map_err(|err| awsl!(DAQError, err, daq_path))
+    }
+
+    /// 列出.xlsx数采文件中所有worksheet名称
+    pub fn daq_sheet_names(&self) -> TLCResult<Vec<String>> {
+        let daq_path = Path::new(&self.daq_path);
+        let excel: Xlsx<_> =
+            open_workbook(daq_path).map_err(|err| awsl!(DAQIOError, err, daq_path))?;
+
+        Ok(excel.sheet_names().to_vec())
+    }
+
+    pub fn set_daq_sheet(
+        &mut self,
+        daq_sheet: DAQSheet,
+        daq_cell_offset: (usize, usize),
+    ) -> TLCResult<&mut Self> {
+        self.daq_sheet = daq_sheet;
+        self.daq_cell_offset = daq_cell_offset;
+        self.init_daq_metadata()?.init_frame_num();
+
+        Ok(self)
+    }
+
+    fn read_daq_from_excel(&self) -> TLCResult<Array2<f32>> {
+        let daq_path = Path::new(&self.daq_path);
+        let sheet = self.open_daq_sheet()?;
+        let (row_offset, column_offset) = self.daq_cell_offset;
+        let total_columns = sheet.width().saturating_sub(column_offset);
+
+        let mut daq = Array2::zeros((self.total_rows, total_columns));
+        for (excel_row, mut daq_col) in sheet.rows().skip(row_offset).zip(daq.rows_mut()) {
+            for (excel_val, daq_val) in excel_row.iter().skip(column_offset).zip(daq_col.iter_mut())
+            {
+                *daq_val = excel_val.get_float().ok_or(awsl!(
+                    DAQError,
+                    "数据采集文件中不应当有数字以外的格式",
+                    daq_path,
+                ))? as f32;
+            }
+        }
+
+        Ok(daq)
+    }
+
+    /// 每个通道一列，较短的通道末尾补NaN，时间戳通道转换为相对首个采样点的秒数
+    fn read_daq_from_tdms(&self) -> TLCResult<Array2<f32>> {
+        let tdms = TdmsFile::from_path(&self.daq_path)?;
+        let channels: Vec<_> = tdms.data_channels().collect();
+
+        let mut daq = Array2::from_elem((self.total_rows, channels.len()), f32::NAN);
+        for (channel, mut daq_col) in channels.iter().zip(daq.columns_mut()) {
+            let offset = if channel.is_timestamp() {
+                channel.data[0]
+            } else {
+                0.
+            };
+            for (tdms_val, daq_val) in channel.data.iter().zip(daq_col.iter_mut()) {
+                *daq_val = (tdms_val - offset) as f32;
+            }
+        }
+
+        Ok(daq)
+    }
+
+    /// 保存配置，不再覆盖默认配置，需要复用时保存为预设；路径尽量保存为相对路径
+    pub fn save(&self) -> TLCResult<()> {
+        let content = self.config_format.to_string(&self.to_portable()?)?;
+        std::fs::write(&self.config_path, content)
+            .map_err(|err| awsl!(ConfigIOError, err, self.config_path))?;
+
+        Ok(())
+    }
+}
+
+/// 当前配置文件版本，修改配置格式时加1，并在`MIGRATIONS`末尾追加对应的升级函数
+const CONFIG_VERSION: u32 = 2;
+
+/// 第i项将版本i的配置升级到版本i+1
+const MIGRATIONS: [fn(&mut Map<String, serde_json::Value>) -> TLCResult<()>;
+    CONFIG_VERSION as usize] = [migrate_v0, migrate_v1];
+
+/// 将任意旧版本的配置逐版本升级到当前版本，没有`version`的配置视为版本0
+fn migrate(mut json: serde_json::Value) -> TLCResult<serde_json::Value> {
+    let config = json
+        .as_object_mut()
+        .ok_or(awsl!(ConfigError, "配置文件格式错误"))?;
+    let version = match config.get("version") {
+        Some(version) => version.as_u64().ok_or(awsl!(
+            ConfigError,
+            format!("无法识别的配置文件版本{}", version)
+        ))? as u32,
+        None => 0,
+    };
+    if version > CONFIG_VERSION {
+        return Err(awsl!(
+            ConfigError,
+            format!(
+                "配置文件版本{}高于当前程序支持的版本{}，请更新程序",
+                version, CONFIG_VERSION
+            )
+        ));
+    }
+    for migration in MIGRATIONS[version as usize..].iter() {
+        migration(config)?;
+    }
+    config.insert("version".to_owned(), json!(CONFIG_VERSION));
+
+    Ok(json)
+}
+
+/// 版本0：热电偶的列数与位置分别保存在`temp_column_num`与`thermocouple_pos`中
+fn migrate_v0(config: &mut Map<String, serde_json::Value>) -> TLCResult<()> {
+    match (
+        config.remove("temp_column_num"),
+        config.remove("thermocouple_pos"),
+    ) {
+        (Some(columns), Some(positions)) => {
+            let columns: Vec<usize> =
+                serde_json::from_value(columns).map_err(|err| awsl!(ConfigError, err))?;
+            let positions: Vec<(i32, i32)> =
+                serde_json::from_value(positions).map_err(|err| awsl!(ConfigError, err))?;
+            if columns.len() != positions.len() {
+                return Err(awsl!(
+                    ConfigError,
+                    "temp_column_num与thermocouple_pos长度不一致"
+                ));
+            }
+            let thermocouples: Vec<_> = columns
+                .into_iter()
+                .zip(positions)
+                .map(|(column_num, pos)| json!({ "column_num": column_num, "pos": pos }))
+                .collect();
+            config
+                .entry("thermocouples")
+                .or_insert(serde_json::Value::Array(thermocouples));
+        }
+        (None, None) => {}
+        _ => {
+            return Err(awsl!(
+                ConfigError,
+                "temp_column_num与thermocouple_pos必须同时存在"
+            ))
+        }
+    }
+
+    Ok(())
+}
+
+/// 版本1：小波滤波只保存阈值比例，如`{"Wavelet": 0.5}`，边界延拓方式取默认
+fn migrate_v1(config: &mut Map<String, serde_json::Value>) -> TLCResult<()> {
+    if let Some(wavelet) = config
+        .get_mut("filter_method")
+        .and_then(|filter_method| filter_method.get_mut("Wavelet"))
+    {
+        if wavelet.is_number() {
+            *wavelet = json!({ "threshold_ratio": wavelet.take() });
+        }
+    }
+
+    Ok(())
+}
+
+/// .lvm文件头中解析出的信息
+#[derive(Debug, Default)]
+struct LvmHeader {
+    /// 采样间隔（s）
+    delta_x: Option<f32>,
+    /// 是否以逗号作为小数点
+    decimal_comma: bool,
+}
+
+/// 逐行读取.lvm文件，跳过`***End_of_Header***`分隔的文件头、段头以及列名行
+///
+/// 首个非空单元格为数字的行视为数据行，数据行中无法解析的单元格（空的X_Value列、Comment列等）记为NaN
+fn read_lvm<P: AsRef<Path>>(lvm_path: P) -> TLCResult<(Vec<Vec<f32>>, LvmHeader)> {
+    let lvm_path = lvm_path.as_ref();
+    let file = File::open(lvm_path).map_err(|err| awsl!(DAQIOError, err, lvm_path))?;
+    let mut header = LvmHeader::default();
+    let mut rows = Vec::new();
+
+    for line in BufReader::new(file).lines() {
+        let line = line.map_err(|err| awsl!(DAQIOError, err, lvm_path))?;
+        let parse = |cell: &str| {
+            let cell = cell.trim();
+            if header.decimal_comma {
+                cell.replace(',', ".").parse::<f32>().ok()
+            } else {
+                cell.parse::<f32>().ok()
+            }
+        };
+
+        let is_data = line
+            .split('\t')
+            .find(|cell| !cell.trim().is_empty())
+            .map_or(false, |cell| parse(cell).is_some());
+        if is_data {
+            rows.push(
+                line.split('\t')
+                    .map(|cell| parse(cell).unwrap_or(f32::NAN))
+                    .collect(),
+            );
+            continue;
+        }
+
+        let mut cells = line.split('\t');
+        match cells.next().map(str::trim) {
+            Some("Decimal_Separator") => {
+                header.decimal_comma = cells.next().map(str::trim) == Some(",");
+            }
+            Some("Delta_X") if header.delta_x.is_none() => {
+                header.delta_x = cells.filter_map(parse).find(|&dx| dx > 0.);
+            }
+            _ => {}
+        }
+    }
+
+    Ok((rows, header))
+}
+
+/// 保存矩阵，`meta`为None或未开启附加内容时为不带表头的纯矩阵
+pub fn save_data<P: AsRef<Path>>(
+    data: ArrayView2<f32>,
+    data_path: P,
+    meta: Option<&DataMeta>,
+) -> TLCResult<()> {
+    let data_path = data_path.as_ref();
+    let file = File::create(data_path).map_err(|err| awsl!(DataSaveError, err, data_path))?;
+
+    write_data(
+        BufWriter::with_capacity(WRITE_BUFFER_SIZE, file),
+        data,
+        meta,
+    )
+    .map_err(|err| awsl!(DataSaveError, err, data_path))
+}
+
+/// 将`x`追加到`text`末尾，不为每个数分配字符串
+fn push_float(text: &mut Vec<u8>, x: f32, precision: Option<usize>, buffer: &mut ryu::Buffer) {
+    match precision {
+        // 写入Vec不会失败
+        Some(precision) => drop(write!(text, "{:.*}", precision, x)),
+        None if x.is_finite() => text.extend_from_slice(buffer.format_finite(x).as_bytes()),
+        // NaN与inf保持原先的写法，read_data可以解析
+        None => drop(write!(text, "{}", x)),
+    }
+}
+
+/// `save_data`的具体实现，也用于写入压缩包
+fn write_data<W: Write>(
+    mut writer: W,
+    data: ArrayView2<f32>,
+    meta: Option<&DataMeta>,
+) -> std::io::Result<()> {
+    let options = meta.map(|meta| meta.options).unwrap_or_default();
+    let coordinate_unit = match meta {
+        Some(meta) if meta.pixel_size > 0. => "mm",
+        _ => "pixel",
+    };
+
+    if let (Some(meta), true) = (meta, options.header) {
+        let mut header = format!(
+            "# case: {}\n# config_hash: {}\n# date: {}\n# unit: {}\n",
+            meta.case_name,
+            meta.config_hash,
+            chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
+            meta.unit,
+        );
+        let InputHashes { video, daq } = &meta.input_hashes;
+        for (name, hash) in [("video", video), ("daq", daq)] {
+            if let Some(hash) = hash {
+                header.push_str(&format!("# {}_sha256: {}\n", name, hash.sha256));
+            }
+        }
+        if options.coordinates {
+            header.push_str(&format!("# coordinates: {}\n", coordinate_unit));
+        }
+        writer.write_all(header.as_bytes())?;
+    }
+
+    let (h, w) = data.dim();
+    let scale = match meta {
+        Some(meta) if meta.pixel_size > 0. => meta.pixel_size,
+        _ => 1.,
+    };
+    let precision = options.precision;
+    if options.coordinates {
+        let mut buffer = ryu::Buffer::new();
+        let mut text = COORDINATE_MARK.as_bytes().to_vec();
+        for x in 0..w {
+            text.push(b',');
+            push_float(&mut text, x as f32 * scale, precision, &mut buffer);
+        }
+        text.push(b'\n');
+        writer.write_all(&text)?;
+    }
+
+    let flipped = meta.map_or(false, |meta| meta.flipped);
+    // 按每个数约12字节预留，序列化时基本不再扩容
+    let capacity = SERIALIZE_ROWS * (w + 1) * 12;
+    let chunks: Vec<_> = data
+        .axis_chunks_iter(Axis(0), SERIALIZE_ROWS)
+        .enumerate()
+        .collect();
+    // 每次并行序列化的块数与线程数相同，同时占用的内存有限
+    for batch in chunks.chunks(rayon::current_num_threads()) {
+        let texts: Vec<_> = batch
+            .par_iter()
+            .map(|(c, rows)| {
+                let mut buffer = ryu::Buffer::new();
+                let mut text = Vec::with_capacity(capacity);
+                for (r, row) in rows.axis_iter(Axis(0)).enumerate() {
+                    if options.coordinates {
+                        // y与计算区域坐标一致，自上而下增大
+                        let i = c * SERIALIZE_ROWS + r;
+                        let y = if flipped { h - 1 - i } else { i };
+                        push_float(&mut text, y as f32 * scale, precision, &mut buffer);
+                        text.push(b',');
+                    }
+                    for (j, &x) in row.iter().enumerate() {
+                        if j > 0 {
+                            text.push(b',');
+                        }
+                        push_float(&mut text, x, precision, &mut buffer);
+                    }
+                    text.push(b'\n');
+                }
+                text
+            })
+            .collect();
+        for text in texts {
+            writer.write_all(&text)?;
+        }
+    }
+    writer.flush()
+}
+
+/// 按`sha256sum`的格式计算文件校验和
+fn sha256_line<P: AsRef<Path>>(path: P) -> TLCResult<String> {
+    let path = path.as_ref();
+
+    Ok(format!("{}  {}\n", sha256_hex(path)?, path.display()))
+}
+
+/// 带表头按列保存
+pub fn save_table<P: AsRef<Path>>(
+    table_path: P,
+    header: &[&str],
+    columns: &[&[f32]],
+) -> TLCResult<()> {
+    let table_path = table_path.as_ref();
+    let mut wtr = WriterBuilder::new()
+        .from_path(table_path)
+        .map_err(|err| awsl!(DataSaveError, err, table_path))?;
+    wtr.write_record(header)
+        .map_err(|err| awsl!(DataSaveError, err, table_path))?;
+    let rows = columns.iter().map(|col| col.len()).max().unwrap_or(0);
+    for i in 0..rows {
+        let record: Vec<_> = columns
+            .iter()
+            .map(|col| col.get(i).map_or(String::new(), |v| v.to_string()))
+            .collect();
+        wtr.write_record(&record)
+            .map_err(|err| awsl!(DataSaveError, err, table_path))?;
+    }
+
+    Ok(())
+}
+
+/// .npy中可以保存的元素类型
+pub trait NpyElement: Copy {
+    /// numpy的dtype描述
+    const DESCR: &'static str;
+
+    fn write_le<W: Write + ?Sized>(self, writer: &mut W) -> std::io::Result<()>;
+}
+
+impl NpyElement for f32 {
+    const DESCR: &'static str = "<f4";
+
+    fn write_le<W: Write + ?Sized>(self, writer: &mut W) -> std::io::Result<()> {
+        writer.write_all(&self.to_le_bytes())
+    }
+}
+
+impl NpyElement for u8 {
+    const DESCR: &'static str = "|u1";
+
+    fn write_le<W: Write + ?Sized>(self, writer: &mut W) -> std::io::Result<()> {
+        writer.write_all(&[self])
+    }
+}
+
+/// .npy v1.0文件头，之后按C顺序紧接着写入数据即可
+fn write_npy_header<W: Write + ?Sized>(
+    writer: &mut W,
+    descr: &str,
+    shape: &[usize],
+) -> std::io::Result<()> {
+    let shape: String = shape.iter().map(|n| format!("{}, ", n)).collect();
+    let mut header = format!(
+        "{{'descr': '{}', 'fortran_order': False, 'shape': ({}), }}",
+        descr,
+        shape.trim_end_matches(' ')
+    );
+    // magic(6) + version(2) + header_len(2) + header + '\n' 需对齐到64字节
+    let padding = 64 - (10 + header.len() + 1) % 64;
+    header.extend(std::iter::repeat(' ').take(padding % 64));
+    header.push('\n');
+
+    writer.write_all(b"\x93NUMPY\x01\x00")?;
+    writer.write_all(&(header.len() as u16).to_le_bytes())?;
+    writer.write_all(header.as_bytes())
+}
+
+fn write_npy<W: Write + ?Sized, T: NpyElement, D: Dimension>(
+    writer: &mut W,
+    data: ArrayView<T, D>,
+) -> std::io::Result<()> {
+    write_npy_header(writer, T::DESCR, data.shape())?;
+    for &v in data.iter() {
+        v.write_le(writer)?;
+    }
+
+    writer.flush()
+}
+
+/// 按.npy v1.0格式保存，numpy可直接`np.load`
+pub fn save_npy<P: AsRef<Path>, T: NpyElement, D: Dimension>(
+    data: ArrayView<T, D>,
+    npy_path: P,
+) -> TLCResult<()> {
+    let npy_path = npy_path.as_ref();
+    let file = File::create(npy_path).map_err(|err| awsl!(DataSaveError, err, npy_path))?;
+    write_npy(&mut BufWriter::new(file), data).map_err(|err| awsl!(DataSaveError, err, npy_path))
+}
+
+#[cfg(feature = "hdf5")]
+fn write_dataset<T: hdf5::H5Type, D: Dimension>(
+    file: &hdf5::File,
+    name: &str,
+    data: ArrayView<T, D>,
+) -> hdf5::Result<()> {
+    file.new_dataset_builder().with_data(data).create(name)?;
+
+    Ok(())
+}
+
+/// 将`[start, end)`帧的参考温度场写为(帧数, 高, 宽)的`interp`数据集
+///
+/// 逐帧展开，避免一次性占用两份内存
+#[cfg(feature = "hdf5")]
+fn write_interp_dataset(
+    file: &hdf5::File,
+    interp: &Interp,
+    (start, end): (usize, usize),
+    region_shape: (usize, usize),
+    h5_path: &Path,
+) -> TLCResult<()> {
+    let save_err = |err: hdf5::Error| awsl!(DataSaveError, err, h5_path);
+    let (cal_h, cal_w) = region_shape;
+    let dataset = file
+        .new_dataset::<f32>()
+        .shape((end - start, cal_h, cal_w))
+        .create("interp")
+        .map_err(save_err)?;
+    for frame in start..end {
+        let field = interp.frame_field(frame, region_shape)?;
+        dataset
+            .write_slice(&field, s![frame - start, .., ..])
+            .map_err(save_err)?;
+    }
+
+    Ok(())
+}
+
+/// 将同尺寸的若干二维场保存为VTK ImageData（.vti），数据按base64编码的二进制写入
+///
+/// 第0行对应y=0，与nu2d的上下方向一致
+pub fn save_vti<P: AsRef<Path>>(
+    vti_path: P,
+    spacing: f32,
+    fields: &[(&str, ArrayView2<f32>)],
+) -> TLCResult<()> {
+    let vti_path = vti_path.as_ref();
+    let (h, w) = fields.first().ok_or(awsl!(fields))?.1.dim();
+    let extent = format!("0 {} 0 {} 0 0", w - 1, h - 1);
+
+    let mut vti = format!(
+        "<?xml version=\"1.0\"?>\n\
+         <VTKFile type=\"ImageData\" version=\"0.1\" byte_order=\"LittleEndian\" \
+         header_type=\"UInt32\">\n\
+         <ImageData WholeExtent=\"{0}\" Origin=\"0 0 0\" Spacing=\"{1} {1} {1}\">\n\
+         <Piece Extent=\"{0}\">\n\
+         <PointData Scalars=\"{2}\">\n",
+        extent, spacing, fields[0].0,
+    );
+    for (name, field) in fields {
+        if field.dim() != (h, w) {
+            return Err(awsl!(DataSaveError, "各场尺寸不一致", vti_path));
+        }
+        // 二进制格式：UInt32的字节数 + 小端数据，整体base64编码
+        let mut buf = Vec::with_capacity(4 + h * w * 4);
+        buf.extend_from_slice(&((h * w * 4) as u32).to_le_bytes());
+        field
+            .iter()
+            .for_each(|v| buf.extend_from_slice(&v.to_le_bytes()));
+        vti.push_str(&format!(
+            "<DataArray type=\"Float32\" Name=\"{}\" format=\"binary\">\n{}\n</DataArray>\n",
+            name,
+            base64::encode(&buf),
+        ));
+    }
+    vti.push_str("</PointData>\n</Piece>\n</ImageData>\n</VTKFile>\n");
+
+    std::fs::write(vti_path, vti).map_err(|err| awsl!(DataSaveError, err, vti_path))
+}
+
+/// 读取`save_data`保存的矩阵，跳过`#`注释及坐标行、列
+pub fn read_data<P: AsRef<Path>>(data_path: P) -> TLCResult<Array2<f32>> {
+    let data_path = data_path.as_ref();
+    let records = ReaderBuilder::new()
+        .has_headers(false)
+        .comment(Some(b'#'))
+        .from_path(data_path)
+        .map_err(|err| awsl!(DataReadError, err, data_path))?
+        .into_records()
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| awsl!(DataReadError, err, data_path))?;
+    let first = records
+        .first()
+        .ok_or(awsl!(DataReadError, "矩阵为空", data_path))?;
+    let skip = (first.get(0) == Some(COORDINATE_MARK)) as usize;
+    let (height, width) = (records.len() - skip, first.len() - skip);
+
+    let mut data = Array2::zeros((height, width));
+    for (csv_row, mut nu_row) in records.iter().skip(skip).zip(data.axis_iter_mut(Axis(0))) {
+        for (csv_val, nu) in csv_row.iter().skip(skip).zip(nu_row.iter_mut()) {
+            *nu = csv_val
+                .parse::<f32>()
+                .map_err(|err| awsl!(DataReadError, err, data_path))?;
+        }
+    }
+
+    Ok(data)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::preprocess::{FilterMethod, WaveletBoundary};
+
+    #[test]
+    fn migrate_legacy_thermocouples() {
+        let legacy = json!({
+            "temp_column_num": [1, 2],
+            "thermocouple_pos": [[10, 20], [10, 40]],
+            "peak_temp": 36.0,
+        });
+        let config: TLCConfig = serde_json::from_value(migrate(legacy).unwrap()).unwrap();
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert_eq!(config.thermocouples.len(), 2);
+        assert_eq!(config.thermocouples[1].column_num, 2);
+        assert_eq!(config.thermocouples[1].pos, (10, 40));
+        assert_eq!(config.peak_temp, 36.);
+
+        assert!(migrate(json!({ "version": CONFIG_VERSION + 1 })).is_err());
+    }
+
+    #[test]
+    fn migrate_wavelet_threshold() {
+        let legacy = json!({ "version": 1, "filter_method": { "Wavelet": 0.5 } });
+        let config: TLCConfig = serde_json::from_value(migrate(legacy).unwrap()).unwrap();
+        match config.filter_method {
+            FilterMethod::Wavelet {
+                threshold_ratio,
+                boundary,
+            } => {
+                assert_eq!(threshold_ratio, 0.5);
+                assert_eq!(boundary, WaveletBoundary::Symmetric);
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn synchronize_with_rate_ratio() {
+        let mut config = TLCConfig::from_json(json!({
+            "frame_rate": 25,
+            "daq_sample_rate": 60.,
+            "daq_resample": "Linear",
+            "total_frames": 100,
+            "total_rows": 250,
+        }))
+        .unwrap();
+        assert!((config.daq_rows_per_frame() - 2.4).abs() < 1e-6);
+
+        // 第10帧对应第24行，起始行取整
+        config.synchronize(10, 50);
+        assert_eq!((config.start_frame, config.start_row), (0, 26));
+        // 最后一帧位于第26 + 92 * 2.4 = 246.8行
+        assert_eq!(config.frame_num, 93);
+        config.synchronize(7, 20);
+        assert_eq!((config.start_frame, config.start_row), (0, 3));
+        // 数采先于视频结束
+        config.synchronize(50, 12);
+        assert_eq!((config.start_frame, config.start_row), (45, 0));
+        assert_eq!(config.frame_num, 55);
+
+        // 最后一帧恰好落在末行
+        config.total_rows = 25;
+        config.synchronize(0, 0);
+        assert_eq!(config.frame_num, 11);
+        config.total_rows = 0;
+        config.init_frame_num();
+        assert_eq!(config.frame_num, 0);
+
+        config.daq_resample = DAQResample::No;
+        assert_eq!(config.daq_rows_per_frame(), 1.);
+    }
+
+    #[test]
+    fn hash_result_config() {
+        let config = TLCConfig::from_json(json!({ "peak_temp": 35.48 })).unwrap();
+        let hash = config.hash().unwrap();
+        assert_eq!(hash.len(), 64);
+
+        let renamed = TLCConfig::from_json(json!({
+            "case_name": "other",
+            "video_path": "other.avi",
+            "daq_path": "other.lvm",
+            "peak_temp": 35.48,
+        }))
+        .unwrap();
+        assert_eq!(renamed.hash().unwrap(), hash);
+
+        let labeled = TLCConfig::from_json(json!({
+            "thermocouples": [{ "column_num": 1, "pos": [0, 0], "label": "inlet" }],
+            "peak_temp": 35.48,
+        }))
+        .unwrap();
+        let mut relabeled = TLCConfig::from_json(json!({
+            "thermocouples": [{ "column_num": 1, "pos": [0, 0], "location": "near the slot" }],
+            "peak_temp": 35.48,
+        }))
+        .unwrap();
+        assert_eq!(labeled.hash().unwrap(), relabeled.hash().unwrap());
+        assert_eq!(labeled.stage_hashes(), relabeled.stage_hashes());
+        relabeled.thermocouples[0].calibration_offset = 0.2;
+        assert_ne!(labeled.hash().unwrap(), relabeled.hash().unwrap());
+
+        let changed = TLCConfig::from_json(json!({ "peak_temp": 36. })).unwrap();
+        assert_ne!(changed.hash().unwrap(), hash);
+        let changed =
+            TLCConfig::from_json(json!({ "peak_temp": 35.48, "daq_cell_offset": [1, 0] })).unwrap();
+        assert_ne!(changed.hash().unwrap(), hash);
+    }
+
+    #[test]
+    fn output_layout_template() {
+        let save_dir = std::env::temp_dir().join("tlc_output_layout_test");
+        let config = TLCConfig::from_json(json!({
+            "save_dir": save_dir,
+            "video_path": "videos/run1.avi",
+            "peak_temp": 35.48,
+            "output_layout": { "data_dir": "nu/{case}", "file_name": "{case}_Tp{peak_temp}" },
+        }))
+        .unwrap();
+        let data_path = save_dir.join("nu").join("run1").join("run1_Tp35.48.csv");
+        assert_eq!(Path::new(&config.data_path), data_path);
+        assert!(Path::new(&config.plots_path).ends_with("plots/run1_Tp35.48.png"));
+
+        let mut config = config;
+        let layout = OutputLayout {
+            file_name: "{coating}".to_owned(),
+            ..Default::default()
+        };
+        assert!(config.set_output_layout(layout).is_err());
+        assert_eq!(Path::new(&config.data_path), data_path);
+        std::fs::remove_dir_all(save_dir).ok();
+    }
+
+    #[test]
+    fn region_overlap() {
+        // 旧区域(1, 1)起2x2，新区域(1, 2)起2x3
+        let columns = overlap_columns(((1, 1), (2, 2)), ((1, 2), (2, 3)));
+        assert_eq!(columns, vec![Some(1), None, None, Some(3), None, None]);
+    }
+
+    #[test]
+    fn state_requires_same_inputs() {
+        let save_dir = std::env::temp_dir().join("tlc_state_test");
+        create_dir_all(&save_dir).unwrap();
+        let video_path = save_dir.join("run.avi");
+        let daq_path = save_dir.join("run.lvm");
+        std::fs::write(&video_path, "video").unwrap();
+        std::fs::write(&daq_path, "1\t2\n").unwrap();
+        let config = TLCConfig::from_json(json!({
+            "save_dir": save_dir,
+            "video_path": video_path,
+            "daq_path": daq_path,
+        }))
+        .unwrap();
+        let mut data = TLCData::from_config(config);
+        data.filtered_g2d = Some(Array2::zeros((3, 2)));
+        data.defects = Some(vec![false, true]);
+        data.peak_frames = Some(vec![1, 2]);
+        let signal = PeakSignal {
+            baseline: 40.,
+            amplitude: 100.,
+        };
+        data.peak_signals = Some(vec![signal; 2]);
+        data.computed(Stage::FilteredG2d);
+        data.computed(Stage::PeakFrames);
+        data.save_state().unwrap();
+
+        data.filtered_g2d = None;
+        data.defects = None;
+        data.peak_frames = None;
+        data.peak_signals = None;
+        let restored = data.load_state().unwrap();
+        assert_eq!(restored, vec![Stage::FilteredG2d, Stage::PeakFrames]);
+        assert_eq!(data.defects, Some(vec![false, true]));
+        assert_eq!(data.get_peak_signals().unwrap().len(), 2);
+
+        // 同一路径的视频被重新录制
+        std::fs::write(&video_path, "another video").unwrap();
+        data.filtered_g2d = None;
+        data.peak_frames = None;
+        assert!(data.load_state().unwrap().is_empty());
+        std::fs::remove_dir_all(save_dir).ok();
+    }
+
+    #[test]
+    fn save_data_roundtrip() {
+        // 行数超过一块，检查各块按顺序写入
+        let data = Array2::from_shape_fn((SERIALIZE_ROWS * 2 + 3, 5), |(i, j)| {
+            if (i, j) == (1, 2) {
+                f32::NAN
+            } else {
+                i as f32 / 3. + j as f32 * 1e-7
+            }
+        });
+        let path = std::env::temp_dir().join("tlc_save_data_test.csv");
+        save_data(data.view(), &path, None).unwrap();
+        let read = read_data(&path).unwrap();
+        assert!(read[(1, 2)].is_nan());
+        assert!(data
+            .iter()
+            .zip(read.iter())
+            .all(|(a, b)| a == b || a.is_nan() && b.is_nan()));
+
+        let mut text = Vec::new();
+        push_float(&mut text, 1. / 3., Some(3), &mut ryu::Buffer::new());
+        assert_eq!(text, b"0.333");
+        std::fs::remove_file(path).ok();
+    }
+}
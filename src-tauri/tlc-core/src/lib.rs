@@ -0,0 +1,1402 @@
+//! 瞬态液晶实验数据处理的计算核心，不依赖界面，桌面端、命令行与脚本共用
+//!
+//! 以配置文件构造[`TLCData`]，修改配置后已缓存的中间结果按需失效，求解时只重新计算过期的阶段：
+//!
+//! ```no_run
+//! use tlc_core::TLCData;
+//!
+//! let mut data = TLCData::from_path("config.json")?;
+//! data.set_peak_temp(35.48).solve()?.save_nu()?;
+//! println!("{}", data.get_nu_nan_mean()?);
+//! # Ok::<(), tlc_core::error::TLCError>(())
+//! ```
+//!
+//! 各阶段（视频、数采、滤波、峰值检测、插值、求解）也可以单独调用，见[`TLCData`]的各方法。
+
+pub mod batch;
+#[cfg(feature = "bench")]
+pub mod bench;
+pub mod binning;
+pub mod catalog;
+pub mod colormap;
+pub mod error;
+pub mod events;
+pub mod extrapolation;
+pub mod history;
+pub mod io;
+pub mod mask;
+pub mod memory;
+pub mod multiview;
+pub mod notify;
+pub mod paths;
+pub mod penetration;
+pub mod pipeline;
+pub mod portable;
+pub mod postprocess;
+pub mod preprocess;
+pub mod preset;
+pub mod provenance;
+pub mod quality;
+pub mod quicklook;
+pub mod refine;
+pub mod report;
+pub mod reproduce;
+pub mod results;
+pub mod schema;
+pub mod script;
+pub mod solve;
+pub mod stream;
+pub mod tdms;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+pub mod threads;
+pub mod timing;
+pub mod transient;
+pub mod tuning;
+pub mod validate;
+pub mod watch;
+
+use std::collections::{BTreeMap, HashMap};
+use std::{path::Path, sync::mpsc::Sender, sync::Arc, time::Instant};
+
+use serde::{Deserialize, Serialize};
+use ndarray::prelude::*;
+
+use colormap::Colormap;
+use postprocess::{
+    FigureInfo, Histogram, MapPaths, NuCorrelation, NuSmoothing, PlotOptions, Profile,
+    RadialProfile, RegionStats,
+};
+use preprocess::{
+    DAQFilter, DAQResample, DriftCorrection, FilterMethod, Interp, InterpMethod, PeakMethod,
+    PeakSignal, TCStatus, TempUnit,
+};
+use solve::{IterationMethod, PropertyCurve, SolveStats, T0Estimation};
+use io::{ConfigFormat, DAQSheet, Decoder, ExportOptions, PreviousG2d, VideoCtx};
+use mask::Mask;
+use notify::StateEvent;
+use paths::OutputLayout;
+use error::TLCResult;
+use events::EventOptions;
+use history::History;
+use provenance::InputHashes;
+use script::PostScript;
+use stream::DaqStream;
+use threads::ThreadOptions;
+use timing::StageTiming;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Thermocouple {
+    /// 热电偶在数采文件中的列数
+    pub column_num: usize,
+    /// 热电偶的位置(y, x)
+    pub pos: (i32, i32),
+    /// 数采文件中该列的单位
+    #[serde(default)]
+    pub unit: TempUnit,
+    /// 是否参与插值，损坏的热电偶可以直接关闭
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// 显示名称
+    #[serde(default)]
+    pub label: String,
+    /// 安装位置备注
+    #[serde(default)]
+    pub location: String,
+    /// 标定修正量（°C），单位换算后叠加到温度上
+    #[serde(default)]
+    pub calibration_offset: f32,
+}
+
+impl Thermocouple {
+    /// 未设置label时以序号命名
+    pub fn name(&self, index: usize) -> String {
+        if self.label.is_empty() {
+            format!("TC{}", index)
+        } else {
+            self.label.clone()
+        }
+    }
+
+    /// 名称及安装位置，用于导出文件与报告
+    pub fn describe(&self, index: usize) -> String {
+        if self.location.is_empty() {
+            self.name(index)
+        } else {
+            format!("{}（{}）", self.name(index), self.location)
+        }
+    }
+}
+
+impl Default for Thermocouple {
+    fn default() -> Self {
+        Self {
+            column_num: 0,
+            pos: (0, 0),
+            unit: TempUnit::default(),
+            enabled: true,
+            label: String::new(),
+            location: String::new(),
+            calibration_offset: 0.,
+        }
+    }
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// 所有配置信息，与case一一对应
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TLCConfig {
+    /// 配置文件版本，读取时据此自动升级旧版本的配置
+    #[serde(default)]
+    version: u32,
+    /// 实验组名称（与视频文件名一致）
+    #[serde(default = "default_case_name")]
+    case_name: String,
+    /// 用户定义的工况标签，如涂层、试验件编号，写入结果索引用于查找
+    #[serde(default)]
+    tags: BTreeMap<String, String>,
+    /// 保存配置信息和所有结果的根目录
+    #[serde(default)]
+    save_dir: String,
+    /// `save_dir`下的目录结构与结果文件命名
+    #[serde(default)]
+    output_layout: OutputLayout,
+    /// 应用数据目录（默认配置、预设等），为空时使用系统默认位置
+    #[serde(default)]
+    app_dir: String,
+    /// 视频文件路径
+    #[serde(default = "default_video_path")]
+    video_path: String,
+    /// 数采文件路径
+    #[serde(default = "default_daq_path")]
+    daq_path: String,
+    /// 最近一次求解所用视频与数采文件的校验和，用于发现输入文件被替换
+    #[serde(default)]
+    input_hashes: InputHashes,
+    /// 配置文件保存路径（仅运行时使用）
+    #[serde(skip)]
+    config_path: String,
+    /// 配置文件格式，与读入时一致（仅运行时使用）
+    #[serde(skip)]
+    config_format: ConfigFormat,
+    /// 图片保存路径（仅运行时使用）
+    #[serde(skip)]
+    plots_path: String,
+    /// 数据保存路径（仅运行时使用）
+    #[serde(skip)]
+    data_path: String,
+    /// 视频起始帧数
+    #[serde(default)]
+    start_frame: usize,
+    /// 视频总帧数
+    #[serde(default)]
+    total_frames: usize,
+    /// 视频帧率
+    #[serde(default)]
+    frame_rate: usize,
+    /// 数采文件起始行数
+    #[serde(default)]
+    start_row: usize,
+    /// 数采文件总行数
+    #[serde(default)]
+    total_rows: usize,
+    /// .xlsx数采文件使用的worksheet
+    #[serde(default)]
+    daq_sheet: DAQSheet,
+    /// 数据在worksheet中的起始单元格（行，列）
+    #[serde(default)]
+    daq_cell_offset: (usize, usize),
+    /// 数采频率，无法从数采文件中读出时为0
+    #[serde(default)]
+    daq_sample_rate: f32,
+    /// 数采频率与视频帧率不同时的重采样方法
+    #[serde(default)]
+    daq_resample: DAQResample,
+    /// 数采文件中记录时间（s）的列，设置后各帧的实验时间取自该列，否则按视频帧率计算
+    #[serde(default)]
+    daq_time_column: Option<usize>,
+    /// 实际处理总帧数
+    #[serde(default)]
+    frame_num: usize,
+    /// 视频尺寸（高，宽）
+    #[serde(default)]
+    video_shape: (usize, usize),
+    /// 计算区域左上角坐标(y, x)
+    #[serde(default)]
+    top_left_pos: (usize, usize),
+    /// 计算区域尺寸（高，宽）
+    #[serde(default = "default_region_shape")]
+    region_shape: (usize, usize),
+    /// 计算区域内参与处理的像素，None时全部参与
+    #[serde(default)]
+    mask: Option<Mask>,
+    /// 计算区域外不受加热影响的参考区域（左上角(y, x)，尺寸(高, 宽)），
+    /// 按其逐帧平均值归一化Green值，抵消相机自动曝光、自动增益造成的整体亮度变化
+    #[serde(default)]
+    reference_patch: Option<((usize, usize), (usize, usize))>,
+    /// 各热电偶
+    #[serde(default)]
+    thermocouples: Vec<Thermocouple>,
+    /// 参考温度预处理方法
+    #[serde(default)]
+    daq_filter: DAQFilter,
+    /// 插值方法
+    #[serde(default)]
+    interp_method: InterpMethod,
+    /// `*Extra`插值方法外插的参考温度超出热电偶温度范围的允许值（°C），超出时给出警告
+    #[serde(default = "default_extrapolation_limit")]
+    extrapolation_limit: f32,
+    /// 不存储完整的参考温度矩阵，求解时再按权重计算，用于节省内存
+    #[serde(default)]
+    lazy_interp: bool,
+    /// 就地滤波，不保留raw_g2d，峰值内存减半，但重新滤波或查看滤波前后对比时须重新读取视频
+    #[serde(default)]
+    filter_in_place: bool,
+    /// 滤波方法
+    #[serde(default)]
+    filter_method: FilterMethod,
+    /// 光照漂移修正，在滤波之前进行
+    #[serde(default)]
+    drift_correction: DriftCorrection,
+    /// 用于检测坏点（卡死或饱和的像素）的前若干帧，为0时不检测
+    #[serde(default)]
+    defect_frames: usize,
+    /// 峰值（特征时刻）检测方法
+    #[serde(default)]
+    peak_method: PeakMethod,
+    /// 一次实验中重复多次加热时的划分方式
+    #[serde(default)]
+    events: EventOptions,
+    /// 导热方程迭代求解方法（初值，最大迭代步数）
+    #[serde(default)]
+    iteration_method: IterationMethod,
+    /// 两遍求解时粗网格的间隔（像素），其余像素以最近的粗网格解为初值，为0或1时不分两遍
+    #[serde(default)]
+    coarse_stride: usize,
+    /// 初始壁面温度的估计方法
+    #[serde(default)]
+    t0_estimation: T0Estimation,
+    /// 峰值温度
+    #[serde(default = "default_peak_temp")]
+    peak_temp: f32,
+    /// 固体导热系数
+    #[serde(default = "default_solid_thermal_conductivity")]
+    solid_thermal_conductivity: f32,
+    /// 固体热扩散系数
+    #[serde(default = "default_solid_thermal_diffusivity")]
+    solid_thermal_diffusivity: f32,
+    /// 随温度变化的固体导热系数，设置后代替固定值
+    #[serde(default)]
+    solid_thermal_conductivity_curve: Option<PropertyCurve>,
+    /// 随温度变化的固体热扩散系数，设置后代替固定值
+    #[serde(default)]
+    solid_thermal_diffusivity_curve: Option<PropertyCurve>,
+    /// 模型厚度（m），用于检查半无限大假设，为0时不检查
+    #[serde(default)]
+    model_thickness: f32,
+    /// 特征长度
+    #[serde(default = "default_characteristic_length")]
+    characteristic_length: f32,
+    /// 空气导热系数
+    #[serde(default = "default_air_thermal_conductivity")]
+    air_thermal_conductivity: f32,
+    /// emmmmmm
+    #[serde(default)]
+    regulator: Vec<f32>,
+    /// 空间标定：每像素对应的长度（mm），为0时坐标轴以像素为单位
+    #[serde(default)]
+    pixel_size: f32,
+    /// 矩阵.csv是否附带文件头与坐标
+    #[serde(default)]
+    export_options: ExportOptions,
+    /// 保存图片的格式、尺寸与分辨率
+    #[serde(default)]
+    plot_options: PlotOptions,
+    /// Nu分布的空间平滑方法
+    #[serde(default)]
+    nu_smoothing: NuSmoothing,
+    /// 绘制Nu分布所用的colormap
+    #[serde(default)]
+    colormap: Colormap,
+    /// 是否反转colormap
+    #[serde(default)]
+    reverse_colormap: bool,
+    /// 计算Nu/Nu0所用的参考关联式
+    #[serde(default)]
+    nu_correlation: NuCorrelation,
+    /// 参考关联式的雷诺数
+    #[serde(default)]
+    reynolds: f32,
+    /// 参考关联式的普朗特数
+    #[serde(default = "default_prandtl")]
+    prandtl: f32,
+    /// 保存Nu时运行的后处理脚本，结果保存在Nu矩阵旁
+    #[serde(default)]
+    post_scripts: Vec<PostScript>,
+    /// 计算所用的线程数与优先级
+    #[serde(default)]
+    threads: ThreadOptions,
+    /// 中间数据的内存预算（MiB），超出时释放可重新计算的数据，为0时不限制
+    #[serde(default)]
+    memory_budget: usize,
+}
+
+fn default_case_name() -> String {
+    "case_name".to_owned()
+}
+
+fn default_video_path() -> String {
+    "video_path".to_owned()
+}
+
+fn default_daq_path() -> String {
+    "daq_path".to_owned()
+}
+
+fn default_region_shape() -> (usize, usize) {
+    (500, 500)
+}
+
+fn default_extrapolation_limit() -> f32 {
+    2.
+}
+
+fn default_prandtl() -> f32 {
+    0.7
+}
+
+fn default_peak_temp() -> f32 {
+    35.48
+}
+
+fn default_solid_thermal_conductivity() -> f32 { 
+    0.19 
+}
+
+fn default_solid_thermal_diffusivity() -> f32 {
+    1.091e-7
+}
+
+fn default_characteristic_length() -> f32 {
+    0.015
+}
+
+fn default_air_thermal_conductivity() -> f32 {
+    0.0276
+}
+
+/// 配置信息 + 运行时数据
+///
+/// 运行时产生的数据会在所依赖配置变化时析构，超出内存预算时释放可重新计算的部分
+pub struct TLCData {
+    /// 配置信息
+    config: TLCConfig,
+    /// 每个视频一份
+    video_ctx: Option<VideoCtx>,
+    /// 每个线程一份
+    decoder_tool: Option<Decoder>,
+    /// 未滤波的Green值二维矩阵，排列方式如下：
+    ///
+    /// 第一帧: | X1Y1 X2Y1 ... XnY1 X1Y2 X2Y2 ... XnY2 ... |
+    ///
+    /// 第二帧: | X1Y1 X2Y1 ... XnY1 X1Y2 X2Y2 ... XnY2 ... |
+    ///
+    /// ......
+    raw_g2d: Option<Array2<u8>>,
+    /// 参考区域各帧的平均Green值，与raw_g2d同时生成，未设置参考区域时为None
+    reference_g: Option<Vec<f32>>,
+    /// 滤波后的Green值二维矩阵
+    filtered_g2d: Option<Array2<u8>>,
+    /// 坏点，与filtered_g2d同时生成，其时间历程已由相邻像素的平均值代替
+    defects: Option<Vec<bool>>,
+    /// 所有点峰值对应帧数
+    peak_frames: Option<Vec<usize>>,
+    /// 各像素的信号基线与峰值升幅，与peak_frames同时生成
+    peak_signals: Option<Vec<PeakSignal>>,
+    /// 数采文件数据
+    /// 数据排列方式与.lvm/.xlsx一致
+    daq: Option<Array2<f32>>,
+    /// 热电偶温度二维矩阵
+    /// 从daq中选出各热电偶对应的列然后转置（保证内存连续，插值时能使用SIMD）
+    /// 排列方式如下：
+    ///
+    /// 1号热电偶：| 第一帧 第二帧 ... |
+    ///
+    /// 2号热电偶：| 第一帧 第二帧 ... |
+    ///
+    /// ......
+    t2d: Option<Array2<f32>>,
+    /// 各热电偶状态，与t2d同时生成
+    tc_status: Option<Vec<TCStatus>>,
+    /// 各帧的实验时间（s），与t2d同时生成，用于与其他测量数据对齐
+    frame_times: Option<Vec<f32>>,
+    /// 插值所得温度场
+    interp: Option<Interp>,
+    /// 努塞尔数二维矩阵
+    nu2d: Option<Array2<f32>>,
+    /// 努赛尔数平均值
+    nu_nan_mean: Option<f32>,
+    /// 求解的像素数、NaN个数与平均迭代步数，与nu2d同时生成
+    solve_stats: Option<SolveStats>,
+    /// 平滑后的努塞尔数，用于绘图与统计，不平滑时为None
+    smoothed_nu2d: Option<Array2<f32>>,
+    /// 各阶段数据计算时的哈希，与当前配置的哈希不一致即为过期
+    stage_hashes: HashMap<Stage, u64>,
+    /// 配置修改记录，用于撤销
+    history: History,
+    /// 各阶段最近一次运行的耗时
+    timings: Vec<StageTiming>,
+    /// 实验过程中实时接收的数采数据
+    daq_stream: Option<DaqStream>,
+    /// 修改计算区域前的raw_g2d，重新读取视频时复用重叠部分
+    previous_g2d: Option<PreviousG2d>,
+    /// 按`threads`配置建立的线程池，默认配置下为None，使用全局线程池
+    thread_pool: Option<Arc<rayon::ThreadPool>>,
+    /// 各阶段数据最近一次被计算或作为输入使用的时刻，超出内存预算时先释放最久未用的
+    needed_at: HashMap<Stage, Instant>,
+    /// 状态变化的接收端，见`set_listener`
+    listener: Option<Sender<StateEvent>>,
+}
+
+/// 计算流程的各阶段，缓存的数据以阶段为单位失效
+///
+/// 视频→raw_g2d→filtered_g2d→peak_frames，数采→t2d→interp，peak_frames与t2d→nu2d→平滑
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Stage {
+    /// 视频解码上下文
+    Video,
+    /// 数采文件数据
+    Daq,
+    /// raw_g2d与reference_g
+    RawG2d,
+    /// filtered_g2d与defects
+    FilteredG2d,
+    /// peak_frames与peak_signals
+    PeakFrames,
+    /// t2d、tc_status与frame_times
+    T2d,
+    Interp,
+    /// nu2d、nu_nan_mean与solve_stats
+    Nu,
+    SmoothedNu,
+}
+
+impl Stage {
+    /// 依赖项在前
+    pub const ALL: [Stage; 9] = [
+        Stage::Video,
+        Stage::Daq,
+        Stage::RawG2d,
+        Stage::FilteredG2d,
+        Stage::PeakFrames,
+        Stage::T2d,
+        Stage::Interp,
+        Stage::Nu,
+        Stage::SmoothedNu,
+    ];
+
+    /// 直接依赖的阶段
+    ///
+    /// interp只是t2d的另一种存储形式（lazy_interp不影响结果），所以求解直接依赖t2d
+    pub fn deps(self) -> &'static [Stage] {
+        use Stage::*;
+        match self {
+            Video | Daq => &[],
+            RawG2d => &[Video],
+            FilteredG2d => &[RawG2d],
+            PeakFrames => &[FilteredG2d],
+            T2d => &[Daq],
+            Interp => &[T2d],
+            Nu => &[PeakFrames, T2d],
+            SmoothedNu => &[Nu],
+        }
+    }
+}
+
+impl TLCData {
+    /// 读取应用配置目录下的默认配置，不存在时使用内置默认值
+    pub fn new() -> TLCResult<Self> {
+        let default_config_path = paths::default_config_path("")?;
+        if default_config_path.exists() {
+            Self::from_path(default_config_path)
+        } else {
+            Ok(Self::from_config(TLCConfig::from_json(serde_json::json!({}))?))
+        }
+    }
+
+    pub fn from_path<P: AsRef<Path>>(config_path: P) -> TLCResult<Self> {
+        Ok(Self::from_config(TLCConfig::from_path(config_path)?))
+    }
+
+    pub fn from_config(config: TLCConfig) -> Self {
+        Self {
+            video_ctx: None,
+            decoder_tool: None,
+            raw_g2d: None,
+            reference_g: None,
+            filtered_g2d: None,
+            defects: None,
+            peak_frames: None,
+            peak_signals: None,
+            daq: None,
+            t2d: None,
+            tc_status: None,
+            frame_times: None,
+            interp: None,
+            nu2d: None,
+            nu_nan_mean: None,
+            solve_stats: None,
+            smoothed_nu2d: None,
+            stage_hashes: HashMap::new(),
+            history: History::new(&config),
+            timings: Vec::new(),
+            daq_stream: None,
+            previous_g2d: None,
+            thread_pool: None,
+            needed_at: HashMap::new(),
+            listener: None,
+            config,
+        }
+    }
+
+    /// 若配置变为`config`，已缓存的数据中需要重新计算的阶段
+    pub fn plan(&self, config: &TLCConfig) -> Vec<Stage> {
+        let hashes = config.stage_hashes();
+        Stage::ALL
+            .iter()
+            .copied()
+            .filter(|stage| matches!(self.stage_hashes.get(stage), Some(h) if *h != hashes[stage]))
+            .collect()
+    }
+
+    /// 记录该阶段的数据由当前配置算得
+    fn computed(&mut self, stage: Stage) {
+        let hash = self.config.stage_hashes()[&stage];
+        self.stage_hashes.insert(stage, hash);
+        self.mark_needed(stage);
+        self.notify(StateEvent::Computed { stage });
+    }
+
+    /// 清空所依赖配置已发生变化的数据，修改配置后调用
+    fn invalidate(&mut self) {
+        self.record_history();
+        self.drop_stages(&self.plan(&self.config));
+    }
+
+    fn drop_stages(&mut self, stages: &[Stage]) {
+        if !stages.is_empty() {
+            self.notify(StateEvent::Invalidated { stages: stages.to_vec() });
+        }
+        for &stage in stages {
+            self.stage_hashes.remove(&stage);
+            match stage {
+                Stage::Video => {
+                    self.video_ctx = None;
+                    self.decoder_tool = None;
+                    self.previous_g2d = None;
+                }
+                Stage::Daq => self.daq = None,
+                Stage::RawG2d => {
+                    self.raw_g2d = None;
+                    self.reference_g = None;
+                }
+                Stage::FilteredG2d => {
+                    self.filtered_g2d = None;
+                    self.defects = None;
+                }
+                Stage::PeakFrames => {
+                    self.peak_frames = None;
+                    self.peak_signals = None;
+                }
+                Stage::T2d => {
+                    self.t2d = None;
+                    self.tc_status = None;
+                    self.frame_times = None;
+                }
+                Stage::Interp => self.interp = None,
+                Stage::Nu => {
+                    self.nu2d = None;
+                    self.nu_nan_mean = None;
+                    self.solve_stats = None;
+                }
+                Stage::SmoothedNu => self.smoothed_nu2d = None,
+            }
+        }
+    }
+
+    pub fn get_config(&self) -> &TLCConfig {
+        &self.config
+    }
+
+    pub fn get_video_ctx(&self) -> TLCResult<&VideoCtx> {
+        self.video_ctx.as_ref().ok_or(awsl!())
+    }
+
+    pub fn get_decoder(&self) -> TLCResult<&Decoder> {
+        self.decoder_tool.as_ref().ok_or(awsl!())
+    }
+
+    pub fn get_raw_g2d(&self) -> TLCResult<ArrayView2<u8>> {
+        self.raw_g2d.as_ref().map(|v| v.view()).ok_or(awsl!())
+    }
+
+    pub fn get_reference_g(&self) -> TLCResult<&Vec<f32>> {
+        self.reference_g.as_ref().ok_or(awsl!())
+    }
+
+    pub fn get_filtered_g2d(&self) -> TLCResult<ArrayView2<u8>> {
+        self.filtered_g2d.as_ref().map(|v| v.view()).ok_or(awsl!())
+    }
+
+    pub fn get_peak_frames(&self) -> TLCResult<&Vec<usize>> {
+        self.peak_frames.as_ref().ok_or(awsl!())
+    }
+
+    pub fn get_peak_signals(&self) -> TLCResult<&Vec<PeakSignal>> {
+        self.peak_signals.as_ref().ok_or(awsl!())
+    }
+
+    pub fn get_daq(&self) -> TLCResult<ArrayView2<f32>> {
+        self.daq.as_ref().map(|v| v.view()).ok_or(awsl!())
+    }
+
+    pub fn get_t2d(&self) -> TLCResult<ArrayView2<f32>> {
+        self.t2d.as_ref().map(|v| v.view()).ok_or(awsl!())
+    }
+
+    pub fn get_tc_status(&self) -> TLCResult<&Vec<TCStatus>> {
+        self.tc_status.as_ref().ok_or(awsl!())
+    }
+
+    pub fn get_frame_times(&self) -> TLCResult<&Vec<f32>> {
+        self.frame_times.as_ref().ok_or(awsl!())
+    }
+
+    pub fn get_interp(&self) -> TLCResult<&Interp> {
+        self.interp.as_ref().ok_or(awsl!())
+    }
+
+    pub fn get_nu2d(&self) -> TLCResult<ArrayView2<f32>> {
+        self.nu2d.as_ref().map(|v| v.view())
+            .ok_or(awsl!(HandleError, "求解设置发生变化，需要重新求解"))
+    }
+
+    /// 绘图与统计所用的Nu分布，未设置平滑时即为原始结果
+    pub fn get_smoothed_nu2d(&self) -> TLCResult<ArrayView2<f32>> {
+        let nu2d = self.get_nu2d()?;
+        Ok(self.smoothed_nu2d.as_ref().map_or(nu2d, |v| v.view()))
+    }
+
+    fn smooth_nu2d(&mut self) {
+        self.smoothed_nu2d = match (self.config.nu_smoothing, &self.nu2d) {
+            (NuSmoothing::No, _) | (_, None) => None,
+            (smoothing, Some(nu2d)) => Some(postprocess::smooth(nu2d.view(), smoothing)),
+        };
+        if self.smoothed_nu2d.is_some() {
+            self.computed(Stage::SmoothedNu);
+        }
+    }
+
+    pub fn get_nu_nan_mean(&self) -> TLCResult<f32> {
+        self.nu_nan_mean.ok_or(awsl!())
+    }
+
+    pub fn get_solve_stats(&self) -> TLCResult<SolveStats> {
+        self.solve_stats.ok_or(awsl!())
+    }
+
+    /// 参考关联式给出的Nu0
+    pub fn get_nu0(&self) -> TLCResult<f32> {
+        let TLCConfig { nu_correlation, reynolds, prandtl, .. } = self.config;
+        if reynolds <= 0. || prandtl <= 0. {
+            return Err(awsl!(HandleError, "未设置雷诺数或普朗特数"));
+        }
+        let nu0 = nu_correlation.nu0(reynolds, prandtl);
+        if !nu0.is_finite() || nu0 <= 0. {
+            return Err(awsl!(HandleError, format!("雷诺数{}超出关联式适用范围", reynolds)));
+        }
+
+        Ok(nu0)
+    }
+
+    /// 强化换热比Nu/Nu0
+    pub fn get_nu_ratio(&self) -> TLCResult<Array2<f32>> {
+        let nu0 = self.get_nu0()?;
+
+        Ok(self.get_nu2d()?.mapv(|nu| nu / nu0))
+    }
+
+    pub fn set_save_dir(&mut self, save_dir: String) -> TLCResult<&mut Self> {
+        self.config.set_save_dir(save_dir)?;
+        self.record_history();
+
+        Ok(self)
+    }
+
+    pub fn set_tags(&mut self, tags: BTreeMap<String, String>) -> &mut Self {
+        self.config.tags = tags;
+        self.invalidate();
+
+        self
+    }
+
+    pub fn set_output_layout(&mut self, output_layout: OutputLayout) -> TLCResult<&mut Self> {
+        self.config.set_output_layout(output_layout)?;
+        self.record_history();
+
+        Ok(self)
+    }
+
+    pub fn set_video_path(&mut self, video_path: String) -> TLCResult<&mut Self> {
+        self.config.set_video_path(video_path)?;
+        self.invalidate();
+
+        Ok(self)
+    }
+
+    pub fn set_daq_path(&mut self, daq_path: String) -> TLCResult<&mut Self> {
+        self.config.set_daq_path(daq_path)?;
+        self.invalidate();
+
+        Ok(self)
+    }
+
+    pub fn set_daq_sheet(
+        &mut self,
+        daq_sheet: DAQSheet,
+        daq_cell_offset: (usize, usize),
+    ) -> TLCResult<&mut Self> {
+        self.config.set_daq_sheet(daq_sheet, daq_cell_offset)?;
+        self.invalidate();
+
+        Ok(self)
+    }
+
+    pub fn set_filter_method(&mut self, filter_method: FilterMethod) -> &mut Self {
+        self.config.filter_method = filter_method;
+        self.invalidate();
+
+        self
+    }
+
+    pub fn set_reference_patch(
+        &mut self,
+        reference_patch: Option<((usize, usize), (usize, usize))>,
+    ) -> &mut Self {
+        self.config.reference_patch = reference_patch;
+        self.invalidate();
+
+        self
+    }
+
+    pub fn set_peak_method(&mut self, peak_method: PeakMethod) -> &mut Self {
+        self.config.peak_method = peak_method;
+        self.invalidate();
+
+        self
+    }
+
+    pub fn set_events(&mut self, events: EventOptions) -> &mut Self {
+        self.config.events = events;
+        self.invalidate();
+
+        self
+    }
+
+    pub fn set_drift_correction(&mut self, drift_correction: DriftCorrection) -> &mut Self {
+        self.config.drift_correction = drift_correction;
+        self.invalidate();
+
+        self
+    }
+
+    pub fn set_filter_in_place(&mut self, filter_in_place: bool) -> &mut Self {
+        self.config.filter_in_place = filter_in_place;
+        self.invalidate();
+
+        self
+    }
+
+    pub fn set_defect_frames(&mut self, defect_frames: usize) -> &mut Self {
+        self.config.defect_frames = defect_frames;
+        self.invalidate();
+
+        self
+    }
+
+    pub fn set_daq_filter(&mut self, daq_filter: DAQFilter) -> &mut Self {
+        self.config.daq_filter = daq_filter;
+        self.invalidate();
+
+        self
+    }
+
+    pub fn set_lazy_interp(&mut self, lazy_interp: bool) -> &mut Self {
+        self.config.lazy_interp = lazy_interp;
+        self.invalidate();
+
+        self
+    }
+
+    pub fn set_interp_method(&mut self, interp_method: InterpMethod) -> TLCResult<&mut Self> {
+        use InterpMethod::*;
+        let tcs = &mut self.config.thermocouples;
+        match interp_method {
+            Horizontal | HorizontalExtra | HorizontalSpline => {
+                tcs.sort_unstable_by_key(|tc| tc.pos.1)
+            }
+            Vertical | VerticalExtra | VerticalSpline => tcs.sort_unstable_by_key(|tc| tc.pos.0),
+            Bilinear((h, w)) | BilinearExtra((h, w)) => {
+                if h * w != tcs.len() {
+                    return Err(awsl!(HandleError,format!("热电偶行数({})列数({})之积不等于热电偶数量", h, w)));
+                }
+                let positions: Vec<_> = tcs.iter().map(|tc| tc.pos).collect();
+                if preprocess::grid_shape(&positions) != Some((h, w)) {
+                    let msg = format!("热电偶位置未构成{}行{}列的规则网格", h, w);
+                    return Err(awsl!(HandleError, msg));
+                }
+                // 先按y排序
+                tcs.sort_unstable_by_key(|tc| tc.pos.0);
+                // 行内按x排序
+                for i in (0..).step_by(w).take(h) {
+                    tcs[i..i + w].sort_unstable_by_key(|tc| tc.pos.1);
+                }
+            }
+            Idw { .. } | ThinPlateSpline => {}
+        }
+        self.config.interp_method = interp_method;
+        self.invalidate();
+
+        Ok(self)
+    }
+
+    pub fn set_extrapolation_limit(&mut self, extrapolation_limit: f32) -> &mut Self {
+        self.config.extrapolation_limit = extrapolation_limit;
+        self.invalidate();
+
+        self
+    }
+
+    /// 根据热电偶位置推断插值方法，见`InterpMethod::infer`，不修改配置
+    pub fn infer_interp_method(&self) -> InterpMethod {
+        let positions: Vec<_> = self.config.thermocouples.iter().map(|tc| tc.pos).collect();
+        self.config.interp_method.infer(&positions)
+    }
+
+    pub fn set_iteration_method(&mut self, iteration_method: IterationMethod) -> &mut Self {
+        self.config.iteration_method = iteration_method;
+        self.invalidate();
+
+        self
+    }
+
+    pub fn set_t0_estimation(&mut self, t0_estimation: T0Estimation) -> &mut Self {
+        self.config.t0_estimation = t0_estimation;
+        self.invalidate();
+
+        self
+    }
+
+    pub fn set_coarse_stride(&mut self, coarse_stride: usize) -> &mut Self {
+        self.config.coarse_stride = coarse_stride;
+        self.invalidate();
+
+        self
+    }
+
+    pub fn set_region(
+        &mut self,
+        top_left_pos: (usize, usize),
+        region_shape: (usize, usize),
+    ) -> &mut Self {
+        // 重叠部分的raw_g2d留待重新读取视频时复用
+        if (top_left_pos, region_shape) != (self.config.top_left_pos, self.config.region_shape) {
+            self.keep_raw_g2d();
+        }
+        self.config.top_left_pos = top_left_pos;
+        self.config.region_shape = region_shape;
+        self.invalidate();
+
+        self
+    }
+
+    pub fn set_regulator(&mut self, regulator: Vec<f32>) -> &mut Self {
+        self.config.regulator = regulator;
+        self.invalidate();
+
+        self
+    }
+
+    pub fn set_nu_correlation(
+        &mut self,
+        nu_correlation: NuCorrelation,
+        reynolds: f32,
+        prandtl: f32,
+    ) -> &mut Self {
+        self.config.nu_correlation = nu_correlation;
+        self.config.reynolds = reynolds;
+        self.config.prandtl = prandtl;
+        self.record_history();
+
+        self
+    }
+
+    /// 只需重新平滑，不必重新求解
+    pub fn set_nu_smoothing(&mut self, nu_smoothing: NuSmoothing) -> TLCResult<&mut Self> {
+        if nu_smoothing.kernel_size() % 2 == 0 {
+            return Err(awsl!(ConfigError, "平滑核尺寸必须为正奇数"));
+        }
+        self.config.nu_smoothing = nu_smoothing;
+        self.invalidate();
+        self.smooth_nu2d();
+
+        Ok(self)
+    }
+
+    pub fn set_pixel_size(&mut self, pixel_size: f32) -> &mut Self {
+        self.config.pixel_size = pixel_size;
+        self.record_history();
+
+        self
+    }
+
+    pub fn set_export_options(&mut self, export_options: ExportOptions) -> &mut Self {
+        self.config.export_options = export_options;
+        self.record_history();
+
+        self
+    }
+
+    pub fn set_plot_options(&mut self, plot_options: PlotOptions) -> &mut Self {
+        self.config.plot_options = plot_options;
+        self.record_history();
+
+        self
+    }
+
+    pub fn set_post_scripts(&mut self, post_scripts: Vec<PostScript>) -> TLCResult<&mut Self> {
+        for script in &post_scripts {
+            script.check_name()?;
+        }
+        self.config.post_scripts = post_scripts;
+        self.record_history();
+
+        Ok(self)
+    }
+
+    pub fn set_colormap(&mut self, colormap: Colormap, reverse: bool) -> TLCResult<&mut Self> {
+        // 自定义查色表先读一遍，保证文件可用
+        colormap.lut(reverse)?;
+        self.config.colormap = colormap;
+        self.config.reverse_colormap = reverse;
+        self.record_history();
+
+        Ok(self)
+    }
+
+    pub fn set_peak_temp(&mut self, peak_temp: f32) -> &mut Self {
+        self.config.peak_temp = peak_temp;
+        self.invalidate();
+
+        self
+    }
+
+    pub fn set_solid_thermal_conductivity(&mut self, solid_thermal_conductivity: f32) -> &mut Self {
+        self.config.solid_thermal_conductivity = solid_thermal_conductivity;
+        self.invalidate();
+
+        self
+    }
+
+    pub fn set_solid_thermal_diffusivity(&mut self, solid_thermal_diffusivity: f32) -> &mut Self {
+        self.config.solid_thermal_diffusivity = solid_thermal_diffusivity;
+        self.invalidate();
+
+        self
+    }
+
+    /// None时恢复使用固定值
+    pub fn set_solid_thermal_conductivity_curve(
+        &mut self,
+        curve: Option<PropertyCurve>,
+    ) -> &mut Self {
+        self.config.solid_thermal_conductivity_curve = curve;
+        self.invalidate();
+
+        self
+    }
+
+    /// None时恢复使用固定值
+    pub fn set_solid_thermal_diffusivity_curve(
+        &mut self,
+        curve: Option<PropertyCurve>,
+    ) -> &mut Self {
+        self.config.solid_thermal_diffusivity_curve = curve;
+        self.invalidate();
+
+        self
+    }
+
+    pub fn set_model_thickness(&mut self, model_thickness: f32) -> &mut Self {
+        self.config.model_thickness = model_thickness;
+        self.record_history();
+
+        self
+    }
+
+    pub fn set_air_thermal_conductivity(&mut self, air_thermal_conductivity: f32) -> &mut Self {
+        self.config.air_thermal_conductivity = air_thermal_conductivity;
+        self.invalidate();
+
+        self
+    }
+
+    pub fn set_characteristic_length(&mut self, characteristic_length: f32) -> &mut Self {
+        self.config.characteristic_length = characteristic_length;
+        self.invalidate();
+
+        self
+    }
+
+    pub fn set_start_frame(&mut self, start_frame: usize) -> TLCResult<&mut Self> {
+        self.config.set_start_frame(start_frame)?;
+        self.invalidate();
+
+        Ok(self)
+    }
+
+    pub fn set_start_row(&mut self, start_row: usize) -> TLCResult<&mut Self> {
+        self.config.set_start_row(start_row)?;
+        self.invalidate();
+
+        Ok(self)
+    }
+
+    pub fn set_daq_sample_rate(&mut self, daq_sample_rate: f32) -> &mut Self {
+        self.config.set_daq_sample_rate(daq_sample_rate);
+        self.invalidate();
+
+        self
+    }
+
+    pub fn set_daq_resample(&mut self, daq_resample: DAQResample) -> &mut Self {
+        self.config.set_daq_resample(daq_resample);
+        self.invalidate();
+
+        self
+    }
+
+    pub fn set_daq_time_column(&mut self, daq_time_column: Option<usize>) -> &mut Self {
+        self.config.daq_time_column = daq_time_column;
+        self.invalidate();
+
+        self
+    }
+
+    pub fn synchronize(&mut self, frame_index: usize, row_index: usize) -> &mut Self {
+        self.config.synchronize(frame_index, row_index);
+        self.invalidate();
+
+        self
+    }
+
+    pub fn set_thermocouples(&mut self, thermocouples: Vec<Thermocouple>) -> &mut Self {
+        self.config.set_thermocouples(thermocouples);
+        self.invalidate();
+
+        self
+    }
+
+    pub fn save_config(&self) -> TLCResult<&Self> {
+        self.config.save()?;
+
+        Ok(self)
+    }
+
+    pub fn save_nu(&mut self) -> TLCResult<&mut Self> {
+        self.config.refresh_paths()?;
+        let meta = self.data_meta("Nu", true)?;
+        io::save_data(self.get_nu2d()?, &self.config.data_path, Some(&meta))?;
+        let path = self.config.data_path.clone();
+        self.notify(StateEvent::ResultsSaved { path });
+        if let Err(err) = self.save_manifest() {
+            tracing::warn!(%err, "failed to write reproducibility manifest");
+        }
+        // 更新结果说明中的文件列表
+        if let Err(err) = self.save_results() {
+            tracing::warn!(%err, "failed to write results metadata");
+        }
+        if let Err(err) = self.update_catalog() {
+            tracing::warn!(%err, "failed to update results catalog");
+        }
+        if let Err(err) = self.run_post_scripts() {
+            tracing::warn!(%err, "failed to run post-processing scripts");
+        }
+
+        Ok(self)
+    }
+
+    /// 沿`axis`平均的Nu分布（0为展向平均的Nu(x)，1为Nu(y)），保存.csv与曲线图
+    pub fn save_profile(&mut self, axis: usize) -> TLCResult<Profile> {
+        let (name, x_desc) = match axis {
+            0 => ("x", "x (pixel)"),
+            1 => ("y", "y (pixel)"),
+            _ => return Err(awsl!(HandleError, "axis只能为0或1")),
+        };
+        let profile = postprocess::profile(self.get_smoothed_nu2d()?, Axis(axis));
+        let pos: Vec<_> = (0..profile.mean.len()).map(|i| i as f32).collect();
+        let mean = profile.mean.as_slice().ok_or(awsl!())?;
+        let std = profile.std.as_slice().ok_or(awsl!())?;
+
+        let file_name = format!("{}_profile_{}", self.config.case_name, name);
+        let data_path = Path::new(&self.config.data_path).with_file_name(&file_name);
+        let header = [name, "nu_mean", "nu_std"];
+        io::save_table(data_path.with_extension("csv"), &header, &[&pos, mean, std])?;
+        let plot_path = Path::new(&self.config.plots_path).with_file_name(&file_name);
+        let (desc, options) = ((x_desc, "Nu"), &self.config.plot_options);
+        postprocess::plot_profile(plot_path, &pos, mean, Some(std), desc, options)?;
+
+        Ok(profile)
+    }
+
+    /// 冲击射流的周向平均Nu(r/D)，保存.csv与带误差线的曲线图
+    pub fn save_radial_profile(
+        &mut self,
+        center: (f32, f32),
+        diameter: f32,
+        bin_width: f32,
+    ) -> TLCResult<RadialProfile> {
+        let nu2d = self.get_smoothed_nu2d()?;
+        let radial = postprocess::radial_profile(nu2d, center, diameter, bin_width)?;
+        let count: Vec<_> = radial.count.iter().map(|&c| c as f32).collect();
+
+        let file_name = format!("{}_radial", self.config.case_name);
+        let data_path = Path::new(&self.config.data_path).with_file_name(&file_name);
+        let header = ["r/D", "nu_mean", "nu_std", "count"];
+        let columns = [&radial.r[..], &radial.mean, &radial.std, &count];
+        io::save_table(data_path.with_extension("csv"), &header, &columns)?;
+        let plot_path = Path::new(&self.config.plots_path).with_file_name(&file_name);
+        let (r, mean, std) = (&radial.r, &radial.mean, &radial.std);
+        let (desc, options) = (("r/D", "Nu"), &self.config.plot_options);
+        postprocess::plot_profile(plot_path, r, mean, Some(std), desc, options)?;
+
+        Ok(radial)
+    }
+
+    /// 沿任意直线采样Nu，返回(距离, Nu)与曲线图(.png)
+    pub fn probe_line(
+        &mut self,
+        p0: (f32, f32),
+        p1: (f32, f32),
+        n: usize,
+    ) -> TLCResult<(Vec<(f32, f32)>, Vec<u8>)> {
+        let probe = postprocess::line_probe(self.get_smoothed_nu2d()?, p0, p1, n);
+        let (dist, nu): (Vec<_>, Vec<_>) = probe.iter().copied().unzip();
+
+        // 返回给前端显示，固定为默认设置的.png
+        let plot_path = Path::new(&self.config.plots_path)
+            .with_file_name(format!("{}_probe.png", self.config.case_name));
+        let (desc, options) = (("distance (pixel)", "Nu"), &PlotOptions::default());
+        postprocess::plot_profile(&plot_path, &dist, &nu, None, desc, options)?;
+        let buf = std::fs::read(&plot_path).map_err(|err| awsl!(err))?;
+
+        Ok((probe, buf))
+    }
+
+    /// 各多边形子区域内Nu的统计量，汇总保存为.csv
+    pub fn save_region_stats(
+        &mut self,
+        polygons: &[Vec<(f32, f32)>],
+    ) -> TLCResult<Vec<RegionStats>> {
+        let stats = postprocess::polygon_stats(self.get_smoothed_nu2d()?, polygons);
+
+        let index: Vec<_> = (0..stats.len()).map(|i| i as f32).collect();
+        let column = |f: fn(&RegionStats) -> f32| stats.iter().map(f).collect::<Vec<_>>();
+        let (mean, std) = (column(|s| s.mean), column(|s| s.std));
+        let (min, max) = (column(|s| s.min), column(|s| s.max));
+        let (area, valid) = (column(|s| s.area as f32), column(|s| s.valid as f32));
+
+        let table_path = Path::new(&self.config.data_path)
+            .with_file_name(format!("{}_regions.csv", self.config.case_name));
+        let header = ["region", "nu_mean", "nu_std", "nu_min", "nu_max", "area", "valid"];
+        let columns = [&index[..], &mean, &std, &min, &max, &area, &valid];
+        io::save_table(table_path, &header, &columns)?;
+
+        Ok(stats)
+    }
+
+    /// Nu分布直方图与百分位数，`render`为true时同时返回直方图(.png)
+    pub fn get_nu_histogram(
+        &self,
+        bins: usize,
+        render: bool,
+    ) -> TLCResult<(Histogram, Option<Vec<u8>>)> {
+        let hist = postprocess::histogram(self.get_smoothed_nu2d()?, bins)?;
+        if !render {
+            return Ok((hist, None));
+        }
+
+        let plot_path = Path::new(&self.config.plots_path)
+            .with_file_name(format!("{}_histogram.png", self.config.case_name));
+        postprocess::plot_histogram(&plot_path, &hist, &PlotOptions::default())?;
+        let buf = std::fs::read(&plot_path).map_err(|err| awsl!(err))?;
+
+        Ok((hist, Some(buf)))
+    }
+
+    /// 保存用于报告的Nu云图，返回保存路径
+    pub fn save_nu_figure(&mut self, range: Option<(f32, f32)>) -> TLCResult<String> {
+        let timer = self.start_stage("plot");
+        let (vmin, vmax) = match range {
+            Some(range) => range,
+            None => {
+                let nu_nan_mean = self.get_nu_nan_mean()?;
+                (nu_nan_mean * 0.6, nu_nan_mean * 2.)
+            }
+        };
+        let colormap = self.config.colormap.lut(self.config.reverse_colormap)?;
+        let info = FigureInfo {
+            title: &self.config.case_name,
+            label: "Nu",
+            pixel_size: self.config.pixel_size,
+        };
+
+        let figure_path = Path::new(&self.config.plots_path)
+            .with_file_name(format!("{}_figure", self.config.case_name));
+        let figure_path = postprocess::plot_figure(
+            figure_path,
+            self.get_smoothed_nu2d()?,
+            (vmin, vmax),
+            &colormap,
+            &info,
+            &self.config.plot_options,
+        )?;
+        self.finish(timer);
+
+        Ok(figure_path.to_str().ok_or(awsl!(figure_path))?.to_owned())
+    }
+
+    /// 按像素顺序排列的`values`整理为与nu2d一致上下翻转的二维矩阵
+    fn pixel_map(&self, values: Vec<f32>) -> TLCResult<Array2<f32>> {
+        let mut map = Array2::from_shape_vec(self.config.region_shape, values)
+            .map_err(|err| awsl!(err))?;
+        map.invert_axis(Axis(0));
+
+        Ok(map)
+    }
+
+    /// 各像素达到峰值的时刻（s，自起始帧起），遮罩排除的像素为NaN
+    ///
+    /// 已生成各帧的实验时间时以其为准，数采时间列为时间基准时与按帧率换算的不同
+    pub fn get_peak_time_map(&self) -> TLCResult<Array2<f32>> {
+        let frame_rate = self.config.frame_rate;
+        let frame_times = match (&self.frame_times, self.config.daq_time_column) {
+            (_, None) if frame_rate == 0 => {
+                return Err(awsl!(ConfigError, "视频帧率为0，没有时间基准"))
+            }
+            (Some(frame_times), _) => frame_times.clone(),
+            (None, None) => (0..self.config.frame_num)
+                .map(|frame| frame as f32 / frame_rate as f32)
+                .collect(),
+            (None, Some(_)) => {
+                return Err(awsl!(
+                    ConfigError,
+                    "以数采时间列为时间基准，需要先读取热电偶温度"
+                ))
+            }
+        };
+        let start = frame_times.first().copied().unwrap_or_default();
+        let mask = self.config.mask_pixels()?;
+        let times = self
+            .get_peak_frames()?
+            .iter()
+            .enumerate()
+            .map(|(pos, &peak_frame)| match (&mask, frame_times.get(peak_frame)) {
+                (Some(mask), _) if !mask[pos] => f32::NAN,
+                (_, Some(time)) => time - start,
+                (_, None) => f32::NAN,
+            })
+            .collect();
+
+        self.pixel_map(times)
+    }
+
+    /// 各像素峰值处Green值相对基线的升幅，遮罩排除的像素为NaN
+    pub fn get_amplitude_map(&self) -> TLCResult<Array2<f32>> {
+        let amplitudes = self.get_peak_signals()?.iter().map(|s| s.amplitude).collect();
+
+        self.pixel_map(amplitudes)
+    }
+
+    /// 保存峰值时刻的.csv与带色标的云图，峰值时刻的空间分布可用于检查同步误差与加热不均匀
+    pub fn save_peak_time_map(&mut self) -> TLCResult<MapPaths> {
+        if self.peak_frames.is_none() {
+            self.detect_peak()?;
+        }
+
+        self.save_map(self.get_peak_time_map()?.view(), "peak_time", "s", "t (s)")
+    }
+
+    /// 保存峰值升幅的.csv与带色标的云图，升幅小的区域涂层薄或光照不足，Nu噪声较大
+    pub fn save_amplitude_map(&mut self) -> TLCResult<MapPaths> {
+        if self.peak_signals.is_none() {
+            self.detect_peak()?;
+        }
+
+        self.save_map(self.get_amplitude_map()?.view(), "amplitude", "G", "amplitude")
+    }
+
+    /// 文件名为工况名加`_{name}`后缀，色标范围取有效值的最小值与最大值
+    fn save_map(
+        &self,
+        map: ArrayView2<f32>,
+        name: &str,
+        unit: &'static str,
+        label: &str,
+    ) -> TLCResult<MapPaths> {
+        let file_name = format!("{}_{}", self.config.case_name, name);
+        let data_path = Path::new(&self.config.data_path)
+            .with_file_name(&file_name)
+            .with_extension("csv");
+        let meta = self.data_meta(unit, true)?;
+        io::save_data(map, &data_path, Some(&meta))?;
+
+        let range = map
+            .iter()
+            .filter(|v| !v.is_nan())
+            .fold((f32::MAX, f32::MIN), |(min, max), &v| (min.min(v), max.max(v)));
+        let colormap = self.config.colormap.lut(self.config.reverse_colormap)?;
+        let info = FigureInfo {
+            title: &self.config.case_name,
+            label,
+            pixel_size: self.config.pixel_size,
+        };
+        let plot_path = Path::new(&self.config.plots_path).with_file_name(&file_name);
+        let plot_path = postprocess::plot_figure(
+            plot_path,
+            map,
+            range,
+            &colormap,
+            &info,
+            &self.config.plot_options,
+        )?;
+
+        Ok(MapPaths {
+            data_path: data_path.to_str().ok_or(awsl!(data_path))?.to_owned(),
+            plot_path: plot_path.to_str().ok_or(awsl!(plot_path))?.to_owned(),
+        })
+    }
+
+    /// Nu/Nu0保存在Nu旁边，文件名加`_ratio`后缀
+    pub fn save_nu_ratio(&mut self) -> TLCResult<&mut Self> {
+        let ratio_path = Path::new(&self.config.data_path)
+            .with_file_name(format!("{}_ratio.csv", self.config.case_name));
+        let meta = self.data_meta("Nu/Nu0", true)?;
+        io::save_data(self.get_nu_ratio()?.view(), ratio_path, Some(&meta))?;
+
+        Ok(self)
+    }
+}
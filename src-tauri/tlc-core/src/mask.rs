@@ -0,0 +1,104 @@
+use serde::{Deserialize, Serialize};
+
+use super::{error::TLCResult, postprocess, TLCConfig, TLCData};
+use crate::awsl;
+
+/// 计算区域内参与处理的像素，孔、肋顶、紧固件等区域排除后不滤波、不求解，Nu记为NaN
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Mask {
+    /// 在界面上绘制的多边形，顶点为计算区域内的(y, x)坐标，多边形内的像素参与处理
+    Polygons(Vec<Vec<(f32, f32)>>),
+    /// 黑白图片，非黑色的像素参与处理，尺寸须与计算区域或视频一致，与视频一致时按计算区域裁剪
+    Image(String),
+}
+
+impl Mask {
+    /// 按计算区域内的像素顺序（行优先）展开，true为参与处理
+    fn rasterize(&self, config: &TLCConfig) -> TLCResult<Vec<bool>> {
+        let (h, w) = config.region_shape;
+        match self {
+            Mask::Polygons(polygons) => Ok((0..h * w)
+                .map(|pos| {
+                    let point = ((pos / w) as f32, (pos % w) as f32);
+                    polygons
+                        .iter()
+                        .any(|polygon| postprocess::contains(polygon, point))
+                })
+                .collect()),
+            Mask::Image(path) => {
+                let img = image::open(path)
+                    .map_err(|err| awsl!(DataReadError, err, path))?
+                    .to_luma8();
+                let (img_w, img_h) = img.dimensions();
+                let (y0, x0) = match (img_h as usize, img_w as usize) {
+                    shape if shape == config.region_shape => (0, 0),
+                    shape if shape == config.video_shape => config.top_left_pos,
+                    (img_h, img_w) => {
+                        return Err(awsl!(
+                            ConfigError,
+                            format!(
+                                "遮罩尺寸({}, {})与计算区域({}, {})及视频({}, {})均不一致",
+                                img_h, img_w, h, w, config.video_shape.0, config.video_shape.1
+                            )
+                        ))
+                    }
+                };
+
+                Ok((0..h * w)
+                    .map(|pos| {
+                        let (y, x) = (y0 + pos / w, x0 + pos % w);
+                        img.get_pixel(x as u32, y as u32)[0] > 0
+                    })
+                    .collect())
+            }
+        }
+    }
+}
+
+impl TLCConfig {
+    /// 未设置遮罩时为None
+    pub(crate) fn mask_pixels(&self) -> TLCResult<Option<Vec<bool>>> {
+        self.mask
+            .as_ref()
+            .map(|mask| mask.rasterize(self))
+            .transpose()
+    }
+}
+
+impl TLCData {
+    /// 设置前先检查遮罩能否读取及尺寸是否匹配，None为取消遮罩
+    pub fn set_mask(&mut self, mask: Option<Mask>) -> TLCResult<&mut Self> {
+        if let Some(mask) = &mask {
+            mask.rasterize(&self.config)?;
+        }
+        self.config.mask = mask;
+        self.invalidate();
+
+        Ok(self)
+    }
+
+    /// 参与处理的像素占计算区域的比例
+    pub fn get_mask_ratio(&self) -> TLCResult<f32> {
+        Ok(match self.config.mask_pixels()? {
+            Some(pixels) => {
+                pixels.iter().filter(|&&p| p).count() as f32 / pixels.len().max(1) as f32
+            }
+            None => 1.,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rasterize_polygon() {
+        let mut config = TLCConfig::from_json(serde_json::json!({})).unwrap();
+        config.region_shape = (4, 4);
+        let square = vec![(0.5, 0.5), (0.5, 2.5), (2.5, 2.5), (2.5, 0.5)];
+        let pixels = Mask::Polygons(vec![square]).rasterize(&config).unwrap();
+        let inside: Vec<_> = (0..16).filter(|&pos| pixels[pos]).collect();
+        assert_eq!(inside, vec![5, 6, 9, 10]);
+    }
+}
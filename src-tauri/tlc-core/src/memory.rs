@@ -0,0 +1,170 @@
+use std::mem::size_of;
+use std::time::Instant;
+
+use serde::Serialize;
+
+use super::{preprocess::Interp, Stage, TLCData};
+
+const MIB: usize = 1 << 20;
+
+/// 各中间数据占用的内存（字节），只计大块数据，为近似值
+#[derive(Debug, Default, Serialize)]
+pub struct MemoryUsage {
+    /// 修改计算区域前的raw_g2d
+    pub previous_g2d: usize,
+    pub raw_g2d: usize,
+    pub filtered_g2d: usize,
+    pub daq: usize,
+    pub t2d: usize,
+    pub interp: usize,
+    /// nu2d与平滑后的nu2d
+    pub nu2d: usize,
+    pub total: usize,
+}
+
+impl Interp {
+    fn bytes(&self) -> usize {
+        match self {
+            Interp::Eager(temps) => temps.len() * size_of::<f32>(),
+            Interp::Lazy { t2d, weights } => {
+                t2d.len() * size_of::<f32>()
+                    + weights
+                        .iter()
+                        .map(|w| {
+                            size_of::<Vec<(usize, f32)>>() + w.len() * size_of::<(usize, f32)>()
+                        })
+                        .sum::<usize>()
+            }
+        }
+    }
+}
+
+impl TLCData {
+    /// 设置内存预算（MiB），为0时不限制；设置后立即按预算释放
+    pub fn set_memory_budget(&mut self, memory_budget: usize) -> &mut Self {
+        self.config.memory_budget = memory_budget;
+        self.invalidate();
+        self.reserve_memory(0, &[]);
+
+        self
+    }
+
+    pub fn get_memory_usage(&self) -> MemoryUsage {
+        let mut usage = MemoryUsage {
+            previous_g2d: self.previous_g2d.as_ref().map_or(0, |p| p.g2d.len()),
+            raw_g2d: self.raw_g2d.as_ref().map_or(0, |g2d| g2d.len()),
+            filtered_g2d: self.filtered_g2d.as_ref().map_or(0, |g2d| g2d.len()),
+            daq: self
+                .daq
+                .as_ref()
+                .map_or(0, |daq| daq.len() * size_of::<f32>()),
+            t2d: self
+                .t2d
+                .as_ref()
+                .map_or(0, |t2d| t2d.len() * size_of::<f32>()),
+            interp: self.interp.as_ref().map_or(0, Interp::bytes),
+            nu2d: [&self.nu2d, &self.smoothed_nu2d]
+                .iter()
+                .filter_map(|nu2d| nu2d.as_ref())
+                .map(|nu2d| nu2d.len() * size_of::<f32>())
+                .sum(),
+            total: 0,
+        };
+        usage.total = usage.previous_g2d
+            + usage.raw_g2d
+            + usage.filtered_g2d
+            + usage.daq
+            + usage.t2d
+            + usage.interp
+            + usage.nu2d;
+
+        usage
+    }
+
+    /// 该阶段刚被计算或刚作为输入使用过
+    pub(crate) fn mark_needed(&mut self, stage: Stage) {
+        let now = Instant::now();
+        self.needed_at.insert(stage, now);
+        for &dep in stage.deps() {
+            self.needed_at.insert(dep, now);
+        }
+    }
+
+    /// 即将分配`bytes`字节前调用，超出预算时按最久未用的顺序释放可重新计算的中间数据，
+    /// `keep`为接下来要用到的阶段
+    ///
+    /// 只释放下游结果已算出、用到时能自动重新读取的数据：修改区域前的raw_g2d、
+    /// 已滤波的raw_g2d、已生成t2d的数采数据（实时接收时除外）
+    pub(crate) fn reserve_memory(&mut self, bytes: usize, keep: &[Stage]) {
+        let budget = self.config.memory_budget * MIB;
+        if budget == 0 {
+            return;
+        }
+        let over = |data: &Self| data.get_memory_usage().total + bytes > budget;
+        if !over(self) {
+            return;
+        }
+
+        if !keep.contains(&Stage::RawG2d) && self.previous_g2d.take().is_some() {
+            tracing::info!("previous raw_g2d evicted");
+        }
+        let mut candidates: Vec<_> = [
+            (
+                Stage::RawG2d,
+                self.raw_g2d.is_some() && self.filtered_g2d.is_some(),
+            ),
+            (
+                Stage::Daq,
+                self.daq.is_some() && self.t2d.is_some() && self.daq_stream.is_none(),
+            ),
+        ]
+        .iter()
+        .filter(|&&(stage, evictable)| evictable && !keep.contains(&stage))
+        .map(|&(stage, _)| (stage, self.needed_at.get(&stage).copied()))
+        .collect();
+        // 从未标记过的排在最前
+        candidates.sort_by_key(|&(_, needed_at)| needed_at);
+
+        for (stage, _) in candidates {
+            if !over(self) {
+                return;
+            }
+            match stage {
+                Stage::RawG2d => self.drop_stages(&[Stage::RawG2d]),
+                Stage::Daq => self.drop_stages(&[Stage::Daq]),
+                _ => {}
+            }
+            tracing::info!(?stage, "evicted to stay within memory budget");
+        }
+        if over(self) {
+            tracing::warn!(
+                budget_mib = self.config.memory_budget,
+                usage_mib = self.get_memory_usage().total / MIB,
+                request_mib = bytes / MIB,
+                "memory budget exceeded, nothing left to evict"
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ndarray::Array2;
+
+    use super::*;
+    use crate::TLCConfig;
+
+    #[test]
+    fn evict_filtered_raw() {
+        let config = TLCConfig::from_json(serde_json::json!({})).unwrap();
+        let mut data = TLCData::from_config(config);
+        data.raw_g2d = Some(Array2::zeros((100, MIB / 100)));
+        data.filtered_g2d = Some(Array2::zeros((100, MIB / 100)));
+        data.set_memory_budget(4);
+        assert!(data.raw_g2d.is_some());
+
+        data.reserve_memory(3 * MIB, &[]);
+        assert!(data.raw_g2d.is_none());
+        assert!(data.filtered_g2d.is_some());
+    }
+}
@@ -0,0 +1,348 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+use ndarray::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value as Json};
+
+use super::{
+    error::TLCResult,
+    io::{self, DataMeta},
+    postprocess,
+    provenance::{sha256_hex, InputHashes},
+    Stage, TLCConfig, TLCData,
+};
+use crate::awsl;
+
+/// 多视角清单：同一表面由多个相机分别拍摄，共用一份数采数据
+#[derive(Debug, Deserialize)]
+pub struct MultiViewManifest {
+    /// 模板配置文件，数采文件与热电偶以模板为准
+    pub template: String,
+    /// 拼接网格的间距（mm），默认取各视角中最小的像素尺寸
+    #[serde(default)]
+    pub spacing: Option<f32>,
+    pub views: Vec<ViewSpec>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ViewSpec {
+    pub name: String,
+    /// 覆盖模板中的部分配置，通常为`video_path`、`start_frame`、`region`等
+    #[serde(default)]
+    pub overrides: Map<String, Json>,
+    pub transform: ViewTransform,
+}
+
+/// 计算区域像素坐标到物理坐标（mm）的映射，物理坐标与图像一致，x向右、y向下
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct ViewTransform {
+    /// 计算区域左上角像素在物理坐标系中的位置
+    pub origin: (f32, f32),
+    /// 该视角下一个像素对应的长度（mm）
+    pub pixel_size: f32,
+    /// 计算区域相对物理坐标系的旋转角度（°），图像中顺时针为正
+    #[serde(default)]
+    pub rotation: f32,
+}
+
+/// 单个视角的处理结果
+#[derive(Debug, Serialize)]
+pub struct ViewSummary {
+    pub name: String,
+    /// 处理失败时的错误信息，失败的视角不参与拼接
+    pub error: Option<String>,
+    pub nu_nan_mean: f32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StitchResult {
+    pub nu_path: String,
+    pub plot_path: String,
+    /// 拼接后矩阵的(行, 列)
+    pub shape: (usize, usize),
+    /// 拼接网格左上角的物理坐标
+    pub origin: (f32, f32),
+    pub spacing: f32,
+    pub nu_nan_mean: f32,
+    pub views: Vec<ViewSummary>,
+}
+
+impl ViewTransform {
+    /// 像素坐标(x, y)到物理坐标
+    fn forward(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        let (sin, cos) = self.rotation.to_radians().sin_cos();
+        let (x, y) = (x * self.pixel_size, y * self.pixel_size);
+
+        (
+            self.origin.0 + x * cos - y * sin,
+            self.origin.1 + x * sin + y * cos,
+        )
+    }
+
+    /// 物理坐标到像素坐标(x, y)
+    fn inverse(&self, (px, py): (f32, f32)) -> (f32, f32) {
+        let (sin, cos) = self.rotation.to_radians().sin_cos();
+        let (dx, dy) = (px - self.origin.0, py - self.origin.1);
+
+        (
+            (dx * cos + dy * sin) / self.pixel_size,
+            (-dx * sin + dy * cos) / self.pixel_size,
+        )
+    }
+}
+
+/// 双线性插值，四个相邻像素中有NaN或超出范围时为None
+fn bilinear(nu2d: ArrayView2<f32>, (x, y): (f32, f32)) -> Option<f32> {
+    let (h, w) = nu2d.dim();
+    if x < 0. || y < 0. || x > (w - 1) as f32 || y > (h - 1) as f32 {
+        return None;
+    }
+    let (x0, y0) = (x.floor() as usize, y.floor() as usize);
+    let (x1, y1) = ((x0 + 1).min(w - 1), (y0 + 1).min(h - 1));
+    let (fx, fy) = (x - x0 as f32, y - y0 as f32);
+    let [a, b, c, d] = [
+        nu2d[(y0, x0)],
+        nu2d[(y0, x1)],
+        nu2d[(y1, x0)],
+        nu2d[(y1, x1)],
+    ];
+    if [a, b, c, d].iter().any(|v| v.is_nan()) {
+        return None;
+    }
+    let top = a + (b - a) * fx;
+    let bottom = c + (d - c) * fx;
+
+    Some(top + (bottom - top) * fy)
+}
+
+/// 将各视角的Nu矩阵（第0行为计算区域最上方）按物理坐标拼接，重叠处取平均，
+/// 返回拼接结果与网格左上角的物理坐标
+fn stitch(views: &[(ArrayView2<f32>, ViewTransform)], spacing: f32) -> (Array2<f32>, (f32, f32)) {
+    let (mut x_min, mut y_min) = (f32::INFINITY, f32::INFINITY);
+    let (mut x_max, mut y_max) = (f32::NEG_INFINITY, f32::NEG_INFINITY);
+    for (nu2d, transform) in views {
+        let (h, w) = nu2d.dim();
+        let (w, h) = ((w - 1) as f32, (h - 1) as f32);
+        for &corner in &[(0., 0.), (w, 0.), (0., h), (w, h)] {
+            let (x, y) = transform.forward(corner);
+            x_min = x_min.min(x);
+            y_min = y_min.min(y);
+            x_max = x_max.max(x);
+            y_max = y_max.max(y);
+        }
+    }
+    let cols = ((x_max - x_min) / spacing).floor() as usize + 1;
+    let rows = ((y_max - y_min) / spacing).floor() as usize + 1;
+
+    let stitched = Array2::from_shape_fn((rows, cols), |(i, j)| {
+        let p = (x_min + j as f32 * spacing, y_min + i as f32 * spacing);
+        let (sum, cnt) = views
+            .iter()
+            .filter_map(|(nu2d, transform)| bilinear(nu2d.view(), transform.inverse(p)))
+            .fold((0., 0), |(sum, cnt), nu| (sum + nu, cnt + 1));
+        if cnt == 0 {
+            f32::NAN
+        } else {
+            sum / cnt as f32
+        }
+    });
+
+    (stitched, (x_min, y_min))
+}
+
+pub struct TLCMultiView {
+    manifest: MultiViewManifest,
+    /// 清单中的相对路径以清单所在目录为基准
+    base_dir: PathBuf,
+    stem: String,
+    manifest_path: PathBuf,
+}
+
+impl TLCMultiView {
+    pub fn from_path<P: AsRef<Path>>(manifest_path: P) -> TLCResult<Self> {
+        let manifest_path = manifest_path.as_ref();
+        let file =
+            File::open(manifest_path).map_err(|err| awsl!(ConfigIOError, err, manifest_path))?;
+        let manifest: MultiViewManifest =
+            serde_json::from_reader(BufReader::new(file)).map_err(|err| awsl!(ConfigError, err))?;
+        if manifest.views.is_empty() {
+            return Err(awsl!(ConfigError, "清单中没有视角"));
+        }
+        let base_dir = manifest_path
+            .parent()
+            .ok_or(awsl!(ConfigIOError, "清单路径无效", manifest_path))?
+            .to_owned();
+        let stem = manifest_path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or("multiview")
+            .to_owned();
+
+        Ok(Self {
+            manifest,
+            base_dir,
+            stem,
+            manifest_path: manifest_path.to_owned(),
+        })
+    }
+
+    /// 依次求解各视角并保存各自的Nu矩阵，再拼接为一张Nu分布，保存在清单旁
+    pub fn run(&self) -> TLCResult<StitchResult> {
+        let template_path = self.base_dir.join(&self.manifest.template);
+        let file =
+            File::open(&template_path).map_err(|err| awsl!(ConfigIOError, err, template_path))?;
+        let template: Json =
+            serde_json::from_reader(BufReader::new(file)).map_err(|err| awsl!(ConfigError, err))?;
+        let configs = self
+            .manifest
+            .views
+            .iter()
+            .map(|view| {
+                let mut json = template.clone();
+                json.as_object_mut()
+                    .ok_or(awsl!(ConfigError, "模板格式错误"))?
+                    .extend(view.overrides.clone());
+                TLCConfig::from_json(json)
+            })
+            .collect::<TLCResult<Vec<_>>>()?;
+        // 所有视角共用同一份数采数据，只读取一次
+        let daq = configs[0].read_daq()?;
+
+        let export_options = configs[0].export_options;
+        let colormap = configs[0].colormap.lut(configs[0].reverse_colormap)?;
+
+        let mut summaries = Vec::with_capacity(configs.len());
+        let mut views = Vec::new();
+        for (view, config) in self.manifest.views.iter().zip(configs) {
+            let mut data = TLCData::from_config(config);
+            data.daq = Some(daq.clone());
+            data.computed(Stage::Daq);
+            // 只保留Nu矩阵，视频等数据随`data`析构
+            match solve_view(&mut data) {
+                Ok((nu_nan_mean, nu2d)) => {
+                    summaries.push(ViewSummary {
+                        name: view.name.clone(),
+                        error: None,
+                        nu_nan_mean,
+                    });
+                    views.push((nu2d, view.transform));
+                }
+                Err(err) => {
+                    tracing::warn!(view = %view.name, %err, "view failed");
+                    summaries.push(ViewSummary {
+                        name: view.name.clone(),
+                        error: Some(err.to_string()),
+                        nu_nan_mean: f32::NAN,
+                    });
+                }
+            }
+        }
+        if views.is_empty() {
+            return Err(awsl!(HandleError, "所有视角均处理失败"));
+        }
+
+        let spacing = match self.manifest.spacing {
+            Some(spacing) if spacing > 0. => spacing,
+            Some(_) => return Err(awsl!(ConfigError, "拼接网格间距必须为正")),
+            None => views
+                .iter()
+                .map(|(_, transform)| transform.pixel_size)
+                .fold(f32::INFINITY, f32::min),
+        };
+        let views: Vec<_> = views
+            .iter()
+            .map(|(nu2d, transform)| (nu2d.view(), *transform))
+            .collect();
+        let (stitched, origin) = stitch(&views, spacing);
+        let (nu_nan_mean, _) = postprocess::nan_mean_std(stitched.iter().copied());
+
+        let meta = DataMeta {
+            options: export_options,
+            case_name: self.stem.clone(),
+            config_hash: sha256_hex(&self.manifest_path)?,
+            input_hashes: InputHashes::default(),
+            unit: "Nu",
+            pixel_size: spacing,
+            flipped: false,
+        };
+        let nu_path = self.base_dir.join(format!("{}_stitched_nu.csv", self.stem));
+        io::save_data(stitched.view(), &nu_path, Some(&meta))?;
+        let plot_path = self.base_dir.join(format!("{}_stitched_nu.png", self.stem));
+        postprocess::plot_area(
+            &plot_path,
+            stitched.view(),
+            nu_nan_mean * 0.6,
+            nu_nan_mean * 2.,
+            &colormap,
+        )?;
+
+        Ok(StitchResult {
+            nu_path: nu_path.to_str().ok_or(awsl!(nu_path))?.to_owned(),
+            plot_path: plot_path.to_str().ok_or(awsl!(plot_path))?.to_owned(),
+            shape: stitched.dim(),
+            origin,
+            spacing,
+            nu_nan_mean,
+            views: summaries,
+        })
+    }
+}
+
+/// 求解单个视角并保存其Nu矩阵，返回Nu平均值与平滑后的Nu矩阵
+///
+/// nu2d第0行对应计算区域最下方，翻转后与像素坐标一致
+fn solve_view(data: &mut TLCData) -> TLCResult<(f32, Array2<f32>)> {
+    data.solve()?.save_nu()?;
+    let nu2d = data
+        .get_smoothed_nu2d()?
+        .slice_move(s![..;-1, ..])
+        .to_owned();
+
+    Ok((data.get_nu_nan_mean()?, nu2d))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn transform_roundtrip() {
+        let transform = ViewTransform {
+            origin: (10., 5.),
+            pixel_size: 0.2,
+            rotation: 30.,
+        };
+        let (x, y) = transform.inverse(transform.forward((12., 7.)));
+        assert!((x - 12.).abs() < 1e-4 && (y - 7.).abs() < 1e-4);
+    }
+
+    #[test]
+    fn stitch_overlap() {
+        let left = Array2::from_elem((3, 4), 1.);
+        let right = Array2::from_elem((3, 4), 3.);
+        let views = [
+            (
+                left.view(),
+                ViewTransform {
+                    origin: (0., 0.),
+                    pixel_size: 1.,
+                    rotation: 0.,
+                },
+            ),
+            (
+                right.view(),
+                ViewTransform {
+                    origin: (2., 0.),
+                    pixel_size: 1.,
+                    rotation: 0.,
+                },
+            ),
+        ];
+        let (stitched, origin) = stitch(&views, 1.);
+        assert_eq!(origin, (0., 0.));
+        assert_eq!(stitched.dim(), (3, 6));
+        assert_eq!(stitched.row(0).to_vec(), vec![1., 1., 2., 2., 3., 3.]);
+    }
+}
@@ -0,0 +1,95 @@
+use std::sync::mpsc::Sender;
+
+use serde::Serialize;
+
+use super::{timing::StageTimer, Stage, TLCData};
+
+/// `TLCData`状态变化的通知，界面据此刷新，不必在每次操作后轮询各项数据
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind")]
+pub enum StateEvent {
+    /// 开始计时的阶段，名称与`StageTiming`一致
+    StageStarted {
+        stage: &'static str,
+    },
+    StageFinished {
+        stage: &'static str,
+        secs: f32,
+    },
+    /// 该阶段的数据由当前配置算得
+    Computed {
+        stage: Stage,
+    },
+    /// 配置变化或释放内存时被清空的阶段
+    Invalidated {
+        stages: Vec<Stage>,
+    },
+    /// 结果文件已写出
+    ResultsSaved {
+        path: String,
+    },
+    /// 由调用方转发的操作失败，`TLCData`本身不产生，错误信息仍由该操作的回调返回
+    Failed {
+        cmd: String,
+    },
+}
+
+impl TLCData {
+    /// 之后的状态变化都发送到`listener`，接收端关闭后不再发送
+    pub fn set_listener(&mut self, listener: Sender<StateEvent>) -> &mut Self {
+        self.listener = Some(listener);
+
+        self
+    }
+
+    pub(crate) fn notify(&mut self, event: StateEvent) {
+        let closed = match &self.listener {
+            Some(listener) => listener.send(event).is_err(),
+            None => false,
+        };
+        if closed {
+            self.listener = None;
+        }
+    }
+
+    /// 开始计时并通知
+    pub(crate) fn start_stage(&mut self, stage: &'static str) -> StageTimer {
+        self.notify(StateEvent::StageStarted { stage });
+        StageTimer::start(stage)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::mpsc::channel;
+
+    use super::*;
+    use crate::{preprocess::InterpMethod, TLCConfig};
+
+    #[test]
+    fn listener_receives_invalidation() {
+        let config = TLCConfig::from_json(serde_json::json!({})).unwrap();
+        let mut data = TLCData::from_config(config);
+        let (tx, rx) = channel();
+        data.set_listener(tx);
+        data.computed(Stage::Interp);
+        data.set_interp_method(InterpMethod::Vertical).unwrap();
+
+        let events: Vec<_> = rx.try_iter().collect();
+        assert!(matches!(
+            events[0],
+            StateEvent::Computed {
+                stage: Stage::Interp
+            }
+        ));
+        assert!(matches!(
+            &events[1],
+            StateEvent::Invalidated { stages } if stages == &[Stage::Interp]
+        ));
+
+        // 接收端关闭后不再发送
+        drop(rx);
+        data.computed(Stage::Interp);
+        assert!(data.listener.is_none());
+    }
+}
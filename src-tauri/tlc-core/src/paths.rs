@@ -0,0 +1,128 @@
+use std::fs::create_dir_all;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::error::TLCResult;
+use crate::awsl;
+
+const APP_NAME: &str = "tlc";
+
+/// `save_dir`下的目录结构与结果文件命名
+///
+/// 各项均为模板，`{case}`为工况名，`{date}`为生成路径当天的日期（YYYY-MM-DD），
+/// 其余`{name}`为同名配置项的值，只能引用数字、字符串等单值配置项，如`{peak_temp}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OutputLayout {
+    /// 配置文件所在的目录，相对于`save_dir`，可以是多级目录
+    pub config_dir: String,
+    /// Nu矩阵等数据所在的目录
+    pub data_dir: String,
+    /// Nu云图等图片所在的目录
+    pub plots_dir: String,
+    /// 配置文件、Nu矩阵与Nu云图的文件名，不含扩展名，其余结果以工况名加后缀命名
+    pub file_name: String,
+}
+
+impl Default for OutputLayout {
+    fn default() -> Self {
+        Self {
+            config_dir: "config".to_owned(),
+            data_dir: "data".to_owned(),
+            plots_dir: "plots".to_owned(),
+            file_name: "{case}".to_owned(),
+        }
+    }
+}
+
+/// 替换`template`中的`{name}`，`lookup`返回None时报错
+pub(crate) fn render_template<F>(template: &str, lookup: F) -> TLCResult<String>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        rendered.push_str(&rest[..open]);
+        let close = rest[open..].find('}').ok_or(awsl!(
+            ConfigError,
+            format!("路径模板{}中的花括号未闭合", template)
+        ))?;
+        let name = &rest[open + 1..open + close];
+        let value = lookup(name).ok_or(awsl!(
+            ConfigError,
+            format!(
+                "路径模板{}中的{{{}}}不是工况名、日期或单值配置项",
+                template, name
+            )
+        ))?;
+        rendered.push_str(&value);
+        rest = &rest[open + close + 1..];
+    }
+    rendered.push_str(rest);
+
+    Ok(rendered)
+}
+
+/// 应用配置目录（默认配置、预设），`app_dir`非空时使用`app_dir/config`
+pub fn config_dir(app_dir: &str) -> TLCResult<PathBuf> {
+    app_subdir(app_dir, "config", dirs::config_dir)
+}
+
+/// 应用缓存目录，`app_dir`非空时使用`app_dir/cache`
+pub fn cache_dir(app_dir: &str) -> TLCResult<PathBuf> {
+    app_subdir(app_dir, "cache", dirs::cache_dir)
+}
+
+/// 默认配置文件，不存在时使用程序内置的默认值
+pub fn default_config_path(app_dir: &str) -> TLCResult<PathBuf> {
+    Ok(config_dir(app_dir)?.join("default_config.json"))
+}
+
+pub fn preset_dir(app_dir: &str) -> TLCResult<PathBuf> {
+    let preset_dir = config_dir(app_dir)?.join("presets");
+    create_dir_all(&preset_dir).map_err(|err| awsl!(CreateDirError, err, preset_dir))?;
+
+    Ok(preset_dir)
+}
+
+/// 安装后的程序工作目录不确定，不能使用相对路径，默认放在各平台的标准位置下
+fn app_subdir(
+    app_dir: &str,
+    name: &str,
+    platform_dir: fn() -> Option<PathBuf>,
+) -> TLCResult<PathBuf> {
+    let dir = if app_dir.is_empty() {
+        platform_dir()
+            .ok_or(awsl!(
+                HandleError,
+                "找不到系统应用数据目录，请在配置中指定app_dir"
+            ))?
+            .join(APP_NAME)
+    } else {
+        Path::new(app_dir).join(name)
+    };
+    create_dir_all(&dir).map_err(|err| awsl!(CreateDirError, err, dir))?;
+
+    Ok(dir)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn template() {
+        let lookup = |name: &str| match name {
+            "case" => Some("run1".to_owned()),
+            "peak_temp" => Some("35.48".to_owned()),
+            _ => None,
+        };
+        let rendered = render_template("{case}_Tp{peak_temp}", lookup).unwrap();
+        assert_eq!(rendered, "run1_Tp35.48");
+        assert_eq!(render_template("nu", lookup).unwrap(), "nu");
+        assert!(render_template("{case", lookup).is_err());
+        assert!(render_template("{coating}", lookup).is_err());
+    }
+}
@@ -0,0 +1,103 @@
+use ndarray::prelude::*;
+use serde::Serialize;
+
+use super::{error::TLCResult, TLCConfig, TLCData};
+use crate::awsl;
+
+/// 穿透深度系数：阶跃加热下温升衰减到壁面温升1%处的深度为3.64√(αt)
+const PENETRATION_COEF: f32 = 3.64;
+
+/// 半无限大假设的检查结果
+#[derive(Debug, Serialize)]
+pub struct PenetrationReport {
+    /// 模型厚度（m）
+    pub thickness: f32,
+    /// 满足半无限大假设的最长加热时间（s）
+    pub max_valid_secs: f32,
+    /// 满足半无限大假设的最后一帧，从起始帧算起
+    pub max_valid_frame: usize,
+    /// 峰值帧超出`max_valid_frame`的像素数
+    pub invalid_pixels: usize,
+    /// 超出的像素占有效像素（峰值帧有效且未被遮罩排除）的比例
+    pub invalid_ratio: f32,
+    /// 所有有效像素中最大的穿透深度与模型厚度之比
+    pub max_depth_ratio: f32,
+}
+
+impl TLCConfig {
+    /// 固体热扩散系数，随温度变化时取峰值温度下的值
+    fn peak_diffusivity(&self) -> f32 {
+        self.solid_thermal_diffusivity_curve
+            .as_ref()
+            .map_or(self.solid_thermal_diffusivity, |curve| {
+                curve.eval(self.peak_temp)
+            })
+    }
+}
+
+impl TLCData {
+    /// 各像素峰值时刻的穿透深度与模型厚度之比，大于1的像素不满足半无限大假设，
+    /// 无效像素为NaN，与nu2d一样上下翻转
+    pub fn get_penetration_ratio(&mut self) -> TLCResult<Array2<f32>> {
+        let thickness = self.config.model_thickness;
+        if thickness <= 0. {
+            return Err(awsl!(ConfigError, "未设置模型厚度，无法检查半无限大假设"));
+        }
+        if self.peak_frames.is_none() {
+            self.detect_peak()?;
+        }
+        let start = self.event_window()?.0;
+        let mask = self.config.mask_pixels()?;
+        let diffusivity = self.config.peak_diffusivity();
+        let dt = 1. / self.config.frame_rate as f32;
+
+        let ratios: Vec<_> = self
+            .get_peak_frames()?
+            .iter()
+            .enumerate()
+            .map(|(pos, &peak_frame)| {
+                let included = mask.as_ref().map_or(true, |mask| mask[pos]);
+                if peak_frame <= start || !included {
+                    return f32::NAN;
+                }
+                let t = (peak_frame - start) as f32 * dt;
+                PENETRATION_COEF * (diffusivity * t).sqrt() / thickness
+            })
+            .collect();
+        let mut ratio = Array1::from(ratios)
+            .into_shape(self.config.region_shape)
+            .map_err(|err| awsl!(err))?;
+        ratio.invert_axis(Axis(0));
+
+        Ok(ratio)
+    }
+
+    /// 检查各像素峰值时刻的热穿透深度是否超过模型厚度
+    pub fn check_penetration(&mut self) -> TLCResult<PenetrationReport> {
+        let ratio = self.get_penetration_ratio()?;
+        let thickness = self.config.model_thickness;
+        let diffusivity = self.config.peak_diffusivity();
+        let max_valid_secs = (thickness / PENETRATION_COEF).powi(2) / diffusivity;
+        let max_valid_frame = (max_valid_secs * self.config.frame_rate as f32) as usize;
+
+        let valid: Vec<_> = ratio.iter().copied().filter(|r| !r.is_nan()).collect();
+        let invalid_pixels = valid.iter().filter(|&&r| r > 1.).count();
+        let invalid_ratio = invalid_pixels as f32 / valid.len().max(1) as f32;
+        if invalid_pixels > 0 {
+            tracing::warn!(
+                invalid_ratio,
+                max_valid_frame,
+                "semi-infinite assumption violated"
+            );
+        }
+
+        Ok(PenetrationReport {
+            thickness,
+            max_valid_secs,
+            max_valid_frame,
+            invalid_pixels,
+            invalid_ratio,
+            max_depth_ratio: valid.iter().copied().fold(f32::NAN, f32::max),
+        })
+    }
+}
@@ -0,0 +1,230 @@
+//! 处理流程的标准顺序与各步骤的前提条件，界面据此禁用尚不能进行的步骤，
+//! 命令行在求解前给出明确的提示，而不是在计算深处才报错
+
+use std::fmt;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::{error::TLCResult, validate::Violation, TLCConfig, TLCData};
+use crate::awsl;
+
+/// 处理步骤，按标准顺序排列
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum PipelineStep {
+    LoadVideo,
+    LoadDaq,
+    /// 框选计算区域
+    Region,
+    /// 设置热电偶位置与插值方法
+    Thermocouples,
+    /// 同步视频起始帧与数采起始行
+    Sync,
+    Filter,
+    Solve,
+}
+
+impl fmt::Display for PipelineStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+impl PipelineStep {
+    pub const ORDER: [PipelineStep; 7] = [
+        PipelineStep::LoadVideo,
+        PipelineStep::LoadDaq,
+        PipelineStep::Region,
+        PipelineStep::Thermocouples,
+        PipelineStep::Sync,
+        PipelineStep::Filter,
+        PipelineStep::Solve,
+    ];
+
+    /// 负责该配置项的步骤
+    fn of_field(field: &str) -> PipelineStep {
+        use PipelineStep::*;
+        match field {
+            "frame_rate" => LoadVideo,
+            "region_shape" | "top_left_pos" | "reference_patch" | "mask" => Region,
+            "thermocouples" | "regulator" | "interp_method" => Thermocouples,
+            "start_frame" | "start_row" | "frame_num" => Sync,
+            "filter_method" => Filter,
+            _ => Solve,
+        }
+    }
+}
+
+/// 进行某一步骤前尚未满足的条件
+#[derive(Debug, Serialize)]
+pub struct Requirement {
+    /// 应在哪一步骤中解决
+    pub step: PipelineStep,
+    pub field: &'static str,
+    pub message: String,
+}
+
+/// 各步骤的状态
+#[derive(Debug, Serialize)]
+pub struct StepStatus {
+    pub step: PipelineStep,
+    pub can_run: bool,
+    pub missing: Vec<Requirement>,
+}
+
+/// 按当前配置检查各步骤的前提条件
+pub struct Pipeline<'a> {
+    config: &'a TLCConfig,
+    violations: Vec<Violation>,
+}
+
+impl TLCData {
+    pub fn pipeline(&self) -> Pipeline<'_> {
+        Pipeline {
+            config: &self.config,
+            violations: self.validate(),
+        }
+    }
+}
+
+impl Pipeline<'_> {
+    /// 完成`step`后应满足的条件中尚未满足的
+    fn unfinished(&self, step: PipelineStep) -> Vec<Requirement> {
+        use PipelineStep::*;
+        let config = self.config;
+        let requirement = |field, message: &str| Requirement {
+            step,
+            field,
+            message: message.to_owned(),
+        };
+        let mut missing = Vec::new();
+        match step {
+            LoadVideo if config.total_frames == 0 || config.video_shape == (0, 0) => {
+                missing.push(requirement("video_path", "未读取视频，请检查视频文件路径"));
+            }
+            LoadDaq if config.total_rows == 0 => {
+                missing.push(requirement(
+                    "daq_path",
+                    "未读取数采文件，请检查数采文件路径",
+                ));
+            }
+            Thermocouples if config.thermocouples.is_empty() => {
+                missing.push(requirement("thermocouples", "未设置热电偶"));
+            }
+            _ => {}
+        }
+        missing.extend(
+            self.violations
+                .iter()
+                .filter(|violation| PipelineStep::of_field(violation.field) == step)
+                .map(|violation| requirement(violation.field, &violation.message)),
+        );
+
+        missing
+    }
+
+    /// 进行`step`前尚未满足的条件：之前各步骤未完成的部分，以及`step`本身的输入
+    pub fn missing_requirements(&self, step: PipelineStep) -> Vec<Requirement> {
+        use PipelineStep::*;
+        let mut missing: Vec<_> = PipelineStep::ORDER
+            .iter()
+            .take_while(|&&s| s < step)
+            .flat_map(|&s| self.unfinished(s))
+            .collect();
+        let input = match step {
+            LoadVideo => Some(("video_path", &self.config.video_path, "视频文件")),
+            LoadDaq => Some(("daq_path", &self.config.daq_path, "数采文件")),
+            _ => None,
+        };
+        match input {
+            Some((field, path, name)) if !Path::new(path).is_file() => {
+                missing.push(Requirement {
+                    step,
+                    field,
+                    message: format!("{}{}不存在", name, path),
+                });
+            }
+            Some(_) => {}
+            // 滤波与求解所用的参数在进行该步骤前就应当合法
+            None if step >= Filter => missing.extend(self.unfinished(step)),
+            None => {}
+        }
+
+        missing
+    }
+
+    pub fn can_run(&self, step: PipelineStep) -> bool {
+        self.missing_requirements(step).is_empty()
+    }
+
+    /// 按标准顺序列出各步骤的状态
+    pub fn status(&self) -> Vec<StepStatus> {
+        PipelineStep::ORDER
+            .iter()
+            .map(|&step| {
+                let missing = self.missing_requirements(step);
+                StepStatus {
+                    step,
+                    can_run: missing.is_empty(),
+                    missing,
+                }
+            })
+            .collect()
+    }
+
+    /// 不能进行`step`时返回列出所有未满足条件的错误
+    pub fn check(&self, step: PipelineStep) -> TLCResult<()> {
+        let missing = self.missing_requirements(step);
+        if missing.is_empty() {
+            return Ok(());
+        }
+        let messages: Vec<_> = missing
+            .iter()
+            .map(|requirement| format!("[{:?}] {}", requirement.step, requirement.message))
+            .collect();
+
+        Err(awsl!(
+            ConfigError,
+            format!("无法进行{}：\n{}", step, messages.join("\n"))
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn requirements_follow_order() {
+        let config = TLCConfig::from_json(json!({
+            "video_path": "",
+            "daq_path": "",
+            "region_shape": [0, 0],
+        }))
+        .unwrap();
+        let data = TLCData::from_config(config);
+        let pipeline = data.pipeline();
+
+        let missing = pipeline.missing_requirements(PipelineStep::LoadVideo);
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].field, "video_path");
+        // 读取视频之前不能框选计算区域
+        let missing = pipeline.missing_requirements(PipelineStep::Region);
+        assert!(missing.iter().any(|r| r.step == PipelineStep::LoadVideo));
+        assert!(missing.iter().all(|r| r.step < PipelineStep::Region));
+        let missing = pipeline.missing_requirements(PipelineStep::Solve);
+        for step in &[
+            PipelineStep::LoadVideo,
+            PipelineStep::LoadDaq,
+            PipelineStep::Region,
+            PipelineStep::Thermocouples,
+        ] {
+            assert!(missing.iter().any(|r| r.step == *step), "{:?}", step);
+        }
+        assert!(!pipeline.can_run(PipelineStep::Solve));
+        assert!(pipeline.check(PipelineStep::Solve).is_err());
+        assert_eq!(pipeline.status().len(), PipelineStep::ORDER.len());
+    }
+}
@@ -0,0 +1,142 @@
+use std::path::{Component, Path, PathBuf};
+
+use super::{error::TLCResult, TLCConfig, TLCData};
+use crate::awsl;
+
+/// `path`相对于`base`的路径，两者不都是绝对路径或不在同一盘符时返回None
+fn relative_path(path: &Path, base: &Path) -> Option<PathBuf> {
+    if !path.is_absolute() || !base.is_absolute() {
+        return None;
+    }
+    let mut path_iter = path.components().peekable();
+    let mut base_iter = base.components().peekable();
+    if path_iter.peek() != base_iter.peek() {
+        return None;
+    }
+    while let (Some(a), Some(b)) = (path_iter.peek(), base_iter.peek()) {
+        if a != b {
+            break;
+        }
+        path_iter.next();
+        base_iter.next();
+    }
+    let mut relative: PathBuf = base_iter.map(|_| Component::ParentDir).collect();
+    relative.extend(path_iter);
+    if relative.as_os_str().is_empty() {
+        relative.push(Component::CurDir);
+    }
+
+    Some(relative)
+}
+
+/// 不访问文件系统，仅按字面去掉路径中的`.`与`..`
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            c => normalized.push(c),
+        }
+    }
+
+    normalized
+}
+
+fn path_to_string(path: &Path) -> TLCResult<String> {
+    Ok(path.to_str().ok_or(awsl!(path))?.to_owned())
+}
+
+impl TLCConfig {
+    /// 用于保存的配置：`save_dir`相对于配置文件所在目录，视频与数采文件相对于`save_dir`，
+    /// 无法表示为相对路径（如不在同一盘符）时保持绝对路径
+    pub(super) fn to_portable(&self) -> TLCResult<serde_json::Value> {
+        let mut json = serde_json::to_value(self).map_err(|err| awsl!(ConfigError, err))?;
+        let object = json.as_object_mut().ok_or(awsl!())?;
+        let save_dir = Path::new(&self.save_dir);
+        let media_paths = [
+            ("video_path", &self.video_path),
+            ("daq_path", &self.daq_path),
+        ];
+        for &(field, path) in media_paths.iter() {
+            if let Some(relative) = relative_path(Path::new(path), save_dir) {
+                object.insert(field.to_owned(), path_to_string(&relative)?.into());
+            }
+        }
+        let config_dir = Path::new(&self.config_path).parent().unwrap_or(save_dir);
+        if let Some(relative) = relative_path(save_dir, config_dir) {
+            object.insert("save_dir".to_owned(), path_to_string(&relative)?.into());
+        }
+
+        Ok(json)
+    }
+
+    /// 读取配置文件时将相对路径还原为绝对路径，与`to_portable`相反
+    ///
+    /// 旧版本配置中的相对路径以工作目录为基准，按`save_dir`找不到文件时保持原样
+    pub(super) fn resolve_paths(&mut self, config_path: &Path) -> TLCResult<&mut Self> {
+        let config_dir = config_path.parent().unwrap_or_else(|| Path::new(""));
+        if !self.save_dir.is_empty() && Path::new(&self.save_dir).is_relative() {
+            let save_dir = normalize(&config_dir.join(&self.save_dir));
+            self.save_dir = path_to_string(&save_dir)?;
+        }
+        let save_dir = PathBuf::from(&self.save_dir);
+        for path in [&mut self.video_path, &mut self.daq_path].iter_mut() {
+            if Path::new(path.as_str()).is_relative() {
+                let resolved = normalize(&save_dir.join(path.as_str()));
+                if resolved.is_file() {
+                    **path = path_to_string(&resolved)?;
+                }
+            }
+        }
+
+        Ok(self)
+    }
+}
+
+impl TLCData {
+    /// 视频与数采文件整体移动到`media_dir`（如换了盘符或机器）后，按原文件名在新目录下重新定位
+    pub fn relocate(&mut self, media_dir: String) -> TLCResult<&mut Self> {
+        let media_dir = Path::new(&media_dir);
+        let locate = |path: &str| -> TLCResult<String> {
+            let file_name = Path::new(path).file_name().ok_or(awsl!(path))?;
+            let new_path = media_dir.join(file_name);
+            if !new_path.is_file() {
+                return Err(awsl!(
+                    HandleError,
+                    format!(
+                        "{}下找不到{}",
+                        media_dir.display(),
+                        file_name.to_string_lossy()
+                    )
+                ));
+            }
+
+            path_to_string(&new_path)
+        };
+        let video_path = locate(&self.config.video_path)?;
+        let daq_path = locate(&self.config.daq_path)?;
+        self.set_video_path(video_path)?.set_daq_path(daq_path)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn relative_round_trip() {
+        let save_dir = Path::new("/data/results");
+        let video_path = Path::new("/data/videos/case1.avi");
+        let relative = relative_path(video_path, save_dir).unwrap();
+        assert_eq!(relative, Path::new("../videos/case1.avi"));
+        assert_eq!(normalize(&save_dir.join(relative)), video_path);
+        assert_eq!(
+            relative_path(save_dir, &save_dir.join("config")).unwrap(),
+            Path::new("..")
+        );
+        assert!(relative_path(video_path, Path::new("results")).is_none());
+    }
+}
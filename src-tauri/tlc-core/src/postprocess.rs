@@ -0,0 +1,898 @@
+use std::path::{Path, PathBuf};
+
+use plotters::coord::Shift;
+use plotters::prelude::*;
+
+use ndarray::prelude::*;
+use ndarray::Zip;
+use serde::{Deserialize, Serialize};
+
+use super::error::TLCResult;
+use crate::awsl;
+
+/// 计算强化换热比Nu/Nu0时所用的参考关联式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum NuCorrelation {
+    /// 管内充分发展湍流：Nu0 = 0.023Re^0.8Pr^0.4
+    DittusBoelter,
+    /// 管内充分发展湍流，适用于较低雷诺数：f = (0.79lnRe - 1.64)^-2
+    Gnielinski,
+    /// 平板湍流边界层（平均）：Nu0 = 0.037Re^0.8Pr^(1/3)
+    FlatPlateTurbulent,
+    /// 平板层流边界层（平均）：Nu0 = 0.664Re^0.5Pr^(1/3)
+    FlatPlateLaminar,
+}
+
+impl Default for NuCorrelation {
+    fn default() -> Self {
+        NuCorrelation::DittusBoelter
+    }
+}
+
+impl NuCorrelation {
+    pub fn nu0(self, reynolds: f32, prandtl: f32) -> f32 {
+        use NuCorrelation::*;
+        match self {
+            DittusBoelter => 0.023 * reynolds.powf(0.8) * prandtl.powf(0.4),
+            Gnielinski => {
+                let f = (0.79 * reynolds.ln() - 1.64).powi(-2);
+                (f / 8.) * (reynolds - 1000.) * prandtl
+                    / (1. + 12.7 * (f / 8.).sqrt() * (prandtl.powf(2. / 3.) - 1.))
+            }
+            FlatPlateTurbulent => 0.037 * reynolds.powf(0.8) * prandtl.cbrt(),
+            FlatPlateLaminar => 0.664 * reynolds.sqrt() * prandtl.cbrt(),
+        }
+    }
+}
+
+pub fn cal_nan_mean<D: Dimension>(data: ArrayView<f32, D>) -> f32 {
+    let (sum, cnt) = data.iter().fold((0., 0), |(s, cnt), &x| {
+        if x.is_nan() {
+            (s, cnt)
+        } else {
+            (s + x, cnt + 1)
+        }
+    });
+    let nan_mean = sum / cnt as f32;
+
+    nan_mean
+}
+
+/// Nu分布的空间平滑，只用于绘图与统计，原始结果单独保留
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum NuSmoothing {
+    No,
+    /// 高斯核尺寸（奇数），sigma按`0.3 * ((k - 1) / 2 - 1) + 0.8`确定
+    Gaussian(usize),
+    /// 中值核尺寸（奇数）
+    Median(usize),
+}
+
+impl Default for NuSmoothing {
+    fn default() -> Self {
+        NuSmoothing::No
+    }
+}
+
+impl NuSmoothing {
+    pub fn kernel_size(&self) -> usize {
+        match *self {
+            NuSmoothing::No => 1,
+            NuSmoothing::Gaussian(k) | NuSmoothing::Median(k) => k,
+        }
+    }
+}
+
+/// 平滑时忽略NaN，NaN像素本身保持NaN
+pub fn smooth(nu2d: ArrayView2<f32>, smoothing: NuSmoothing) -> Array2<f32> {
+    match smoothing {
+        NuSmoothing::No => nu2d.to_owned(),
+        NuSmoothing::Gaussian(kernel_size) => gaussian_smooth(nu2d, kernel_size),
+        NuSmoothing::Median(kernel_size) => median_smooth(nu2d, kernel_size),
+    }
+}
+
+fn gaussian_smooth(nu2d: ArrayView2<f32>, kernel_size: usize) -> Array2<f32> {
+    let r = kernel_size / 2;
+    let sigma = 0.3 * (r as f32 - 1.) + 0.8;
+    let kernel: Vec<_> = (0..=2 * r)
+        .map(|i| (-(i as f32 - r as f32).powi(2) / (2. * sigma * sigma)).exp())
+        .collect();
+
+    // 归一化卷积：有效值与有效掩膜分别卷积后相除，NaN不参与加权
+    let values = nu2d.mapv(|nu| if nu.is_nan() { 0. } else { nu });
+    let mask = nu2d.mapv(|nu| if nu.is_nan() { 0. } else { 1. });
+    let conv = |arr: Array2<f32>| {
+        let arr = convolve(arr.view(), &kernel, Axis(0));
+        convolve(arr.view(), &kernel, Axis(1))
+    };
+    let (values, mask) = (conv(values), conv(mask));
+
+    Zip::from(&values)
+        .and(&mask)
+        .and(nu2d)
+        .map_collect(|&v, &m, &nu| if nu.is_nan() { f32::NAN } else { v / m })
+}
+
+/// 沿`axis`的一维卷积，边界外视为0
+fn convolve(arr: ArrayView2<f32>, kernel: &[f32], axis: Axis) -> Array2<f32> {
+    let r = kernel.len() / 2;
+    let mut out = Array2::zeros(arr.dim());
+    for (lane, mut out_lane) in arr.lanes(axis).into_iter().zip(out.lanes_mut(axis)) {
+        for (i, v) in out_lane.iter_mut().enumerate() {
+            *v = kernel
+                .iter()
+                .enumerate()
+                .filter_map(|(k, w)| Some(lane.get((i + k).checked_sub(r)?)? * w))
+                .sum();
+        }
+    }
+
+    out
+}
+
+fn median_smooth(nu2d: ArrayView2<f32>, kernel_size: usize) -> Array2<f32> {
+    let r = kernel_size / 2;
+    let (h, w) = nu2d.dim();
+    let mut window: Vec<f32> = Vec::with_capacity(kernel_size * kernel_size);
+
+    Array2::from_shape_fn((h, w), |(y, x)| {
+        if nu2d[(y, x)].is_nan() {
+            return f32::NAN;
+        }
+        window.clear();
+        let rows = y.saturating_sub(r)..(y + r + 1).min(h);
+        let cols = x.saturating_sub(r)..(x + r + 1).min(w);
+        window.extend(
+            nu2d.slice(s![rows, cols])
+                .iter()
+                .filter(|nu| !nu.is_nan())
+                .copied(),
+        );
+        window.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap());
+
+        window[window.len() / 2]
+    })
+}
+
+/// 沿某一方向取平均后的Nu分布，如展向平均的Nu(x)
+#[derive(Debug, Serialize)]
+pub struct Profile {
+    pub mean: Array1<f32>,
+    pub std: Array1<f32>,
+}
+
+/// 沿`axis`求平均（忽略NaN），`Axis(0)`得到各列的平均值，`Axis(1)`得到各行的平均值
+pub fn profile(nu2d: ArrayView2<f32>, axis: Axis) -> Profile {
+    let (mean, std): (Vec<_>, Vec<_>) = nu2d
+        .axis_iter(Axis(1 - axis.index()))
+        .map(|lane| nan_mean_std(lane.iter().copied()))
+        .unzip();
+
+    Profile {
+        mean: Array1::from(mean),
+        std: Array1::from(std),
+    }
+}
+
+/// 冲击射流的周向平均Nu(r/D)
+#[derive(Debug, Serialize)]
+pub struct RadialProfile {
+    /// 各区间中心的r/D
+    pub r: Vec<f32>,
+    pub mean: Vec<f32>,
+    pub std: Vec<f32>,
+    /// 各区间内的有效像素数
+    pub count: Vec<usize>,
+}
+
+/// 以驻点`center`（计算区域内坐标(y, x)）为圆心，按r/D分区间求周向平均
+///
+/// `diameter`为以像素计的射流直径，`bin_width`为区间宽度（以r/D计）
+pub fn radial_profile(
+    nu2d: ArrayView2<f32>,
+    center: (f32, f32),
+    diameter: f32,
+    bin_width: f32,
+) -> TLCResult<RadialProfile> {
+    if diameter <= 0. || bin_width <= 0. {
+        return Err(awsl!(HandleError, "射流直径与区间宽度必须为正"));
+    }
+    let (h, w) = nu2d.dim();
+    let corners = [
+        (0., 0.),
+        (0., w as f32),
+        (h as f32, 0.),
+        (h as f32, w as f32),
+    ];
+    let r_max = corners
+        .iter()
+        .map(|&(y, x): &(f32, f32)| (y - center.0).hypot(x - center.1))
+        .fold(0., f32::max);
+    let bin_num = (r_max / diameter / bin_width).ceil() as usize + 1;
+
+    let mut bins = vec![Vec::new(); bin_num];
+    // nu2d上下翻转过，第0行对应计算区域最下方
+    for ((row, x), &nu) in nu2d.indexed_iter() {
+        if nu.is_nan() {
+            continue;
+        }
+        let y = (h - 1 - row) as f32;
+        let r = (y - center.0).hypot(x as f32 - center.1) / diameter;
+        bins[(r / bin_width) as usize].push(nu);
+    }
+
+    let mut radial = RadialProfile {
+        r: Vec::new(),
+        mean: Vec::new(),
+        std: Vec::new(),
+        count: Vec::new(),
+    };
+    for (i, bin) in bins.iter().enumerate().filter(|(_, bin)| !bin.is_empty()) {
+        let (mean, std) = nan_mean_std(bin.iter().copied());
+        radial.r.push((i as f32 + 0.5) * bin_width);
+        radial.mean.push(mean);
+        radial.std.push(std);
+        radial.count.push(bin.len());
+    }
+
+    Ok(radial)
+}
+
+/// 沿`p0`到`p1`（计算区域内坐标(y, x)）等距采样`n`个点，返回(距起点的像素距离, Nu)
+pub fn line_probe(
+    nu2d: ArrayView2<f32>,
+    p0: (f32, f32),
+    p1: (f32, f32),
+    n: usize,
+) -> Vec<(f32, f32)> {
+    let len = (p1.0 - p0.0).hypot(p1.1 - p0.1);
+    let step = if n > 1 { 1. / (n - 1) as f32 } else { 0. };
+
+    (0..n)
+        .map(|i| {
+            let t = i as f32 * step;
+            let y = p0.0 + (p1.0 - p0.0) * t;
+            let x = p0.1 + (p1.1 - p0.1) * t;
+            (len * t, sample_bilinear(nu2d, y, x))
+        })
+        .collect()
+}
+
+/// 在计算区域内坐标(y, x)处双线性插值，超出范围为NaN
+fn sample_bilinear(nu2d: ArrayView2<f32>, y: f32, x: f32) -> f32 {
+    let (h, w) = nu2d.dim();
+    if !(0. ..=(h - 1) as f32).contains(&y) || !(0. ..=(w - 1) as f32).contains(&x) {
+        return f32::NAN;
+    }
+    let (y0, x0) = (y.floor() as usize, x.floor() as usize);
+    let (y1, x1) = ((y0 + 1).min(h - 1), (x0 + 1).min(w - 1));
+    let (dy, dx) = (y - y0 as f32, x - x0 as f32);
+    // nu2d上下翻转过，第0行对应计算区域最下方
+    let at = |y: usize, x: usize| nu2d[(h - 1 - y, x)];
+
+    // 权重为0的点不参与计算，避免相邻的NaN污染恰好落在网格线上的采样点
+    [
+        (y0, x0, (1. - dy) * (1. - dx)),
+        (y0, x1, (1. - dy) * dx),
+        (y1, x0, dy * (1. - dx)),
+        (y1, x1, dy * dx),
+    ]
+    .iter()
+    .filter(|&&(_, _, weight)| weight > 0.)
+    .map(|&(y, x, weight)| at(y, x) * weight)
+    .sum()
+}
+
+/// 多边形子区域内Nu的统计量
+#[derive(Debug, Serialize)]
+pub struct RegionStats {
+    pub mean: f32,
+    pub std: f32,
+    pub min: f32,
+    pub max: f32,
+    /// 多边形内的像素数
+    pub area: usize,
+    /// 其中Nu有效（非NaN）的像素数
+    pub valid: usize,
+}
+
+/// 各多边形（顶点为计算区域内坐标(y, x)）内Nu的统计量，以像素中心是否在多边形内判断
+pub fn polygon_stats(nu2d: ArrayView2<f32>, polygons: &[Vec<(f32, f32)>]) -> Vec<RegionStats> {
+    let h = nu2d.nrows();
+
+    polygons
+        .iter()
+        .map(|polygon| {
+            let inside: Vec<_> = nu2d
+                .indexed_iter()
+                // nu2d上下翻转过，第0行对应计算区域最下方
+                .filter(|&((row, x), _)| contains(polygon, ((h - 1 - row) as f32, x as f32)))
+                .map(|(_, &nu)| nu)
+                .collect();
+            let valid: Vec<_> = inside.iter().copied().filter(|nu| !nu.is_nan()).collect();
+            let (mean, std) = nan_mean_std(valid.iter().copied());
+
+            RegionStats {
+                mean,
+                std,
+                min: valid.iter().copied().fold(f32::NAN, f32::min),
+                max: valid.iter().copied().fold(f32::NAN, f32::max),
+                area: inside.len(),
+                valid: valid.len(),
+            }
+        })
+        .collect()
+}
+
+/// 射线法判断点是否在多边形内
+pub(crate) fn contains(polygon: &[(f32, f32)], (y, x): (f32, f32)) -> bool {
+    let mut inside = false;
+    for (i, &(yi, xi)) in polygon.iter().enumerate() {
+        let (yj, xj) = polygon[(i + polygon.len() - 1) % polygon.len()];
+        if (yi > y) != (yj > y) && x < (xj - xi) * (y - yi) / (yj - yi) + xi {
+            inside = !inside;
+        }
+    }
+
+    inside
+}
+
+/// 图片输出格式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum PlotFormat {
+    Png,
+    /// 矢量图，可直接用于论文排版
+    Svg,
+}
+
+/// 图片输出设置
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct PlotOptions {
+    pub format: PlotFormat,
+    /// 图片尺寸，为(0, 0)时按内容确定
+    pub size: (u32, u32),
+    /// 位图分辨率，以100为基准按比例放大尺寸、字体与边距，对矢量图无效
+    pub dpi: u32,
+}
+
+impl Default for PlotOptions {
+    fn default() -> Self {
+        PlotOptions {
+            format: PlotFormat::Png,
+            size: (0, 0),
+            dpi: 100,
+        }
+    }
+}
+
+impl PlotOptions {
+    fn scale(&self) -> f64 {
+        match self.format {
+            PlotFormat::Png if self.dpi > 0 => self.dpi as f64 / 100.,
+            _ => 1.,
+        }
+    }
+
+    fn dims(&self, default_size: (u32, u32)) -> (u32, u32) {
+        let (w, h) = if self.size.0 > 0 && self.size.1 > 0 {
+            self.size
+        } else {
+            default_size
+        };
+        let k = self.scale();
+
+        ((w as f64 * k) as u32, (h as f64 * k) as u32)
+    }
+
+    /// 按输出格式替换扩展名
+    pub fn path<P: AsRef<Path>>(&self, plot_path: P) -> PathBuf {
+        let extension = match self.format {
+            PlotFormat::Png => "png",
+            PlotFormat::Svg => "svg",
+        };
+
+        plot_path.as_ref().with_extension(extension)
+    }
+}
+
+/// 按输出格式选择绘图后端，返回实际保存路径
+///
+/// `$draw`为以(`DrawingArea`, 缩放比例)开头的泛型绘图函数
+macro_rules! render {
+    ($options:expr, $plot_path:expr, $size:expr, $draw:ident($($arg:expr),* $(,)*)) => {{
+        let options: &PlotOptions = $options;
+        let plot_path = options.path($plot_path);
+        let size = options.dims($size);
+        let k = options.scale();
+        match options.format {
+            PlotFormat::Png => {
+                $draw(BitMapBackend::new(&plot_path, size).into_drawing_area(), k, $($arg),*)
+            }
+            PlotFormat::Svg => {
+                $draw(SVGBackend::new(&plot_path, size).into_drawing_area(), k, $($arg),*)
+            }
+        }
+        .map(|_| plot_path)
+    }};
+}
+
+/// 报告中常用的百分位数
+pub const PERCENTILES: [f32; 5] = [1., 5., 50., 95., 99.];
+
+/// Nu分布直方图
+#[derive(Debug, Serialize)]
+pub struct Histogram {
+    /// 区间边界，长度比`counts`多1
+    pub edges: Vec<f32>,
+    pub counts: Vec<usize>,
+    /// (百分位, 对应的Nu)
+    pub percentiles: Vec<(f32, f32)>,
+}
+
+/// 忽略NaN，在最小值与最大值之间等分为`bins`个区间
+pub fn histogram(nu2d: ArrayView2<f32>, bins: usize) -> TLCResult<Histogram> {
+    let mut valid: Vec<_> = nu2d.iter().copied().filter(|nu| !nu.is_nan()).collect();
+    if valid.is_empty() || bins == 0 {
+        return Err(awsl!(HandleError, "没有有效的Nu或区间数为0"));
+    }
+    valid.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap());
+    let (min, max) = (valid[0], valid[valid.len() - 1]);
+    let width = (max - min) / bins as f32;
+
+    let edges = (0..=bins).map(|i| min + width * i as f32).collect();
+    let mut counts = vec![0; bins];
+    for &nu in &valid {
+        let i = if width > 0. {
+            (((nu - min) / width) as usize).min(bins - 1)
+        } else {
+            0
+        };
+        counts[i] += 1;
+    }
+    // 相邻两个有序值之间线性插值
+    let percentiles = PERCENTILES
+        .iter()
+        .map(|&p| {
+            let pos = p / 100. * (valid.len() - 1) as f32;
+            let (lo, hi) = (pos.floor() as usize, pos.ceil() as usize);
+            (p, valid[lo] + (valid[hi] - valid[lo]) * (pos - lo as f32))
+        })
+        .collect();
+
+    Ok(Histogram {
+        edges,
+        counts,
+        percentiles,
+    })
+}
+
+pub fn plot_histogram<P: AsRef<Path>>(
+    plot_path: P,
+    hist: &Histogram,
+    options: &PlotOptions,
+) -> TLCResult<PathBuf> {
+    render!(options, plot_path, (800, 600), draw_histogram(hist))
+}
+
+fn draw_histogram<DB: DrawingBackend>(
+    root: DrawingArea<DB, Shift>,
+    k: f64,
+    hist: &Histogram,
+) -> TLCResult<()> {
+    let (x_min, x_max) = (hist.edges[0], hist.edges[hist.edges.len() - 1]);
+    let count_max = hist.counts.iter().copied().max().unwrap_or(0);
+
+    root.fill(&WHITE).map_err(|err| awsl!(PlotError, err))?;
+    let mut chart = ChartBuilder::on(&root)
+        .margin(30. * k)
+        .x_label_area_size(40. * k)
+        .y_label_area_size(60. * k)
+        .build_cartesian_2d(x_min..x_max.max(x_min + f32::EPSILON), 0..count_max + 1)
+        .map_err(|err| awsl!(PlotError, err))?;
+    chart
+        .configure_mesh()
+        .x_desc("Nu")
+        .y_desc("count")
+        .label_style(("sans-serif", 12. * k))
+        .axis_desc_style(("sans-serif", 14. * k))
+        .draw()
+        .map_err(|err| awsl!(PlotError, err))?;
+    chart
+        .draw_series(
+            hist.edges
+                .windows(2)
+                .zip(hist.counts.iter())
+                .map(|(edge, &count)| {
+                    Rectangle::new([(edge[0], 0), (edge[1], count)], BLUE.mix(0.6).filled())
+                }),
+        )
+        .map_err(|err| awsl!(PlotError, err))?;
+
+    Ok(())
+}
+
+/// 忽略NaN的均值与标准差
+pub fn nan_mean_std<I: Iterator<Item = f32>>(data: I) -> (f32, f32) {
+    // Welford算法，均值远大于标准差时也不会因相减而损失精度
+    let (mean, m2, cnt) =
+        data.filter(|x| !x.is_nan())
+            .fold((0f64, 0f64, 0usize), |(mean, m2, cnt), x| {
+                let cnt = cnt + 1;
+                let delta = x as f64 - mean;
+                let mean = mean + delta / cnt as f64;
+                (mean, m2 + delta * (x as f64 - mean), cnt)
+            });
+    if cnt == 0 {
+        return (f32::NAN, f32::NAN);
+    }
+
+    (mean as f32, (m2 / cnt as f64).sqrt() as f32)
+}
+
+/// 带坐标轴标题的曲线图，`band`不为空时同时画出上下误差线
+pub fn plot_profile<P: AsRef<Path>>(
+    plot_path: P,
+    x: &[f32],
+    y: &[f32],
+    band: Option<&[f32]>,
+    desc: (&str, &str),
+    options: &PlotOptions,
+) -> TLCResult<PathBuf> {
+    render!(
+        options,
+        plot_path,
+        (800, 600),
+        draw_profile(x, y, band, desc)
+    )
+}
+
+fn draw_profile<DB: DrawingBackend>(
+    root: DrawingArea<DB, Shift>,
+    k: f64,
+    x: &[f32],
+    y: &[f32],
+    band: Option<&[f32]>,
+    (x_desc, y_desc): (&str, &str),
+) -> TLCResult<()> {
+    let band = band.map(|band| band.to_vec()).unwrap_or(vec![0.; y.len()]);
+    // (x, y, 误差)，跳过NaN
+    let points: Vec<_> = x
+        .iter()
+        .zip(y.iter())
+        .zip(band.iter())
+        .map(|((&x, &y), &b)| (x, y, b))
+        .filter(|(_, y, _)| !y.is_nan())
+        .collect();
+    if points.is_empty() {
+        return Err(awsl!(PlotError, "empty data"));
+    }
+    let (mut x_min, mut x_max) = (f32::MAX, f32::MIN);
+    let (mut y_min, mut y_max) = (f32::MAX, f32::MIN);
+    for &(x, y, b) in &points {
+        x_min = x_min.min(x);
+        x_max = x_max.max(x);
+        y_min = y_min.min(y - b);
+        y_max = y_max.max(y + b);
+    }
+    let delta = (y_max - y_min).max(f32::EPSILON);
+    // 只有一个有效点时横轴范围为空，向两侧各留出一段
+    if x_max <= x_min {
+        let pad = x_min.abs().max(1.) * 0.1;
+        x_min -= pad;
+        x_max += pad;
+    }
+
+    root.fill(&WHITE).map_err(|err| awsl!(PlotError, err))?;
+    let mut chart = ChartBuilder::on(&root)
+        .margin(30. * k)
+        .x_label_area_size(40. * k)
+        .y_label_area_size(50. * k)
+        .build_cartesian_2d(x_min..x_max, (y_min - delta * 0.1)..(y_max + delta * 0.1))
+        .map_err(|err| awsl!(PlotError, err))?;
+    chart
+        .configure_mesh()
+        .x_desc(x_desc)
+        .y_desc(y_desc)
+        .label_style(("sans-serif", 12. * k))
+        .axis_desc_style(("sans-serif", 14. * k))
+        .draw()
+        .map_err(|err| awsl!(PlotError, err))?;
+    if band.iter().any(|&b| b != 0.) {
+        for &sign in [-1., 1.].iter() {
+            chart
+                .draw_series(LineSeries::new(
+                    points.iter().map(|&(x, y, b)| (x, y + sign * b)),
+                    BLUE.mix(0.4).stroke_width(k.ceil() as u32),
+                ))
+                .map_err(|err| awsl!(PlotError, err))?;
+        }
+    }
+    chart
+        .draw_series(LineSeries::new(
+            points.iter().map(|&(x, y, _)| (x, y)),
+            RED.stroke_width(k.ceil() as u32),
+        ))
+        .map_err(|err| awsl!(PlotError, err))?;
+
+    Ok(())
+}
+
+/// 云图的标注信息
+pub struct FigureInfo<'a> {
+    /// 标题，一般为工况名
+    pub title: &'a str,
+    /// 色标标题，如`Nu`
+    pub label: &'a str,
+    /// 每像素对应的长度（mm），为0时坐标轴以像素为单位
+    pub pixel_size: f32,
+}
+
+/// 逐像素的二维矩阵及其云图的保存路径
+#[derive(Debug, Serialize)]
+pub struct MapPaths {
+    pub data_path: String,
+    pub plot_path: String,
+}
+
+/// 用于报告的完整云图：带刻度的坐标轴、色标、标题以及平均值标注
+///
+/// `area`第0行对应图像最下方，与`nu2d`一致
+pub fn plot_figure<P: AsRef<Path>>(
+    plot_path: P,
+    area: ArrayView2<f32>,
+    (vmin, vmax): (f32, f32),
+    colormap: &[[f32; 3]],
+    info: &FigureInfo,
+    options: &PlotOptions,
+) -> TLCResult<PathBuf> {
+    let (h, w) = area.dim();
+    let size = (w as u32 + 260, h as u32 + 140);
+    render!(
+        options,
+        plot_path,
+        size,
+        draw_figure(area, (vmin, vmax), colormap, info)
+    )
+}
+
+fn draw_figure<DB: DrawingBackend>(
+    root: DrawingArea<DB, Shift>,
+    k: f64,
+    area: ArrayView2<f32>,
+    (vmin, vmax): (f32, f32),
+    colormap: &[[f32; 3]],
+    info: &FigureInfo,
+) -> TLCResult<()> {
+    let (h, w) = area.dim();
+    let color = |v: f32| {
+        let index =
+            ((v.max(vmin).min(vmax) - vmin) / (vmax - vmin) * (colormap.len() - 1) as f32) as usize;
+        let rgb = colormap[index];
+        RGBColor(
+            (rgb[0] * 255.) as u8,
+            (rgb[1] * 255.) as u8,
+            (rgb[2] * 255.) as u8,
+        )
+    };
+    let (scale, unit) = if info.pixel_size > 0. {
+        (info.pixel_size, "mm")
+    } else {
+        (1., "pixel")
+    };
+
+    root.fill(&WHITE).map_err(|err| awsl!(PlotError, err))?;
+    let root = root
+        .titled(info.title, ("sans-serif", 24. * k))
+        .map_err(|err| awsl!(PlotError, err))?;
+    let (main, colorbar) = root.split_horizontally(root.dim_in_pixel().0 - (140. * k) as u32);
+
+    let mut chart = ChartBuilder::on(&main)
+        .margin(10. * k)
+        .x_label_area_size(40. * k)
+        .y_label_area_size(60. * k)
+        .build_cartesian_2d(0f32..w as f32 * scale, 0f32..h as f32 * scale)
+        .map_err(|err| awsl!(PlotError, err))?;
+    chart
+        .configure_mesh()
+        .disable_mesh()
+        .x_desc(format!("x ({})", unit))
+        .y_desc(format!("y ({})", unit))
+        .label_style(("sans-serif", 14. * k))
+        .axis_desc_style(("sans-serif", 16. * k))
+        .draw()
+        .map_err(|err| awsl!(PlotError, err))?;
+    chart
+        .draw_series(area.indexed_iter().map(|((y, x), &v)| {
+            let style = if v.is_nan() { WHITE } else { color(v) };
+            let (x, y) = (x as f32 * scale, y as f32 * scale);
+            Rectangle::new([(x, y), (x + scale, y + scale)], style.filled())
+        }))
+        .map_err(|err| awsl!(PlotError, err))?;
+    let mean = cal_nan_mean(area);
+    chart
+        .draw_series(std::iter::once(Text::new(
+            format!("mean {} = {:.2}", info.label, mean),
+            (w as f32 * scale * 0.02, h as f32 * scale * 0.98),
+            ("sans-serif", 18. * k).into_font().color(&BLACK),
+        )))
+        .map_err(|err| awsl!(PlotError, err))?;
+
+    let mut bar = ChartBuilder::on(&colorbar)
+        .margin(10. * k)
+        .margin_left(20. * k)
+        .x_label_area_size(40. * k)
+        .right_y_label_area_size(70. * k)
+        .build_cartesian_2d(0f32..1., vmin..vmax)
+        .map_err(|err| awsl!(PlotError, err))?;
+    bar.configure_mesh()
+        .disable_mesh()
+        .disable_x_axis()
+        .y_desc(info.label)
+        .label_style(("sans-serif", 14. * k))
+        .axis_desc_style(("sans-serif", 16. * k))
+        .draw()
+        .map_err(|err| awsl!(PlotError, err))?;
+    let step = (vmax - vmin) / colormap.len() as f32;
+    bar.draw_series((0..colormap.len()).map(|i| {
+        let v = vmin + step * i as f32;
+        Rectangle::new([(0., v), (1., v + step)], color(v + step / 2.).filled())
+    }))
+    .map_err(|err| awsl!(PlotError, err))?;
+
+    Ok(())
+}
+
+pub fn plot_area<P: AsRef<Path>>(
+    plot_path: P,
+    area: ArrayView2<f32>,
+    vmin: f32,
+    vmax: f32,
+    colormap: &[[f32; 3]],
+) -> TLCResult<()> {
+    let (h, w) = area.dim();
+    let root = BitMapBackend::new(&plot_path, (w as u32, h as u32)).into_drawing_area();
+    let chart = ChartBuilder::on(&root)
+        .build_cartesian_2d(0..w, 0..h)
+        .map_err(|err| awsl!(PlotError, err))?;
+    let pix_plotter = chart.plotting_area();
+
+    let delta = vmax - vmin;
+
+    let mut it = area.iter();
+    for y in 0..h {
+        for x in 0..w {
+            if let Some(nu) = it.next() {
+                if nu.is_nan() {
+                    pix_plotter
+                        .draw_pixel((x, y), &WHITE)
+                        .map_err(|err| awsl!(PlotError, err))?;
+                    continue;
+                }
+                let color_index = ((nu.max(vmin).min(vmax) - vmin) / delta
+                    * (colormap.len() - 1) as f32) as usize;
+                let mut rgb = colormap[color_index];
+                rgb.iter_mut().for_each(|c| *c = *c * 255.);
+                pix_plotter
+                    .draw_pixel((x, y), &RGBColor(rgb[0] as u8, rgb[1] as u8, rgb[2] as u8))
+                    .map_err(|err| awsl!(PlotError, err))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// 叠加在视频帧上的测量设置
+pub struct Overlay<'a> {
+    /// 计算区域左上角坐标(y, x)
+    pub top_left_pos: (usize, usize),
+    /// 计算区域尺寸(h, w)
+    pub region_shape: (usize, usize),
+    /// 热电偶名称及位置(y, x)
+    pub thermocouples: Vec<(String, (i32, i32))>,
+    /// Nu分布及等值线取值，为空时不画等值线
+    pub contours: Option<(ArrayView2<'a, f32>, &'a [f32])>,
+}
+
+/// 在RGB视频帧上画出计算区域、热电偶位置和Nu等值线，保存为png
+pub fn plot_overlay<P: AsRef<Path>>(
+    plot_path: P,
+    mut rgb: Vec<u8>,
+    (frame_h, frame_w): (usize, usize),
+    overlay: &Overlay,
+) -> TLCResult<()> {
+    let (frame_h, frame_w) = (frame_h as u32, frame_w as u32);
+    let root = BitMapBackend::with_buffer(&mut rgb, (frame_w, frame_h)).into_drawing_area();
+    let (tl_y, tl_x) = (overlay.top_left_pos.0 as i32, overlay.top_left_pos.1 as i32);
+    let (h, w) = overlay.region_shape;
+
+    if let Some((nu2d, levels)) = overlay.contours {
+        const CONTOUR_COLORS: [RGBColor; 4] = [CYAN, MAGENTA, YELLOW, WHITE];
+        // 与右侧或下方相邻像素分处等值线两侧的像素即为等值线上的点
+        let crossed =
+            |a: f32, b: f32, level: f32| !a.is_nan() && !b.is_nan() && (a < level) != (b < level);
+        for (&level, color) in levels.iter().zip(CONTOUR_COLORS.iter().cycle()) {
+            for ((row, x), &nu) in nu2d.indexed_iter() {
+                let right = nu2d.get((row, x + 1)).copied().unwrap_or(f32::NAN);
+                let below = nu2d.get((row + 1, x)).copied().unwrap_or(f32::NAN);
+                if crossed(nu, right, level) || crossed(nu, below, level) {
+                    // nu2d上下翻转过，第0行对应计算区域最下方
+                    let pos = (tl_x + x as i32, tl_y + (h - 1 - row) as i32);
+                    root.draw_pixel(pos, color)
+                        .map_err(|err| awsl!(PlotError, err))?;
+                }
+            }
+        }
+    }
+
+    root.draw(&Rectangle::new(
+        [(tl_x, tl_y), (tl_x + w as i32, tl_y + h as i32)],
+        RED.stroke_width(2),
+    ))
+    .map_err(|err| awsl!(PlotError, err))?;
+
+    let font = ("sans-serif", 16).into_font().color(&YELLOW);
+    for (name, (y, x)) in &overlay.thermocouples {
+        root.draw(&Cross::new((*x, *y), 5, GREEN.stroke_width(2)))
+            .map_err(|err| awsl!(PlotError, err))?;
+        root.draw(&Text::new(name.as_str(), (x + 7, y - 18), font.clone()))
+            .map_err(|err| awsl!(PlotError, err))?;
+    }
+
+    root.present().map_err(|err| awsl!(PlotError, err))?;
+    drop(root);
+    image::save_buffer(plot_path, &rgb, frame_w, frame_h, image::ColorType::Rgb8)
+        .map_err(|err| awsl!(PlotError, err))?;
+
+    Ok(())
+}
+
+pub fn plot_line(arr: ArrayView1<f32>) -> TLCResult<()> {
+    let len = arr.len();
+    let x0 = *arr.first().ok_or(awsl!(PlotError, "empty data"))?;
+    let min = arr.iter().fold(x0, |m, &x| if x < m { x } else { m });
+    let max = arr.iter().fold(x0, |m, &x| if x > m { x } else { m });
+    let delta = max - min;
+
+    let root = BitMapBackend::new("cache/simple_plot.png", (640, 480)).into_drawing_area();
+    root.fill(&WHITE).map_err(|err| awsl!(PlotError, err))?;
+    let mut chart = ChartBuilder::on(&root)
+        .margin(30)
+        .x_label_area_size(30)
+        .y_label_area_size(30)
+        .build_cartesian_2d(0..len, (min - delta * 0.1)..(max + delta * 0.1))
+        .map_err(|err| awsl!(PlotError, err))?;
+    chart
+        .configure_mesh()
+        .draw()
+        .map_err(|err| awsl!(PlotError, err))?;
+    chart
+        .draw_series(LineSeries::new(
+            arr.iter().enumerate().map(|(i, v)| (i, *v)),
+            &RED,
+        ))
+        .map_err(|err| awsl!(PlotError, err))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn mean_std_precision() {
+        // 均值远大于标准差，单遍的平方和公式在f32下只剩舍入误差
+        let data: Vec<f32> = (0..100_000)
+            .map(|i| 1000. + if i % 2 == 0 { 0.01 } else { -0.01 })
+            .chain(std::iter::once(f32::NAN))
+            .collect();
+        let (mean, std) = nan_mean_std(data.iter().copied());
+        assert!((mean - 1000.).abs() < 1e-3);
+        assert!((std - 0.01).abs() < 1e-4, "{}", std);
+
+        let (mean, std) = nan_mean_std([f32::NAN].iter().copied());
+        assert!(mean.is_nan() && std.is_nan());
+    }
+}
@@ -0,0 +1,2604 @@
+use median::Filter;
+
+use ndarray::parallel::prelude::*;
+use ndarray::prelude::*;
+
+use serde::{Deserialize, Serialize};
+
+use packed_simd::f32x8;
+
+use dwt::{transform, wavelet::Wavelet, Operation};
+
+use super::{
+    error::{TLCError, TLCResult},
+    Stage, TLCConfig, TLCData, Thermocouple,
+};
+use crate::awsl;
+
+const SCALING: usize = 5;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub enum FilterMethod {
+    No,
+    Median(usize),
+    /// 小波阈值去噪：各层细节系数的阈值占该层最大系数的比例，边界延拓方式
+    Wavelet {
+        threshold_ratio: f32,
+        #[serde(default)]
+        boundary: WaveletBoundary,
+    },
+    /// 陷波滤除灯光闪烁：闪烁频率（Hz），品质因数（越大阻带越窄）
+    ///
+    /// 工频照明的闪烁频率为工频的两倍，高于帧率一半时按混叠到的频率滤除
+    Notch {
+        freq: f32,
+        q: f32,
+    },
+}
+
+impl Default for FilterMethod {
+    fn default() -> Self {
+        FilterMethod::No
+    }
+}
+
+/// 小波变换前的边界延拓方式，两端的延拓决定了记录首尾附近的滤波结果
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum WaveletBoundary {
+    /// 镜像延拓，两端不引入跳变，峰值靠近记录首尾时也不会偏移
+    Symmetric,
+    /// 周期延拓，首尾相接，适合首尾Green值相近的信号
+    Periodic,
+}
+
+impl Default for WaveletBoundary {
+    fn default() -> Self {
+        WaveletBoundary::Symmetric
+    }
+}
+
+impl WaveletBoundary {
+    /// 延拓后信号第`i`个点对应的原信号下标，原信号从延拓后的第`left`个点开始，长度为`len`
+    fn source(self, i: usize, left: usize, len: usize) -> usize {
+        let i = i as isize - left as isize;
+        let len = len as isize;
+        let i = match self {
+            WaveletBoundary::Symmetric => {
+                let i = i.rem_euclid(2 * len);
+                if i < len {
+                    i
+                } else {
+                    2 * len - 1 - i
+                }
+            }
+            WaveletBoundary::Periodic => i.rem_euclid(len),
+        };
+
+        i as usize
+    }
+}
+
+impl FilterMethod {
+    /// 对单个像素的Green值历史滤波，整个区域滤波时各方法的准备工作只进行一次，见`filtering`
+    pub(crate) fn apply(&self, g: ArrayViewMut1<u8>, frame_num: usize, frame_rate: usize) {
+        match *self {
+            FilterMethod::No => {}
+            FilterMethod::Median(window_size) => median_filter(g, window_size),
+            FilterMethod::Wavelet {
+                threshold_ratio,
+                boundary,
+            } => {
+                let db8 = db8();
+                let (dwt_max_level, extended_len) = wavelet_prepare(frame_num, &db8);
+                wavelet_filter(
+                    g,
+                    &db8,
+                    dwt_max_level,
+                    extended_len,
+                    threshold_ratio,
+                    boundary,
+                );
+            }
+            FilterMethod::Notch { freq, q } => {
+                if let Some(coefs) = notch_coefs(freq, q, frame_rate) {
+                    notch_filter(g, coefs);
+                }
+            }
+        }
+    }
+}
+
+/// `get_point_history`的结果
+#[derive(Debug, Serialize)]
+pub struct PointHistory {
+    /// 各帧的实验时间（s）
+    pub times: Vec<f32>,
+    /// 滤波后的Green值
+    pub green: Vec<u8>,
+    /// 参考温度（°C）
+    pub temps: Vec<f32>,
+}
+
+/// 一种滤波方法下单个像素的滤波结果
+#[derive(Debug, Serialize)]
+pub struct FilteredSeries {
+    pub filter_method: FilterMethod,
+    pub green: Vec<u8>,
+    /// 检测出的峰值帧，历史为空时为None
+    pub peak_frame: Option<usize>,
+}
+
+/// `compare_filters`的结果，各序列逐帧对齐
+#[derive(Debug, Serialize)]
+pub struct FilterComparison {
+    /// 滤波前的Green值（已归一化、修复坏点并修正漂移）
+    pub unfiltered: Vec<u8>,
+    pub series: Vec<FilteredSeries>,
+}
+
+/// 计算信号基线所用的帧数，与默认估计初始温度所用的帧数相同
+const BASELINE_FRAMES: usize = 4;
+
+/// 峰值检测时一并记录的信号强度，遮罩排除的像素均为NaN
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct PeakSignal {
+    /// 检测范围内前`BASELINE_FRAMES`帧Green值的平均值
+    pub baseline: f32,
+    /// 峰值帧的Green值与基线之差，涂层薄或光照不足处偏小
+    pub amplitude: f32,
+}
+
+impl PeakSignal {
+    const EXCLUDED: Self = Self {
+        baseline: f32::NAN,
+        amplitude: f32::NAN,
+    };
+
+    /// `peak`为峰值在`col`中的位置
+    pub(crate) fn of(col: ArrayView1<u8>, peak: usize) -> Self {
+        let first = col.slice(s![..BASELINE_FRAMES.min(col.len())]);
+        let baseline = first.iter().map(|&g| g as f32).sum::<f32>() / first.len() as f32;
+
+        Self {
+            baseline,
+            amplitude: col[peak] as f32 - baseline,
+        }
+    }
+}
+
+/// 由相邻帧之差估计的噪声标准差，缓慢变化的信号本身几乎不计入，与滤波方法无关
+pub(crate) fn noise_level(g: ArrayView1<u8>) -> f32 {
+    let diff_sq: f32 = g
+        .iter()
+        .zip(g.iter().skip(1))
+        .map(|(&a, &b)| (b as f32 - a as f32).powi(2))
+        .sum();
+
+    (diff_sq / (2 * (g.len() - 1).max(1)) as f32).sqrt()
+}
+
+/// 各像素的特征时刻（求解时对应`peak_temp`）的检测方法
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub enum PeakMethod {
+    /// Green值最大的帧
+    Max,
+    /// Green值上升最快的帧，适用于显色起点比峰值更锐利的窄带液晶，
+    /// 此时`peak_temp`应取该点对应的温度
+    MaxSlope,
+}
+
+impl Default for PeakMethod {
+    fn default() -> Self {
+        PeakMethod::Max
+    }
+}
+
+impl PeakMethod {
+    /// 单个像素的特征帧，历史为空时为None
+    pub(crate) fn locate(self, green: ArrayView1<u8>) -> Option<usize> {
+        match self {
+            PeakMethod::Max => green
+                .iter()
+                .enumerate()
+                .max_by_key(|(_, g)| *g)
+                .map(|(i, _)| i),
+            // 中心差分，两端取单侧差分
+            PeakMethod::MaxSlope => {
+                let last = green.len().checked_sub(1)?;
+                (0..=last).max_by_key(|&i| {
+                    let (prev, next) = (i.saturating_sub(1), (i + 1).min(last));
+                    (green[next] as i32 - green[prev] as i32) * 2 / (next - prev).max(1) as i32
+                })
+            }
+        }
+    }
+}
+
+/// 光照缓慢漂移的修正，在滤波与峰值检测之前从Green值中去除
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub enum DriftCorrection {
+    No,
+    /// 逐像素拟合低阶多项式基线（阶数），拟合时迭代削去高于基线的部分，避免把变色峰当作漂移
+    Polynomial(usize),
+    /// 以计算区域内不受加热影响的参考区域（左上角(y, x)，尺寸(高, 宽)）的平均Green值
+    /// 相对第一帧的变化为漂移，所有像素减去同一漂移
+    Patch((usize, usize), (usize, usize)),
+}
+
+impl Default for DriftCorrection {
+    fn default() -> Self {
+        DriftCorrection::No
+    }
+}
+
+/// 多项式基线拟合的迭代次数
+const DRIFT_ITERATIONS: usize = 8;
+
+/// 去除漂移所需的、所有像素共用的数据
+enum Drift {
+    /// 帧序号上的离散正交多项式基
+    Basis(Vec<Array1<f32>>),
+    /// 各帧相对第一帧的漂移量
+    Common(Array1<f32>),
+}
+
+impl DriftCorrection {
+    fn prepare(
+        self,
+        raw_g2d: ArrayView2<u8>,
+        region_shape: (usize, usize),
+    ) -> TLCResult<Option<Drift>> {
+        let frame_num = raw_g2d.nrows();
+        match self {
+            DriftCorrection::No => Ok(None),
+            DriftCorrection::Polynomial(order) => {
+                // 帧序号映射到[-1, 1]后对单项式做Gram-Schmidt正交化
+                let x = Array1::<f32>::linspace(-1., 1., frame_num);
+                let mut basis: Vec<Array1<f32>> = Vec::new();
+                for k in 0..=order.min(frame_num.saturating_sub(1)) {
+                    let mut q = x.mapv(|x| x.powi(k as i32));
+                    for b in &basis {
+                        let proj = q.dot(b);
+                        q.scaled_add(-proj, b);
+                    }
+                    let norm = q.dot(&q).sqrt();
+                    if norm > f32::EPSILON {
+                        basis.push(q / norm);
+                    }
+                }
+
+                Ok(Some(Drift::Basis(basis)))
+            }
+            DriftCorrection::Patch((y, x), (h, w)) => {
+                let (cal_h, cal_w) = region_shape;
+                if h == 0 || w == 0 || y + h > cal_h || x + w > cal_w {
+                    return Err(awsl!(
+                        ConfigError,
+                        format!(
+                            "参考区域(左上角({}, {})，尺寸{}×{})超出计算区域{}×{}",
+                            y, x, h, w, cal_h, cal_w
+                        )
+                    ));
+                }
+                let reference: Array1<f32> = raw_g2d
+                    .outer_iter()
+                    .map(|frame| {
+                        let sum: u32 = (y..y + h)
+                            .flat_map(|row| row * cal_w + x..row * cal_w + x + w)
+                            .map(|pos| frame[pos] as u32)
+                            .sum();
+                        sum as f32 / (h * w) as f32
+                    })
+                    .collect();
+                let first = reference.get(0).copied().unwrap_or_default();
+
+                Ok(Some(Drift::Common(reference - first)))
+            }
+        }
+    }
+}
+
+impl Drift {
+    fn remove(&self, mut data: ArrayViewMut1<u8>) {
+        let g = data.mapv(|g| g as f32);
+        let drift = match self {
+            Drift::Basis(basis) => {
+                let fit = |y: &Array1<f32>| {
+                    basis.iter().fold(Array1::zeros(y.len()), |mut acc, q| {
+                        acc.scaled_add(q.dot(y), q);
+                        acc
+                    })
+                };
+                let mut clipped = g.clone();
+                for _ in 0..DRIFT_ITERATIONS {
+                    let base = fit(&clipped);
+                    clipped.zip_mut_with(&base, |y, &b| *y = y.min(b));
+                }
+                let base = fit(&clipped);
+                let first = base.get(0).copied().unwrap_or_default();
+                base - first
+            }
+            Drift::Common(drift) => drift.clone(),
+        };
+        for ((d, g), drift) in data.iter_mut().zip(g).zip(drift) {
+            *d = (g - drift).round().max(0.).min(255.) as u8;
+        }
+    }
+}
+
+/// 数采频率与视频帧率不同时，将参考温度重采样到各帧对应时刻
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub enum DAQResample {
+    /// 每帧对应一行
+    No,
+    Linear,
+    /// Catmull-Rom三次插值
+    Cubic,
+}
+
+impl Default for DAQResample {
+    fn default() -> Self {
+        DAQResample::No
+    }
+}
+
+/// 参考温度（t2d）沿时间轴的预处理，用于去除单个热电偶的电噪声尖峰
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub enum DAQFilter {
+    No,
+    /// 滑动中值去尖峰（窗口大小）
+    MedianDespike(usize),
+    /// 滑动平均（窗口大小）
+    MovingAverage(usize),
+    /// Savitzky-Golay平滑（窗口大小，多项式阶数）
+    SavitzkyGolay(usize, usize),
+}
+
+impl Default for DAQFilter {
+    fn default() -> Self {
+        DAQFilter::No
+    }
+}
+
+impl DAQFilter {
+    /// 窗口在两端超出范围的部分取端点值
+    fn apply(self, mut data: ArrayViewMut1<f32>) {
+        let (window, weights) = match self {
+            DAQFilter::No => return,
+            DAQFilter::MedianDespike(window) => (window, None),
+            DAQFilter::MovingAverage(window) => (window, Some(vec![1. / window as f32; window])),
+            DAQFilter::SavitzkyGolay(window, order) => (window, Some(savgol_coefs(window, order))),
+        };
+        if window < 2 || data.len() < 2 {
+            return;
+        }
+        let half = (window / 2) as isize;
+        let last = data.len() as isize - 1;
+        let raw = data.to_vec();
+        let at = |i: isize| raw[i.max(0).min(last) as usize];
+
+        let mut buf = Vec::with_capacity(window);
+        for (i, v) in data.iter_mut().enumerate() {
+            let i = i as isize;
+            *v = match &weights {
+                Some(weights) => weights.iter().zip(i - half..).map(|(w, j)| w * at(j)).sum(),
+                None => {
+                    buf.clear();
+                    buf.extend((i - half..=i + half).map(at));
+                    buf.sort_unstable_by(|a, b| {
+                        a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal)
+                    });
+                    buf[buf.len() / 2]
+                }
+            };
+        }
+    }
+}
+
+/// Savitzky-Golay平滑卷积系数，窗口大小取奇数
+///
+/// 在窗口内以最小二乘拟合`order`阶多项式，取中心点的拟合值：
+/// 解`(AᵀA)x = e₀`，第j个系数为`Σₖ xₖ·jᵏ`
+fn savgol_coefs(window: usize, order: usize) -> Vec<f32> {
+    let half = (window / 2) as i32;
+    let n = order.min(2 * half as usize) + 1;
+    let mut ata = vec![vec![0f64; n + 1]; n];
+    for j in -half..=half {
+        for k in 0..n {
+            for l in 0..n {
+                ata[k][l] += (j as f64).powi((k + l) as i32);
+            }
+        }
+    }
+    ata[0][n] = 1.;
+    // 高斯消元
+    for col in 0..n {
+        let pivot = (col..n)
+            .max_by(|&a, &b| ata[a][col].abs().partial_cmp(&ata[b][col].abs()).unwrap())
+            .unwrap();
+        ata.swap(col, pivot);
+        for row in 0..n {
+            if row != col {
+                let factor = ata[row][col] / ata[col][col];
+                for k in col..=n {
+                    ata[row][k] -= factor * ata[col][k];
+                }
+            }
+        }
+    }
+    let x: Vec<_> = (0..n).map(|k| ata[k][n] / ata[k][k]).collect();
+
+    (-half..=half)
+        .map(|j| {
+            x.iter()
+                .enumerate()
+                .map(|(k, xk)| xk * (j as f64).powi(k as i32))
+                .sum::<f64>() as f32
+        })
+        .collect()
+}
+
+/// 温度历史极差小于该值时认为热电偶卡死（°C）
+const STUCK_TEMP_RANGE: f32 = 1e-3;
+
+/// 热电偶状态
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum TCStatus {
+    Ok,
+    /// 用户手动关闭
+    Disabled,
+    /// 温度历史中存在NaN（断线、数采文件缺值）
+    NaN,
+    /// 温度历史几乎不变
+    Stuck,
+}
+
+impl TCStatus {
+    fn check(enabled: bool, temps: ArrayView1<f32>) -> Self {
+        if !enabled {
+            return TCStatus::Disabled;
+        }
+        if temps.iter().any(|t| t.is_nan()) {
+            return TCStatus::NaN;
+        }
+        let (min, max) = temps.iter().fold((f32::MAX, f32::MIN), |(min, max), &t| {
+            (min.min(t), max.max(t))
+        });
+        if max - min < STUCK_TEMP_RANGE {
+            return TCStatus::Stuck;
+        }
+
+        TCStatus::Ok
+    }
+}
+
+/// 热电偶类型，用于电压-温度转换
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub enum TCType {
+    K,
+    T,
+}
+
+/// 数采文件中温度的单位，统一转换为摄氏度后参与计算
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub enum TempUnit {
+    Celsius,
+    Kelvin,
+    Fahrenheit,
+    /// 热电势（mV），参考端为0°C
+    MilliVolt(TCType),
+}
+
+impl Default for TempUnit {
+    fn default() -> Self {
+        TempUnit::Celsius
+    }
+}
+
+/// NIST ITS-90热电势-温度反函数系数，分别对应负电势与正电势区间
+#[rustfmt::skip]
+const TYPE_K_INVERSE: [&[f64]; 2] = [
+    &[0., 2.5173462e1, -1.1662878, -1.0833638, -8.9773540e-1, -3.7342377e-1,
+        -8.6632643e-2, -1.0450598e-2, -5.1920577e-4],
+    &[0., 2.508355e1, 7.860106e-2, -2.503131e-1, 8.315270e-2, -1.228034e-2,
+        9.804036e-4, -4.413030e-5, 1.057734e-6, -1.052755e-8],
+];
+#[rustfmt::skip]
+const TYPE_T_INVERSE: [&[f64]; 2] = [
+    &[0., 2.5949192e1, 2.1316967e-1, 7.9018692e-1, 4.2527777e-1, 1.3304473e-1,
+        2.0241446e-2, 1.2668171e-3],
+    &[0., 2.592800e1, -7.602961e-1, 4.637791e-2, -2.165394e-3, 6.048144e-5,
+        -7.293422e-7],
+];
+
+impl TempUnit {
+    pub fn to_celsius(self, v: f32) -> f32 {
+        match self {
+            TempUnit::Celsius => v,
+            TempUnit::Kelvin => v - 273.15,
+            TempUnit::Fahrenheit => (v - 32.) / 1.8,
+            TempUnit::MilliVolt(tc_type) => {
+                let coefs = match tc_type {
+                    TCType::K => TYPE_K_INVERSE,
+                    TCType::T => TYPE_T_INVERSE,
+                };
+                let coefs = if v < 0. { coefs[0] } else { coefs[1] };
+                let e = v as f64;
+                coefs.iter().rev().fold(0., |t, c| t * e + c) as f32
+            }
+        }
+    }
+}
+
+/// 在数采某一列的（小数）行位置`row_pos`处取值
+fn resample_at(col: ArrayView1<f32>, row_pos: f32, resample: DAQResample) -> f32 {
+    let last = col.len() - 1;
+    // 超出末行时取末行，避免三次插值外推
+    let row_pos = row_pos.min(last as f32);
+    let i = row_pos.floor() as usize;
+    let s = row_pos - i as f32;
+    match resample {
+        DAQResample::No => col[(row_pos.round() as usize).min(last)],
+        DAQResample::Linear => {
+            let (p1, p2) = (col[i], col[(i + 1).min(last)]);
+            p1 + (p2 - p1) * s
+        }
+        DAQResample::Cubic => {
+            let p0 = col[i.saturating_sub(1)];
+            let (p1, p2, p3) = (col[i], col[(i + 1).min(last)], col[(i + 2).min(last)]);
+            p1 + 0.5
+                * s
+                * (p2 - p0
+                    + s * (2. * p0 - 5. * p1 + 4. * p2 - p3 + s * (3. * (p1 - p2) + p3 - p0)))
+        }
+    }
+}
+
+/// 二阶陷波器系数(b0, b1, b2, a1, a2)，已按a0归一化；闪烁混叠到直流附近时无法滤除，返回None
+pub(crate) fn notch_coefs(freq: f32, q: f32, frame_rate: usize) -> Option<[f32; 5]> {
+    let fs = frame_rate as f32;
+    if fs <= 0. || q <= 0. {
+        return None;
+    }
+    let aliased = (freq - (freq / fs).round() * fs).abs();
+    if aliased < fs * 1e-3 {
+        return None;
+    }
+    let w0 = 2. * std::f32::consts::PI * aliased / fs;
+    let alpha = w0.sin() / (2. * q);
+    let a0 = 1. + alpha;
+    let cos = w0.cos();
+
+    Some([
+        1. / a0,
+        -2. * cos / a0,
+        1. / a0,
+        -2. * cos / a0,
+        (1. - alpha) / a0,
+    ])
+}
+
+/// 正反向各滤波一次以消除相位延迟，避免峰值帧偏移；初始状态取首个值的稳态，减小端点的瞬态
+fn notch_filter(mut data: ArrayViewMut1<u8>, [b0, b1, b2, a1, a2]: [f32; 5]) {
+    let pass = |xs: &mut [f32]| {
+        let x0 = match xs.first() {
+            Some(&x0) => x0,
+            None => return,
+        };
+        let mut z2 = (b2 - a2) * x0;
+        let mut z1 = (b1 - a1) * x0 + z2;
+        for x in xs.iter_mut() {
+            let y = b0 * *x + z1;
+            z1 = b1 * *x - a1 * y + z2;
+            z2 = b2 * *x - a2 * y;
+            *x = y;
+        }
+    };
+    let mut arr: Vec<_> = data.iter().map(|&g| g as f32).collect();
+    pass(&mut arr);
+    arr.reverse();
+    pass(&mut arr);
+    arr.reverse();
+    data.iter_mut()
+        .zip(arr)
+        .for_each(|(g, v)| *g = v.round().max(0.).min(255.) as u8);
+}
+
+/// 坏点检测时，前若干帧的最小值不低于该值即认为饱和
+const HOT_PIXEL_LEVEL: u8 = 250;
+
+/// 前`frames`帧中值始终不变（卡死）或始终饱和（热像素）的像素
+fn detect_defects(raw_g2d: ArrayView2<u8>, frames: usize) -> Vec<bool> {
+    let head = raw_g2d.slice(s![..frames.min(raw_g2d.nrows()), ..]);
+    head.axis_iter(Axis(1))
+        .into_par_iter()
+        .map(|col| {
+            let (min, max) = col.iter().fold((u8::MAX, u8::MIN), |(min, max), &g| {
+                (min.min(g), max.max(g))
+            });
+            col.len() > 1 && (min == max || min >= HOT_PIXEL_LEVEL)
+        })
+        .collect()
+}
+
+/// 坏点8邻域中的正常像素
+fn defect_neighbors(pos: usize, defects: &[bool], (cal_h, cal_w): (usize, usize)) -> Vec<usize> {
+    let (y, x) = ((pos / cal_w) as isize, (pos % cal_w) as isize);
+    (-1..=1)
+        .flat_map(|dy| (-1..=1).map(move |dx| (y + dy, x + dx)))
+        .filter(|&(ny, nx)| ny >= 0 && nx >= 0 && ny < cal_h as isize && nx < cal_w as isize)
+        .map(|(ny, nx)| ny as usize * cal_w + nx as usize)
+        .filter(|&n| !defects[n])
+        .collect()
+}
+
+/// 各列逐帧的平均值，`columns`为空时为None
+fn average_columns(columns: &[Array1<u8>]) -> Option<Array1<u8>> {
+    let first = columns.first()?;
+    let mut sum = Array1::<u32>::zeros(first.len());
+    for col in columns {
+        sum.zip_mut_with(col, |s, &g| *s += g as u32);
+    }
+    let n = columns.len() as u32;
+
+    Some(sum.mapv(|s| ((s + n / 2) / n) as u8))
+}
+
+/// 按参考区域的亮度变化归一化，使参考区域各帧的平均值与第一帧一致
+fn normalize_by_reference(mut data: ArrayViewMut1<u8>, reference: &[f32]) {
+    let base = match reference.first() {
+        Some(&base) => base,
+        None => return,
+    };
+    for (g, &r) in data.iter_mut().zip(reference) {
+        if r > 0. {
+            *g = (*g as f32 * base / r).round().min(255.) as u8;
+        }
+    }
+}
+
+fn median_filter(mut data: ArrayViewMut1<u8>, window_size: usize) {
+    let mut filter = Filter::new(window_size);
+    data.iter_mut().for_each(|g| *g = filter.consume(*g));
+}
+
+/// [参考pywavelets官方文档](https://pywavelets.readthedocs.io/en/latest/ref)
+///
+/// 返回分解层数与延拓后的长度：两端各至少延拓一个滤波器长度，总长补齐到`2^level`的整数倍，
+/// 整段信号都参与滤波
+fn wavelet_prepare(data_len: usize, wavelet: &Wavelet<f32>) -> (usize, usize) {
+    let dwt_max_level = ((data_len / (wavelet.length - 1)) as f32).log2() as usize;
+    let level_2 = 1 << dwt_max_level;
+    let min_len = data_len + 2 * (wavelet.length - 1);
+    let extended_len = (min_len + level_2 - 1) / level_2 * level_2;
+
+    (dwt_max_level, extended_len)
+}
+
+fn wavelet_filter(
+    mut data: ArrayViewMut1<u8>,
+    wavelet: &Wavelet<f32>,
+    level: usize,
+    extended_len: usize,
+    threshold_ratio: f32,
+    boundary: WaveletBoundary,
+) {
+    let len = data.len();
+    if len == 0 {
+        return;
+    }
+    // 变换本身是周期的，先按边界方式延拓，延拓部分吸收首尾的边界效应
+    let left = (extended_len - len) / 2;
+    let mut arr: Vec<_> = (0..extended_len)
+        .map(|i| data[boundary.source(i, left, len)] as f32)
+        .collect();
+    // decomposition
+    transform(&mut arr, Operation::Forward, &wavelet, level);
+    let mut start = extended_len >> level;
+    for _ in 0..level {
+        let end = start << 1;
+        let m = arr[start..end].iter().fold(0., |m, &v| f32::max(m, v));
+        let threshold = m * threshold_ratio;
+        for v in &mut arr[start..end] {
+            *v = v.signum() * f32::max(v.abs() - threshold, 0.);
+        }
+        start = end;
+    }
+    // reconstruction
+    transform(&mut arr, Operation::Inverse, &wavelet, level);
+    data.iter_mut()
+        .zip(&arr[left..])
+        .for_each(|(g, &b)| *g = b.round().clamp(0., 255.) as u8);
+}
+
+/// 小波基采用[Daubechies 8](http://wavelets.pybytes.com/wavelet/db8)。
+/// 重建滤波器水平翻转
+fn db8() -> Wavelet<f32> {
+    #[rustfmt::skip]
+    let lo = vec![
+        -0.00011747678400228192, 0.0006754494059985568,
+        -0.0003917403729959771,  -0.00487035299301066,
+        0.008746094047015655,    0.013981027917015516,
+        -0.04408825393106472,    -0.01736930100202211,
+        0.128747426620186,       0.00047248457399797254,
+        -0.2840155429624281,     -0.015829105256023893,
+        0.5853546836548691,      0.6756307362980128,
+        0.3128715909144659,      0.05441584224308161,
+    ];
+    #[rustfmt::skip]
+    let hi = vec![
+        -0.05441584224308161,    0.3128715909144659,    
+        -0.6756307362980128,     0.5853546836548691,
+        0.015829105256023893,    -0.2840155429624281,    
+        -0.00047248457399797254, 0.128747426620186,
+        0.01736930100202211,     -0.04408825393106472,   
+        -0.013981027917015516,   0.008746094047015655,
+        0.00487035299301066,     -0.0003917403729959771, 
+        -0.0006754494059985568,  -0.00011747678400228192,
+    ];
+
+    Wavelet {
+        length: lo.len(),
+        offset: 0,
+        dec_lo: lo.clone(),
+        dec_hi: hi.clone(),
+        rec_lo: lo,
+        rec_hi: hi,
+    }
+}
+
+impl TLCData {
+    /// 对Green值矩阵沿时间轴滤波
+    pub fn filtering(&mut self) -> TLCResult<&mut Self> {
+        if self.raw_g2d.is_none() {
+            self.read_video()?;
+        }
+        self.in_pool(Self::filter_g2d)?
+    }
+
+    fn filter_g2d(&mut self) -> TLCResult<&mut Self> {
+        let timer = self.start_stage("filter");
+        let in_place = self.config.filter_in_place;
+        let bytes = if in_place {
+            0
+        } else {
+            self.get_raw_g2d()?.len()
+        };
+        self.reserve_memory(bytes, &[Stage::RawG2d]);
+        let raw_g2d = self.get_raw_g2d()?;
+        let drift = self
+            .config
+            .drift_correction
+            .prepare(raw_g2d, self.config.region_shape)?;
+        let defects = match self.config.defect_frames {
+            0 => None,
+            frames => Some(detect_defects(raw_g2d, frames)),
+        };
+        // 就地滤波时直接取走raw_g2d，不再复制一份，之后用到未滤波的数据时重新读取视频
+        let mut filtered_g2d = if in_place {
+            self.raw_g2d.take().ok_or(awsl!())?
+        } else {
+            raw_g2d.to_owned()
+        };
+        // 遮罩排除的像素不滤波
+        let mask = self.config.mask_pixels()?;
+        let included = |pos: usize| mask.as_ref().map_or(true, |mask| mask[pos]);
+
+        if let Some(reference) = &self.reference_g {
+            filtered_g2d
+                .axis_iter_mut(Axis(1))
+                .into_par_iter()
+                .enumerate()
+                .filter(|&(pos, _)| included(pos))
+                .for_each(|(_, col)| normalize_by_reference(col, reference));
+        }
+        if let Some(defects) = &defects {
+            let region_shape = self.config.region_shape;
+            let repaired: Vec<_> = (0..defects.len())
+                .filter(|&pos| defects[pos] && included(pos))
+                .filter_map(|pos| {
+                    let neighbors: Vec<_> = defect_neighbors(pos, defects, region_shape)
+                        .into_iter()
+                        .map(|n| filtered_g2d.column(n).to_owned())
+                        .collect();
+                    Some((pos, average_columns(&neighbors)?))
+                })
+                .collect();
+            for (pos, col) in repaired {
+                filtered_g2d.column_mut(pos).assign(&col);
+            }
+            let defect_num = defects.iter().filter(|&&d| d).count();
+            if defect_num > 0 {
+                tracing::info!(defect_num, "defective pixels repaired");
+            }
+        }
+        if let Some(drift) = &drift {
+            filtered_g2d
+                .axis_iter_mut(Axis(1))
+                .into_par_iter()
+                .enumerate()
+                .filter(|&(pos, _)| included(pos))
+                .for_each(|(_, col)| drift.remove(col));
+        }
+
+        match self.config.filter_method {
+            FilterMethod::No => {}
+            FilterMethod::Median(window_size) => {
+                filtered_g2d
+                    .axis_iter_mut(Axis(1))
+                    .into_par_iter()
+                    .enumerate()
+                    .filter(|&(pos, _)| included(pos))
+                    .for_each(|(_, col)| median_filter(col, window_size));
+            }
+            FilterMethod::Wavelet {
+                threshold_ratio,
+                boundary,
+            } => {
+                let db8 = db8();
+                let (dwt_max_level, extended_len) = wavelet_prepare(self.config.frame_num, &db8);
+                filtered_g2d
+                    .axis_iter_mut(Axis(1))
+                    .into_par_iter()
+                    .enumerate()
+                    .filter(|&(pos, _)| included(pos))
+                    .for_each(|(_, col)| {
+                        wavelet_filter(
+                            col,
+                            &db8,
+                            dwt_max_level,
+                            extended_len,
+                            threshold_ratio,
+                            boundary,
+                        )
+                    });
+            }
+            FilterMethod::Notch { freq, q } => {
+                if let Some(coefs) = notch_coefs(freq, q, self.config.frame_rate) {
+                    filtered_g2d
+                        .axis_iter_mut(Axis(1))
+                        .into_par_iter()
+                        .enumerate()
+                        .filter(|&(pos, _)| included(pos))
+                        .for_each(|(_, col)| notch_filter(col, coefs));
+                }
+            }
+        }
+        self.filtered_g2d.insert(filtered_g2d);
+        self.defects = defects;
+        if in_place {
+            self.drop_stages(&[Stage::RawG2d]);
+        }
+        self.computed(Stage::FilteredG2d);
+        self.finish(timer);
+
+        Ok(self)
+    }
+
+    pub fn filtering_single_point(&mut self, pos: usize) -> TLCResult<Vec<u8>> {
+        let mut filtered_g = self.unfiltered_single_point(pos)?;
+        let TLCConfig {
+            ref filter_method,
+            frame_num,
+            frame_rate,
+            ..
+        } = self.config;
+        filter_method.apply(filtered_g.view_mut(), frame_num, frame_rate);
+
+        Ok(filtered_g.to_vec())
+    }
+
+    /// 以`filter_methods`分别对同一像素滤波并检测峰值，用于对比、选择滤波参数，不修改配置
+    pub fn compare_filters(
+        &mut self,
+        pos: usize,
+        filter_methods: &[FilterMethod],
+    ) -> TLCResult<FilterComparison> {
+        let unfiltered = self.unfiltered_single_point(pos)?;
+        let (start, end) = self.event_window()?;
+        let TLCConfig {
+            frame_num,
+            frame_rate,
+            peak_method,
+            ..
+        } = self.config;
+        let series = filter_methods
+            .iter()
+            .map(|filter_method| {
+                let mut green = unfiltered.clone();
+                filter_method.apply(green.view_mut(), frame_num, frame_rate);
+                let peak_frame = peak_method
+                    .locate(green.slice(s![start..end]))
+                    .map(|peak| start + peak);
+                FilteredSeries {
+                    filter_method: filter_method.clone(),
+                    green: green.to_vec(),
+                    peak_frame,
+                }
+            })
+            .collect();
+
+        Ok(FilterComparison {
+            unfiltered: unfiltered.to_vec(),
+            series,
+        })
+    }
+
+    /// 单个像素经过参考区域归一化、坏点修复与漂移修正，尚未滤波的Green值
+    fn unfiltered_single_point(&mut self, pos: usize) -> TLCResult<Array1<u8>> {
+        self.unfiltered_points(&[pos])?.pop().ok_or(awsl!())
+    }
+
+    /// 同`unfiltered_single_point`，坏点检测与漂移估计只进行一次
+    pub(crate) fn unfiltered_points(&mut self, positions: &[usize]) -> TLCResult<Vec<Array1<u8>>> {
+        if self.raw_g2d.is_none() {
+            self.read_video()?;
+        }
+        let raw_g2d = self.get_raw_g2d()?;
+        let column = |pos: usize| {
+            let mut col = raw_g2d.column(pos).to_owned();
+            if let Some(reference) = &self.reference_g {
+                normalize_by_reference(col.view_mut(), reference);
+            }
+            col
+        };
+        let defects = match self.config.defect_frames {
+            0 => None,
+            frames => Some(detect_defects(raw_g2d, frames)),
+        };
+        let drift = self
+            .config
+            .drift_correction
+            .prepare(raw_g2d, self.config.region_shape)?;
+
+        Ok(positions
+            .iter()
+            .map(|&pos| {
+                let mut g = column(pos);
+                if let Some(defects) = defects.as_ref().filter(|defects| defects[pos]) {
+                    let neighbors: Vec<_> =
+                        defect_neighbors(pos, defects, self.config.region_shape)
+                            .into_iter()
+                            .map(column)
+                            .collect();
+                    if let Some(repaired) = average_columns(&neighbors) {
+                        g = repaired;
+                    }
+                }
+                if let Some(drift) = &drift {
+                    drift.remove(g.view_mut());
+                }
+                g
+            })
+            .collect())
+    }
+
+    /// 检测出的坏点在计算区域内的坐标(y, x)，未检测时为空
+    pub fn get_defect_pixels(&mut self) -> TLCResult<Vec<(usize, usize)>> {
+        if self.filtered_g2d.is_none() {
+            self.filtering()?;
+        }
+        let cal_w = self.config.region_shape.1;
+        let defects = self.defects.as_deref().unwrap_or_default();
+
+        Ok((0..defects.len())
+            .filter(|&pos| defects[pos])
+            .map(|pos| (pos / cal_w, pos % cal_w))
+            .collect())
+    }
+
+    /// 单点的时间历程，各帧的实验时间与滤波后的Green值、参考温度一一对应
+    pub fn get_point_history(&mut self, pos: usize) -> TLCResult<PointHistory> {
+        let green = self.filtering_single_point(pos)?;
+        if self.interp.is_none() {
+            self.interp()?;
+        }
+        if self.frame_times.is_none() {
+            self.init_frame_times()?;
+        }
+        let temps = self
+            .get_interp()?
+            .interp_single_point(pos, self.config.region_shape)
+            .to_vec();
+
+        Ok(PointHistory {
+            times: self.get_frame_times()?.clone(),
+            green,
+            temps,
+        })
+    }
+
+    /// 峰值检测
+    pub fn detect_peak(&mut self) -> TLCResult<&mut Self> {
+        if self.filtered_g2d.is_none() {
+            self.filtering()?;
+        }
+        let (start, end) = self.event_window()?;
+        let timer = self.start_stage("peak");
+
+        let mask = self.config.mask_pixels()?;
+        let filtered_g2d = self.get_filtered_g2d()?;
+        // 遮罩排除的像素峰值帧记为0，求解时视为无效
+        let mut peak_frames = vec![0; filtered_g2d.ncols()];
+        let mut peak_signals = vec![PeakSignal::EXCLUDED; filtered_g2d.ncols()];
+
+        let peak_method = self.config.peak_method;
+        // 选择了单次加热时只在该次加热的帧内检测
+        filtered_g2d
+            .slice(s![start..end, ..])
+            .axis_iter(Axis(1))
+            .into_par_iter()
+            .zip(peak_frames.par_iter_mut())
+            .zip(peak_signals.par_iter_mut())
+            .enumerate()
+            .filter(|&(pos, _)| mask.as_ref().map_or(true, |mask| mask[pos]))
+            .try_for_each(|(_, ((col, p), signal))| -> TLCResult<()> {
+                let peak = peak_method.locate(col).ok_or(awsl!("峰值检测出错"))?;
+                *p = start + peak;
+                *signal = PeakSignal::of(col, peak);
+
+                Ok(())
+            })?;
+        self.peak_frames.insert(peak_frames);
+        self.peak_signals = Some(peak_signals);
+        self.computed(Stage::PeakFrames);
+        self.finish(timer);
+
+        Ok(self)
+    }
+
+    pub fn init_t2d(&mut self) -> TLCResult<&mut Self> {
+        if self.daq.is_none() {
+            self.read_daq()?;
+        }
+
+        let TLCConfig {
+            ref thermocouples,
+            frame_num,
+            start_row,
+            ref regulator,
+            daq_resample,
+            ..
+        } = self.config;
+        let mut t2d = Array2::zeros((thermocouples.len(), frame_num));
+        let rows_per_frame = self.config.daq_rows_per_frame();
+        let daq = self.get_daq()?;
+        if let Some((index, tc)) = thermocouples
+            .iter()
+            .enumerate()
+            .find(|(_, tc)| tc.column_num >= daq.ncols())
+        {
+            return Err(TLCError::ThermocoupleError {
+                index,
+                column_num: tc.column_num,
+                daq_columns: daq.ncols(),
+            });
+        }
+
+        for (frame, mut t2d_col) in t2d.axis_iter_mut(Axis(1)).enumerate() {
+            let row_pos = start_row as f32 + frame as f32 * rows_per_frame;
+            for (tc, t) in thermocouples.iter().zip(t2d_col.iter_mut()) {
+                *t = tc.unit.to_celsius(resample_at(
+                    daq.column(tc.column_num),
+                    row_pos,
+                    daq_resample,
+                )) + tc.calibration_offset;
+            }
+        }
+
+        let regulator = Array::from_shape_vec((regulator.len(), 1), regulator.clone())
+            .map_err(|err| awsl!(err))?;
+
+        let mut t2d = t2d * regulator;
+        let daq_filter = self.config.daq_filter;
+        t2d.axis_iter_mut(Axis(0))
+            .into_par_iter()
+            .for_each(|row| daq_filter.apply(row));
+
+        let tc_status = thermocouples
+            .iter()
+            .zip(t2d.axis_iter(Axis(0)))
+            .map(|(tc, temps)| TCStatus::check(tc.enabled, temps))
+            .collect();
+
+        self.t2d.insert(t2d);
+        self.tc_status.insert(tc_status);
+        self.init_frame_times()?;
+        self.computed(Stage::T2d);
+
+        Ok(self)
+    }
+
+    /// 各帧的实验时间（s）：设置了数采时间列时按该帧对应的数采行线性插值，
+    /// 否则为视频时间，即(起始帧数 + 帧数) / 帧率
+    pub fn init_frame_times(&mut self) -> TLCResult<&mut Self> {
+        let TLCConfig {
+            start_frame,
+            frame_num,
+            frame_rate,
+            start_row,
+            daq_time_column,
+            ..
+        } = self.config;
+        let frame_times = match daq_time_column {
+            None => (0..frame_num)
+                .map(|frame| (start_frame + frame) as f32 / frame_rate as f32)
+                .collect(),
+            Some(column) => {
+                if self.daq.is_none() {
+                    self.read_daq()?;
+                }
+                let rows_per_frame = self.config.daq_rows_per_frame();
+                let daq = self.get_daq()?;
+                if column >= daq.ncols() {
+                    return Err(awsl!(
+                        ConfigError,
+                        format!("时间列{}超出数采文件的列数{}", column, daq.ncols())
+                    ));
+                }
+                (0..frame_num)
+                    .map(|frame| {
+                        let row_pos = start_row as f32 + frame as f32 * rows_per_frame;
+                        resample_at(daq.column(column), row_pos, DAQResample::Linear)
+                    })
+                    .collect()
+            }
+        };
+        self.frame_times = Some(frame_times);
+
+        Ok(self)
+    }
+
+    pub fn interp_single_frame(&mut self, frame: usize) -> TLCResult<Array2<f32>> {
+        if self.interp.is_none() {
+            self.interp()?;
+        }
+        if frame >= self.config.frame_num {
+            return Err(TLCError::FrameError {
+                frame,
+                frame_num: self.config.frame_num,
+            });
+        }
+        self.get_interp()?
+            .interp_single_frame(frame, self.config.region_shape)
+    }
+
+    /// interpolation of reference temperature matrix
+    pub fn interp(&mut self) -> TLCResult<&mut Self> {
+        if self.t2d.is_none() {
+            self.init_t2d()?;
+        }
+        let timer = self.start_stage("interp");
+        if !self.config.lazy_interp {
+            let (cal_h, cal_w) = self.config.region_shape;
+            let bytes = cal_h * cal_w * self.config.frame_num * std::mem::size_of::<f32>();
+            self.reserve_memory(bytes, &[Stage::T2d]);
+        }
+        let interp = self.build_interp(self.config.lazy_interp)?;
+        self.interp = Some(interp);
+        self.computed(Stage::Interp);
+        self.finish(timer);
+        if let Some(extrapolation) = self.extrapolation()? {
+            if extrapolation.exceeded_frames > 0 {
+                tracing::warn!(
+                    max_overshoot = extrapolation.max_overshoot,
+                    exceeded_frames = extrapolation.exceeded_frames,
+                    limit = self.config.extrapolation_limit,
+                    "extrapolated reference temperature exceeds the thermocouple range"
+                );
+            }
+        }
+
+        Ok(self)
+    }
+
+    /// 按当前的t2d与热电偶状态插值，不保存
+    pub(crate) fn build_interp(&self, lazy: bool) -> TLCResult<Interp> {
+        let TLCConfig {
+            interp_method,
+            top_left_pos,
+            region_shape,
+            ref thermocouples,
+            ..
+        } = self.config;
+        let t2d = self.get_t2d()?;
+        let active: Vec<_> = self
+            .get_tc_status()?
+            .iter()
+            .map(|&status| status == TCStatus::Ok)
+            .collect();
+
+        Interp::new(
+            t2d,
+            interp_method,
+            thermocouples,
+            &active,
+            top_left_pos,
+            region_shape,
+            lazy,
+        )
+    }
+
+    /// 以表面等温（如加热前）的一帧为基准，计算使各热电偶读数一致的调节系数
+    ///
+    /// 基准温度取该帧所有可用热电偶的平均值，不可用热电偶的调节系数保持不变
+    pub fn calibrate_regulator(&mut self, frame: usize) -> TLCResult<&mut Self> {
+        if self.t2d.is_none() {
+            self.init_t2d()?;
+        }
+        if frame >= self.config.frame_num {
+            return Err(TLCError::FrameError {
+                frame,
+                frame_num: self.config.frame_num,
+            });
+        }
+
+        let t2d = self.get_t2d()?;
+        let tc_status = self.get_tc_status()?;
+        let mut regulator = self.config.regulator.clone();
+        // t2d中已乘以原调节系数，还原为原始读数
+        let raw: Vec<_> = t2d
+            .column(frame)
+            .iter()
+            .zip(regulator.iter())
+            .map(|(&t, &r)| if r == 0. { f32::NAN } else { t / r })
+            .collect();
+        let valid: Vec<_> = (0..raw.len())
+            .filter(|&i| tc_status[i] == TCStatus::Ok && raw[i].is_finite() && raw[i] != 0.)
+            .collect();
+        if valid.is_empty() {
+            return Err(awsl!(HandleError, "没有可用的热电偶"));
+        }
+        let reference = valid.iter().map(|&i| raw[i]).sum::<f32>() / valid.len() as f32;
+        for &i in &valid {
+            regulator[i] = reference / raw[i];
+        }
+
+        Ok(self.set_regulator(regulator))
+    }
+
+    /// 留一法交叉验证当前插值方法，返回各热电偶的均方根误差，不可用的热电偶为NaN
+    pub fn validate_interp(&mut self) -> TLCResult<Vec<f32>> {
+        if self.t2d.is_none() {
+            self.init_t2d()?;
+        }
+
+        let TLCConfig {
+            interp_method,
+            top_left_pos,
+            region_shape,
+            ref thermocouples,
+            ..
+        } = self.config;
+        let active: Vec<_> = self
+            .get_tc_status()?
+            .iter()
+            .map(|&status| status == TCStatus::Ok)
+            .collect();
+
+        Ok(Interp::validate(
+            self.get_t2d()?,
+            interp_method,
+            thermocouples,
+            &active,
+            top_left_pos,
+            region_shape,
+        ))
+    }
+}
+
+/// 用网格中同一行（不足时用同一列）最近的两个可用节点补齐不可用节点
+fn fill_grid_gaps(
+    t2d: &mut Array2<f32>,
+    tcs: &[Thermocouple],
+    active: &[bool],
+    tc_shape: (usize, usize),
+) -> TLCResult<()> {
+    let (tc_h, tc_w) = tc_shape;
+    let idx = |y: usize, x: usize| y * tc_w + x;
+    for y in 0..tc_h {
+        for x in 0..tc_w {
+            let i = idx(y, x);
+            if active[i] {
+                continue;
+            }
+            let row: Vec<_> = (0..tc_w)
+                .map(|x| idx(y, x))
+                .filter(|&j| active[j])
+                .collect();
+            let col: Vec<_> = (0..tc_h)
+                .map(|y| idx(y, x))
+                .filter(|&j| active[j])
+                .collect();
+            let (neighbors, coord): (_, fn(&Thermocouple) -> i32) = if row.len() >= 2 {
+                (row, |tc| tc.pos.1)
+            } else if col.len() >= 2 {
+                (col, |tc| tc.pos.0)
+            } else {
+                return Err(awsl!(
+                    HandleError,
+                    format!(
+                        "热电偶{}不可用，且所在行列可用热电偶不足",
+                        tcs[i].describe(i)
+                    )
+                ));
+            };
+            let p = coord(&tcs[i]);
+            // 取位置最近的两个可用节点
+            let mut neighbors = neighbors;
+            neighbors.sort_unstable_by_key(|&j| (coord(&tcs[j]) - p).abs());
+            let (l, r) = (neighbors[0], neighbors[1]);
+            let (pl, pr) = (coord(&tcs[l]), coord(&tcs[r]));
+            let (wl, wr) = if pl == pr {
+                (0.5, 0.5)
+            } else {
+                let wr = (p - pl) as f32 / (pr - pl) as f32;
+                (1. - wr, wr)
+            };
+            let filled = &t2d.row(l) * wl + &t2d.row(r) * wr;
+            t2d.row_mut(i).assign(&filled);
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize, Copy, Clone)]
+pub enum InterpMethod {
+    Horizontal,
+    HorizontalExtra,
+    Vertical,
+    VerticalExtra,
+    /// 沿热电偶连线的自然三次样条，避免分段线性插值在热电偶处的折角
+    HorizontalSpline,
+    VerticalSpline,
+    Bilinear((usize, usize)),
+    BilinearExtra((usize, usize)),
+    /// 反距离加权，适用于任意分布的热电偶
+    Idw {
+        power: f32,
+    },
+    /// 薄板样条，适用于任意分布的热电偶，插值结果光滑
+    ThinPlateSpline,
+}
+
+impl Default for InterpMethod {
+    fn default() -> Self {
+        InterpMethod::Horizontal
+    }
+}
+
+use InterpMethod::*;
+
+impl InterpMethod {
+    /// 根据热电偶位置推断插值方法：一行为水平插值，一列为竖直插值，规则网格为双线性插值，
+    /// 其余为薄板样条（不足三个时为反距离加权）；是否外插、是否用样条沿用`self`的选择
+    pub fn infer(self, positions: &[(i32, i32)]) -> InterpMethod {
+        let extra = matches!(self, HorizontalExtra | VerticalExtra | BilinearExtra(_));
+        let spline = matches!(self, HorizontalSpline | VerticalSpline);
+        match grid_shape(positions) {
+            Some((1, w)) if w >= 2 => match (extra, spline) {
+                (true, _) => HorizontalExtra,
+                (_, true) => HorizontalSpline,
+                _ => Horizontal,
+            },
+            Some((h, 1)) if h >= 2 => match (extra, spline) {
+                (true, _) => VerticalExtra,
+                (_, true) => VerticalSpline,
+                _ => Vertical,
+            },
+            Some(shape) if shape.0 >= 2 && shape.1 >= 2 => {
+                if extra {
+                    BilinearExtra(shape)
+                } else {
+                    Bilinear(shape)
+                }
+            }
+            _ => match self {
+                Idw { .. } | ThinPlateSpline => self,
+                _ if positions.len() >= 3 => ThinPlateSpline,
+                _ => Idw { power: 2. },
+            },
+        }
+    }
+}
+
+/// 热电偶位置构成规则网格（同一行y相同、同一列x相同，每个交点恰有一个热电偶）时返回（行数，列数）
+pub(crate) fn grid_shape(positions: &[(i32, i32)]) -> Option<(usize, usize)> {
+    let distinct = |coord: fn(&(i32, i32)) -> i32| {
+        let mut coords: Vec<_> = positions.iter().map(coord).collect();
+        coords.sort_unstable();
+        coords.dedup();
+        coords.len()
+    };
+    let (h, w) = (distinct(|p| p.0), distinct(|p| p.1));
+    let mut unique = positions.to_vec();
+    unique.sort_unstable();
+    unique.dedup();
+
+    (h * w == positions.len() && unique.len() == positions.len()).then(|| (h, w))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum Interp {
+    /// 完整的参考温度矩阵
+    Eager(Array2<f32>),
+    /// 只存储热电偶温度与各插值点对应的权重，用到时再计算，以少量计算换取内存
+    Lazy {
+        t2d: Array2<f32>,
+        weights: Vec<Vec<(usize, f32)>>,
+    },
+}
+
+impl Interp {
+    /// `active`标记各热电偶是否可用：
+    /// 一维插值直接跳过不可用的热电偶；
+    /// 双线性插值需要完整网格，不可用节点由同一行（或同一列）相邻的可用节点线性插值补齐
+    fn new(
+        t2d: ArrayView2<f32>,
+        interp_method: InterpMethod,
+        thermocouples: &[Thermocouple],
+        active: &[bool],
+        top_left_pos: (usize, usize),
+        region_shape: (usize, usize),
+        lazy: bool,
+    ) -> TLCResult<Self> {
+        let (t2d, tcs) = match interp_method {
+            Bilinear(tc_shape) | BilinearExtra(tc_shape) => {
+                let mut t2d = t2d.to_owned();
+                fill_grid_gaps(&mut t2d, thermocouples, active, tc_shape)?;
+                (t2d, thermocouples.to_vec())
+            }
+            _ => {
+                let rows: Vec<_> = (0..thermocouples.len()).filter(|&i| active[i]).collect();
+                if rows.len() < 2 {
+                    return Err(awsl!(HandleError, "可用热电偶少于两个，无法插值"));
+                }
+                let tcs = rows.iter().map(|&i| thermocouples[i].clone()).collect();
+                (t2d.select(Axis(0), &rows), tcs)
+            }
+        };
+
+        if lazy {
+            let weights = Self::weights(interp_method, region_shape, &tcs, top_left_pos)?;
+            return Ok(Interp::Lazy { t2d, weights });
+        }
+
+        let t2d = t2d.view();
+        match interp_method {
+            Bilinear(_) | BilinearExtra(_) => {
+                Self::interp_bilinear(t2d, interp_method, region_shape, &tcs, top_left_pos)
+            }
+            Idw { power } => Self::interp_idw(t2d, power, region_shape, &tcs, top_left_pos),
+            ThinPlateSpline => {
+                return Self::interp_thin_plate_spline(t2d, region_shape, &tcs, top_left_pos);
+            }
+            HorizontalSpline | VerticalSpline => {
+                Self::interp_spline(t2d, interp_method, region_shape, &tcs, top_left_pos)
+            }
+            _ => Self::interp1d(t2d, interp_method, region_shape, &tcs, top_left_pos),
+        }
+        .ok_or(awsl!("参考温度插值错误"))
+    }
+
+    /// 依次去掉每个可用的热电偶，用其余热电偶插值出该位置的温度，与实测温度比较
+    fn validate(
+        t2d: ArrayView2<f32>,
+        interp_method: InterpMethod,
+        thermocouples: &[Thermocouple],
+        active: &[bool],
+        top_left_pos: (usize, usize),
+        region_shape: (usize, usize),
+    ) -> Vec<f32> {
+        let (cal_h, cal_w) = region_shape;
+        let rms = |i: usize| -> Option<f32> {
+            let y = thermocouples[i].pos.0 - top_left_pos.0 as i32;
+            let x = thermocouples[i].pos.1 - top_left_pos.1 as i32;
+            if !active[i] || y < 0 || x < 0 || y >= cal_h as i32 || x >= cal_w as i32 {
+                return None;
+            }
+            let mut others = active.to_vec();
+            others[i] = false;
+            // 只需要取单点，不必生成完整的参考温度矩阵
+            let interp = Interp::new(
+                t2d,
+                interp_method,
+                thermocouples,
+                &others,
+                top_left_pos,
+                region_shape,
+                true,
+            )
+            .ok()?;
+            let temps = interp.interp_single_point(y as usize * cal_w + x as usize, region_shape);
+            let mse = (&temps - &t2d.row(i)).mapv(|d| d * d).mean()?;
+
+            Some(mse.sqrt())
+        };
+
+        (0..thermocouples.len())
+            .map(|i| rms(i).unwrap_or(f32::NAN))
+            .collect()
+    }
+
+    /// 各插值点对应的热电偶序号与权重
+    fn weights(
+        interp_method: InterpMethod,
+        region_shape: (usize, usize),
+        tcs: &[Thermocouple],
+        tl_pos: (usize, usize),
+    ) -> TLCResult<Vec<Vec<(usize, f32)>>> {
+        let (cal_h, cal_w) = region_shape;
+        let sparse = |weights: Vec<f32>| -> Vec<(usize, f32)> {
+            weights
+                .into_iter()
+                .enumerate()
+                .filter(|&(_, w)| w != 0.)
+                .collect()
+        };
+        let weights = match interp_method {
+            Horizontal | HorizontalExtra => (0..cal_w)
+                .into_par_iter()
+                .map(Self::linear_weights(interp_method, tcs, tl_pos))
+                .collect(),
+            Vertical | VerticalExtra => (0..cal_h)
+                .into_par_iter()
+                .map(Self::linear_weights(interp_method, tcs, tl_pos))
+                .collect(),
+            Bilinear(_) | BilinearExtra(_) => (0..cal_h * cal_w)
+                .into_par_iter()
+                .map(Self::bilinear_weights(
+                    interp_method,
+                    region_shape,
+                    tcs,
+                    tl_pos,
+                ))
+                .collect(),
+            Idw { power } => (0..cal_h * cal_w)
+                .into_par_iter()
+                .map(Self::idw_weights(power, region_shape, tcs, tl_pos))
+                .map(sparse)
+                .collect(),
+            ThinPlateSpline => (0..cal_h * cal_w)
+                .into_par_iter()
+                .map(Self::thin_plate_spline_weights(region_shape, tcs, tl_pos)?)
+                .map(sparse)
+                .collect(),
+            HorizontalSpline | VerticalSpline => {
+                let (interp_len, weights_at) =
+                    Self::spline_weights(interp_method, region_shape, tcs, tl_pos)
+                        .ok_or(awsl!("参考温度插值错误"))?;
+                (0..interp_len)
+                    .into_par_iter()
+                    .map(weights_at)
+                    .map(sparse)
+                    .collect()
+            }
+        };
+
+        Ok(weights)
+    }
+
+    fn interp_len(&self) -> usize {
+        match self {
+            Interp::Eager(temps) => temps.nrows(),
+            Interp::Lazy { weights, .. } => weights.len(),
+        }
+    }
+
+    pub fn interp_single_point(
+        &self,
+        pos: usize,
+        region_shape: (usize, usize),
+    ) -> CowArray<f32, Ix1> {
+        let (cal_h, cal_w) = region_shape;
+        let pos = match self.interp_len() {
+            h if h == cal_w => pos % cal_w,
+            h if h == cal_h => pos / cal_w,
+            _ => pos,
+        };
+
+        match self {
+            Interp::Eager(temps) => temps.row(pos).into(),
+            Interp::Lazy { t2d, weights } => {
+                let mut temps = Array1::zeros(t2d.ncols());
+                for &(i, w) in &weights[pos] {
+                    temps.scaled_add(w, &t2d.row(i));
+                }
+                temps.into()
+            }
+        }
+    }
+
+    /// 单帧完整分辨率的参考温度场
+    pub fn frame_field(
+        &self,
+        frame: usize,
+        region_shape: (usize, usize),
+    ) -> TLCResult<Array2<f32>> {
+        let (cal_h, cal_w) = region_shape;
+        let col = match self {
+            Interp::Eager(temps) => temps.column(frame).to_owned(),
+            Interp::Lazy { t2d, weights } => weights
+                .iter()
+                .map(|ws| ws.iter().map(|&(i, w)| t2d[(i, frame)] * w).sum())
+                .collect(),
+        };
+        let single_frame = match self.interp_len() {
+            h if h == cal_w => col
+                .broadcast((cal_h, cal_w))
+                .ok_or(awsl!("参考温度矩阵形状转换失败"))?
+                .to_owned(),
+            h if h == cal_h => col
+                .to_owned()
+                .into_shape((cal_h, 1))
+                .map_err(|err| awsl!(err))?
+                .broadcast((cal_h, cal_w))
+                .ok_or(awsl!("参考温度矩阵形状转换失败"))?
+                .to_owned(),
+            _ => col
+                .to_owned()
+                .into_shape(region_shape)
+                .map_err(|err| awsl!(err))?
+                .to_owned(),
+        };
+
+        Ok(single_frame)
+    }
+
+    fn interp_single_frame(
+        &self,
+        frame: usize,
+        region_shape: (usize, usize),
+    ) -> TLCResult<Array2<f32>> {
+        let (cal_h, cal_w) = region_shape;
+        let single_frame = self.frame_field(frame, region_shape)?;
+        let arr: Vec<f32> = single_frame
+            .exact_chunks((SCALING, SCALING))
+            .into_iter()
+            .map(|a| a.mean().unwrap())
+            .collect();
+        let mut single_frame = Array2::from_shape_vec((cal_h / SCALING, cal_w / SCALING), arr)
+            .map_err(|err| awsl!(err))?;
+        single_frame.invert_axis(Axis(0));
+
+        Ok(single_frame)
+    }
+
+    fn interp1d(
+        t2d: ArrayView2<f32>,
+        interp_method: InterpMethod,
+        region_shape: (usize, usize),
+        tcs: &[Thermocouple],
+        tl_pos: (usize, usize),
+    ) -> Option<Interp> {
+        let (cal_h, cal_w) = region_shape;
+        let frame_num = t2d.ncols();
+
+        let (interp_len, tc_pos): (_, Vec<_>) = match interp_method {
+            Horizontal | HorizontalExtra => (
+                cal_w,
+                tcs.iter().map(|tc| tc.pos.1 - tl_pos.1 as i32).collect(),
+            ),
+            Vertical | VerticalExtra => (
+                cal_h,
+                tcs.iter().map(|tc| tc.pos.0 - tl_pos.0 as i32).collect(),
+            ),
+            _ => unreachable!(),
+        };
+
+        let do_extra = match interp_method {
+            HorizontalExtra | VerticalExtra => true,
+            _ => false,
+        };
+
+        let mut temps = Array2::zeros((interp_len, frame_num));
+
+        temps
+            .axis_iter_mut(Axis(0))
+            .into_par_iter()
+            .zip(0..interp_len)
+            .try_for_each(|(mut row, pos)| -> Option<()> {
+                let pos = pos as i32;
+                let (mut li, mut ri) = (0, 1);
+                while pos >= tc_pos[ri] && ri < tc_pos.len() - 1 {
+                    li += 1;
+                    ri += 1;
+                }
+                let (l, r) = (tc_pos[li], tc_pos[ri]);
+                let (l_temps, r_temps) = (t2d.row(li), t2d.row(ri));
+                let l_temps = l_temps.as_slice_memory_order()?;
+                let r_temps = r_temps.as_slice_memory_order()?;
+
+                let pos = if do_extra { pos } else { pos.max(l).min(r) };
+
+                let row = row.as_slice_memory_order_mut()?;
+                let mut frame = 0;
+                while frame + f32x8::lanes() < frame_num {
+                    let lv = f32x8::from_slice_unaligned(&l_temps[frame..]);
+                    let rv = f32x8::from_slice_unaligned(&r_temps[frame..]);
+                    let v8 = (lv * (r - pos) as f32 + rv * (pos - l) as f32) / (r - l) as f32;
+                    v8.write_to_slice_unaligned(&mut row[frame..]);
+                    frame += f32x8::lanes();
+                }
+                while frame < frame_num {
+                    let (lv, rv) = (l_temps[frame], r_temps[frame]);
+                    row[frame] = (lv * (r - pos) as f32 + rv * (pos - l) as f32) / (r - l) as f32;
+                    frame += 1;
+                }
+
+                Some(())
+            })?;
+
+        Some(Interp::Eager(temps))
+    }
+
+    /// 与interp1d相同的一维线性插值，只给出权重
+    fn linear_weights(
+        interp_method: InterpMethod,
+        tcs: &[Thermocouple],
+        tl_pos: (usize, usize),
+    ) -> impl Fn(usize) -> Vec<(usize, f32)> + Sync + Send {
+        let (tc_pos, do_extra): (Vec<_>, _) = match interp_method {
+            Horizontal => (
+                tcs.iter().map(|tc| tc.pos.1 - tl_pos.1 as i32).collect(),
+                false,
+            ),
+            HorizontalExtra => (
+                tcs.iter().map(|tc| tc.pos.1 - tl_pos.1 as i32).collect(),
+                true,
+            ),
+            Vertical => (
+                tcs.iter().map(|tc| tc.pos.0 - tl_pos.0 as i32).collect(),
+                false,
+            ),
+            VerticalExtra => (
+                tcs.iter().map(|tc| tc.pos.0 - tl_pos.0 as i32).collect(),
+                true,
+            ),
+            _ => unreachable!(),
+        };
+
+        move |pos| {
+            let pos = pos as i32;
+            let (mut li, mut ri) = (0, 1);
+            while pos >= tc_pos[ri] && ri < tc_pos.len() - 1 {
+                li += 1;
+                ri += 1;
+            }
+            let (l, r) = (tc_pos[li], tc_pos[ri]);
+            let pos = if do_extra { pos } else { pos.max(l).min(r) };
+            let d = (r - l) as f32;
+
+            vec![(li, (r - pos) as f32 / d), (ri, (pos - l) as f32 / d)]
+        }
+    }
+
+    /// 与interp_bilinear相同的双线性插值，只给出权重
+    fn bilinear_weights(
+        interp_method: InterpMethod,
+        region_shape: (usize, usize),
+        tcs: &[Thermocouple],
+        tl_pos: (usize, usize),
+    ) -> impl Fn(usize) -> Vec<(usize, f32)> + Sync + Send {
+        let (tc_shape, do_extra) = match interp_method {
+            Bilinear(tc_shape) => (tc_shape, false),
+            BilinearExtra(tc_shape) => (tc_shape, true),
+            _ => unreachable!(),
+        };
+        let (tc_h, tc_w) = tc_shape;
+        let tc_x: Vec<_> = tcs
+            .iter()
+            .take(tc_w)
+            .map(|tc| tc.pos.1 - tl_pos.1 as i32)
+            .collect();
+        let tc_y: Vec<_> = tcs
+            .iter()
+            .step_by(tc_w)
+            .take(tc_h)
+            .map(|tc| tc.pos.0 - tl_pos.0 as i32)
+            .collect();
+        let cal_w = region_shape.1;
+
+        move |pos| {
+            let x = (pos % cal_w) as i32;
+            let y = (pos / cal_w) as i32;
+            let (mut yi0, mut yi1) = (0, 1);
+            while y >= tc_y[yi1] && yi1 < tc_h - 1 {
+                yi0 += 1;
+                yi1 += 1;
+            }
+            let (mut xi0, mut xi1) = (0, 1);
+            while x >= tc_x[xi1] && xi1 < tc_w - 1 {
+                xi0 += 1;
+                xi1 += 1;
+            }
+            let (x0, x1, y0, y1) = (tc_x[xi0], tc_x[xi1], tc_y[yi0], tc_y[yi1]);
+            let x = if do_extra { x } else { x.max(x0).min(x1) };
+            let y = if do_extra { y } else { y.max(y0).min(y1) };
+            let area = ((x1 - x0) * (y1 - y0)) as f32;
+
+            vec![
+                (tc_w * yi0 + xi0, ((x1 - x) * (y1 - y)) as f32 / area),
+                (tc_w * yi0 + xi1, ((x - x0) * (y1 - y)) as f32 / area),
+                (tc_w * yi1 + xi0, ((x1 - x) * (y - y0)) as f32 / area),
+                (tc_w * yi1 + xi1, ((x - x0) * (y - y0)) as f32 / area),
+            ]
+        }
+    }
+
+    /// 自然三次样条插值，两端以外取端点温度
+    fn interp_spline(
+        t2d: ArrayView2<f32>,
+        interp_method: InterpMethod,
+        region_shape: (usize, usize),
+        tcs: &[Thermocouple],
+        tl_pos: (usize, usize),
+    ) -> Option<Interp> {
+        let (interp_len, weights_at) =
+            Self::spline_weights(interp_method, region_shape, tcs, tl_pos)?;
+        Self::interp_weighted(t2d, interp_len, weights_at)
+    }
+
+    /// 样条插值结果对热电偶温度是线性的，因此先对单位向量求出各点的权重
+    fn spline_weights(
+        interp_method: InterpMethod,
+        region_shape: (usize, usize),
+        tcs: &[Thermocouple],
+        tl_pos: (usize, usize),
+    ) -> Option<(usize, impl Fn(usize) -> Vec<f32> + Sync + Send)> {
+        let (cal_h, cal_w) = region_shape;
+        let (interp_len, tc_pos): (_, Vec<_>) = match interp_method {
+            HorizontalSpline => (
+                cal_w,
+                tcs.iter()
+                    .map(|tc| (tc.pos.1 - tl_pos.1 as i32) as f32)
+                    .collect(),
+            ),
+            VerticalSpline => (
+                cal_h,
+                tcs.iter()
+                    .map(|tc| (tc.pos.0 - tl_pos.0 as i32) as f32)
+                    .collect(),
+            ),
+            _ => unreachable!(),
+        };
+        let n = tc_pos.len();
+        if tc_pos.windows(2).any(|w| w[1] <= w[0]) {
+            return None;
+        }
+
+        // second_derivs[j]为第j个热电偶取1、其余取0时的二阶导数
+        let second_derivs: Vec<_> = (0..n)
+            .map(|j| {
+                let y: Vec<_> = (0..n).map(|i| if i == j { 1. } else { 0. }).collect();
+                natural_spline_second_derivs(&tc_pos, &y)
+            })
+            .collect();
+
+        let weights_at = move |pos: usize| -> Vec<f32> {
+            let x = (pos as f32).max(tc_pos[0]).min(tc_pos[n - 1]);
+            let mut k = 0;
+            while k < n - 2 && x > tc_pos[k + 1] {
+                k += 1;
+            }
+            let h = tc_pos[k + 1] - tc_pos[k];
+            let a = (tc_pos[k + 1] - x) / h;
+            let b = (x - tc_pos[k]) / h;
+            (0..n)
+                .map(|j| {
+                    let m = &second_derivs[j];
+                    let y = |i| if i == j { 1. } else { 0. };
+                    a * y(k)
+                        + b * y(k + 1)
+                        + ((a.powi(3) - a) * m[k] + (b.powi(3) - b) * m[k + 1]) * h * h / 6.
+                })
+                .collect()
+        };
+
+        Some((interp_len, weights_at))
+    }
+
+    fn interp_idw(
+        t2d: ArrayView2<f32>,
+        power: f32,
+        region_shape: (usize, usize),
+        tcs: &[Thermocouple],
+        tl_pos: (usize, usize),
+    ) -> Option<Interp> {
+        let weights_at = Self::idw_weights(power, region_shape, tcs, tl_pos);
+        Self::interp_weighted(t2d, region_shape.0 * region_shape.1, weights_at)
+    }
+
+    fn idw_weights(
+        power: f32,
+        region_shape: (usize, usize),
+        tcs: &[Thermocouple],
+        tl_pos: (usize, usize),
+    ) -> impl Fn(usize) -> Vec<f32> + Sync + Send {
+        let tc_pos = Self::relative_pos(tcs, tl_pos);
+
+        move |pos| {
+            let y = (pos / region_shape.1) as f32;
+            let x = (pos % region_shape.1) as f32;
+            let mut weights: Vec<_> = tc_pos
+                .iter()
+                .map(|&(ty, tx)| {
+                    let d2 = (ty - y).powi(2) + (tx - x).powi(2);
+                    if d2 == 0. {
+                        f32::INFINITY
+                    } else {
+                        d2.powf(-power / 2.)
+                    }
+                })
+                .collect();
+            // 与热电偶重合的点直接取该热电偶温度
+            if let Some(i) = weights.iter().position(|w| w.is_infinite()) {
+                weights.iter_mut().for_each(|w| *w = 0.);
+                weights[i] = 1.;
+            }
+            let sum: f32 = weights.iter().sum();
+            weights.iter_mut().for_each(|w| *w /= sum);
+            weights
+        }
+    }
+
+    /// 薄板样条插值
+    /// 系数矩阵只与热电偶位置有关，因此只求一次逆，
+    /// 每一帧的插值都可以写成各热电偶温度的线性组合
+    fn interp_thin_plate_spline(
+        t2d: ArrayView2<f32>,
+        region_shape: (usize, usize),
+        tcs: &[Thermocouple],
+        tl_pos: (usize, usize),
+    ) -> TLCResult<Interp> {
+        let weights_at = Self::thin_plate_spline_weights(region_shape, tcs, tl_pos)?;
+        Self::interp_weighted(t2d, region_shape.0 * region_shape.1, weights_at).ok_or(awsl!())
+    }
+
+    fn thin_plate_spline_weights(
+        region_shape: (usize, usize),
+        tcs: &[Thermocouple],
+        tl_pos: (usize, usize),
+    ) -> TLCResult<impl Fn(usize) -> Vec<f32> + Sync + Send> {
+        let tc_pos: Vec<_> = Self::relative_pos(tcs, tl_pos)
+            .into_iter()
+            .map(|(y, x)| (y as f64, x as f64))
+            .collect();
+        let n = tc_pos.len();
+        if n < 3 {
+            return Err(awsl!(HandleError, "薄板样条插值至少需要三个热电偶"));
+        }
+
+        let kernel = |d2: f64| if d2 == 0. { 0. } else { d2 * d2.ln() / 2. };
+        let mut a = Array2::zeros((n + 3, n + 3));
+        for (i, &(yi, xi)) in tc_pos.iter().enumerate() {
+            for (j, &(yj, xj)) in tc_pos.iter().enumerate() {
+                a[(i, j)] = kernel((yi - yj).powi(2) + (xi - xj).powi(2));
+            }
+            for (j, v) in [1., yi, xi].iter().enumerate() {
+                a[(i, n + j)] = *v;
+                a[(n + j, i)] = *v;
+            }
+        }
+        let a_inv = invert(a).ok_or(awsl!(HandleError, "热电偶共线，无法进行薄板样条插值"))?;
+
+        Ok(move |pos| {
+            let y = (pos / region_shape.1) as f64;
+            let x = (pos % region_shape.1) as f64;
+            let b: Vec<_> = tc_pos
+                .iter()
+                .map(|&(ty, tx)| kernel((ty - y).powi(2) + (tx - x).powi(2)))
+                .chain([1., y, x].iter().copied())
+                .collect();
+            // 系数矩阵对称，插值权重即 A^-1 * b 的前n项
+            (0..n)
+                .map(|i| {
+                    a_inv
+                        .row(i)
+                        .iter()
+                        .zip(b.iter())
+                        .map(|(a, b)| a * b)
+                        .sum::<f64>() as f32
+                })
+                .collect()
+        })
+    }
+
+    fn relative_pos(tcs: &[Thermocouple], tl_pos: (usize, usize)) -> Vec<(f32, f32)> {
+        tcs.iter()
+            .map(|tc| {
+                (
+                    (tc.pos.0 - tl_pos.0 as i32) as f32,
+                    (tc.pos.1 - tl_pos.1 as i32) as f32,
+                )
+            })
+            .collect()
+    }
+
+    /// 每个插值点的温度为各热电偶温度的加权和，第pos个点的权重由weights_at(pos)给出
+    fn interp_weighted<F>(t2d: ArrayView2<f32>, interp_len: usize, weights_at: F) -> Option<Interp>
+    where
+        F: Fn(usize) -> Vec<f32> + Sync,
+    {
+        let frame_num = t2d.ncols();
+        let mut temps = Array2::zeros((interp_len, frame_num));
+
+        temps
+            .axis_iter_mut(Axis(0))
+            .into_par_iter()
+            .zip(0..interp_len)
+            .try_for_each(|(mut row, pos)| -> Option<()> {
+                let weights = weights_at(pos);
+                let row = row.as_slice_memory_order_mut()?;
+                for (tc_temps, &w) in t2d.axis_iter(Axis(0)).zip(weights.iter()) {
+                    let tc_temps = tc_temps.to_slice()?;
+                    let mut frame = 0;
+                    while frame + f32x8::lanes() < frame_num {
+                        let v8 = f32x8::from_slice_unaligned(&tc_temps[frame..]) * w
+                            + f32x8::from_slice_unaligned(&row[frame..]);
+                        v8.write_to_slice_unaligned(&mut row[frame..]);
+                        frame += f32x8::lanes();
+                    }
+                    while frame < frame_num {
+                        row[frame] += tc_temps[frame] * w;
+                        frame += 1;
+                    }
+                }
+
+                Some(())
+            })?;
+
+        Some(Interp::Eager(temps))
+    }
+
+    fn interp_bilinear(
+        t2d: ArrayView2<f32>,
+        interp_method: InterpMethod,
+        region_shape: (usize, usize),
+        tcs: &[Thermocouple],
+        tl_pos: (usize, usize),
+    ) -> Option<Interp> {
+        let (tc_shape, do_extra) = match interp_method {
+            Bilinear(tc_shape) => (tc_shape, false),
+            BilinearExtra(tc_shape) => (tc_shape, true),
+            _ => unreachable!(),
+        };
+        let (tc_h, tc_w) = tc_shape;
+        let tc_x: Vec<_> = tcs
+            .iter()
+            .take(tc_w)
+            .map(|tc| tc.pos.1 - tl_pos.1 as i32)
+            .collect();
+        let tc_y: Vec<_> = tcs
+            .iter()
+            .step_by(tc_w)
+            .take(tc_h)
+            .map(|tc| tc.pos.0 - tl_pos.0 as i32)
+            .collect();
+
+        let (cal_h, cal_w) = region_shape;
+        let frame_num = t2d.ncols();
+        let pix_num = cal_h * cal_w;
+        let mut temps = Array2::zeros((pix_num, frame_num));
+
+        temps
+            .axis_iter_mut(Axis(0))
+            .into_par_iter()
+            .zip(0..pix_num)
+            .try_for_each(|(mut row, pos)| -> Option<()> {
+                let x = (pos % cal_w) as i32;
+                let y = (pos / cal_w) as i32;
+                let (mut yi0, mut yi1) = (0, 1);
+                while y >= tc_y[yi1] && yi1 < tc_h - 1 {
+                    yi0 += 1;
+                    yi1 += 1;
+                }
+                let (mut xi0, mut xi1) = (0, 1);
+                while x >= tc_x[xi1] && xi1 < tc_w - 1 {
+                    xi0 += 1;
+                    xi1 += 1;
+                }
+                let (x0, x1, y0, y1) = (tc_x[xi0], tc_x[xi1], tc_y[yi0], tc_y[yi1]);
+                let t00 = t2d.row(tc_w * yi0 + xi0);
+                let t01 = t2d.row(tc_w * yi0 + xi1);
+                let t10 = t2d.row(tc_w * yi1 + xi0);
+                let t11 = t2d.row(tc_w * yi1 + xi1);
+                let t00 = t00.as_slice_memory_order()?;
+                let t01 = t01.as_slice_memory_order()?;
+                let t10 = t10.as_slice_memory_order()?;
+                let t11 = t11.as_slice_memory_order()?;
+
+                let x = if do_extra { x } else { x.max(x0).min(x1) };
+                let y = if do_extra { y } else { y.max(y0).min(y1) };
+
+                let row = row.as_slice_memory_order_mut()?;
+                let mut frame = 0;
+                while frame + f32x8::lanes() < frame_num {
+                    let v00 = f32x8::from_slice_unaligned(&t00[frame..]);
+                    let v01 = f32x8::from_slice_unaligned(&t01[frame..]);
+                    let v10 = f32x8::from_slice_unaligned(&t10[frame..]);
+                    let v11 = f32x8::from_slice_unaligned(&t11[frame..]);
+                    let v8 = (v00 * (x1 - x) as f32 * (y1 - y) as f32
+                        + v01 * (x - x0) as f32 * (y1 - y) as f32
+                        + v10 * (x1 - x) as f32 * (y - y0) as f32
+                        + v11 * (x - x0) as f32 * (y - y0) as f32)
+                        / (x1 - x0) as f32
+                        / (y1 - y0) as f32;
+                    v8.write_to_slice_unaligned(&mut row[frame..]);
+                    frame += f32x8::lanes();
+                }
+                while frame < frame_num {
+                    let v00 = t00[frame];
+                    let v01 = t01[frame];
+                    let v10 = t10[frame];
+                    let v11 = t11[frame];
+                    row[frame] = (v00 * (x1 - x) as f32 * (y1 - y) as f32
+                        + v01 * (x - x0) as f32 * (y1 - y) as f32
+                        + v10 * (x1 - x) as f32 * (y - y0) as f32
+                        + v11 * (x - x0) as f32 * (y - y0) as f32)
+                        / (x1 - x0) as f32
+                        / (y1 - y0) as f32;
+                    frame += 1;
+                }
+
+                Some(())
+            })?;
+
+        Some(Interp::Eager(temps))
+    }
+}
+
+/// 自然三次样条各节点处的二阶导数，追赶法求解三对角方程组
+fn natural_spline_second_derivs(x: &[f32], y: &[f32]) -> Vec<f32> {
+    let n = x.len();
+    let mut m = vec![0.; n];
+    if n < 3 {
+        return m;
+    }
+    let (mut c, mut d) = (vec![0.; n], vec![0.; n]);
+    for i in 1..n - 1 {
+        let (h0, h1) = (x[i] - x[i - 1], x[i + 1] - x[i]);
+        let rhs = 6. * ((y[i + 1] - y[i]) / h1 - (y[i] - y[i - 1]) / h0);
+        let diag = 2. * (h0 + h1) - h0 * c[i - 1];
+        c[i] = h1 / diag;
+        d[i] = (rhs - h0 * d[i - 1]) / diag;
+    }
+    for i in (1..n - 1).rev() {
+        m[i] = d[i] - c[i] * m[i + 1];
+    }
+
+    m
+}
+
+/// 部分主元高斯-约当消元求逆，矩阵奇异时返回None
+fn invert(mut a: Array2<f64>) -> Option<Array2<f64>> {
+    let n = a.nrows();
+    let mut inv = Array2::eye(n);
+    for col in 0..n {
+        let pivot =
+            (col..n).max_by(|&i, &j| a[(i, col)].abs().partial_cmp(&a[(j, col)].abs()).unwrap())?;
+        if a[(pivot, col)].abs() < 1e-10 {
+            return None;
+        }
+        for m in [&mut a, &mut inv].iter_mut() {
+            for j in 0..n {
+                m.swap((col, j), (pivot, j));
+            }
+        }
+        let p = a[(col, col)];
+        a.row_mut(col).mapv_inplace(|v| v / p);
+        inv.row_mut(col).mapv_inplace(|v| v / p);
+        for row in 0..n {
+            let factor = a[(row, col)];
+            if row == col || factor == 0. {
+                continue;
+            }
+            for j in 0..n {
+                a[(row, j)] -= factor * a[(col, j)];
+                inv[(row, j)] -= factor * inv[(col, j)];
+            }
+        }
+    }
+
+    Some(inv)
+}
+
+#[cfg(test)]
+mod test {
+    use ndarray::prelude::*;
+
+    use super::{
+        resample_at, DAQResample, DriftCorrection, FilterMethod, Interp, InterpMethod::*,
+        PeakMethod, TCType, TLCData, TempUnit, Thermocouple,
+    };
+    use crate::postprocess;
+
+    #[test]
+    fn resample_daq_rows() {
+        let col = array![0., 10., 20., 30.];
+        let at = |row_pos, resample| resample_at(col.view(), row_pos, resample);
+        // 数采频率为帧率的2.4倍时各帧落在非整数行
+        assert_eq!(at(2.4, DAQResample::No), 20.);
+        assert!((at(2.4, DAQResample::Linear) - 24.).abs() < 1e-4);
+        assert!((at(1.5, DAQResample::Cubic) - 15.).abs() < 1e-4);
+        // 首末行
+        for &resample in &[DAQResample::No, DAQResample::Linear, DAQResample::Cubic] {
+            assert_eq!(at(0., resample), 0.);
+            assert_eq!(at(3., resample), 30.);
+            // 超出末行时取末行
+            assert_eq!(at(3.4, resample), 30.);
+        }
+        let first = at(0.5, DAQResample::Cubic);
+        assert!(first > 0. && first < 10.);
+        let last = at(2.5, DAQResample::Cubic);
+        assert!(last > 20. && last < 30.);
+    }
+
+    #[test]
+    fn interp_idw() {
+        let t2d = array![[1., 2.], [3., 4.], [5., 6.]];
+        let tcs: Vec<Thermocouple> = [(0, 0), (0, 4), (4, 0)]
+            .iter()
+            .map(|&pos| Thermocouple {
+                pos,
+                ..Default::default()
+            })
+            .collect();
+        let region_shape = (5, 5);
+
+        let interp = Interp::interp_idw(t2d.view(), 2., region_shape, &tcs, (0, 0)).unwrap();
+        let point = |pos| interp.interp_single_point(pos, region_shape).to_vec();
+        // 热电偶所在位置严格等于其温度
+        assert_eq!(point(0), vec![1., 2.]);
+        assert_eq!(point(4), vec![3., 4.]);
+        assert_eq!(point(20), vec![5., 6.]);
+        // 其余位置在最小值与最大值之间
+        assert!((0..25).all(|pos| (1. ..=5.).contains(&point(pos)[0])));
+    }
+
+    #[test]
+    fn interp_thin_plate_spline() {
+        let t2d = array![[1.], [3.], [5.], [7.]];
+        let tcs: Vec<Thermocouple> = [(0, 0), (0, 4), (4, 0), (4, 4)]
+            .iter()
+            .map(|&pos| Thermocouple {
+                pos,
+                ..Default::default()
+            })
+            .collect();
+
+        let interp = Interp::interp_thin_plate_spline(t2d.view(), (5, 5), &tcs, (0, 0)).unwrap();
+        let point = |pos| interp.interp_single_point(pos, (5, 5))[0];
+        for (&pos, &t) in [0, 4, 20, 24].iter().zip([1., 3., 5., 7.].iter()) {
+            assert!((point(pos) - t).abs() < 1e-3);
+        }
+        // 对称布置的热电偶，中心处为平均温度
+        assert!((point(12) - 4.).abs() < 1e-3);
+    }
+
+    #[test]
+    fn interp_spline() {
+        // 节点非等距时自然样条仍能精确还原直线
+        let t2d = array![[1.], [3.], [9.], [11.]];
+        let tcs: Vec<Thermocouple> = [(0, 0), (0, 2), (0, 8), (0, 10)]
+            .iter()
+            .map(|&pos| Thermocouple {
+                pos,
+                ..Default::default()
+            })
+            .collect();
+
+        let interp =
+            Interp::interp_spline(t2d.view(), HorizontalSpline, (1, 11), &tcs, (0, 0)).unwrap();
+        for x in 0..11 {
+            let t = interp.interp_single_point(x, (1, 11))[0];
+            assert!((t - (x as f32 + 1.)).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn interp_bilinear() -> Result<(), Box<dyn std::error::Error>> {
+        let t2d = array![[1.], [2.], [3.], [4.], [5.], [6.]];
+        println!("{:?}", t2d.shape());
+        let interp_method = BilinearExtra((2, 3));
+        let region_shape = (14, 14);
+        let tcs: Vec<Thermocouple> = [(10, 10), (10, 15), (10, 20), (20, 10), (20, 15), (20, 20)]
+            .iter()
+            .map(|&pos| Thermocouple {
+                column_num: 0,
+                pos,
+                ..Default::default()
+            })
+            .collect();
+        let tl_pos = (8, 8);
+
+        let interp =
+            Interp::interp_bilinear(t2d.view(), interp_method, region_shape, &tcs, tl_pos).unwrap();
+
+        let res = interp.interp_single_frame(0, region_shape)?;
+        println!("{:?}", res);
+
+        Ok(())
+    }
+
+    #[test]
+    fn interp_lazy() {
+        let t2d = array![[1., 2.], [2., 3.], [3., 4.], [4., 5.], [5., 6.], [6., 7.]];
+        let region_shape = (14, 14);
+        let tcs: Vec<Thermocouple> = [(10, 10), (10, 15), (10, 20), (20, 10), (20, 15), (20, 20)]
+            .iter()
+            .map(|&pos| Thermocouple {
+                pos,
+                ..Default::default()
+            })
+            .collect();
+        let active = [true; 6];
+        let tl_pos = (8, 8);
+
+        for &interp_method in [
+            Horizontal,
+            VerticalExtra,
+            BilinearExtra((2, 3)),
+            Idw { power: 2. },
+            ThinPlateSpline,
+        ]
+        .iter()
+        {
+            let rows = match interp_method {
+                Horizontal => vec![0, 1, 2],
+                VerticalExtra => vec![0, 3],
+                _ => (0..6).collect(),
+            };
+            let tcs: Vec<_> = rows.iter().map(|&i| tcs[i].clone()).collect();
+            let t2d = t2d.select(Axis(0), &rows);
+            let new = |lazy| {
+                Interp::new(
+                    t2d.view(),
+                    interp_method,
+                    &tcs,
+                    &active,
+                    tl_pos,
+                    region_shape,
+                    lazy,
+                )
+                .unwrap()
+            };
+            let (eager, lazy) = (new(false), new(true));
+            for pos in 0..region_shape.0 * region_shape.1 {
+                let e = eager.interp_single_point(pos, region_shape);
+                let l = lazy.interp_single_point(pos, region_shape);
+                assert!(e.iter().zip(l.iter()).all(|(e, l)| (e - l).abs() < 1e-4));
+            }
+        }
+    }
+
+    #[test]
+    fn infer_interp_method() {
+        let grid = [(0, 0), (0, 10), (0, 20), (8, 20), (8, 0), (8, 10)];
+        assert_eq!(super::grid_shape(&grid), Some((2, 3)));
+        match HorizontalExtra.infer(&grid) {
+            BilinearExtra(shape) => assert_eq!(shape, (2, 3)),
+            method => panic!("{:?}", method),
+        }
+        // 缺一个交点时不是网格
+        let scattered = [(0, 0), (0, 10), (8, 20), (8, 0)];
+        assert_eq!(super::grid_shape(&scattered), None);
+        assert!(matches!(
+            Bilinear((2, 2)).infer(&scattered),
+            ThinPlateSpline
+        ));
+        assert!(matches!(Horizontal.infer(&[(5, 0), (5, 9)]), Horizontal));
+        assert!(matches!(
+            HorizontalSpline.infer(&[(0, 5), (9, 5)]),
+            VerticalSpline
+        ));
+        assert!(matches!(Horizontal.infer(&[(5, 0), (5, 0)]), Idw { .. }));
+    }
+
+    #[test]
+    fn interp_validate() {
+        // 温度沿x线性分布，线性插值的留一误差应为0，两端的热电偶无法内插
+        let t2d = array![[1., 2.], [2., 3.], [3., 4.], [4., 5.]];
+        let tcs: Vec<Thermocouple> = [(0, 0), (0, 2), (0, 4), (0, 6)]
+            .iter()
+            .map(|&pos| Thermocouple {
+                pos,
+                ..Default::default()
+            })
+            .collect();
+        let active = [true, true, false, true];
+
+        let rms = Interp::validate(t2d.view(), Horizontal, &tcs, &active, (0, 0), (1, 7));
+        assert!(rms[1].abs() < 1e-6);
+        assert!(rms[2].is_nan());
+        assert!((rms[3] - 2.).abs() < 1e-6);
+    }
+
+    #[test]
+    fn frame_times_from_video() {
+        let mut config = crate::TLCConfig::from_json(serde_json::json!({})).unwrap();
+        config.start_frame = 10;
+        config.frame_rate = 20;
+        config.frame_num = 3;
+        let mut data = TLCData::from_config(config);
+        let times = data.init_frame_times().unwrap().get_frame_times().unwrap();
+        assert_eq!(times, &vec![0.5, 0.55, 0.6]);
+    }
+
+    #[test]
+    fn filter_in_place() {
+        let raw = Array2::from_shape_fn((40, 4), |(t, pos)| ((t * 7 + pos * 13) % 50) as u8);
+        let filtered: Vec<_> = [false, true]
+            .iter()
+            .map(|&in_place| {
+                let mut config = crate::TLCConfig::from_json(serde_json::json!({})).unwrap();
+                config.region_shape = (2, 2);
+                config.frame_num = 40;
+                config.frame_rate = 25;
+                config.filter_method = super::FilterMethod::Notch { freq: 5., q: 2. };
+                config.filter_in_place = in_place;
+                let mut data = TLCData::from_config(config);
+                data.raw_g2d = Some(raw.clone());
+                data.filtering().unwrap();
+                assert_eq!(data.raw_g2d.is_none(), in_place);
+                data.filtered_g2d.unwrap()
+            })
+            .collect();
+        assert_eq!(filtered[0], filtered[1]);
+    }
+
+    #[test]
+    fn wavelet_boundary() {
+        use super::WaveletBoundary::{Periodic, Symmetric};
+        // 原信号长度为3，从延拓后的第2个点开始
+        let extend = |boundary: super::WaveletBoundary| -> Vec<_> {
+            (0..9).map(|i| boundary.source(i, 2, 3)).collect()
+        };
+        assert_eq!(extend(Symmetric), vec![1, 0, 0, 1, 2, 2, 1, 0, 0]);
+        assert_eq!(extend(Periodic), vec![1, 2, 0, 1, 2, 0, 1, 2, 0]);
+
+        // 延拓后的长度可被2^level整除，两端各留出一个滤波器长度
+        let db8 = super::db8();
+        let (level, extended_len) = super::wavelet_prepare(1001, &db8);
+        assert_eq!(extended_len % (1 << level), 0);
+        assert!(extended_len >= 1001 + 2 * (db8.length - 1));
+    }
+
+    #[test]
+    fn polynomial_drift() {
+        // 线性漂移使最后一帧高于变色峰，去除后峰值帧恢复
+        let g: Array1<u8> = (0..200)
+            .map(|t| {
+                let t = t as f32;
+                (100. + 0.4 * t + 50. * (-((t - 60.) / 5.).powi(2)).exp()).round() as u8
+            })
+            .collect();
+        let g2d = g.clone().into_shape((200, 1)).unwrap();
+        let drift = DriftCorrection::Polynomial(1)
+            .prepare(g2d.view(), (1, 1))
+            .unwrap()
+            .unwrap();
+        let mut corrected = g.clone();
+        drift.remove(corrected.view_mut());
+        let argmax = |g: &Array1<u8>| g.iter().enumerate().max_by_key(|(_, g)| **g).unwrap().0;
+        assert_eq!(argmax(&g), 199);
+        assert_eq!(argmax(&corrected), 60);
+        assert!((corrected[199] as i32 - 100).abs() <= 2);
+    }
+
+    #[test]
+    fn notch_aliased_flicker() {
+        // 30帧/s拍摄100Hz闪烁，混叠为10Hz
+        let bump = |t: f32| 100. + 60. * (-((t - 150.) / 40.).powi(2)).exp();
+        let mut g: Array1<u8> = (0..300)
+            .map(|i| {
+                let t = i as f32;
+                let flicker = 10. * (2. * std::f32::consts::PI * 100. * t / 30.).sin();
+                (bump(t) + flicker).round() as u8
+            })
+            .collect();
+        super::notch_filter(g.view_mut(), super::notch_coefs(100., 2., 30).unwrap());
+        let max_err = (20..280)
+            .map(|i| (g[i] as f32 - bump(i as f32)).abs())
+            .fold(0., f32::max);
+        assert!(max_err < 2., "{}", max_err);
+        assert!(super::notch_coefs(60., 2., 30).is_none());
+    }
+
+    #[test]
+    fn compare_filters() {
+        let case = crate::testing::SyntheticCase::new((4, 5), 400).unwrap();
+        let mut data = case.data().unwrap();
+        let filter_methods = [FilterMethod::No, FilterMethod::Median(10)];
+        let comparison = data.compare_filters(7, &filter_methods).unwrap();
+        let series = &comparison.series;
+        assert_eq!(series.len(), 2);
+        assert_eq!(series[0].green, comparison.unfiltered);
+        assert_eq!(series[0].green, data.filtering_single_point(7).unwrap());
+        assert_eq!(series[1].green.len(), series[0].green.len());
+        let (p0, p1) = (series[0].peak_frame.unwrap(), series[1].peak_frame.unwrap());
+        assert!((p0 as i32 - p1 as i32).abs() <= 3);
+    }
+
+    #[test]
+    fn peak_signal() {
+        let col = array![10u8, 12, 10, 12, 30, 60, 40];
+        let signal = super::PeakSignal::of(col.view(), 5);
+        assert_eq!(signal.baseline, 11.);
+        assert_eq!(signal.amplitude, 49.);
+    }
+
+    #[test]
+    fn reference_normalization() {
+        // 自动增益使整体亮度在第二帧升高25%
+        let mut g = array![80u8, 100, 120];
+        super::normalize_by_reference(g.view_mut(), &[40., 50., 40.]);
+        assert_eq!(g, array![80, 80, 120]);
+    }
+
+    #[test]
+    fn defect_repair() {
+        // 3×3区域，中心像素卡死，右下角像素饱和
+        let mut g2d = Array2::from_shape_fn((4, 9), |(t, pos)| (10 * t + pos) as u8);
+        g2d.column_mut(4).fill(7);
+        g2d.column_mut(8).fill(255);
+        let defects = super::detect_defects(g2d.view(), 3);
+        let found: Vec<_> = (0..9).filter(|&pos| defects[pos]).collect();
+        assert_eq!(found, vec![4, 8]);
+        let neighbors = super::defect_neighbors(4, &defects, (3, 3));
+        assert_eq!(neighbors, vec![0, 1, 2, 3, 5, 6, 7]);
+        let columns: Vec<_> = neighbors
+            .iter()
+            .map(|&n| g2d.column(n).to_owned())
+            .collect();
+        let repaired = super::average_columns(&columns).unwrap();
+        assert_eq!(repaired, array![3, 13, 23, 33]);
+    }
+
+    #[test]
+    fn peak_max_slope() {
+        let g = array![10u8, 10, 12, 20, 40, 60, 70, 74, 75, 74, 70];
+        assert_eq!(PeakMethod::Max.locate(g.view()), Some(8));
+        assert_eq!(PeakMethod::MaxSlope.locate(g.view()), Some(4));
+    }
+
+    #[test]
+    fn thermocouple_voltage() {
+        let t_k = TempUnit::MilliVolt(TCType::K).to_celsius(4.096);
+        let t_t = TempUnit::MilliVolt(TCType::T).to_celsius(4.279);
+        let t_neg = TempUnit::MilliVolt(TCType::K).to_celsius(-0.778);
+        assert!((t_k - 100.).abs() < 0.05, "{}", t_k);
+        assert!((t_t - 100.).abs() < 0.05, "{}", t_t);
+        assert!((t_neg + 20.).abs() < 0.05, "{}", t_neg);
+        assert!((TempUnit::Kelvin.to_celsius(300.) - 26.85).abs() < 1e-3);
+    }
+
+    #[test]
+    fn savgol() {
+        // 5点2阶的经典系数(-3, 12, 17, 12, -3) / 35
+        let coefs = super::savgol_coefs(5, 2);
+        let expected = [-3., 12., 17., 12., -3.];
+        for (c, e) in coefs.iter().zip(expected.iter()) {
+            assert!((c - e / 35.).abs() < 1e-6, "{:?}", coefs);
+        }
+    }
+
+    #[test]
+    fn interp() -> Result<(), Box<dyn std::error::Error>> {
+        const CONFIG_PATH: &str = "./cache/default_config.json";
+        let mut tlc_data = TLCData::from_path(CONFIG_PATH).unwrap();
+        tlc_data.read_daq()?;
+        let t = std::time::Instant::now();
+        tlc_data.interp()?;
+        println!("{:?}", t.elapsed());
+        postprocess::plot_line(
+            tlc_data
+                .get_interp()?
+                .interp_single_point(1000, tlc_data.get_config().region_shape)
+                .view(),
+        )?;
+
+        Ok(())
+    }
+}
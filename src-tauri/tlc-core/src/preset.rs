@@ -0,0 +1,82 @@
+use std::fs::read_dir;
+use std::path::PathBuf;
+
+use super::{error::TLCResult, io::ConfigFormat, paths, TLCConfig, TLCData};
+use crate::awsl;
+
+/// 与具体实验（视频、数采文件）绑定的配置项，保存预设时去掉
+const CASE_FIELDS: [&str; 11] = [
+    "case_name",
+    "video_path",
+    "daq_path",
+    "start_frame",
+    "total_frames",
+    "frame_rate",
+    "start_row",
+    "total_rows",
+    "daq_sample_rate",
+    "frame_num",
+    "video_shape",
+];
+
+fn preset_path(app_dir: &str, name: &str) -> TLCResult<PathBuf> {
+    if name.is_empty() || name.contains(|c| matches!(c, '/' | '\\' | ':' | '.')) {
+        return Err(awsl!(HandleError, format!("预设名称\"{}\"不合法", name)));
+    }
+
+    Ok(paths::preset_dir(app_dir)?
+        .join(name)
+        .with_extension("json"))
+}
+
+/// 已保存的预设名称，按名称排序
+pub fn list_presets(app_dir: &str) -> TLCResult<Vec<String>> {
+    let preset_dir = paths::preset_dir(app_dir)?;
+    let entries = read_dir(&preset_dir).map_err(|err| awsl!(ConfigIOError, err, preset_dir))?;
+    let mut names: Vec<_> = entries
+        .filter_map(|entry| entry.ok()?.path().file_stem()?.to_str().map(str::to_owned))
+        .collect();
+    names.sort();
+
+    Ok(names)
+}
+
+impl TLCConfig {
+    /// 将当前配置中与具体实验无关的部分（区域、热电偶、物性、各种方法等）保存为命名预设
+    pub fn save_preset(&self, name: &str) -> TLCResult<String> {
+        let preset_path = preset_path(&self.app_dir, name)?;
+        let mut json = serde_json::to_value(self).map_err(|err| awsl!(ConfigError, err))?;
+        let preset = json.as_object_mut().ok_or(awsl!())?;
+        for field in CASE_FIELDS.iter() {
+            preset.remove(*field);
+        }
+        let content = ConfigFormat::Json.to_string(&json)?;
+        std::fs::write(&preset_path, content)
+            .map_err(|err| awsl!(ConfigIOError, err, preset_path))?;
+
+        Ok(preset_path.to_str().ok_or(awsl!(preset_path))?.to_owned())
+    }
+
+    /// 以预设为基础新建工况，实验相关的配置项取默认值
+    pub fn from_preset(app_dir: &str, name: &str) -> TLCResult<Self> {
+        TLCConfig::from_path(preset_path(app_dir, name)?)
+    }
+}
+
+impl TLCData {
+    pub fn list_presets(&self) -> TLCResult<Vec<String>> {
+        list_presets(&self.config.app_dir)
+    }
+
+    /// 以预设为基础新建工况，沿用当前的应用数据目录
+    pub fn new_from_preset(&self, name: &str) -> TLCResult<Self> {
+        Ok(Self::from_config(TLCConfig::from_preset(
+            &self.config.app_dir,
+            name,
+        )?))
+    }
+
+    pub fn save_preset(&self, name: &str) -> TLCResult<String> {
+        self.config.save_preset(name)
+    }
+}
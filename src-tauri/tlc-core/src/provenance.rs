@@ -0,0 +1,186 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::{error::TLCResult, TLCConfig, TLCData};
+use crate::awsl;
+
+/// 输入文件的内容校验和，大小与修改时间用于跳过未变化文件的重新计算
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileHash {
+    pub sha256: String,
+    pub size: u64,
+    /// 修改时间，unix秒
+    pub modified: u64,
+}
+
+/// 最近一次求解所用的视频与数采文件
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct InputHashes {
+    #[serde(default)]
+    pub video: Option<FileHash>,
+    #[serde(default)]
+    pub daq: Option<FileHash>,
+}
+
+/// 与记录不一致的输入文件
+#[derive(Debug, Serialize)]
+pub struct InputMismatch {
+    /// "video"或"daq"
+    pub input: &'static str,
+    pub path: String,
+    pub expected: String,
+    /// 文件无法读取时为None
+    pub actual: Option<String>,
+}
+
+/// 按块读取，避免把整个视频读入内存
+pub fn sha256_hex<P: AsRef<Path>>(path: P) -> TLCResult<String> {
+    let path = path.as_ref();
+    let mut file = File::open(path).map_err(|err| awsl!(DataReadError, err, path))?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0; 1 << 20];
+    loop {
+        match file.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => hasher.update(&buf[..n]),
+            Err(err) => return Err(awsl!(DataReadError, err, path)),
+        }
+    }
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// 内存中数据的SHA-256，格式与`sha256_hex`一致
+pub(crate) fn sha256_bytes(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// 文件大小与修改时间
+fn stat(path: &Path) -> TLCResult<(u64, u64)> {
+    let metadata = path
+        .metadata()
+        .map_err(|err| awsl!(DataReadError, err, path))?;
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_secs());
+
+    Ok((metadata.len(), modified))
+}
+
+impl FileHash {
+    pub fn of<P: AsRef<Path>>(path: P) -> TLCResult<Self> {
+        let path = path.as_ref();
+        let (size, modified) = stat(path)?;
+
+        Ok(Self {
+            sha256: sha256_hex(path)?,
+            size,
+            modified,
+        })
+    }
+
+    /// 大小与修改时间都未变化时视为同一文件，否则重新计算校验和
+    fn current<P: AsRef<Path>>(&self, path: P) -> TLCResult<Self> {
+        let path = path.as_ref();
+        match stat(path)? {
+            (size, modified) if size == self.size && modified == self.modified => Ok(self.clone()),
+            _ => Self::of(path),
+        }
+    }
+}
+
+impl TLCConfig {
+    /// 检查视频与数采文件是否与最近一次求解时一致
+    pub fn verify_inputs(&self) -> Vec<InputMismatch> {
+        let InputHashes { video, daq } = &self.input_hashes;
+        [
+            ("video", video, &self.video_path),
+            ("daq", daq, &self.daq_path),
+        ]
+        .iter()
+        .filter_map(|&(input, recorded, path)| {
+            let recorded = recorded.as_ref()?;
+            let actual = recorded.current(path).ok().map(|current| current.sha256);
+            if actual.as_ref() == Some(&recorded.sha256) {
+                return None;
+            }
+            tracing::warn!(input, %path, "input file differs from the one used for results");
+            Some(InputMismatch {
+                input,
+                path: path.clone(),
+                expected: recorded.sha256.clone(),
+                actual,
+            })
+        })
+        .collect()
+    }
+
+    /// 记录当前视频与数采文件的校验和，未变化的文件不重新计算
+    fn record_inputs(&mut self) -> TLCResult<()> {
+        let input_hashes = InputHashes {
+            video: Some(self.current_input(&self.input_hashes.video, &self.video_path)?),
+            daq: Some(self.current_input(&self.input_hashes.daq, &self.daq_path)?),
+        };
+        self.input_hashes = input_hashes;
+
+        Ok(())
+    }
+
+    /// 当前视频与数采文件的校验和，不修改记录，文件无法读取时为None
+    pub(crate) fn current_inputs(&self) -> InputHashes {
+        InputHashes {
+            video: self
+                .current_input(&self.input_hashes.video, &self.video_path)
+                .ok(),
+            daq: self
+                .current_input(&self.input_hashes.daq, &self.daq_path)
+                .ok(),
+        }
+    }
+
+    fn current_input(&self, recorded: &Option<FileHash>, path: &str) -> TLCResult<FileHash> {
+        match recorded {
+            Some(recorded) => recorded.current(path),
+            None => FileHash::of(path),
+        }
+    }
+}
+
+impl TLCData {
+    /// 求解完成后调用，结果文件头与配置中都会带上输入文件的校验和
+    pub(crate) fn record_inputs(&mut self) -> TLCResult<()> {
+        self.config.record_inputs()
+    }
+
+    pub fn verify_inputs(&self) -> Vec<InputMismatch> {
+        self.config.verify_inputs()
+    }
+
+    pub fn get_input_hashes(&self) -> &InputHashes {
+        &self.config.input_hashes
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn file_hash_changes_with_content() {
+        let path = std::env::temp_dir().join("tlc_provenance_test.lvm");
+        std::fs::write(&path, "1\t2\t3\n").unwrap();
+        let recorded = FileHash::of(&path).unwrap();
+        assert_eq!(recorded.current(&path).unwrap(), recorded);
+
+        std::fs::write(&path, "1\t2\t3\n4\t5\t6\n").unwrap();
+        assert_ne!(recorded.current(&path).unwrap().sha256, recorded.sha256);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,187 @@
+use ndarray::parallel::prelude::*;
+use ndarray::prelude::*;
+use serde::Serialize;
+
+use super::preprocess::{noise_level, PeakSignal};
+use super::{error::TLCResult, TLCData};
+
+/// 峰值处的Green值升幅与噪声之比低于该值的像素视为低信噪比
+const MIN_SNR: f32 = 5.;
+
+/// 偏离中位数超过该倍数（以MAD估计的标准差计）的Nu视为超出范围
+const OUTLIER_SIGMAS: f32 = 5.;
+
+/// 分块统计有效像素占比时每边的最大块数
+const QUALITY_GRID: usize = 8;
+
+/// 像素的分类，一个像素只归入最先满足的一类
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Quality {
+    Valid,
+    /// 遮罩排除
+    Masked,
+    /// 峰值帧不大于估计初始温度所用的帧数，无法求解
+    EarlyPeak,
+    LowSnr,
+    /// 迭代发散，Nu为NaN
+    Diverged,
+    /// Nu非正或远离分布中心
+    OutOfRange,
+}
+
+/// 某一类像素的个数及占计算区域的百分比
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct QualityCount {
+    pub count: usize,
+    pub percent: f32,
+}
+
+/// 数据有效率，各类互斥，合计为计算区域的全部像素
+#[derive(Debug, Serialize)]
+pub struct QualitySummary {
+    pub total: usize,
+    pub valid: QualityCount,
+    pub masked: QualityCount,
+    pub low_snr: QualityCount,
+    pub early_peak: QualityCount,
+    pub diverged: QualityCount,
+    pub out_of_range: QualityCount,
+    /// 分块的有效像素占比，与nu2d方向一致，每边最多`QUALITY_GRID`块
+    pub valid_map: Vec<Vec<f32>>,
+}
+
+/// 各列峰值升幅与噪声之比
+fn snr(g2d: ArrayView2<u8>, peak_signals: &[PeakSignal]) -> Vec<f32> {
+    g2d.axis_iter(Axis(1))
+        .into_par_iter()
+        .zip(peak_signals.par_iter())
+        .map(|(col, signal)| signal.amplitude / noise_level(col))
+        .collect()
+}
+
+/// 有效Nu的中位数与以MAD估计的标准差
+fn median_sigma(nus: &[f32]) -> (f32, f32) {
+    let median = |values: &mut Vec<f32>| {
+        values.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        values.get(values.len() / 2).copied().unwrap_or(f32::NAN)
+    };
+    let mut values: Vec<_> = nus.iter().copied().filter(|nu| nu.is_finite()).collect();
+    let center = median(&mut values);
+    let mut deviations: Vec<_> = values.iter().map(|nu| (nu - center).abs()).collect();
+
+    (center, 1.4826 * median(&mut deviations))
+}
+
+impl TLCData {
+    /// 统计遮罩、低信噪比、峰值过早、发散与Nu超出范围的像素，用于报告数据有效率，需先求解
+    ///
+    /// raw_g2d已释放时以filtered_g2d估计噪声
+    pub fn quality_summary(&mut self) -> TLCResult<QualitySummary> {
+        if self.peak_signals.is_none() {
+            self.detect_peak()?;
+        }
+        let start = self.event_window()?.0;
+        let (cal_h, cal_w) = self.config.region_shape;
+        let n0 = self.config.t0_estimation.frames();
+        let mask = self.config.mask_pixels()?;
+        let peak_frames = self.get_peak_frames()?;
+        let g2d = self.get_raw_g2d().or_else(|_| self.get_filtered_g2d())?;
+        let snr = snr(g2d, self.get_peak_signals()?);
+
+        // nu2d是上下翻转的，翻转回与峰值帧相同的顺序
+        let mut nu2d = self.get_nu2d()?.to_owned();
+        nu2d.invert_axis(Axis(0));
+        let nus: Vec<_> = nu2d.iter().copied().collect();
+        let (center, sigma) = median_sigma(&nus);
+
+        let qualities: Vec<_> = (0..cal_h * cal_w)
+            .map(|pos| {
+                let nu = nus[pos];
+                if mask.as_ref().map_or(false, |mask| !mask[pos]) {
+                    Quality::Masked
+                } else if peak_frames[pos].saturating_sub(start) <= n0 {
+                    Quality::EarlyPeak
+                } else if snr[pos] < MIN_SNR {
+                    Quality::LowSnr
+                } else if nu.is_nan() {
+                    Quality::Diverged
+                } else if nu <= 0. || (nu - center).abs() > OUTLIER_SIGMAS * sigma {
+                    Quality::OutOfRange
+                } else {
+                    Quality::Valid
+                }
+            })
+            .collect();
+
+        let total = qualities.len();
+        let count = |quality| {
+            let count = qualities.iter().filter(|&&q| q == quality).count();
+            QualityCount {
+                count,
+                percent: count as f32 / total.max(1) as f32 * 100.,
+            }
+        };
+
+        let (block_h, block_w) = (
+            (cal_h + QUALITY_GRID - 1) / QUALITY_GRID,
+            (cal_w + QUALITY_GRID - 1) / QUALITY_GRID,
+        );
+        let valid_map = (0..(cal_h + block_h - 1) / block_h)
+            .map(|bi| {
+                // 分块按nu2d的行划分，再换算回峰值帧的顺序
+                let rows: Vec<_> = (bi * block_h..((bi + 1) * block_h).min(cal_h))
+                    .map(|i| cal_h - 1 - i)
+                    .collect();
+                (0..(cal_w + block_w - 1) / block_w)
+                    .map(|bj| {
+                        let cols = bj * block_w..((bj + 1) * block_w).min(cal_w);
+                        let valid = rows
+                            .iter()
+                            .flat_map(|&i| cols.clone().map(move |j| i * cal_w + j))
+                            .filter(|&pos| qualities[pos] == Quality::Valid)
+                            .count();
+                        valid as f32 / (rows.len() * cols.len()) as f32
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Ok(QualitySummary {
+            total,
+            valid: count(Quality::Valid),
+            masked: count(Quality::Masked),
+            low_snr: count(Quality::LowSnr),
+            early_peak: count(Quality::EarlyPeak),
+            diverged: count(Quality::Diverged),
+            out_of_range: count(Quality::OutOfRange),
+            valid_map,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{mask::Mask, testing::SyntheticCase};
+
+    #[test]
+    fn synthetic_yield() {
+        let case = SyntheticCase::new((24, 32), 800).unwrap();
+        let mut data = case.data().unwrap();
+        data.solve().unwrap();
+        let summary = data.quality_summary().unwrap();
+        assert_eq!(summary.total, 24 * 32);
+        assert_eq!(summary.valid.count, summary.total);
+        assert_eq!(summary.valid_map.len(), 8);
+        assert!(summary.valid_map.iter().flatten().all(|&ratio| ratio == 1.));
+
+        // 只保留左半边
+        let left = vec![(0., 0.), (24., 0.), (24., 16.), (0., 16.)];
+        data.set_mask(Some(Mask::Polygons(vec![left]))).unwrap();
+        data.solve().unwrap();
+        let summary = data.quality_summary().unwrap();
+        assert_eq!(summary.masked.count + summary.valid.count, summary.total);
+        assert!((summary.masked.percent - 50.).abs() < 5.);
+        assert_eq!(summary.valid_map[0][0], 1.);
+        assert_eq!(summary.valid_map[0][7], 0.);
+    }
+}
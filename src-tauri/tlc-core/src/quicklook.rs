@@ -0,0 +1,193 @@
+use std::path::Path;
+
+use ndarray::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Map;
+
+use super::{
+    error::TLCResult,
+    io, postprocess,
+    preprocess::{DriftCorrection, FilterMethod, Interp},
+    solve::{IterationMethod, T0Estimation},
+    TLCData,
+};
+use crate::awsl;
+
+/// 快速预览时牛顿迭代的最大步数
+const QUICK_LOOK_MAX_ITER_NUM: usize = 4;
+
+/// 快速预览的抽帧与分块
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct QuickLook {
+    /// 每隔几帧取一帧，须整除帧率
+    pub frame_step: usize,
+    /// 分块尺寸（像素），每块平均后作为一个点
+    pub bin: usize,
+}
+
+/// 快速预览的结果，Nu矩阵为分块后的尺寸
+#[derive(Debug, Serialize)]
+pub struct QuickLookResult {
+    pub binned_shape: (usize, usize),
+    /// 抽帧后的帧数
+    pub frames: usize,
+    pub nu_nan_mean: f32,
+    pub nu_path: String,
+    /// 整个流程的耗时（s）
+    pub secs: f32,
+}
+
+/// 按帧数计的配置项随抽帧缩小，至少为1
+fn decimate(frames: usize, frame_step: usize) -> usize {
+    ((frames + frame_step - 1) / frame_step).max(1)
+}
+
+impl TLCData {
+    /// 抽帧、分块后走一遍完整流程（读取视频、滤波、峰值检测、插值、求解），
+    /// 并减少迭代步数，几秒内得到近似的Nu分布，用于修改同步或计算区域后检查大致结果
+    ///
+    /// 预览的中间数据不保存，不影响当前的计算结果；遮罩排除全部像素的块Nu为NaN，
+    /// 块内平均已冲淡坏点的影响，不做坏点修复
+    pub fn quick_look(&mut self, options: QuickLook) -> TLCResult<QuickLookResult> {
+        let QuickLook { frame_step, bin } = options;
+        if frame_step == 0 || bin == 0 {
+            return Err(awsl!(HandleError, "抽帧间隔与分块尺寸应大于0"));
+        }
+        if self.config.frame_rate % frame_step != 0 {
+            return Err(awsl!(
+                ConfigError,
+                format!(
+                    "抽帧间隔{}不能整除帧率{}",
+                    frame_step, self.config.frame_rate
+                )
+            ));
+        }
+        let timer = self.start_stage("quick_look");
+        let region_shape = self.config.region_shape;
+        let (cal_h, cal_w) = region_shape;
+        let binned_shape = ((cal_h + bin - 1) / bin, (cal_w + bin - 1) / bin);
+        let mask = self.config.mask_pixels()?;
+        // 各块参与处理的像素及块中心
+        let (groups, centers): (Vec<Vec<usize>>, Vec<usize>) = (0..binned_shape.0 * binned_shape.1)
+            .map(|b| {
+                let (y0, x0) = (b / binned_shape.1 * bin, b % binned_shape.1 * bin);
+                let (y1, x1) = ((y0 + bin).min(cal_h), (x0 + bin).min(cal_w));
+                let group = (y0..y1)
+                    .flat_map(|y| (x0..x1).map(move |x| y * cal_w + x))
+                    .filter(|&pos| mask.as_ref().map_or(true, |mask| mask[pos]))
+                    .collect();
+                (group, (y0 + y1) / 2 * cal_w + (x0 + x1) / 2)
+            })
+            .unzip();
+        let (raw_g2d, reference_g) = self.read_grouped(frame_step, &groups)?;
+        let frames = raw_g2d.nrows();
+
+        if self.t2d.is_none() {
+            self.init_t2d()?;
+        }
+        // 已插值时直接取用，否则临时构造惰性插值，只计算各块中心处的参考温度
+        let lazy;
+        let interp = match &self.interp {
+            Some(interp) => interp,
+            None => {
+                lazy = self.build_interp(true)?;
+                &lazy
+            }
+        };
+        let temps: Vec<f32> = centers
+            .iter()
+            .flat_map(|&center| {
+                let temps = interp.interp_single_point(center, region_shape);
+                temps
+                    .iter()
+                    .step_by(frame_step)
+                    .copied()
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        let temps =
+            Array2::from_shape_vec((centers.len(), frames), temps).map_err(|err| awsl!(err))?;
+
+        let mut config = self.config.with_overrides(Map::new())?;
+        // 预览不写计时日志
+        config.save_dir = String::new();
+        config.frame_rate /= frame_step;
+        config.frame_num = frames;
+        config.region_shape = binned_shape;
+        config.mask = None;
+        config.defect_frames = 0;
+        config.coarse_stride = 0;
+        if let FilterMethod::Median(window_size) = config.filter_method {
+            config.filter_method = FilterMethod::Median(decimate(window_size, frame_step));
+        }
+        if let DriftCorrection::Patch((y, x), (h, w)) = config.drift_correction {
+            let (y1, x1) = (decimate(y + h, bin), decimate(x + w, bin));
+            config.drift_correction =
+                DriftCorrection::Patch((y / bin, x / bin), (y1 - y / bin, x1 - x / bin));
+        }
+        if config.events.min_separation > 0 {
+            config.events.min_separation = decimate(config.events.min_separation, frame_step);
+        }
+        config.t0_estimation = match config.t0_estimation {
+            T0Estimation::Mean(n) => T0Estimation::Mean(decimate(n, frame_step)),
+            T0Estimation::Median(n) => T0Estimation::Median(decimate(n, frame_step)),
+            uniform => uniform,
+        };
+        config.iteration_method = match config.iteration_method {
+            IterationMethod::NewtonTangent { h0, max_iter_num } => IterationMethod::NewtonTangent {
+                h0,
+                max_iter_num: max_iter_num.min(QUICK_LOOK_MAX_ITER_NUM),
+            },
+            IterationMethod::NewtonDown { h0, max_iter_num } => IterationMethod::NewtonDown {
+                h0,
+                max_iter_num: max_iter_num.min(QUICK_LOOK_MAX_ITER_NUM),
+            },
+            plugin => plugin,
+        };
+
+        let mut preview = TLCData::from_config(config);
+        preview.raw_g2d = Some(raw_g2d);
+        preview.reference_g = reference_g;
+        preview.interp = Some(Interp::Eager(temps));
+        preview.detect_peak()?;
+        let start = preview.event_window()?.0;
+        preview.solve_nu(start)?;
+        let mut nu2d = preview.get_nu2d()?.to_owned();
+        drop(preview);
+        // nu2d上下翻转，第0行对应最下方的块
+        for (b, group) in groups.iter().enumerate() {
+            if group.is_empty() {
+                let (by, bx) = (b / binned_shape.1, b % binned_shape.1);
+                nu2d[(binned_shape.0 - 1 - by, bx)] = f32::NAN;
+            }
+        }
+
+        let mut meta = self.data_meta("Nu", true)?;
+        meta.pixel_size *= bin as f32;
+        let nu_path = Path::new(&self.config.data_path)
+            .with_file_name(format!("{}_quick_look_nu.csv", self.config.case_name));
+        io::save_data(nu2d.view(), &nu_path, Some(&meta))?;
+        self.finish(timer);
+
+        Ok(QuickLookResult {
+            binned_shape,
+            frames,
+            nu_nan_mean: postprocess::cal_nan_mean(nu2d.view()),
+            nu_path: nu_path.to_str().ok_or(awsl!(nu_path))?.to_owned(),
+            secs: self.timings.last().map_or(0., |timing| timing.secs),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn decimate_frames() {
+        assert_eq!(decimate(9, 2), 5);
+        assert_eq!(decimate(8, 2), 4);
+        assert_eq!(decimate(1, 4), 1);
+        assert_eq!(decimate(0, 4), 1);
+    }
+}
@@ -0,0 +1,167 @@
+use serde::Serialize;
+
+use super::preprocess::TCStatus;
+use super::{error::TLCResult, TLCConfig, TLCData};
+use crate::awsl;
+
+/// 搜索半径（像素）的上限，手工测量的位置通常只偏差几个像素
+const MAX_REFINE_RADIUS: usize = 10;
+
+/// 候选位置的时间差须比原位置至少小这么多帧才移动，避免随峰值帧的噪声跳动
+const MIN_IMPROVEMENT: f32 = 1.;
+
+/// 一个热电偶的位置微调结果
+#[derive(Debug, Serialize)]
+pub struct TCRefinement {
+    /// 热电偶序号
+    pub index: usize,
+    /// 热电偶温度首次达到峰值温度的帧，未达到或热电偶不可用时为None，不调整
+    pub target_frame: Option<usize>,
+    /// 原位置像素的峰值帧与`target_frame`之差（帧），不在计算区域内时为NaN
+    pub before: f32,
+    /// 调整后位置像素的峰值帧与`target_frame`之差（帧）
+    pub after: f32,
+    /// 位置偏移(dy, dx)
+    pub offset: (i32, i32),
+}
+
+impl TLCData {
+    /// 在各热电偶周围`radius`像素内寻找峰值帧与该热电偶温度达到峰值温度的帧最接近的像素，
+    /// 返回各热电偶的偏移，`apply`时同时修改热电偶位置
+    ///
+    /// 热电偶贴在壁面上时，其所在像素应恰在热电偶读数经过峰值温度时显示峰值颜色
+    pub fn refine_thermocouples(
+        &mut self,
+        radius: usize,
+        apply: bool,
+    ) -> TLCResult<Vec<TCRefinement>> {
+        if radius == 0 || radius > MAX_REFINE_RADIUS {
+            return Err(awsl!(
+                HandleError,
+                format!("搜索半径{}应在1到{}之间", radius, MAX_REFINE_RADIUS)
+            ));
+        }
+        if self.peak_frames.is_none() {
+            self.detect_peak()?;
+        }
+        if self.t2d.is_none() {
+            self.init_t2d()?;
+        }
+        let (start, end) = self.event_window()?;
+        let TLCConfig {
+            top_left_pos: (y0, x0),
+            region_shape: (cal_h, cal_w),
+            peak_temp,
+            ref thermocouples,
+            ..
+        } = self.config;
+        let peak_frames = self.get_peak_frames()?;
+        let t2d = self.get_t2d()?;
+        let tc_status = self.get_tc_status()?;
+
+        // 像素峰值帧与目标帧之差，不在计算区域内时为None
+        let mismatch = |(y, x): (i32, i32), target: usize| {
+            let (y, x) = (y - y0 as i32, x - x0 as i32);
+            if y < 0 || x < 0 || y >= cal_h as i32 || x >= cal_w as i32 {
+                return None;
+            }
+            let peak = peak_frames[y as usize * cal_w + x as usize];
+            Some((peak as f32 - target as f32).abs())
+        };
+        let r = radius as i32;
+        let refinements: Vec<_> = thermocouples
+            .iter()
+            .enumerate()
+            .map(|(index, tc)| {
+                let target_frame = if tc_status[index] == TCStatus::Ok {
+                    (start..end).find(|&frame| t2d[(index, frame)] >= peak_temp)
+                } else {
+                    None
+                };
+                let unchanged = |before| TCRefinement {
+                    index,
+                    target_frame,
+                    before,
+                    after: before,
+                    offset: (0, 0),
+                };
+                let target = match target_frame {
+                    Some(target) => target,
+                    None => return unchanged(f32::NAN),
+                };
+                let before = match mismatch(tc.pos, target) {
+                    Some(before) => before,
+                    None => return unchanged(f32::NAN),
+                };
+                // 时间差相同时取离原位置最近的
+                let best = (-r..=r)
+                    .flat_map(|dy| (-r..=r).map(move |dx| (dy, dx)))
+                    .filter_map(|(dy, dx)| {
+                        let after = mismatch((tc.pos.0 + dy, tc.pos.1 + dx), target)?;
+                        Some((after, dy * dy + dx * dx, (dy, dx)))
+                    })
+                    .min_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+                match best {
+                    Some((after, _, offset)) if after + MIN_IMPROVEMENT <= before => TCRefinement {
+                        index,
+                        target_frame,
+                        before,
+                        after,
+                        offset,
+                    },
+                    _ => unchanged(before),
+                }
+            })
+            .collect();
+
+        if apply && refinements.iter().any(|r| r.offset != (0, 0)) {
+            let mut thermocouples = thermocouples.clone();
+            for (tc, refinement) in thermocouples.iter_mut().zip(&refinements) {
+                tc.pos.0 += refinement.offset.0;
+                tc.pos.1 += refinement.offset.1;
+            }
+            self.set_thermocouples(thermocouples);
+        }
+
+        Ok(refinements)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ndarray::prelude::*;
+    use serde_json::json;
+
+    use crate::{preprocess::TCStatus, TLCConfig, TLCData};
+
+    #[test]
+    fn refine_towards_matching_peak() {
+        let config = TLCConfig::from_json(json!({
+            "frame_num": 10,
+            "region_shape": [5, 5],
+            "peak_temp": 35.,
+            "thermocouples": [{ "column_num": 0, "pos": [2, 2] }],
+        }))
+        .unwrap();
+        let mut data = TLCData::from_config(config);
+        // 热电偶在第4帧达到峰值温度，只有(3, 4)处的像素在第4帧达到峰值
+        let mut peak_frames = vec![9; 25];
+        peak_frames[3 * 5 + 4] = 4;
+        data.peak_frames = Some(peak_frames);
+        data.t2d = Some(Array2::from_shape_fn((1, 10), |(_, frame)| {
+            30. + frame as f32 * 1.5
+        }));
+        data.tc_status = Some(vec![TCStatus::Ok]);
+
+        let refinements = data.refine_thermocouples(1, false).unwrap();
+        assert_eq!(refinements[0].target_frame, Some(4));
+        assert_eq!(refinements[0].offset, (0, 0));
+
+        let refinements = data.refine_thermocouples(2, true).unwrap();
+        assert_eq!(refinements[0].offset, (1, 2));
+        assert_eq!((refinements[0].before, refinements[0].after), (5., 0.));
+        assert_eq!(data.get_config().thermocouples[0].pos, (3, 4));
+
+        assert!(data.refine_thermocouples(0, false).is_err());
+    }
+}
@@ -0,0 +1,216 @@
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use ndarray::prelude::*;
+use serde_json::Value as Json;
+
+use super::postprocess::{self, FigureInfo, PlotOptions};
+use super::{error::TLCResult, TLCConfig, TLCData};
+use crate::awsl;
+
+const STYLE: &str = "body{font-family:sans-serif;max-width:1100px;margin:auto;padding:20px}\
+table{border-collapse:collapse;margin:10px 0}\
+td,th{border:1px solid #ccc;padding:3px 8px;text-align:left}\
+th{background:#eee}img{max-width:100%}.na{color:#999}";
+
+impl TLCData {
+    /// 生成单个工况的HTML报告，图片以base64内嵌，可直接发送给他人查看，返回保存路径
+    ///
+    /// 包括测量设置、配置、Nu云图、平均分布、统计量与求解诊断，视频不可用时跳过测量设置图
+    pub fn save_report(&mut self) -> TLCResult<String> {
+        let case_name = self.config.case_name.clone();
+        let plots_path = PathBuf::from(&self.config.plots_path);
+        let plot_path =
+            |suffix: &str| plots_path.with_file_name(format!("{}_{}", case_name, suffix));
+        let options = PlotOptions::default();
+        let mut html = String::new();
+
+        let _ = write!(
+            html,
+            "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>{0}</title>\
+             <style>{1}</style></head><body><h1>{0}</h1><p>{2}，配置哈希 {3}</p>",
+            escape(&case_name),
+            STYLE,
+            chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
+            self.config.hash()?,
+        );
+
+        html.push_str("<h2>测量设置</h2>");
+        match self.get_setup_overlay(self.config.start_frame, &[]) {
+            Ok(png) => push_img(&mut html, &png),
+            Err(err) => {
+                let _ = write!(html, "<p class=\"na\">{}</p>", escape(&err.to_string()));
+            }
+        }
+
+        html.push_str("<h2>配置</h2>");
+        push_config(&mut html, &self.config)?;
+
+        let nu2d = self.get_smoothed_nu2d()?;
+        let nu_nan_mean = self.get_nu_nan_mean()?;
+        let colormap = self.config.colormap.lut(self.config.reverse_colormap)?;
+        let info = FigureInfo {
+            title: &case_name,
+            label: "Nu",
+            pixel_size: self.config.pixel_size,
+        };
+        let range = (nu_nan_mean * 0.6, nu_nan_mean * 2.);
+        let figure_path = postprocess::plot_figure(
+            plot_path("report_nu"),
+            nu2d,
+            range,
+            &colormap,
+            &info,
+            &options,
+        )?;
+        html.push_str("<h2>Nu分布</h2>");
+        push_img_file(&mut html, &figure_path)?;
+
+        html.push_str("<h2>平均分布</h2>");
+        for (axis, name) in [(0, "x"), (1, "y")] {
+            let profile = postprocess::profile(nu2d, Axis(axis));
+            let pos: Vec<_> = (0..profile.mean.len()).map(|i| i as f32).collect();
+            let mean = profile.mean.as_slice().ok_or(awsl!())?;
+            let std = profile.std.as_slice().ok_or(awsl!())?;
+            let x_desc = format!("{} (pixel)", name);
+            let profile_path = postprocess::plot_profile(
+                plot_path(&format!("report_profile_{}", name)),
+                &pos,
+                mean,
+                Some(std),
+                (&x_desc, "Nu"),
+                &options,
+            )?;
+            push_img_file(&mut html, &profile_path)?;
+        }
+
+        html.push_str("<h2>统计量</h2><table>");
+        let valid: Vec<_> = nu2d.iter().copied().filter(|nu| !nu.is_nan()).collect();
+        let (mean, std) = postprocess::nan_mean_std(valid.iter().copied());
+        let min = valid.iter().copied().fold(f32::NAN, f32::min);
+        let max = valid.iter().copied().fold(f32::NAN, f32::max);
+        push_row(
+            &mut html,
+            "th",
+            &["平均值", "标准差", "最小值", "最大值", "有效像素"],
+        );
+        let valid_ratio = format!("{} / {}", valid.len(), nu2d.len());
+        let values = [fmt(mean), fmt(std), fmt(min), fmt(max), valid_ratio];
+        push_row(&mut html, "td", &values);
+        html.push_str("</table><table>");
+        let hist = postprocess::histogram(nu2d, 50)?;
+        let (percents, nus): (Vec<_>, Vec<_>) = hist
+            .percentiles
+            .iter()
+            .map(|&(p, nu)| (format!("P{}", p), fmt(nu)))
+            .unzip();
+        push_row(&mut html, "th", &percents);
+        push_row(&mut html, "td", &nus);
+        html.push_str("</table>");
+        if let Ok(nu0) = self.get_nu0() {
+            let _ = write!(
+                html,
+                "<p>Nu0 = {}，平均Nu/Nu0 = {}</p>",
+                fmt(nu0),
+                fmt(mean / nu0)
+            );
+        }
+
+        html.push_str("<h2>求解诊断</h2>");
+        let TLCConfig {
+            ref interp_method,
+            ref filter_method,
+            ref iteration_method,
+            ..
+        } = self.config;
+        let _ = write!(
+            html,
+            "<p>插值：{}<br>滤波：{}<br>迭代：{}</p>",
+            escape(&format!("{:?}", interp_method)),
+            escape(&format!("{:?}", filter_method)),
+            escape(&format!("{:?}", iteration_method)),
+        );
+        // 留一法检验各热电偶处的插值误差
+        let status = self.get_tc_status().ok().cloned();
+        let rms = self.validate_interp().ok();
+        html.push_str("<table>");
+        push_row(
+            &mut html,
+            "th",
+            &["热电偶", "安装位置", "位置(y, x)", "状态", "留一法RMS (°C)"],
+        );
+        for (i, tc) in self.config.thermocouples.iter().enumerate() {
+            let status = status.as_ref().and_then(|status| status.get(i));
+            let rms = rms.as_ref().and_then(|rms| rms.get(i)).copied();
+            let row = [
+                escape(&tc.name(i)),
+                escape(&tc.location),
+                format!("{:?}", tc.pos),
+                status.map_or("-".to_owned(), |s| format!("{:?}", s)),
+                rms.map_or("-".to_owned(), fmt),
+            ];
+            push_row(&mut html, "td", &row);
+        }
+        html.push_str("</table></body></html>");
+
+        let report_path = plot_path("report.html");
+        std::fs::write(&report_path, html).map_err(|err| awsl!(DataSaveError, err, report_path))?;
+
+        Ok(report_path.to_str().ok_or(awsl!(report_path))?.to_owned())
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// NaN显示为`-`
+fn fmt(v: f32) -> String {
+    if v.is_nan() {
+        "-".to_owned()
+    } else {
+        format!("{:.3}", v)
+    }
+}
+
+fn push_row<S: AsRef<str>>(html: &mut String, tag: &str, cells: &[S]) {
+    html.push_str("<tr>");
+    for cell in cells {
+        let _ = write!(html, "<{0}>{1}</{0}>", tag, cell.as_ref());
+    }
+    html.push_str("</tr>");
+}
+
+fn push_img(html: &mut String, png: &[u8]) {
+    let _ = write!(
+        html,
+        "<img src=\"data:image/png;base64,{}\">",
+        base64::encode(png)
+    );
+}
+
+fn push_img_file(html: &mut String, png_path: &Path) -> TLCResult<()> {
+    let buf = std::fs::read(png_path).map_err(|err| awsl!(DataReadError, err, png_path))?;
+    push_img(html, &buf);
+
+    Ok(())
+}
+
+/// 每项配置一行，嵌套的值显示为json
+fn push_config(html: &mut String, config: &TLCConfig) -> TLCResult<()> {
+    let config = serde_json::to_value(config).map_err(|err| awsl!(err))?;
+    html.push_str("<table>");
+    for (key, value) in config.as_object().ok_or(awsl!(config))? {
+        let value = match value {
+            Json::String(s) => s.clone(),
+            _ => value.to_string(),
+        };
+        push_row(html, "td", &[escape(key), escape(&value)]);
+    }
+    html.push_str("</table>");
+
+    Ok(())
+}
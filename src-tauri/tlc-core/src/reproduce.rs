@@ -0,0 +1,361 @@
+//! 随Nu矩阵保存的复现清单，记录得到结果的软件、配置、输入文件与计算环境，
+//! 并抽取少量像素的Nu，事后在同样的输入上重新求解这些像素以核对结果
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::provenance::InputHashes;
+use super::solve::{IterationMethod, T0Estimation};
+use super::{error::TLCResult, TLCData};
+use crate::awsl;
+
+/// 抽取的像素数
+const SAMPLE_NUM: usize = 16;
+
+/// 重新求解的Nu与清单中的相对误差不超过此值时视为一致，
+/// 两遍求解时各像素的迭代初值不同，收敛判据内的差别不计
+const SAMPLE_TOLERANCE: f32 = 1e-3;
+
+/// 计算相对误差时分母的下限，记录的Nu为0时退化为绝对误差
+const MIN_EXPECTED_NU: f32 = 1e-6;
+
+/// 计算环境
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Backend {
+    pub arch: String,
+    pub os: String,
+    /// 编译时启用的SIMD指令集，求解中的f32x8运算按此生成
+    pub simd: Vec<String>,
+    pub threads: usize,
+}
+
+impl Backend {
+    fn current(threads: usize) -> Self {
+        let features = [
+            ("sse2", cfg!(target_feature = "sse2")),
+            ("sse4.1", cfg!(target_feature = "sse4.1")),
+            ("avx", cfg!(target_feature = "avx")),
+            ("avx2", cfg!(target_feature = "avx2")),
+            ("fma", cfg!(target_feature = "fma")),
+            ("neon", cfg!(target_feature = "neon")),
+        ];
+        Self {
+            arch: std::env::consts::ARCH.to_owned(),
+            os: std::env::consts::OS.to_owned(),
+            simd: features
+                .iter()
+                .filter(|(_, enabled)| *enabled)
+                .map(|(name, _)| (*name).to_owned())
+                .collect(),
+            threads,
+        }
+    }
+}
+
+/// 影响求解结果的设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SolverSettings {
+    pub iteration_method: IterationMethod,
+    pub coarse_stride: usize,
+    pub t0_estimation: T0Estimation,
+}
+
+/// 抽取的像素，(row, col)为nu2d中的位置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PixelSample {
+    pub row: usize,
+    pub col: usize,
+    pub nu: f32,
+}
+
+/// 复现清单
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReproManifest {
+    pub case_name: String,
+    pub version: String,
+    pub config_hash: String,
+    pub input_hashes: InputHashes,
+    pub backend: Backend,
+    pub solver: SolverSettings,
+    pub samples: Vec<PixelSample>,
+}
+
+impl ReproManifest {
+    pub fn from_path<P: AsRef<Path>>(path: P) -> TLCResult<Self> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|err| awsl!(DataReadError, err, path))?;
+
+        serde_json::from_reader(BufReader::new(file)).map_err(|err| awsl!(DataReadError, err, path))
+    }
+}
+
+/// 一个像素的核对结果
+#[derive(Debug, Serialize)]
+pub struct SampleCheck {
+    pub row: usize,
+    pub col: usize,
+    pub expected: f32,
+    pub actual: f32,
+    pub relative_error: f32,
+}
+
+/// 核对结果
+#[derive(Debug, Serialize)]
+pub struct ReproReport {
+    /// 配置、输入文件与抽取像素的Nu都一致
+    pub reproducible: bool,
+    /// 导致结果可能不同的差异：配置、输入文件
+    pub mismatches: Vec<String>,
+    /// 不影响判断的差异：软件版本、计算环境，供排查时参考
+    pub notes: Vec<String>,
+    pub samples: Vec<SampleCheck>,
+    pub max_relative_error: f32,
+}
+
+/// 重新求解发散时为NaN
+fn relative_error(actual: f32, expected: f32) -> f32 {
+    (actual - expected).abs() / expected.abs().max(MIN_EXPECTED_NU)
+}
+
+/// 在有效像素中均匀抽取
+fn sample_positions(nus: &[f32], num: usize) -> Vec<usize> {
+    let valid: Vec<_> = (0..nus.len()).filter(|&i| !nus[i].is_nan()).collect();
+    let step = (valid.len() / num.max(1)).max(1);
+
+    valid.into_iter().step_by(step).take(num).collect()
+}
+
+impl TLCData {
+    /// 复现清单与Nu矩阵保存在同一目录
+    pub fn manifest_path(&self) -> PathBuf {
+        let file_name = format!("{}_manifest.json", self.config.case_name);
+        Path::new(&self.config.data_path).with_file_name(file_name)
+    }
+
+    fn backend(&mut self) -> TLCResult<Backend> {
+        let threads = self.in_pool(|_| rayon::current_num_threads())?;
+
+        Ok(Backend::current(threads))
+    }
+
+    /// 写出复现清单，已求解后调用，返回保存路径
+    pub fn save_manifest(&mut self) -> TLCResult<String> {
+        let nu2d = self.get_nu2d()?;
+        let ncols = nu2d.ncols();
+        let nus: Vec<_> = nu2d.iter().copied().collect();
+        let samples = sample_positions(&nus, SAMPLE_NUM)
+            .into_iter()
+            .map(|i| PixelSample {
+                row: i / ncols,
+                col: i % ncols,
+                nu: nus[i],
+            })
+            .collect();
+        let manifest = ReproManifest {
+            case_name: self.config.case_name.clone(),
+            version: env!("CARGO_PKG_VERSION").to_owned(),
+            config_hash: self.config.hash()?,
+            input_hashes: self.config.input_hashes.clone(),
+            backend: self.backend()?,
+            solver: SolverSettings {
+                iteration_method: self.config.iteration_method.clone(),
+                coarse_stride: self.config.coarse_stride,
+                t0_estimation: self.config.t0_estimation,
+            },
+            samples,
+        };
+
+        let manifest_path = self.manifest_path();
+        let file =
+            File::create(&manifest_path).map_err(|err| awsl!(DataSaveError, err, manifest_path))?;
+        serde_json::to_writer_pretty(BufWriter::new(file), &manifest).map_err(|err| awsl!(err))?;
+
+        Ok(manifest_path
+            .to_str()
+            .ok_or(awsl!(manifest_path))?
+            .to_owned())
+    }
+
+    /// 按当前配置重新求解清单中抽取的像素并与记录的Nu比较，同时核对配置与输入文件
+    pub fn verify_reproducibility(&mut self, manifest: &ReproManifest) -> TLCResult<ReproReport> {
+        let mut mismatches = Vec::new();
+        let mut notes = Vec::new();
+        if self.config.hash()? != manifest.config_hash {
+            mismatches.push("影响结果的配置与清单记录的不一致".to_owned());
+        }
+        let inputs = [
+            (
+                "视频文件",
+                &manifest.input_hashes.video,
+                &self.config.video_path,
+            ),
+            (
+                "数采文件",
+                &manifest.input_hashes.daq,
+                &self.config.daq_path,
+            ),
+        ];
+        for &(name, recorded, path) in inputs.iter() {
+            if let Some(recorded) = recorded {
+                // 按内容比较，不使用可能已更新的缓存
+                if super::provenance::sha256_hex(path).ok().as_ref() != Some(&recorded.sha256) {
+                    mismatches.push(format!("{}{}与清单记录的校验和不一致", name, path));
+                }
+            }
+        }
+        let version = env!("CARGO_PKG_VERSION");
+        if version != manifest.version {
+            notes.push(format!(
+                "软件版本{}，清单记录为{}",
+                version, manifest.version
+            ));
+        }
+        let backend = self.backend()?;
+        if backend != manifest.backend {
+            notes.push(format!(
+                "计算环境{:?}，清单记录为{:?}",
+                backend, manifest.backend
+            ));
+        }
+
+        let (cal_h, cal_w) = self.config.region_shape;
+        let mut positions = Vec::with_capacity(manifest.samples.len());
+        for sample in &manifest.samples {
+            if sample.row >= cal_h || sample.col >= cal_w {
+                return Err(awsl!(
+                    ConfigError,
+                    format!(
+                        "清单中的像素({}, {})超出计算区域{}×{}",
+                        sample.row, sample.col, cal_h, cal_w
+                    )
+                ));
+            }
+            // nu2d的行序与计算区域相反
+            positions.push((cal_h - 1 - sample.row) * cal_w + sample.col);
+        }
+        let actual = self.solve_pixels(&positions)?;
+        let samples: Vec<_> = manifest
+            .samples
+            .iter()
+            .zip(actual)
+            .map(|(sample, actual)| SampleCheck {
+                row: sample.row,
+                col: sample.col,
+                expected: sample.nu,
+                actual,
+                relative_error: relative_error(actual, sample.nu),
+            })
+            .collect();
+        let max_relative_error =
+            samples
+                .iter()
+                .map(|s| s.relative_error)
+                .fold(0., |max: f32, e| {
+                    if e.is_nan() {
+                        f32::INFINITY
+                    } else {
+                        max.max(e)
+                    }
+                });
+        if max_relative_error > SAMPLE_TOLERANCE {
+            mismatches.push(format!(
+                "重新求解的Nu与清单记录的最大相对误差为{}",
+                max_relative_error
+            ));
+        }
+
+        Ok(ReproReport {
+            reproducible: mismatches.is_empty(),
+            mismatches,
+            notes,
+            samples,
+            max_relative_error,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ndarray::prelude::*;
+    use serde_json::json;
+
+    use super::*;
+    use crate::preprocess::Interp;
+    use crate::TLCConfig;
+
+    #[test]
+    fn samples_skip_nan() {
+        let nus = [f32::NAN, 1., 2., f32::NAN, 3., 4.];
+        assert_eq!(sample_positions(&nus, 2), vec![1, 4]);
+        assert_eq!(sample_positions(&nus, 16), vec![1, 2, 4, 5]);
+        assert!(sample_positions(&[f32::NAN], 16).is_empty());
+    }
+
+    #[test]
+    fn relative_error_of_zero_nu() {
+        assert_eq!(relative_error(0., 0.), 0.);
+        assert!(relative_error(1e-9, 0.) < SAMPLE_TOLERANCE);
+        assert!(relative_error(1., 0.) > SAMPLE_TOLERANCE);
+        assert!((relative_error(101., 100.) - 0.01).abs() < 1e-6);
+        assert!(relative_error(f32::NAN, 100.).is_nan());
+    }
+
+    #[test]
+    fn verify_resolved_samples() {
+        let config = TLCConfig::from_json(json!({
+            "frame_num": 40,
+            "frame_rate": 25,
+            "region_shape": [3, 2],
+            "peak_temp": 35.,
+        }))
+        .unwrap();
+        let mut data = TLCData::from_config(config);
+        // 参考温度在第5帧阶跃，各像素峰值帧不同
+        data.peak_frames = Some(vec![20, 24, 28, 32, 36, 39]);
+        data.interp = Some(Interp::Eager(Array2::from_shape_fn(
+            (6, 40),
+            |(_, frame)| {
+                if frame < 5 {
+                    25.
+                } else {
+                    45.
+                }
+            },
+        )));
+        data.solve_nu(0).unwrap();
+        let nu2d = data.get_nu2d().unwrap();
+        let samples: Vec<_> = [(0, 1), (2, 0)]
+            .iter()
+            .map(|&(row, col)| PixelSample {
+                row,
+                col,
+                nu: nu2d[(row, col)],
+            })
+            .collect();
+        let mut manifest = ReproManifest {
+            case_name: String::new(),
+            version: env!("CARGO_PKG_VERSION").to_owned(),
+            config_hash: data.config.hash().unwrap(),
+            input_hashes: InputHashes::default(),
+            backend: data.backend().unwrap(),
+            solver: SolverSettings {
+                iteration_method: IterationMethod::default(),
+                coarse_stride: 0,
+                t0_estimation: T0Estimation::default(),
+            },
+            samples,
+        };
+
+        let report = data.verify_reproducibility(&manifest).unwrap();
+        assert!(report.reproducible, "{:?}", report);
+        assert!(report.notes.is_empty());
+
+        manifest.samples[1].nu *= 1.01;
+        let report = data.verify_reproducibility(&manifest).unwrap();
+        assert!(!report.reproducible);
+        assert!((report.max_relative_error - 0.01 / 1.01).abs() < 1e-4);
+    }
+}
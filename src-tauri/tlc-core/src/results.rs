@@ -0,0 +1,63 @@
+use std::fs::{create_dir_all, File};
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+use serde_json::{json, to_writer_pretty};
+
+use super::{error::TLCResult, TLCData};
+use crate::awsl;
+
+impl TLCData {
+    /// 本工况结果的说明文件，与Nu矩阵保存在同一目录
+    pub fn results_path(&self) -> PathBuf {
+        let file_name = format!("{}_results.json", self.config.case_name);
+        Path::new(&self.config.data_path).with_file_name(file_name)
+    }
+
+    /// 写出配置、软件版本、输入文件校验和、求解统计与已保存的结果文件列表，
+    /// 结果目录单独归档后仍可知道是怎样得到的，返回保存路径
+    pub fn save_results(&self) -> TLCResult<String> {
+        let results_path = self.results_path();
+        let stats = self.get_solve_stats()?;
+        let secs = self
+            .timings
+            .iter()
+            .find(|timing| timing.stage == "solve")
+            .map(|timing| timing.secs);
+        let outputs: Vec<_> = self
+            .case_outputs()?
+            .into_iter()
+            .filter(|(_, path)| *path != results_path)
+            .map(|(dir_name, path)| {
+                let name = path.file_name().unwrap_or_default().to_string_lossy();
+                format!("{}/{}", dir_name, name)
+            })
+            .collect();
+        let results = json!({
+            "case_name": self.config.case_name,
+            "date": chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            "version": env!("CARGO_PKG_VERSION"),
+            "config_hash": self.config.hash()?,
+            "config": self.config,
+            "input_hashes": self.config.input_hashes,
+            "solver": {
+                "iteration_method": self.config.iteration_method,
+                "total_pixels": self.get_nu2d()?.len(),
+                "solved": stats.solved,
+                "nan_count": stats.nan_count,
+                "mean_iterations": stats.mean_iterations,
+                "nu_nan_mean": self.get_nu_nan_mean()?,
+                "secs": secs,
+            },
+            "outputs": outputs,
+        });
+
+        let dir = results_path.parent().ok_or(awsl!(results_path))?;
+        create_dir_all(dir).map_err(|err| awsl!(CreateDirError, err, dir))?;
+        let file =
+            File::create(&results_path).map_err(|err| awsl!(DataSaveError, err, results_path))?;
+        to_writer_pretty(BufWriter::new(file), &results).map_err(|err| awsl!(err))?;
+
+        Ok(results_path.to_str().ok_or(awsl!(results_path))?.to_owned())
+    }
+}
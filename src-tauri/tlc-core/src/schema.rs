@@ -0,0 +1,664 @@
+//! `TLCConfig`各配置项的描述（类型、单位、取值范围、中英文名称），以JSON Schema导出，
+//! 界面表单与命令行据此检查输入，不必各自重复配置的定义
+
+use serde_json::{json, Map, Value as Json};
+
+use super::error::{self, Lang, TLCResult};
+use super::TLCConfig;
+use crate::awsl;
+
+/// 配置项的JSON类型，复杂的枚举与结构体统一为`Object`，由各自的反序列化检查
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    Integer,
+    Number,
+    String,
+    Boolean,
+    Array,
+    Object,
+}
+
+impl Kind {
+    fn name(self) -> &'static str {
+        match self {
+            Kind::Integer => "integer",
+            Kind::Number => "number",
+            Kind::String => "string",
+            Kind::Boolean => "boolean",
+            Kind::Array => "array",
+            Kind::Object => "object",
+        }
+    }
+
+    fn matches(self, value: &Json) -> bool {
+        match self {
+            Kind::Integer => value.is_u64(),
+            Kind::Number => value.is_number(),
+            Kind::String => value.is_string(),
+            Kind::Boolean => value.is_boolean(),
+            Kind::Array => value.is_array(),
+            // 单元枚举序列化为字符串
+            Kind::Object => value.is_object() || value.is_string(),
+        }
+    }
+}
+
+struct Field {
+    name: &'static str,
+    kind: Kind,
+    /// 可为null
+    nullable: bool,
+    unit: Option<&'static str>,
+    min: Option<f64>,
+    max: Option<f64>,
+    label_zh: &'static str,
+    label_en: &'static str,
+}
+
+impl Field {
+    /// 按错误信息的语言选择名称
+    fn label(&self) -> &'static str {
+        match error::lang() {
+            Lang::Zh => self.label_zh,
+            Lang::En => self.label_en,
+        }
+    }
+}
+
+const BASE: Field = Field {
+    name: "",
+    kind: Kind::Number,
+    nullable: false,
+    unit: None,
+    min: None,
+    max: None,
+    label_zh: "",
+    label_en: "",
+};
+
+/// 与`TLCConfig`的可序列化字段一一对应，顺序一致
+const FIELDS: &[Field] = &[
+    Field {
+        name: "version",
+        kind: Kind::Integer,
+        label_zh: "配置文件版本",
+        label_en: "Config version",
+        ..BASE
+    },
+    Field {
+        name: "case_name",
+        kind: Kind::String,
+        label_zh: "实验组名称",
+        label_en: "Case name",
+        ..BASE
+    },
+    Field {
+        name: "tags",
+        kind: Kind::Object,
+        label_zh: "工况标签",
+        label_en: "Tags",
+        ..BASE
+    },
+    Field {
+        name: "save_dir",
+        kind: Kind::String,
+        label_zh: "保存目录",
+        label_en: "Save directory",
+        ..BASE
+    },
+    Field {
+        name: "output_layout",
+        kind: Kind::Object,
+        label_zh: "输出目录结构",
+        label_en: "Output layout",
+        ..BASE
+    },
+    Field {
+        name: "app_dir",
+        kind: Kind::String,
+        label_zh: "应用数据目录",
+        label_en: "App data directory",
+        ..BASE
+    },
+    Field {
+        name: "video_path",
+        kind: Kind::String,
+        label_zh: "视频文件路径",
+        label_en: "Video path",
+        ..BASE
+    },
+    Field {
+        name: "daq_path",
+        kind: Kind::String,
+        label_zh: "数采文件路径",
+        label_en: "DAQ path",
+        ..BASE
+    },
+    Field {
+        name: "input_hashes",
+        kind: Kind::Object,
+        label_zh: "输入文件校验和",
+        label_en: "Input file hashes",
+        ..BASE
+    },
+    Field {
+        name: "start_frame",
+        kind: Kind::Integer,
+        unit: Some("frame"),
+        min: Some(0.),
+        label_zh: "视频起始帧",
+        label_en: "Start frame",
+        ..BASE
+    },
+    Field {
+        name: "total_frames",
+        kind: Kind::Integer,
+        unit: Some("frame"),
+        min: Some(0.),
+        label_zh: "视频总帧数",
+        label_en: "Total frames",
+        ..BASE
+    },
+    Field {
+        name: "frame_rate",
+        kind: Kind::Integer,
+        unit: Some("Hz"),
+        min: Some(0.),
+        label_zh: "视频帧率",
+        label_en: "Frame rate",
+        ..BASE
+    },
+    Field {
+        name: "start_row",
+        kind: Kind::Integer,
+        min: Some(0.),
+        label_zh: "数采起始行",
+        label_en: "DAQ start row",
+        ..BASE
+    },
+    Field {
+        name: "total_rows",
+        kind: Kind::Integer,
+        min: Some(0.),
+        label_zh: "数采总行数",
+        label_en: "DAQ total rows",
+        ..BASE
+    },
+    Field {
+        name: "daq_sheet",
+        kind: Kind::Object,
+        label_zh: "数采worksheet",
+        label_en: "DAQ worksheet",
+        ..BASE
+    },
+    Field {
+        name: "daq_cell_offset",
+        kind: Kind::Array,
+        label_zh: "数采起始单元格",
+        label_en: "DAQ cell offset",
+        ..BASE
+    },
+    Field {
+        name: "daq_sample_rate",
+        kind: Kind::Number,
+        unit: Some("Hz"),
+        min: Some(0.),
+        label_zh: "数采频率",
+        label_en: "DAQ sample rate",
+        ..BASE
+    },
+    Field {
+        name: "daq_resample",
+        kind: Kind::Object,
+        label_zh: "数采重采样方法",
+        label_en: "DAQ resampling",
+        ..BASE
+    },
+    Field {
+        name: "daq_time_column",
+        kind: Kind::Integer,
+        nullable: true,
+        min: Some(0.),
+        label_zh: "数采时间列",
+        label_en: "DAQ time column",
+        ..BASE
+    },
+    Field {
+        name: "frame_num",
+        kind: Kind::Integer,
+        unit: Some("frame"),
+        min: Some(0.),
+        label_zh: "处理帧数",
+        label_en: "Frame count",
+        ..BASE
+    },
+    Field {
+        name: "video_shape",
+        kind: Kind::Array,
+        unit: Some("px"),
+        label_zh: "视频尺寸",
+        label_en: "Video shape",
+        ..BASE
+    },
+    Field {
+        name: "top_left_pos",
+        kind: Kind::Array,
+        unit: Some("px"),
+        label_zh: "计算区域左上角",
+        label_en: "Region top-left",
+        ..BASE
+    },
+    Field {
+        name: "region_shape",
+        kind: Kind::Array,
+        unit: Some("px"),
+        label_zh: "计算区域尺寸",
+        label_en: "Region shape",
+        ..BASE
+    },
+    Field {
+        name: "mask",
+        kind: Kind::Object,
+        nullable: true,
+        label_zh: "掩膜",
+        label_en: "Mask",
+        ..BASE
+    },
+    Field {
+        name: "reference_patch",
+        kind: Kind::Array,
+        nullable: true,
+        unit: Some("px"),
+        label_zh: "亮度参考区域",
+        label_en: "Reference patch",
+        ..BASE
+    },
+    Field {
+        name: "thermocouples",
+        kind: Kind::Array,
+        label_zh: "热电偶",
+        label_en: "Thermocouples",
+        ..BASE
+    },
+    Field {
+        name: "daq_filter",
+        kind: Kind::Object,
+        label_zh: "参考温度预处理",
+        label_en: "DAQ filter",
+        ..BASE
+    },
+    Field {
+        name: "interp_method",
+        kind: Kind::Object,
+        label_zh: "插值方法",
+        label_en: "Interpolation method",
+        ..BASE
+    },
+    Field {
+        name: "extrapolation_limit",
+        kind: Kind::Number,
+        unit: Some("°C"),
+        min: Some(0.),
+        label_zh: "外插允许超出量",
+        label_en: "Extrapolation limit",
+        ..BASE
+    },
+    Field {
+        name: "lazy_interp",
+        kind: Kind::Boolean,
+        label_zh: "求解时再插值",
+        label_en: "Lazy interpolation",
+        ..BASE
+    },
+    Field {
+        name: "filter_in_place",
+        kind: Kind::Boolean,
+        label_zh: "就地滤波",
+        label_en: "Filter in place",
+        ..BASE
+    },
+    Field {
+        name: "filter_method",
+        kind: Kind::Object,
+        label_zh: "滤波方法",
+        label_en: "Filter method",
+        ..BASE
+    },
+    Field {
+        name: "drift_correction",
+        kind: Kind::Object,
+        label_zh: "光照漂移修正",
+        label_en: "Drift correction",
+        ..BASE
+    },
+    Field {
+        name: "defect_frames",
+        kind: Kind::Integer,
+        unit: Some("frame"),
+        min: Some(0.),
+        label_zh: "坏点检测帧数",
+        label_en: "Defect detection frames",
+        ..BASE
+    },
+    Field {
+        name: "peak_method",
+        kind: Kind::Object,
+        label_zh: "峰值检测方法",
+        label_en: "Peak detection method",
+        ..BASE
+    },
+    Field {
+        name: "events",
+        kind: Kind::Object,
+        label_zh: "多次加热划分",
+        label_en: "Heating events",
+        ..BASE
+    },
+    Field {
+        name: "iteration_method",
+        kind: Kind::Object,
+        label_zh: "迭代求解方法",
+        label_en: "Iteration method",
+        ..BASE
+    },
+    Field {
+        name: "coarse_stride",
+        kind: Kind::Integer,
+        unit: Some("px"),
+        min: Some(0.),
+        label_zh: "粗网格间隔",
+        label_en: "Coarse stride",
+        ..BASE
+    },
+    Field {
+        name: "t0_estimation",
+        kind: Kind::Object,
+        label_zh: "初始壁温估计",
+        label_en: "Initial temperature estimation",
+        ..BASE
+    },
+    Field {
+        name: "peak_temp",
+        kind: Kind::Number,
+        unit: Some("°C"),
+        min: Some(0.),
+        max: Some(100.),
+        label_zh: "峰值温度",
+        label_en: "Peak temperature",
+        ..BASE
+    },
+    Field {
+        name: "solid_thermal_conductivity",
+        kind: Kind::Number,
+        unit: Some("W/(m·K)"),
+        min: Some(0.),
+        label_zh: "固体导热系数",
+        label_en: "Solid thermal conductivity",
+        ..BASE
+    },
+    Field {
+        name: "solid_thermal_diffusivity",
+        kind: Kind::Number,
+        unit: Some("m²/s"),
+        min: Some(0.),
+        label_zh: "固体热扩散系数",
+        label_en: "Solid thermal diffusivity",
+        ..BASE
+    },
+    Field {
+        name: "solid_thermal_conductivity_curve",
+        kind: Kind::Object,
+        nullable: true,
+        label_zh: "变物性导热系数",
+        label_en: "Thermal conductivity curve",
+        ..BASE
+    },
+    Field {
+        name: "solid_thermal_diffusivity_curve",
+        kind: Kind::Object,
+        nullable: true,
+        label_zh: "变物性热扩散系数",
+        label_en: "Thermal diffusivity curve",
+        ..BASE
+    },
+    Field {
+        name: "model_thickness",
+        kind: Kind::Number,
+        unit: Some("m"),
+        min: Some(0.),
+        label_zh: "模型厚度",
+        label_en: "Model thickness",
+        ..BASE
+    },
+    Field {
+        name: "characteristic_length",
+        kind: Kind::Number,
+        unit: Some("m"),
+        min: Some(0.),
+        label_zh: "特征长度",
+        label_en: "Characteristic length",
+        ..BASE
+    },
+    Field {
+        name: "air_thermal_conductivity",
+        kind: Kind::Number,
+        unit: Some("W/(m·K)"),
+        min: Some(0.),
+        label_zh: "空气导热系数",
+        label_en: "Air thermal conductivity",
+        ..BASE
+    },
+    Field {
+        name: "regulator",
+        kind: Kind::Array,
+        label_zh: "热电偶修正系数",
+        label_en: "Regulator",
+        ..BASE
+    },
+    Field {
+        name: "pixel_size",
+        kind: Kind::Number,
+        unit: Some("mm"),
+        min: Some(0.),
+        label_zh: "像素尺寸",
+        label_en: "Pixel size",
+        ..BASE
+    },
+    Field {
+        name: "export_options",
+        kind: Kind::Object,
+        label_zh: "导出选项",
+        label_en: "Export options",
+        ..BASE
+    },
+    Field {
+        name: "plot_options",
+        kind: Kind::Object,
+        label_zh: "绘图选项",
+        label_en: "Plot options",
+        ..BASE
+    },
+    Field {
+        name: "nu_smoothing",
+        kind: Kind::Object,
+        label_zh: "Nu空间平滑",
+        label_en: "Nu smoothing",
+        ..BASE
+    },
+    Field {
+        name: "colormap",
+        kind: Kind::Object,
+        label_zh: "colormap",
+        label_en: "Colormap",
+        ..BASE
+    },
+    Field {
+        name: "reverse_colormap",
+        kind: Kind::Boolean,
+        label_zh: "反转colormap",
+        label_en: "Reverse colormap",
+        ..BASE
+    },
+    Field {
+        name: "nu_correlation",
+        kind: Kind::Object,
+        label_zh: "参考关联式",
+        label_en: "Nu correlation",
+        ..BASE
+    },
+    Field {
+        name: "reynolds",
+        kind: Kind::Number,
+        min: Some(0.),
+        label_zh: "雷诺数",
+        label_en: "Reynolds number",
+        ..BASE
+    },
+    Field {
+        name: "prandtl",
+        kind: Kind::Number,
+        min: Some(0.),
+        label_zh: "普朗特数",
+        label_en: "Prandtl number",
+        ..BASE
+    },
+    Field {
+        name: "post_scripts",
+        kind: Kind::Array,
+        label_zh: "后处理脚本",
+        label_en: "Post-processing scripts",
+        ..BASE
+    },
+    Field {
+        name: "threads",
+        kind: Kind::Object,
+        label_zh: "线程",
+        label_en: "Threads",
+        ..BASE
+    },
+    Field {
+        name: "memory_budget",
+        kind: Kind::Integer,
+        unit: Some("MiB"),
+        min: Some(0.),
+        label_zh: "内存预算",
+        label_en: "Memory budget",
+        ..BASE
+    },
+];
+
+/// 默认配置，不读取视频与数采文件
+fn default_config() -> TLCResult<Map<String, Json>> {
+    let config: TLCConfig =
+        serde_json::from_value(json!({})).map_err(|err| awsl!(ConfigError, err))?;
+    match serde_json::to_value(config).map_err(|err| awsl!(err))? {
+        Json::Object(map) => Ok(map),
+        _ => Err(awsl!(ConfigError, "配置应序列化为对象")),
+    }
+}
+
+/// `TLCConfig`的JSON Schema，各属性另附`x-unit`（单位）与`x-label-en`（英文名称），
+/// `title`为中文名称，`default`为默认值
+pub fn config_schema() -> TLCResult<Json> {
+    let defaults = default_config()?;
+    let properties: Map<_, _> = FIELDS
+        .iter()
+        .map(|field| {
+            let mut property = Map::new();
+            let kind = field.kind.name();
+            property.insert(
+                "type".to_owned(),
+                if field.nullable {
+                    json!([kind, "null"])
+                } else {
+                    json!(kind)
+                },
+            );
+            property.insert("title".to_owned(), json!(field.label_zh));
+            property.insert("x-label-en".to_owned(), json!(field.label_en));
+            if let Some(unit) = field.unit {
+                property.insert("x-unit".to_owned(), json!(unit));
+            }
+            if let Some(min) = field.min {
+                property.insert("minimum".to_owned(), json!(min));
+            }
+            if let Some(max) = field.max {
+                property.insert("maximum".to_owned(), json!(max));
+            }
+            if let Some(default) = defaults.get(field.name) {
+                property.insert("default".to_owned(), default.clone());
+            }
+            (field.name.to_owned(), Json::Object(property))
+        })
+        .collect();
+
+    Ok(json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "TLCConfig",
+        "type": "object",
+        "properties": properties,
+    }))
+}
+
+/// 按描述检查单个配置项的类型与取值范围，复杂类型的内部结构留给反序列化检查
+pub fn check_field(name: &str, value: &Json) -> TLCResult<()> {
+    let field = FIELDS
+        .iter()
+        .find(|field| field.name == name)
+        .ok_or(awsl!(ConfigError, format!("未知的配置项{}", name)))?;
+    if value.is_null() && field.nullable {
+        return Ok(());
+    }
+    if !field.kind.matches(value) {
+        return Err(awsl!(
+            ConfigError,
+            format!("{}（{}）应为{}", field.label(), name, field.kind.name())
+        ));
+    }
+    if let Some(v) = value.as_f64() {
+        let below = field.min.map_or(false, |min| v < min);
+        let above = field.max.map_or(false, |max| v > max);
+        if below || above {
+            let bound = |b: Option<f64>| b.map_or("∞".to_owned(), |b| b.to_string());
+            return Err(awsl!(
+                ConfigError,
+                format!(
+                    "{}（{}）为{}，应在{}到{}之间",
+                    field.label(),
+                    name,
+                    v,
+                    bound(field.min),
+                    bound(field.max)
+                )
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn schema_covers_config() {
+        let defaults = default_config().unwrap();
+        let schema = config_schema().unwrap();
+        let properties = schema["properties"].as_object().unwrap();
+        let mut names: Vec<_> = defaults.keys().collect();
+        let mut described: Vec<_> = properties.keys().collect();
+        names.sort_unstable();
+        described.sort_unstable();
+        assert_eq!(names, described);
+        assert_eq!(properties["peak_temp"]["x-unit"], "°C");
+
+        assert!(check_field("peak_temp", &json!(35.48)).is_ok());
+        assert!(check_field("peak_temp", &json!(-1)).is_err());
+        assert!(check_field("peak_temp", &json!("35")).is_err());
+        assert!(check_field("frame_rate", &json!(25.5)).is_err());
+        assert!(check_field("daq_time_column", &Json::Null).is_ok());
+        assert!(check_field("filter_method", &json!("No")).is_ok());
+        assert!(check_field("peak_tmp", &json!(35)).is_err());
+    }
+}
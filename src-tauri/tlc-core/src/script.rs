@@ -0,0 +1,248 @@
+//! 按工况配置的后处理脚本（[rhai](https://rhai.rs)），计算内置后处理之外的自定义指标
+//!
+//! 脚本中可读取`nu`（Nu分布，按行排列的二维数组）、`t2d`（各热电偶的温度历史）与`config`，
+//! 另提供忽略NaN的`nan_mean`、`nan_min`、`nan_max`。最后一个表达式的值即为结果：
+//! 各列等长的数值map保存为.csv表格，其他值保存为.json，`()`不保存。
+//!
+//! ```text
+//! let row = nu[nu.len() / 2];
+//! #{ mean: nan_mean(row), max: nan_max(row) }
+//! ```
+
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+use ndarray::ArrayView2;
+use rhai::{Array, Dynamic, Engine, Scope, FLOAT};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as Json;
+
+use super::{error::TLCResult, io, TLCData};
+use crate::awsl;
+
+/// 单个脚本的运算步数上限，每次保存都会运行，避免死循环卡住保存
+const MAX_SCRIPT_OPERATIONS: u64 = 10_000_000;
+
+/// 一个后处理脚本
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PostScript {
+    /// 结果文件名后缀，`{case_name}_{name}.csv`或`.json`，只能包含字母、数字、`_`与`-`
+    pub name: String,
+    pub source: String,
+}
+
+/// 名称直接拼入文件名，含`/`、`..`或`.`时会写到工况目录之外或被当作扩展名截断
+pub(crate) fn valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+impl PostScript {
+    pub(crate) fn check_name(&self) -> TLCResult<()> {
+        if valid_name(&self.name) {
+            Ok(())
+        } else {
+            Err(awsl!(
+                ConfigError,
+                format!("后处理脚本名称{}只能包含字母、数字、_与-", self.name)
+            ))
+        }
+    }
+}
+
+fn to_dynamic_2d(arr: ArrayView2<f32>) -> Dynamic {
+    arr.rows()
+        .into_iter()
+        .map(|row| {
+            row.iter()
+                .map(|&v| Dynamic::from_float(v as FLOAT))
+                .collect::<Array>()
+                .into()
+        })
+        .collect::<Array>()
+        .into()
+}
+
+/// 嵌套数组中的所有非NaN数值
+fn numbers(arr: &Array, out: &mut Vec<FLOAT>) {
+    for v in arr {
+        if let Ok(v) = v.as_float() {
+            if !v.is_nan() {
+                out.push(v);
+            }
+        } else if let Ok(v) = v.as_int() {
+            out.push(v as FLOAT);
+        } else if let Some(inner) = v.read_lock::<Array>() {
+            numbers(&inner, out);
+        }
+    }
+}
+
+fn nan_fold(arr: &mut Array, f: fn(&[FLOAT]) -> FLOAT) -> FLOAT {
+    let mut values = Vec::new();
+    numbers(arr, &mut values);
+    if values.is_empty() {
+        FLOAT::NAN
+    } else {
+        f(&values)
+    }
+}
+
+fn engine() -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_SCRIPT_OPERATIONS);
+    engine.register_fn("nan_mean", |arr: &mut Array| {
+        nan_fold(arr, |v| v.iter().sum::<FLOAT>() / v.len() as FLOAT)
+    });
+    engine.register_fn("nan_min", |arr: &mut Array| {
+        nan_fold(arr, |v| v.iter().copied().fold(FLOAT::INFINITY, FLOAT::min))
+    });
+    engine.register_fn("nan_max", |arr: &mut Array| {
+        nan_fold(arr, |v| {
+            v.iter().copied().fold(FLOAT::NEG_INFINITY, FLOAT::max)
+        })
+    });
+
+    engine
+}
+
+/// 各列等长的数值map，作为表格保存
+fn as_table(result: &Json) -> Option<(Vec<&str>, Vec<Vec<f32>>)> {
+    let map = result.as_object()?;
+    let mut header = Vec::with_capacity(map.len());
+    let mut columns = Vec::with_capacity(map.len());
+    for (name, column) in map {
+        let column = column
+            .as_array()?
+            .iter()
+            .map(|v| match v {
+                Json::Number(v) => v.as_f64().map(|v| v as f32),
+                Json::Null => Some(f32::NAN),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()?;
+        header.push(name.as_str());
+        columns.push(column);
+    }
+    let len = columns.first()?.len();
+    if columns.iter().any(|column| column.len() != len) {
+        return None;
+    }
+
+    Some((header, columns))
+}
+
+impl TLCData {
+    /// 依次运行配置中的后处理脚本，返回保存的结果文件，已求解后调用
+    pub fn run_post_scripts(&mut self) -> TLCResult<Vec<String>> {
+        if self.config.post_scripts.is_empty() {
+            return Ok(Vec::new());
+        }
+        if self.t2d.is_none() {
+            self.init_t2d()?;
+        }
+        let config = rhai::serde::to_dynamic(&self.config).map_err(|err| awsl!(err))?;
+        let mut scope = Scope::new();
+        scope.push_constant_dynamic("nu", to_dynamic_2d(self.get_nu2d()?));
+        scope.push_constant_dynamic("t2d", to_dynamic_2d(self.get_t2d()?));
+        scope.push_constant_dynamic("config", config);
+        let engine = engine();
+        let base = scope.len();
+
+        let mut saved = Vec::new();
+        for script in &self.config.post_scripts {
+            // 配置文件中的脚本未经过`set_post_scripts`
+            script.check_name()?;
+            // 各脚本互不影响，清除上一个脚本定义的变量
+            scope.rewind(base);
+            let result = engine
+                .eval_with_scope::<Dynamic>(&mut scope, &script.source)
+                .map_err(|err| {
+                    awsl!(
+                        HandleError,
+                        format!("后处理脚本{}运行失败：{}", script.name, err)
+                    )
+                })?;
+            if result.is_unit() {
+                continue;
+            }
+            let result: Json = rhai::serde::from_dynamic(&result).map_err(|err| awsl!(err))?;
+            // 工况名中可能有`.`，不用`with_extension`
+            let file_name = format!("{}_{}", self.config.case_name, script.name);
+            let output_path = |ext| {
+                Path::new(&self.config.data_path).with_file_name(format!("{}.{}", file_name, ext))
+            };
+            let path = match as_table(&result) {
+                Some((header, columns)) => {
+                    let path = output_path("csv");
+                    let columns: Vec<_> = columns.iter().map(|c| c.as_slice()).collect();
+                    io::save_table(&path, &header, &columns)?;
+                    path
+                }
+                None => {
+                    let path = output_path("json");
+                    let file =
+                        File::create(&path).map_err(|err| awsl!(DataSaveError, err, path))?;
+                    serde_json::to_writer_pretty(BufWriter::new(file), &result)
+                        .map_err(|err| awsl!(err))?;
+                    path
+                }
+            };
+            saved.push(path.to_str().ok_or(awsl!(path))?.to_owned());
+        }
+
+        Ok(saved)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ndarray::prelude::*;
+    use serde_json::json;
+
+    use super::*;
+
+    fn try_eval(source: &str) -> Result<Dynamic, Box<rhai::EvalAltResult>> {
+        let nu = array![[1., f32::NAN], [3., 5.]];
+        let mut scope = Scope::new();
+        scope.push_constant_dynamic("nu", to_dynamic_2d(nu.view()));
+
+        engine().eval_with_scope::<Dynamic>(&mut scope, source)
+    }
+
+    fn eval(source: &str) -> Json {
+        rhai::serde::from_dynamic(&try_eval(source).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn post_script() {
+        assert_eq!(eval("nan_mean(nu)"), json!(3.));
+        assert_eq!(
+            eval("#{ mean: nan_mean(nu), max: nan_max(nu[1]) }")["max"],
+            json!(5.)
+        );
+        // 只读
+        assert!(try_eval("nu = []").is_err());
+        assert!(try_eval("nu[0][0] = 2.0").is_err());
+
+        let table = eval("#{ x: [0, 1], nu: nu[1] }");
+        let (header, columns) = as_table(&table).unwrap();
+        assert_eq!(header, ["nu", "x"]);
+        assert_eq!(columns, [vec![3., 5.], vec![0., 1.]]);
+        assert!(as_table(&eval("#{ x: [0], nu: nu[1] }")).is_none());
+        assert!(as_table(&eval("nan_min(nu)")).is_none());
+    }
+
+    #[test]
+    fn script_names() {
+        assert!(valid_name("profile_x-2"));
+        for name in ["", "v1.2", "../nu", "a/b", "a\\b", "结果"].iter() {
+            assert!(!valid_name(name), "{}", name);
+        }
+        // 死循环在步数上限处停止
+        assert!(try_eval("loop {}").is_err());
+    }
+}
@@ -0,0 +1,1063 @@
+use std::collections::BTreeMap;
+use std::f32::{consts::PI, NAN};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+
+use libm::erfcf;
+use ndarray::prelude::*;
+use packed_simd::{f32x8, Simd};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::preprocess::Interp;
+use super::{error::TLCResult, io, postprocess, Stage, TLCConfig, TLCData};
+use crate::awsl;
+
+/// 默认初始对流换热系数
+const DEFAULT_H0: f32 = 50.;
+
+/// 默认最大迭代步数
+const DEFAULT_MAX_ITER_NUM: usize = 10;
+
+/// 默认用参考温度历史的**前4个**数计算初始温度
+const FIRST_FEW_TO_CAL_T0: usize = 4;
+
+/// 两遍求解时在粗网格上向外搜索有效解的最大圈数，超出后以统一初值求解
+const COARSE_SEARCH_RINGS: usize = 2;
+
+/// 通过`register_point_solver`注册的自定义求解器
+static POINT_SOLVERS: RwLock<BTreeMap<String, Arc<dyn PointSolver>>> = RwLock::new(BTreeMap::new());
+
+/// 迭代方法（初值，最大迭代步数）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub enum IterationMethod {
+    NewtonTangent {
+        h0: f32,
+        max_iter_num: usize,
+    },
+    NewtonDown {
+        h0: f32,
+        max_iter_num: usize,
+    },
+    /// 以`name`注册的自定义求解器
+    Plugin {
+        name: String,
+        h0: f32,
+    },
+}
+
+impl Default for IterationMethod {
+    fn default() -> Self {
+        Self::NewtonTangent {
+            h0: DEFAULT_H0,
+            max_iter_num: DEFAULT_MAX_ITER_NUM,
+        }
+    }
+}
+
+/// 初始壁面温度的估计方法
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub enum T0Estimation {
+    /// 各点参考温度前n帧的平均值
+    Mean(usize),
+    /// 各点参考温度前n帧的中位数，受加热器开启瞬间的跳变影响更小
+    Median(usize),
+    /// 用户指定的统一初始温度（°C）
+    Uniform(f32),
+}
+
+impl Default for T0Estimation {
+    fn default() -> Self {
+        Self::Mean(FIRST_FEW_TO_CAL_T0)
+    }
+}
+
+impl IterationMethod {
+    /// 迭代初值
+    pub fn h0(&self) -> f32 {
+        match *self {
+            IterationMethod::NewtonTangent { h0, .. }
+            | IterationMethod::NewtonDown { h0, .. }
+            | IterationMethod::Plugin { h0, .. } => h0,
+        }
+    }
+
+    fn point_solver(&self) -> TLCResult<Arc<dyn PointSolver>> {
+        Ok(match *self {
+            IterationMethod::NewtonTangent { max_iter_num, .. } => {
+                Arc::new(newton_tangent(max_iter_num))
+            }
+            IterationMethod::NewtonDown { max_iter_num, .. } => Arc::new(newton_down(max_iter_num)),
+            IterationMethod::Plugin { ref name, .. } => POINT_SOLVERS
+                .read()
+                .map_err(|err| awsl!(err))?
+                .get(name)
+                .cloned()
+                .ok_or(awsl!(ConfigError, format!("未注册名为{}的求解器", name)))?,
+        })
+    }
+}
+
+/// 单个像素的求解结果
+#[derive(Debug, Clone, Copy)]
+pub struct PointSolution {
+    /// 对流换热系数，发散时为NaN
+    pub h: f32,
+    /// 迭代步数，计入`SolveStats::mean_iterations`
+    pub iterations: usize,
+}
+
+/// 由单个像素的参考温度历史与峰值帧反求对流换热系数，用于在不修改求解流程的前提下试验
+/// 其他反问题求解方法，闭包`Fn(&PointData, f32) -> PointSolution`可直接作为求解器
+pub trait PointSolver: Send + Sync {
+    /// 以`h0`为初值求解，`h0`在两遍求解时为邻近像素的解
+    fn solve(&self, point: &PointData, h0: f32) -> PointSolution;
+}
+
+impl<F> PointSolver for F
+where
+    F: Fn(&PointData, f32) -> PointSolution + Send + Sync,
+{
+    fn solve(&self, point: &PointData, h0: f32) -> PointSolution {
+        self(point, h0)
+    }
+}
+
+/// 以`name`注册求解器，之后以`IterationMethod::Plugin`选用，同名时替换之前注册的求解器
+pub fn register_point_solver<S: PointSolver + 'static>(name: &str, solver: S) -> TLCResult<()> {
+    POINT_SOLVERS
+        .write()
+        .map_err(|err| awsl!(err))?
+        .insert(name.to_owned(), Arc::new(solver));
+
+    Ok(())
+}
+
+/// 已注册的求解器名称
+pub fn point_solvers() -> TLCResult<Vec<String>> {
+    Ok(POINT_SOLVERS
+        .read()
+        .map_err(|err| awsl!(err))?
+        .keys()
+        .cloned()
+        .collect())
+}
+
+impl T0Estimation {
+    /// 估计初始温度所用的帧数，峰值帧不大于该帧数的点无法求解
+    pub fn frames(self) -> usize {
+        match self {
+            T0Estimation::Mean(n) | T0Estimation::Median(n) => n,
+            T0Estimation::Uniform(_) => 0,
+        }
+    }
+
+    /// `temps`为该点的参考温度历史
+    pub fn estimate(self, temps: &[f32]) -> f32 {
+        match self {
+            T0Estimation::Mean(n) => {
+                let first = &temps[..n.min(temps.len())];
+                first.iter().sum::<f32>() / first.len() as f32
+            }
+            T0Estimation::Median(n) => {
+                let mut first = temps[..n.min(temps.len())].to_vec();
+                first
+                    .sort_unstable_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+                match first.len() {
+                    0 => NAN,
+                    len if len % 2 == 1 => first[len / 2],
+                    len => (first[len / 2 - 1] + first[len / 2]) / 2.,
+                }
+            }
+            T0Estimation::Uniform(t0) => t0,
+        }
+    }
+}
+
+/// 随温度变化的固体物性，在各点的定性温度下取值
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub enum PropertyCurve {
+    /// (温度°C, 物性值)，温度升序，区间内线性插值，区间外取端点值
+    Table(Vec<(f32, f32)>),
+    /// 多项式系数，依次为常数项、一次项、二次项……
+    Polynomial(Vec<f32>),
+}
+
+impl PropertyCurve {
+    pub fn eval(&self, temp: f32) -> f32 {
+        match self {
+            PropertyCurve::Table(table) => match table.iter().position(|&(t, _)| t >= temp) {
+                None => table.last().map_or(NAN, |&(_, v)| v),
+                Some(0) => table[0].1,
+                Some(i) => {
+                    let ((t0, v0), (t1, v1)) = (table[i - 1], table[i]);
+                    v0 + (v1 - v0) * (temp - t0) / (t1 - t0)
+                }
+            },
+            PropertyCurve::Polynomial(coefs) => {
+                coefs.iter().rev().fold(0., |acc, &c| acc * temp + c)
+            }
+        }
+    }
+}
+
+/// 参数扫描中可变化的求解参数，改变它们只需重新求解，视频、滤波、峰值与插值均可复用
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub enum SweepParam {
+    PeakTemp,
+    SolidThermalConductivity,
+    SolidThermalDiffusivity,
+    AirThermalConductivity,
+    CharacteristicLength,
+    /// 迭代初值，迭代方法与最大步数不变
+    H0,
+}
+
+/// 参数扫描中单个取值的结果
+#[derive(Debug, Serialize)]
+pub struct SweepResult {
+    pub value: f32,
+    pub nu_nan_mean: f32,
+    /// 该取值下Nu矩阵的保存路径
+    pub nu_path: String,
+}
+
+/// 截断帧数历史后的一次求解结果
+#[derive(Debug, Serialize)]
+pub struct ConvergenceStep {
+    /// 使用的帧数占总帧数的比例
+    pub fraction: f32,
+    pub frames: usize,
+    pub nu_nan_mean: f32,
+    /// 有效（非NaN）像素占比，截断处仍未达到峰值的像素无效
+    pub valid_ratio: f32,
+    /// 各指定点的Nu
+    pub point_nus: Vec<f32>,
+}
+
+/// 实验时长收敛性检查的结果
+#[derive(Debug, Serialize)]
+pub struct Convergence {
+    pub steps: Vec<ConvergenceStep>,
+    pub table_path: String,
+    pub plot_path: String,
+}
+
+/// 最近一次求解的统计
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct SolveStats {
+    /// 求解的像素数，遮罩排除与峰值过早的像素不计入
+    pub solved: usize,
+    /// nu2d中NaN的个数，包括遮罩排除、峰值过早与发散的像素
+    pub nan_count: usize,
+    /// 平均迭代步数，两遍求解时按求解次数平均
+    pub mean_iterations: f32,
+}
+
+/// `take_solution`取出的求解结果及其阶段哈希
+pub(crate) struct SolutionSnapshot {
+    peak_frames: Option<Vec<usize>>,
+    nu2d: Option<Array2<f32>>,
+    nu_nan_mean: Option<f32>,
+    solve_stats: Option<SolveStats>,
+    smoothed_nu2d: Option<Array2<f32>>,
+    hashes: Vec<(Stage, Option<u64>)>,
+}
+
+/// temporary fake SIMD wrapper of erfcf
+fn erfcf_simd(arr: Simd<[f32; 8]>) -> Simd<[f32; 8]> {
+    let (x0, x1, x2, x3, x4, x5, x6, x7): (f32, f32, f32, f32, f32, f32, f32, f32) =
+        unsafe { std::mem::transmute(arr) };
+    f32x8::new(
+        erfcf(x0),
+        erfcf(x1),
+        erfcf(x2),
+        erfcf(x3),
+        erfcf(x4),
+        erfcf(x5),
+        erfcf(x6),
+        erfcf(x7),
+    )
+}
+
+/// struct that stores necessary information for solving the equation
+pub struct PointData<'a> {
+    /// 从加热开始计的峰值帧
+    pub peak_frame: usize,
+    /// 从加热开始的参考温度历史
+    pub temps: &'a [f32],
+    /// 初始壁面温度
+    pub t0: f32,
+    pub peak_temp: f32,
+    /// 帧间隔（s）
+    pub dt: f32,
+    /// 定性温度下的固体导热系数
+    pub solid_thermal_conductivity: f32,
+    /// 定性温度下的固体热扩散系数
+    pub solid_thermal_diffusivity: f32,
+}
+
+impl PointData<'_> {
+    /// semi-infinite plate heat transfer equation of each pixel(simd)
+    /// ### Return:
+    /// equation and its derivative
+    pub fn thermal_equation(&self, h: f32) -> (f32, f32) {
+        let (k, a, dt, temps, t0, tw, peak_frame) = (
+            self.solid_thermal_conductivity,
+            self.solid_thermal_diffusivity,
+            self.dt,
+            self.temps,
+            self.t0,
+            self.peak_temp,
+            self.peak_frame,
+        );
+        let (mut sum, mut diff_sum) = (f32x8::splat(0.), f32x8::splat(0.));
+
+        let mut frame = 1;
+        while frame + f32x8::lanes() < peak_frame {
+            let delta_temp = unsafe {
+                f32x8::from_slice_unaligned_unchecked(&temps[frame..])
+                    - f32x8::from_slice_unaligned_unchecked(&temps[frame - 1..])
+            };
+            let at = a
+                * dt
+                * f32x8::new(
+                    (peak_frame - frame) as f32,
+                    (peak_frame - frame - 1) as f32,
+                    (peak_frame - frame - 2) as f32,
+                    (peak_frame - frame - 3) as f32,
+                    (peak_frame - frame - 4) as f32,
+                    (peak_frame - frame - 5) as f32,
+                    (peak_frame - frame - 6) as f32,
+                    (peak_frame - frame - 7) as f32,
+                );
+            let exp_erfc = (h.powf(2.) / k.powf(2.) * at).exp() * erfcf_simd(h / k * at.sqrt());
+            let step = (1. - exp_erfc) * delta_temp;
+            let d_step = -delta_temp
+                * (2. * at.sqrt() / k / PI.sqrt() - (2. * at * h * exp_erfc) / k.powf(2.));
+
+            frame += f32x8::lanes();
+            sum += step;
+            diff_sum += d_step;
+        }
+
+        let (mut sum, mut diff_sum) = (sum.sum(), diff_sum.sum());
+
+        while frame < peak_frame {
+            let delta_temp = unsafe { temps.get_unchecked(frame) - temps.get_unchecked(frame - 1) };
+            let at = a * dt * (peak_frame - frame) as f32;
+            let exp_erfc = (h.powf(2.) / k.powf(2.) * at).exp() * erfcf(h / k * at.sqrt());
+            let step = (1. - exp_erfc) * delta_temp;
+            let d_step = -delta_temp
+                * (2. * at.sqrt() / k / PI.sqrt() - (2. * at * h * exp_erfc) / k.powf(2.));
+
+            frame += 1;
+            sum += step;
+            diff_sum += d_step;
+        }
+
+        (tw - t0 - sum, diff_sum)
+    }
+}
+
+fn solution(h: f32, iterations: usize) -> PointSolution {
+    PointSolution { h, iterations }
+}
+
+fn newton_tangent(max_iter_num: usize) -> impl Fn(&PointData, f32) -> PointSolution {
+    move |point_data, h0| {
+        let mut h = h0;
+        for iter_num in 1..=max_iter_num {
+            let (f, df) = point_data.thermal_equation(h);
+            let next_h = h - f / df;
+            if next_h.abs() > 10000. {
+                return solution(NAN, iter_num);
+            }
+            if (next_h - h).abs() < 1e-3 {
+                return solution(next_h, iter_num);
+            }
+            h = next_h;
+        }
+
+        solution(h, max_iter_num)
+    }
+}
+
+/// 步长折半的回溯不计入步数
+fn newton_down(max_iter_num: usize) -> impl Fn(&PointData, f32) -> PointSolution {
+    move |point_data, h0| {
+        let mut h = h0;
+        let (mut f, mut df) = point_data.thermal_equation(h);
+        for iter_num in 1..=max_iter_num {
+            let mut lambda = 1.;
+            loop {
+                let next_h = h - lambda * f / df;
+                if (next_h - h).abs() < 1e-3 {
+                    return solution(next_h, iter_num);
+                }
+                let (next_f, next_df) = point_data.thermal_equation(next_h);
+                if next_f.abs() < f.abs() {
+                    h = next_h;
+                    f = next_f;
+                    df = next_df;
+                    break;
+                }
+                lambda /= 2.;
+                if lambda < 1e-3 {
+                    return solution(NAN, iter_num);
+                }
+            }
+            if h.abs() > 10000. {
+                return solution(NAN, iter_num);
+            }
+        }
+
+        solution(h, max_iter_num)
+    }
+}
+
+impl TLCData {
+    pub fn solve(&mut self) -> TLCResult<&mut Self> {
+        if self.peak_frames.is_none() {
+            self.detect_peak()?;
+        }
+        if self.interp.is_none() {
+            self.interp()?;
+        }
+
+        let start = self.event_window()?.0;
+        let timer = self.start_stage("solve");
+        self.solve_nu(start)?;
+        self.finish(timer);
+        // 输入文件已在前面的阶段读取过，校验和计算失败不影响求解结果
+        if let Err(err) = self.record_inputs() {
+            tracing::warn!(%err, "failed to hash input files");
+        }
+        if let Err(err) = self.save_results() {
+            tracing::warn!(%err, "failed to write results metadata");
+        }
+
+        Ok(self)
+    }
+
+    /// 按当前的峰值帧与插值结果求解，`start`为加热开始的帧
+    pub(crate) fn solve_nu(&mut self, start: usize) -> TLCResult<&mut Self> {
+        let solver = self.config.iteration_method.point_solver()?;
+        let h0 = self.config.iteration_method.h0();
+        self.in_pool(|data| data.solve_core(solver.as_ref(), h0, start))?
+    }
+
+    /// 临时替换峰值帧重新求解前取出当前结果，之后用`restore_solution`恢复
+    pub(crate) fn take_solution(&mut self) -> SolutionSnapshot {
+        SolutionSnapshot {
+            peak_frames: self.peak_frames.take(),
+            nu2d: self.nu2d.take(),
+            nu_nan_mean: self.nu_nan_mean.take(),
+            solve_stats: self.solve_stats.take(),
+            smoothed_nu2d: self.smoothed_nu2d.take(),
+            hashes: [Stage::PeakFrames, Stage::Nu, Stage::SmoothedNu]
+                .iter()
+                .map(|stage| (*stage, self.stage_hashes.get(stage).copied()))
+                .collect(),
+        }
+    }
+
+    pub(crate) fn restore_solution(&mut self, snapshot: SolutionSnapshot) {
+        self.peak_frames = snapshot.peak_frames;
+        self.nu2d = snapshot.nu2d;
+        self.nu_nan_mean = snapshot.nu_nan_mean;
+        self.solve_stats = snapshot.solve_stats;
+        self.smoothed_nu2d = snapshot.smoothed_nu2d;
+        for (stage, hash) in snapshot.hashes {
+            match hash {
+                Some(hash) => self.stage_hashes.insert(stage, hash),
+                None => self.stage_hashes.remove(&stage),
+            };
+        }
+    }
+
+    /// 只使用前`fractions`（如0.5、0.6……1.0）比例的帧重新检测峰值并求解，
+    /// 检查Nu平均值与`points`（nu2d中的(行, 列)）处的Nu是否随实验时长收敛，保存表格与曲线图
+    ///
+    /// 截断后在最后一帧仍未达到峰值的像素视为无效，结束后恢复原来的峰值帧与Nu
+    pub fn convergence(
+        &mut self,
+        fractions: &[f32],
+        points: &[(usize, usize)],
+    ) -> TLCResult<Convergence> {
+        if self.peak_frames.is_none() {
+            self.detect_peak()?;
+        }
+        if self.interp.is_none() {
+            self.interp()?;
+        }
+        let frame_num = self.config.frame_num;
+        let peak_method = self.config.peak_method;
+        let filtered_g2d = self.get_filtered_g2d()?;
+        let truncated_peaks: Vec<_> = fractions
+            .iter()
+            .map(|&fraction| {
+                let frames = ((frame_num as f32 * fraction).round() as usize).clamp(1, frame_num);
+                let peaks: Vec<_> = filtered_g2d
+                    .slice(s![..frames, ..])
+                    .axis_iter(Axis(1))
+                    .into_par_iter()
+                    .map(|col| {
+                        let peak = peak_method.locate(col).unwrap_or(0);
+                        // 仍在上升，真正的峰值在截断之后
+                        if peak + 1 == frames && frames < frame_num {
+                            0
+                        } else {
+                            peak
+                        }
+                    })
+                    .collect();
+                (fraction, frames, peaks)
+            })
+            .collect();
+
+        let start = self.event_window()?.0;
+        let snapshot = self.take_solution();
+        let run = || -> TLCResult<Vec<ConvergenceStep>> {
+            let mut steps = Vec::with_capacity(truncated_peaks.len());
+            for (fraction, frames, peaks) in truncated_peaks {
+                self.peak_frames = Some(peaks);
+                self.solve_nu(start)?;
+                let nu2d = self.get_nu2d()?;
+                let valid = nu2d.iter().filter(|nu| !nu.is_nan()).count();
+                let point_nus = points
+                    .iter()
+                    .map(|&pos| nu2d.get(pos).copied().unwrap_or(NAN))
+                    .collect();
+                steps.push(ConvergenceStep {
+                    fraction,
+                    frames,
+                    nu_nan_mean: self.get_nu_nan_mean()?,
+                    valid_ratio: valid as f32 / nu2d.len() as f32,
+                    point_nus,
+                });
+            }
+
+            Ok(steps)
+        };
+        let steps = run();
+        self.restore_solution(snapshot);
+        let steps = steps?;
+
+        let file_name = format!("{}_convergence", self.config.case_name);
+        let table_path = Path::new(&self.config.data_path)
+            .with_file_name(&file_name)
+            .with_extension("csv");
+        let percents: Vec<_> = steps.iter().map(|s| s.fraction * 100.).collect();
+        let frames: Vec<_> = steps.iter().map(|s| s.frames as f32).collect();
+        let means: Vec<_> = steps.iter().map(|s| s.nu_nan_mean).collect();
+        let valid: Vec<_> = steps.iter().map(|s| s.valid_ratio).collect();
+        let point_columns: Vec<Vec<_>> = (0..points.len())
+            .map(|i| steps.iter().map(|s| s.point_nus[i]).collect())
+            .collect();
+        let point_names: Vec<_> = points
+            .iter()
+            .map(|(row, col)| format!("nu_{}_{}", row, col))
+            .collect();
+        let mut header = vec!["frames_percent", "frames", "nu_nan_mean", "valid_ratio"];
+        header.extend(point_names.iter().map(|name| name.as_str()));
+        let mut columns = vec![percents.as_slice(), &frames, &means, &valid];
+        columns.extend(point_columns.iter().map(|column| column.as_slice()));
+        io::save_table(&table_path, &header, &columns)?;
+
+        let plot_path = Path::new(&self.config.plots_path).with_file_name(&file_name);
+        let desc = ("frames (%)", "Nu mean");
+        let plot_path = postprocess::plot_profile(
+            plot_path,
+            &percents,
+            &means,
+            None,
+            desc,
+            &self.config.plot_options,
+        )?;
+
+        Ok(Convergence {
+            steps,
+            table_path: table_path.to_str().ok_or(awsl!(table_path))?.to_owned(),
+            plot_path: plot_path.to_str().ok_or(awsl!(plot_path))?.to_owned(),
+        })
+    }
+
+    /// 依次取`values`求解并保存各自的Nu矩阵，结束后恢复原参数
+    pub fn sweep(&mut self, param: SweepParam, values: &[f32]) -> TLCResult<Vec<SweepResult>> {
+        let original = self.sweep_param(param);
+        let mut sweep = || -> TLCResult<Vec<SweepResult>> {
+            let mut results = Vec::with_capacity(values.len());
+            for (i, &value) in values.iter().enumerate() {
+                self.set_sweep_param(param, value).solve()?;
+                let nu_path = Path::new(&self.config.data_path).with_file_name(format!(
+                    "{}_sweep_{:?}_{}.csv",
+                    self.config.case_name, param, i
+                ));
+                let meta = self.data_meta("Nu", true)?;
+                io::save_data(self.get_nu2d()?, &nu_path, Some(&meta))?;
+                results.push(SweepResult {
+                    value,
+                    nu_nan_mean: self.get_nu_nan_mean()?,
+                    nu_path: nu_path.to_str().ok_or(awsl!(nu_path))?.to_owned(),
+                });
+            }
+
+            Ok(results)
+        };
+        let results = sweep();
+        self.set_sweep_param(param, original);
+
+        results
+    }
+
+    fn sweep_param(&self, param: SweepParam) -> f32 {
+        use SweepParam::*;
+        match param {
+            PeakTemp => self.config.peak_temp,
+            SolidThermalConductivity => self.config.solid_thermal_conductivity,
+            SolidThermalDiffusivity => self.config.solid_thermal_diffusivity,
+            AirThermalConductivity => self.config.air_thermal_conductivity,
+            CharacteristicLength => self.config.characteristic_length,
+            H0 => self.config.iteration_method.h0(),
+        }
+    }
+
+    fn set_sweep_param(&mut self, param: SweepParam, value: f32) -> &mut Self {
+        use SweepParam::*;
+        match param {
+            PeakTemp => self.set_peak_temp(value),
+            SolidThermalConductivity => self.set_solid_thermal_conductivity(value),
+            SolidThermalDiffusivity => self.set_solid_thermal_diffusivity(value),
+            AirThermalConductivity => self.set_air_thermal_conductivity(value),
+            CharacteristicLength => self.set_characteristic_length(value),
+            H0 => {
+                let iteration_method = match self.config.iteration_method.clone() {
+                    IterationMethod::NewtonTangent { max_iter_num, .. } => {
+                        IterationMethod::NewtonTangent {
+                            h0: value,
+                            max_iter_num,
+                        }
+                    }
+                    IterationMethod::NewtonDown { max_iter_num, .. } => {
+                        IterationMethod::NewtonDown {
+                            h0: value,
+                            max_iter_num,
+                        }
+                    }
+                    IterationMethod::Plugin { name, .. } => {
+                        IterationMethod::Plugin { name, h0: value }
+                    }
+                };
+                self.set_iteration_method(iteration_method)
+            }
+        }
+    }
+
+    /// 求解各像素共用的数据，`start`为加热开始的帧
+    fn pixel_context<'a>(
+        &'a self,
+        solver: &'a dyn PointSolver,
+        start: usize,
+    ) -> TLCResult<PixelContext<'a>> {
+        Ok(PixelContext {
+            peak_frames: self.get_peak_frames()?,
+            interp: self.get_interp()?,
+            config: &self.config,
+            dt: 1. / self.config.frame_rate as f32,
+            mask: self.config.mask_pixels()?,
+            start,
+            solver,
+        })
+    }
+
+    /// 只求解`positions`（计算区域内按行展开的像素序号，与nu2d的行序相反）处的Nu，
+    /// 不使用两遍求解，不修改已有结果
+    pub(crate) fn solve_pixels(&mut self, positions: &[usize]) -> TLCResult<Vec<f32>> {
+        if self.peak_frames.is_none() {
+            self.detect_peak()?;
+        }
+        if self.interp.is_none() {
+            self.interp()?;
+        }
+        let start = self.event_window()?.0;
+        let solver = self.config.iteration_method.point_solver()?;
+        let h0 = self.config.iteration_method.h0();
+        let ctx = self.pixel_context(solver.as_ref(), start)?;
+        let TLCConfig {
+            characteristic_length,
+            air_thermal_conductivity,
+            ..
+        } = self.config;
+
+        positions
+            .iter()
+            .map(|&pos| {
+                let h = match ctx.solve(pos, h0).ok_or(awsl!())? {
+                    Some(solution) => solution.h,
+                    None => NAN,
+                };
+                Ok(h * characteristic_length / air_thermal_conductivity)
+            })
+            .collect()
+    }
+
+    fn solve_core(
+        &mut self,
+        solver: &dyn PointSolver,
+        h0: f32,
+        start: usize,
+    ) -> TLCResult<&mut Self> {
+        let ctx = self.pixel_context(solver, start)?;
+        let TLCConfig {
+            region_shape,
+            characteristic_length,
+            air_thermal_conductivity,
+            coarse_stride,
+            ..
+        } = self.config;
+        let (solved, iterations) = (AtomicUsize::new(0), AtomicUsize::new(0));
+
+        // 以`h0`为初值求解单个像素的对流换热系数，遮罩排除的像素为NaN
+        let solve_point = |pos: usize, h0: f32| -> Option<f32> {
+            let solution = match ctx.solve(pos, h0)? {
+                Some(solution) => solution,
+                None => return Some(NAN),
+            };
+            solved.fetch_add(1, Ordering::Relaxed);
+            iterations.fetch_add(solution.iterations, Ordering::Relaxed);
+
+            Some(solution.h)
+        };
+
+        let hs = if coarse_stride > 1 {
+            two_pass(region_shape, coarse_stride, h0, solve_point)
+        } else {
+            (0..ctx.peak_frames.len())
+                .into_par_iter()
+                .map(|pos| solve_point(pos, h0))
+                .collect()
+        }
+        .ok_or(awsl!())?;
+        let nus: Vec<_> = hs
+            .into_iter()
+            .map(|h| h * characteristic_length / air_thermal_conductivity)
+            .collect();
+
+        let mut nu2d = Array1::from(nus)
+            .into_shape(region_shape)
+            .map_err(|err| awsl!(err))?;
+        nu2d.invert_axis(Axis(0));
+        let (solved, iterations) = (solved.into_inner(), iterations.into_inner());
+        self.solve_stats = Some(SolveStats {
+            solved,
+            nan_count: nu2d.iter().filter(|nu| nu.is_nan()).count(),
+            mean_iterations: iterations as f32 / solved.max(1) as f32,
+        });
+        self.nu_nan_mean
+            .insert(postprocess::cal_nan_mean(nu2d.view()));
+        self.nu2d.insert(nu2d);
+        self.computed(Stage::Nu);
+        self.smooth_nu2d();
+
+        Ok(self)
+    }
+}
+
+/// 求解单个像素所需的、各像素共用的数据
+struct PixelContext<'a> {
+    peak_frames: &'a [usize],
+    interp: &'a Interp,
+    config: &'a TLCConfig,
+    dt: f32,
+    mask: Option<Vec<bool>>,
+    start: usize,
+    solver: &'a dyn PointSolver,
+}
+
+impl PixelContext<'_> {
+    /// 以`h0`为初值求解单个像素，遮罩排除与峰值过早的像素为Some(None)，
+    /// 参考温度不连续存储时为None
+    fn solve(&self, pos: usize, h0: f32) -> Option<Option<PointSolution>> {
+        let TLCConfig {
+            region_shape,
+            peak_temp,
+            solid_thermal_conductivity,
+            solid_thermal_diffusivity,
+            t0_estimation,
+            ref solid_thermal_conductivity_curve,
+            ref solid_thermal_diffusivity_curve,
+            ..
+        } = *self.config;
+        let start = self.start;
+        // 从加热开始计时，峰值在加热开始之前的像素无效
+        let peak_frame = self.peak_frames[pos].saturating_sub(start);
+        let excluded = self.mask.as_ref().map_or(false, |mask| !mask[pos]);
+        if peak_frame <= t0_estimation.frames() || excluded {
+            return Some(None);
+        }
+        let temps = self.interp.interp_single_point(pos, region_shape);
+        let temps = &temps.as_slice_memory_order()?[start..];
+        // 定性温度取壁面温度与峰值时刻参考温度的平均值
+        let film_temp = (peak_temp + temps[peak_frame]) / 2.;
+        let property = |curve: &Option<PropertyCurve>, constant: f32| {
+            curve
+                .as_ref()
+                .map_or(constant, |curve| curve.eval(film_temp))
+        };
+        let point_data = PointData {
+            peak_frame,
+            temps,
+            t0: t0_estimation.estimate(temps),
+            peak_temp,
+            dt: self.dt,
+            solid_thermal_conductivity: property(
+                solid_thermal_conductivity_curve,
+                solid_thermal_conductivity,
+            ),
+            solid_thermal_diffusivity: property(
+                solid_thermal_diffusivity_curve,
+                solid_thermal_diffusivity,
+            ),
+        };
+
+        Some(Some(self.solver.solve(&point_data, h0)))
+    }
+}
+
+/// 两遍求解：先求解每隔`stride`个像素的粗网格，其余像素（及粗网格中发散的像素）以最近的
+/// 粗网格有效解为初值求解，仍然发散时再以统一初值`h0`求解
+///
+/// 相邻像素的h相近，以邻近解为初值迭代步数更少，也能挽救以统一初值发散的像素
+fn two_pass<S>((h, w): (usize, usize), stride: usize, h0: f32, solve_point: S) -> Option<Vec<f32>>
+where
+    S: Fn(usize, f32) -> Option<f32> + Sync,
+{
+    let (ch, cw) = ((h + stride - 1) / stride, (w + stride - 1) / stride);
+    let coarse = (0..ch * cw)
+        .into_par_iter()
+        .map(|k| solve_point((k / cw) * stride * w + (k % cw) * stride, h0))
+        .collect::<Option<Vec<_>>>()?;
+    let coarse = Array2::from_shape_vec((ch, cw), coarse).ok()?;
+
+    (0..h * w)
+        .into_par_iter()
+        .map(|pos| {
+            let (i, j) = (pos / w, pos % w);
+            if i % stride == 0 && j % stride == 0 {
+                let h = coarse[(i / stride, j / stride)];
+                if !h.is_nan() {
+                    return Some(h);
+                }
+            }
+            let nearest = ((i + stride / 2) / stride, (j + stride / 2) / stride);
+            let nearest = (nearest.0.min(ch - 1), nearest.1.min(cw - 1));
+            match nearest_valid(coarse.view(), nearest) {
+                Some(guess) => match solve_point(pos, guess)? {
+                    h if h.is_nan() => solve_point(pos, h0),
+                    h => Some(h),
+                },
+                None => solve_point(pos, h0),
+            }
+        })
+        .collect()
+}
+
+/// 从粗网格上的(ci, cj)开始逐圈向外搜索有效解
+fn nearest_valid(coarse: ArrayView2<f32>, (ci, cj): (usize, usize)) -> Option<f32> {
+    let (ch, cw) = coarse.dim();
+    for r in 0..=COARSE_SEARCH_RINGS {
+        let rows = ci.saturating_sub(r)..(ci + r + 1).min(ch);
+        for i in rows {
+            let cols = cj.saturating_sub(r)..(cj + r + 1).min(cw);
+            for j in cols {
+                // 只检查第r圈上的格点，内圈已经检查过
+                if i.max(ci) - i.min(ci) != r && j.max(cj) - j.min(cj) != r {
+                    continue;
+                }
+                let h = coarse[(i, j)];
+                if !h.is_nan() {
+                    return Some(h);
+                }
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn t0_estimation() {
+        // 第2帧为加热器开启瞬间的跳变
+        let temps = [20., 20.2, 35., 20.1, 20.3];
+        assert!((T0Estimation::Mean(4).estimate(&temps) - 23.825).abs() < 1e-4);
+        assert!((T0Estimation::Median(4).estimate(&temps) - 20.15).abs() < 1e-4);
+        assert_eq!(T0Estimation::Uniform(19.5).estimate(&temps), 19.5);
+    }
+
+    #[test]
+    fn property_curve() {
+        let table = PropertyCurve::Table(vec![(20., 0.19), (40., 0.21)]);
+        assert!((table.eval(30.) - 0.2).abs() < 1e-6);
+        assert_eq!(table.eval(10.), 0.19);
+        assert_eq!(table.eval(50.), 0.21);
+        let polynomial = PropertyCurve::Polynomial(vec![0.18, 0.001]);
+        assert!((polynomial.eval(20.) - 0.2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn two_pass_rescues_divergent_pixels() {
+        // 只有第0个像素能以统一初值收敛，其余像素的初值必须接近100
+        let solve_point = |pos: usize, h0: f32| {
+            Some(if pos == 0 || (h0 - 100.).abs() < 10. {
+                100.
+            } else {
+                NAN
+            })
+        };
+        let hs = two_pass((4, 5), 2, DEFAULT_H0, solve_point).unwrap();
+        assert!(hs.iter().all(|&h| h == 100.));
+    }
+
+    #[test]
+    fn plugin_solver() {
+        use crate::{preprocess::Interp, TLCConfig};
+
+        register_point_solver("double_h0", |_: &PointData, h0: f32| PointSolution {
+            h: h0 * 2.,
+            iterations: 1,
+        })
+        .unwrap();
+        assert!(point_solvers().unwrap().contains(&"double_h0".to_owned()));
+
+        let config = TLCConfig::from_json(serde_json::json!({
+            "frame_num": 10,
+            "frame_rate": 25,
+            "region_shape": [2, 2],
+            "iteration_method": { "Plugin": { "name": "double_h0", "h0": 50. } },
+        }))
+        .unwrap();
+        let mut data = TLCData::from_config(config);
+        data.peak_frames = Some(vec![6, 6, 6, 2]);
+        data.interp = Some(Interp::Eager(Array2::from_elem((4, 10), 30.)));
+        data.solve_nu(0).unwrap();
+        let c = data.get_config();
+        let nu = 100. * c.characteristic_length / c.air_thermal_conductivity;
+        assert_eq!(data.get_nu2d().unwrap()[(1, 0)], nu);
+        // 峰值过早的像素不交给求解器
+        assert!(data.get_nu2d().unwrap()[(0, 1)].is_nan());
+        assert_eq!(data.get_solve_stats().unwrap().solved, 3);
+
+        data.set_iteration_method(IterationMethod::Plugin {
+            name: "missing".to_owned(),
+            h0: 50.,
+        });
+        assert!(data.solve_nu(0).is_err());
+    }
+
+    const K: f32 = 0.19;
+    const A: f32 = 1.091e-7;
+    const DT: f32 = 1. / 25.;
+    const T0: f32 = 25.;
+    const DELTA_T: f32 = 20.;
+
+    /// 参考温度在第`step`帧阶跃`DELTA_T`后，半无限大平板壁面温升的解析解（f64）
+    fn step_response(h: f64, step: usize, peak_frame: usize) -> f64 {
+        let t = (peak_frame - step) as f64 * DT as f64;
+        let beta = h * (A as f64 * t).sqrt() / K as f64;
+        DELTA_T as f64 * (1. - (beta * beta).exp() * libm::erfc(beta))
+    }
+
+    /// 峰值温度取真值`h`对应的壁面温度
+    fn step_case(h: f32, step: usize, peak_frame: usize) -> (Vec<f32>, f32) {
+        let temps = (0..=peak_frame)
+            .map(|frame| if frame < step { T0 } else { T0 + DELTA_T })
+            .collect();
+        let peak_temp = T0 + step_response(h as f64, step, peak_frame) as f32;
+
+        (temps, peak_temp)
+    }
+
+    fn point_data(temps: &[f32], peak_frame: usize, peak_temp: f32) -> PointData<'_> {
+        PointData {
+            peak_frame,
+            temps,
+            t0: T0,
+            peak_temp,
+            dt: DT,
+            solid_thermal_conductivity: K,
+            solid_thermal_diffusivity: A,
+        }
+    }
+
+    /// (阶跃帧, 峰值帧)：峰值帧不超过9时只走标量循环，阶跃帧在SIMD分块内时走SIMD，
+    /// 阶跃帧在分块之后的余数部分时走标量收尾
+    const STEP_CASES: [(usize, usize); 4] = [(1, 6), (1, 400), (200, 400), (395, 400)];
+
+    #[test]
+    fn thermal_equation_step() {
+        for &(step, peak_frame) in &STEP_CASES {
+            for &h in &[30., 100., 300.] {
+                let (temps, peak_temp) = step_case(h, step, peak_frame);
+                let (f, df) = point_data(&temps, peak_frame, peak_temp).thermal_equation(h);
+                assert!(
+                    f.abs() < 2e-3,
+                    "f = {} at h = {}, {:?}",
+                    f,
+                    h,
+                    (step, peak_frame)
+                );
+
+                // 方程对h的导数与解析解的中心差分比较
+                let dh = 1e-3;
+                let expected = -(step_response(h as f64 + dh, step, peak_frame)
+                    - step_response(h as f64 - dh, step, peak_frame))
+                    / (2. * dh);
+                let error = (df as f64 - expected).abs() / expected.abs();
+                assert!(error < 1e-3, "df = {}, expected {}", df, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn newton_recovers_step_h() {
+        for &(step, peak_frame) in &STEP_CASES {
+            for &h in &[30., 100., 300.] {
+                let (temps, peak_temp) = step_case(h, step, peak_frame);
+                for (name, solved) in [
+                    (
+                        "tangent",
+                        newton_tangent(DEFAULT_MAX_ITER_NUM)(
+                            &point_data(&temps, peak_frame, peak_temp),
+                            DEFAULT_H0,
+                        )
+                        .h,
+                    ),
+                    (
+                        "down",
+                        newton_down(DEFAULT_MAX_ITER_NUM)(
+                            &point_data(&temps, peak_frame, peak_temp),
+                            DEFAULT_H0,
+                        )
+                        .h,
+                    ),
+                ] {
+                    let error = (solved - h).abs() / h;
+                    assert!(
+                        error < 1e-3,
+                        "newton {} solved {} for h = {}, {:?}",
+                        name,
+                        solved,
+                        h,
+                        (step, peak_frame)
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,259 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::net::{Shutdown, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use ndarray::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{error::TLCResult, Stage, TLCData};
+use crate::awsl;
+
+/// 实验过程中实时接收数采数据的来源
+///
+/// 协议为纯文本，每行一帧数采数据，各列以制表符、逗号或空格分隔，与.lvm的数据行一致，
+/// 以`#`开头的行与空行忽略
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum DaqSource {
+    /// 主动连接的地址，如"192.168.1.10:5025"
+    Tcp(String),
+    /// 串口设备（如/dev/ttyUSB0、COM3）或命名管道，按文件逐行读取，波特率需事先用系统工具设置
+    Serial(String),
+}
+
+/// 接收线程与`TLCData`之间共享的状态
+#[derive(Default)]
+struct Shared {
+    /// 尚未并入daq的行
+    pending: Mutex<Vec<Vec<f32>>>,
+    /// 列数与第一行不一致或无法解析的行数
+    skipped: Mutex<usize>,
+    /// 连接断开或读取出错的原因
+    closed: Mutex<Option<String>>,
+    stop: AtomicBool,
+}
+
+/// 正在接收的数采数据流，析构时断开
+pub struct DaqStream {
+    shared: Arc<Shared>,
+    /// 用于在析构时打断阻塞的读取，串口只能等下一行到达后退出
+    tcp: Option<TcpStream>,
+}
+
+/// `poll_daq_stream`的结果
+#[derive(Debug, Serialize)]
+pub struct LiveDaq {
+    /// 已接收的总行数
+    pub total_rows: usize,
+    /// 本次新增各行对应的各热电偶温度（°C），(热电偶, 行)
+    pub temps: Vec<Vec<f32>>,
+    pub skipped: usize,
+    /// 连接已断开时为断开原因
+    pub closed: Option<String>,
+}
+
+fn is_comment(line: &str) -> bool {
+    let line = line.trim();
+    line.is_empty() || line.starts_with('#')
+}
+
+/// 任一单元格无法解析时返回None
+fn parse_row(line: &str) -> Option<Vec<f32>> {
+    line.trim()
+        .split(|c: char| c == '\t' || c == ',' || c == ' ')
+        .filter(|cell| !cell.is_empty())
+        .map(|cell| cell.parse().ok())
+        .collect()
+}
+
+/// 逐行接收直到断开或停止，返回结束的原因
+fn receive(reader: impl Read, shared: &Shared) -> String {
+    let mut ncols = None;
+    for line in BufReader::new(reader).lines() {
+        if shared.stop.load(Ordering::Relaxed) {
+            return "已停止".to_owned();
+        }
+        let line = match line {
+            Ok(line) => line,
+            Err(err) => return err.to_string(),
+        };
+        if is_comment(&line) {
+            continue;
+        }
+        match parse_row(&line) {
+            // 列数以第一行为准
+            Some(row) if *ncols.get_or_insert(row.len()) == row.len() => {
+                if let Ok(mut pending) = shared.pending.lock() {
+                    pending.push(row);
+                }
+            }
+            _ => {
+                if let Ok(mut skipped) = shared.skipped.lock() {
+                    *skipped += 1;
+                }
+            }
+        }
+    }
+
+    "连接已断开".to_owned()
+}
+
+impl DaqStream {
+    pub fn connect(source: &DaqSource) -> TLCResult<Self> {
+        let (reader, tcp): (Box<dyn Read + Send>, _) = match source {
+            DaqSource::Tcp(addr) => {
+                let stream =
+                    TcpStream::connect(addr).map_err(|err| awsl!(DAQIOError, err, addr))?;
+                let clone = stream
+                    .try_clone()
+                    .map_err(|err| awsl!(DAQIOError, err, addr))?;
+                (Box::new(stream), Some(clone))
+            }
+            DaqSource::Serial(path) => {
+                let file = File::open(path).map_err(|err| awsl!(DAQIOError, err, path))?;
+                (Box::new(file), None)
+            }
+        };
+
+        let shared = Arc::new(Shared::default());
+        let shared_ref = Arc::clone(&shared);
+        thread::spawn(move || {
+            let reason = receive(reader, &shared_ref);
+            if let Ok(mut closed) = shared_ref.closed.lock() {
+                *closed = Some(reason);
+            }
+        });
+
+        Ok(Self { shared, tcp })
+    }
+
+    fn take_pending(&self) -> Vec<Vec<f32>> {
+        self.shared
+            .pending
+            .lock()
+            .map(|mut pending| std::mem::take(&mut *pending))
+            .unwrap_or_default()
+    }
+}
+
+impl Drop for DaqStream {
+    fn drop(&mut self) {
+        self.shared.stop.store(true, Ordering::Relaxed);
+        if let Some(tcp) = &self.tcp {
+            let _ = tcp.shutdown(Shutdown::Both);
+        }
+    }
+}
+
+impl TLCData {
+    /// 开始实时接收数采数据，已有的数采数据被清空
+    pub fn start_daq_stream(&mut self, source: DaqSource) -> TLCResult<&mut Self> {
+        let stream = DaqStream::connect(&source)?;
+        self.drop_stages(&Stage::Daq.dependents());
+        self.daq_stream = Some(stream);
+
+        Ok(self)
+    }
+
+    /// 停止接收，已接收的数据保留
+    pub fn stop_daq_stream(&mut self) -> &mut Self {
+        self.daq_stream = None;
+        self
+    }
+
+    /// 将接收到的新数据并入daq，依赖daq的阶段随之失效，需要定期调用
+    pub fn poll_daq_stream(&mut self) -> TLCResult<LiveDaq> {
+        let stream = self
+            .daq_stream
+            .as_ref()
+            .ok_or(awsl!(HandleError, "没有正在接收的数采数据"))?;
+        let rows = stream.take_pending();
+        let skipped = stream.shared.skipped.lock().map_or(0, |skipped| *skipped);
+        let closed = stream
+            .shared
+            .closed
+            .lock()
+            .ok()
+            .and_then(|closed| closed.clone());
+
+        let temps = self
+            .config
+            .thermocouples
+            .iter()
+            .map(|tc| {
+                rows.iter()
+                    .map(|row| match row.get(tc.column_num) {
+                        Some(&v) => tc.unit.to_celsius(v) + tc.calibration_offset,
+                        None => f32::NAN,
+                    })
+                    .collect()
+            })
+            .collect();
+
+        if !rows.is_empty() {
+            let ncols = rows[0].len();
+            let new_rows =
+                Array2::from_shape_vec((rows.len(), ncols), rows.into_iter().flatten().collect())
+                    .map_err(|err| awsl!(err))?;
+            let daq = match self.daq.take() {
+                Some(daq) if daq.ncols() == ncols => {
+                    ndarray::concatenate(Axis(0), &[daq.view(), new_rows.view()])
+                        .map_err(|err| awsl!(err))?
+                }
+                _ => new_rows,
+            };
+            self.config.total_rows = daq.nrows();
+            self.config.init_frame_num();
+            // t2d之后的阶段基于旧数据，帧数变化时视频相关的阶段同样失效，按需重新计算
+            let mut stages = Stage::T2d.dependents();
+            for stage in self.plan(&self.config) {
+                if !stages.contains(&stage) {
+                    stages.push(stage);
+                }
+            }
+            self.drop_stages(&stages);
+            self.daq = Some(daq);
+            self.computed(Stage::Daq);
+            self.history.resync(&self.config);
+        }
+
+        Ok(LiveDaq {
+            total_rows: self.daq.as_ref().map_or(0, |daq| daq.nrows()),
+            temps,
+            skipped,
+            closed,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_rows() {
+        assert_eq!(parse_row("1.5\t2,3 4"), Some(vec![1.5, 2., 3., 4.]));
+        assert!(is_comment("# time\tT1"));
+        assert_eq!(parse_row("1\tabc"), None);
+    }
+
+    #[test]
+    fn tcp_stream() {
+        use std::io::Write;
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let stream = DaqStream::connect(&DaqSource::Tcp(addr)).unwrap();
+        let (mut conn, _) = listener.accept().unwrap();
+        conn.write_all(b"# t\tT1\n0\t25.1\n1\t25.3\n2\n").unwrap();
+        drop(conn);
+        while stream.shared.closed.lock().unwrap().is_none() {
+            thread::yield_now();
+        }
+        assert_eq!(stream.take_pending(), vec![vec![0., 25.1], vec![1., 25.3]]);
+        assert_eq!(*stream.shared.skipped.lock().unwrap(), 1);
+    }
+}
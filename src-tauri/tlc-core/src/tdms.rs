@@ -0,0 +1,563 @@
+//! LabVIEW TDMS文件读取
+//!
+//! 只实现了数采文件需要的子集：小端、非DAQmx原始数据，支持交错/非交错存储。
+//! [格式说明](https://www.ni.com/en-us/support/documentation/supplemental/07/tdms-file-format-internal-structure.html)
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use serde::Serialize;
+
+use super::error::TLCResult;
+use crate::awsl;
+
+const TOC_META_DATA: u32 = 1 << 1;
+const TOC_NEW_OBJ_LIST: u32 = 1 << 2;
+const TOC_RAW_DATA: u32 = 1 << 3;
+const TOC_INTERLEAVED_DATA: u32 = 1 << 5;
+const TOC_BIG_ENDIAN: u32 = 1 << 6;
+const TOC_DAQMX_RAW_DATA: u32 = 1 << 7;
+
+const LEAD_IN_LEN: usize = 28;
+const NO_RAW_DATA: u32 = 0xFFFF_FFFF;
+const SAME_AS_PREVIOUS: u32 = 0x0000_0000;
+
+/// TDMS数据类型编号
+const TDS_TYPE_STRING: u32 = 0x20;
+const TDS_TYPE_TIMESTAMP: u32 = 0x44;
+
+/// 属性值
+#[derive(Debug, Clone, Serialize)]
+pub enum TdmsValue {
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    String(String),
+    /// 自1904-01-01 00:00:00 UTC起的秒数
+    Timestamp(f64),
+}
+
+impl fmt::Display for TdmsValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TdmsValue::Int(v) => write!(f, "{}", v),
+            TdmsValue::Float(v) => write!(f, "{}", v),
+            TdmsValue::Bool(v) => write!(f, "{}", v),
+            TdmsValue::String(v) => write!(f, "{}", v),
+            TdmsValue::Timestamp(v) => write!(f, "{}", v),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct TdmsChannel {
+    /// 所属group名称
+    pub group: String,
+    /// 通道名称
+    pub name: String,
+    /// 通道属性（wf_increment、unit_string等）
+    pub properties: BTreeMap<String, TdmsValue>,
+    /// 数据类型编号
+    pub data_type: u32,
+    /// 时间戳通道以秒为单位，其余数值类型统一转换为f64
+    pub data: Vec<f64>,
+}
+
+#[derive(Debug, Default)]
+pub struct TdmsFile {
+    /// 文件级属性
+    pub properties: BTreeMap<String, TdmsValue>,
+    /// group属性，key为group名称
+    pub groups: BTreeMap<String, BTreeMap<String, TdmsValue>>,
+    /// 按首次出现顺序排列的所有通道
+    pub channels: Vec<TdmsChannel>,
+}
+
+/// 当前段中某个对象的原始数据索引
+#[derive(Debug, Clone, Copy)]
+struct RawDataIndex {
+    data_type: u32,
+    values_per_chunk: usize,
+    /// 字符串通道每块的总字节数（含偏移表），其余类型为0
+    string_bytes: usize,
+}
+
+impl RawDataIndex {
+    /// 每块中该通道的字节数
+    fn chunk_bytes(&self) -> TLCResult<usize> {
+        if self.data_type == TDS_TYPE_STRING {
+            return Ok(self.string_bytes);
+        }
+        self.values_per_chunk
+            .checked_mul(type_size(self.data_type)?)
+            .ok_or(awsl!(
+                DAQError,
+                "TDMS原始数据索引错误",
+                self.values_per_chunk
+            ))
+    }
+}
+
+struct Cursor<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn take(&mut self, n: usize) -> TLCResult<&'a [u8]> {
+        let bytes = self.buf.get(self.pos..self.pos + n).ok_or(awsl!(
+            DAQError,
+            "TDMS文件意外结束",
+            self.pos
+        ))?;
+        self.pos += n;
+
+        Ok(bytes)
+    }
+
+    fn u32(&mut self) -> TLCResult<u32> {
+        let mut b = [0; 4];
+        b.copy_from_slice(self.take(4)?);
+        Ok(u32::from_le_bytes(b))
+    }
+
+    fn u64(&mut self) -> TLCResult<u64> {
+        let mut b = [0; 8];
+        b.copy_from_slice(self.take(8)?);
+        Ok(u64::from_le_bytes(b))
+    }
+
+    fn string(&mut self) -> TLCResult<String> {
+        let len = self.u32()? as usize;
+        Ok(String::from_utf8_lossy(self.take(len)?).into_owned())
+    }
+
+    fn value(&mut self, data_type: u32) -> TLCResult<TdmsValue> {
+        use TdmsValue::*;
+        let v = match data_type {
+            TDS_TYPE_STRING => String(self.string()?),
+            0x21 => Bool(self.take(1)?[0] != 0),
+            TDS_TYPE_TIMESTAMP => Timestamp(timestamp(self.take(16)?)),
+            9 | 10 | 0x19 | 0x1A => Float(number(data_type, self.take(type_size(data_type)?)?)),
+            _ => Int(number(data_type, self.take(type_size(data_type)?)?) as i64),
+        };
+
+        Ok(v)
+    }
+}
+
+fn type_size(data_type: u32) -> TLCResult<usize> {
+    let size = match data_type {
+        1 | 5 | 0x21 => 1,
+        2 | 6 => 2,
+        3 | 7 | 9 | 0x19 => 4,
+        4 | 8 | 10 | 0x1A => 8,
+        TDS_TYPE_TIMESTAMP => 16,
+        _ => return Err(awsl!(DAQError, "不支持的TDMS数据类型", data_type)),
+    };
+
+    Ok(size)
+}
+
+/// 将小端存储的数值转换为f64
+fn number(data_type: u32, b: &[u8]) -> f64 {
+    let mut b8 = [0; 8];
+    let len = b.len().min(8);
+    b8[..len].copy_from_slice(&b[..len]);
+    match data_type {
+        1 => b[0] as i8 as f64,
+        2 => i16::from_le_bytes([b[0], b[1]]) as f64,
+        3 => i32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64,
+        4 => i64::from_le_bytes(b8) as f64,
+        5 | 0x21 => b[0] as f64,
+        6 => u16::from_le_bytes([b[0], b[1]]) as f64,
+        7 => u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64,
+        8 => u64::from_le_bytes(b8) as f64,
+        9 | 0x19 => f32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64,
+        10 | 0x1A => f64::from_le_bytes(b8),
+        TDS_TYPE_TIMESTAMP => timestamp(b),
+        _ => f64::NAN,
+    }
+}
+
+/// 时间戳：前8字节为2^-64秒的小数部分，后8字节为秒数
+fn timestamp(b: &[u8]) -> f64 {
+    let mut fractions = [0; 8];
+    let mut seconds = [0; 8];
+    fractions.copy_from_slice(&b[..8]);
+    seconds.copy_from_slice(&b[8..16]);
+
+    i64::from_le_bytes(seconds) as f64 + u64::from_le_bytes(fractions) as f64 / 2f64.powi(64)
+}
+
+/// 解析形如`/'group'/'channel'`的对象路径，单引号以`''`转义
+fn split_path(path: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut chars = path.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\'' {
+            continue;
+        }
+        let mut part = String::new();
+        while let Some(c) = chars.next() {
+            if c == '\'' {
+                if chars.peek() == Some(&'\'') {
+                    chars.next();
+                    part.push('\'');
+                    continue;
+                }
+                break;
+            }
+            part.push(c);
+        }
+        parts.push(part);
+    }
+
+    parts
+}
+
+impl TdmsFile {
+    pub fn from_path<P: AsRef<Path>>(path: P) -> TLCResult<Self> {
+        let path = path.as_ref();
+        let mut buf = Vec::new();
+        File::open(path)
+            .map_err(|err| awsl!(DAQIOError, err, path))?
+            .read_to_end(&mut buf)
+            .map_err(|err| awsl!(DAQIOError, err, path))?;
+
+        Self::from_bytes(&buf).map_err(|err| awsl!(DAQError, err.to_string(), path))
+    }
+
+    pub fn from_bytes(buf: &[u8]) -> TLCResult<Self> {
+        let mut file = TdmsFile::default();
+        // 对象路径 -> channels中的下标
+        let mut channel_index: BTreeMap<String, usize> = BTreeMap::new();
+        // 每个对象最近一次的原始数据索引
+        let mut last_index: BTreeMap<String, RawDataIndex> = BTreeMap::new();
+        // 当前段中包含原始数据的对象
+        let mut active: Vec<String> = Vec::new();
+
+        let mut segment_start = 0;
+        while segment_start + LEAD_IN_LEN <= buf.len() {
+            let mut cur = Cursor {
+                buf,
+                pos: segment_start,
+            };
+            if cur.take(4)? != b"TDSm" {
+                return Err(awsl!(DAQError, "TDMS段标识错误", segment_start));
+            }
+            let toc = cur.u32()?;
+            let _version = cur.u32()?;
+            let next_segment_offset = cur.u64()?;
+            let raw_data_offset = cur.u64()? as usize;
+            if toc & TOC_BIG_ENDIAN != 0 {
+                return Err(awsl!(DAQError, "暂不支持大端存储的TDMS文件", segment_start));
+            }
+            if toc & TOC_DAQMX_RAW_DATA != 0 {
+                return Err(awsl!(DAQError, "暂不支持DAQmx原始数据格式", segment_start));
+            }
+
+            let data_start = segment_start + LEAD_IN_LEN;
+            // 未正常关闭的文件最后一段长度为全1，此时读到文件末尾；写入中断时最后一段不完整
+            let segment_end = if next_segment_offset == u64::MAX {
+                buf.len()
+            } else {
+                data_start
+                    .checked_add(next_segment_offset as usize)
+                    .ok_or(awsl!(DAQError, "TDMS段长度错误", segment_start))?
+                    .min(buf.len())
+            };
+
+            if toc & TOC_META_DATA != 0 {
+                if toc & TOC_NEW_OBJ_LIST != 0 {
+                    active.clear();
+                }
+                let object_num = cur.u32()?;
+                for _ in 0..object_num {
+                    let path = cur.string()?;
+                    let index_len = cur.u32()?;
+                    let index = match index_len {
+                        NO_RAW_DATA => None,
+                        SAME_AS_PREVIOUS => last_index.get(&path).copied(),
+                        _ => {
+                            let data_type = cur.u32()?;
+                            let _dimension = cur.u32()?;
+                            let values_per_chunk = cur.u64()? as usize;
+                            // 字符串通道不参与计算，只记录字节数以便跳过
+                            let string_bytes = if data_type == TDS_TYPE_STRING {
+                                cur.u64()? as usize
+                            } else {
+                                0
+                            };
+                            Some(RawDataIndex {
+                                data_type,
+                                values_per_chunk,
+                                string_bytes,
+                            })
+                        }
+                    };
+
+                    let parts = split_path(&path);
+                    let mut properties = BTreeMap::new();
+                    for _ in 0..cur.u32()? {
+                        let name = cur.string()?;
+                        let data_type = cur.u32()?;
+                        properties.insert(name, cur.value(data_type)?);
+                    }
+
+                    match parts.len() {
+                        0 => file.properties.extend(properties),
+                        1 => file
+                            .groups
+                            .entry(parts[0].clone())
+                            .or_default()
+                            .extend(properties),
+                        _ => {
+                            let i = *channel_index.entry(path.clone()).or_insert_with(|| {
+                                file.channels.push(TdmsChannel {
+                                    group: parts[0].clone(),
+                                    name: parts[1].clone(),
+                                    ..Default::default()
+                                });
+                                file.channels.len() - 1
+                            });
+                            file.channels[i].properties.extend(properties);
+                            if let Some(index) = index {
+                                file.channels[i].data_type = index.data_type;
+                                last_index.insert(path.clone(), index);
+                                if !active.contains(&path) {
+                                    active.push(path);
+                                }
+                            } else {
+                                active.retain(|p| p != &path);
+                            }
+                        }
+                    }
+                }
+            }
+
+            if toc & TOC_RAW_DATA != 0 {
+                let indices: Vec<_> = active
+                    .iter()
+                    .map(|path| (channel_index[path], last_index[path]))
+                    .collect();
+                let raw = data_start
+                    .checked_add(raw_data_offset)
+                    .and_then(|raw_start| buf.get(raw_start..segment_end))
+                    .ok_or(awsl!(DAQError, "TDMS原始数据偏移超出段范围", segment_start))?;
+                Self::read_raw_data(&mut file, &indices, raw, toc & TOC_INTERLEAVED_DATA != 0)?;
+            }
+
+            segment_start = segment_end;
+        }
+
+        Ok(file)
+    }
+
+    fn read_raw_data(
+        file: &mut TdmsFile,
+        indices: &[(usize, RawDataIndex)],
+        raw: &[u8],
+        interleaved: bool,
+    ) -> TLCResult<()> {
+        let has_string = indices
+            .iter()
+            .any(|(_, index)| index.data_type == TDS_TYPE_STRING);
+        if interleaved && has_string {
+            // 交错存储时字符串长度不定，无法定位其后的数值
+            return Err(awsl!(
+                DAQError,
+                "暂不支持含字符串通道的交错存储TDMS文件",
+                indices.len()
+            ));
+        }
+        let chunk_bytes = indices
+            .iter()
+            .map(|(_, index)| index.chunk_bytes())
+            .collect::<TLCResult<Vec<_>>>()?;
+        let chunk_size = chunk_bytes
+            .iter()
+            .try_fold(0usize, |sum, &bytes| sum.checked_add(bytes))
+            .ok_or(awsl!(DAQError, "TDMS原始数据索引错误", chunk_bytes))?;
+        if chunk_size == 0 {
+            return Ok(());
+        }
+
+        for chunk in raw.chunks_exact(chunk_size) {
+            if interleaved {
+                let sizes = indices
+                    .iter()
+                    .map(|(_, index)| type_size(index.data_type))
+                    .collect::<TLCResult<Vec<_>>>()?;
+                let row_size: usize = sizes.iter().sum();
+                for row in chunk.chunks_exact(row_size) {
+                    let mut offset = 0;
+                    for ((i, index), &size) in indices.iter().zip(&sizes) {
+                        file.channels[*i]
+                            .data
+                            .push(number(index.data_type, &row[offset..offset + size]));
+                        offset += size;
+                    }
+                }
+            } else {
+                let mut offset = 0;
+                for ((i, index), &len) in indices.iter().zip(&chunk_bytes) {
+                    // 字符串通道对参考温度没有意义，跳过
+                    if index.data_type != TDS_TYPE_STRING {
+                        let size = type_size(index.data_type)?;
+                        file.channels[*i].data.extend(
+                            chunk[offset..offset + len]
+                                .chunks_exact(size)
+                                .map(|b| number(index.data_type, b)),
+                        );
+                    }
+                    offset += len;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 包含数据的通道，顺序即数采矩阵的列顺序
+    pub fn data_channels(&self) -> impl Iterator<Item = &TdmsChannel> {
+        self.channels.iter().filter(|c| !c.data.is_empty())
+    }
+}
+
+impl TdmsChannel {
+    /// 通道是否为时间戳类型
+    pub fn is_timestamp(&self) -> bool {
+        self.data_type == TDS_TYPE_TIMESTAMP
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn string(s: &str) -> Vec<u8> {
+        let mut v = (s.len() as u32).to_le_bytes().to_vec();
+        v.extend(s.as_bytes());
+        v
+    }
+
+    /// 单段文件，各通道为(路径, 数据类型, 原始数据, 值个数)，不含属性
+    fn segment(channels: &[(&str, u32, Vec<u8>, u64)], raw_data_offset: Option<u64>) -> Vec<u8> {
+        let mut meta = (channels.len() as u32).to_le_bytes().to_vec();
+        let mut raw = Vec::new();
+        for (path, data_type, data, values) in channels {
+            meta.extend(string(path));
+            meta.extend(
+                if *data_type == TDS_TYPE_STRING {
+                    28u32
+                } else {
+                    20
+                }
+                .to_le_bytes(),
+            );
+            meta.extend(data_type.to_le_bytes());
+            meta.extend(1u32.to_le_bytes());
+            meta.extend(values.to_le_bytes());
+            if *data_type == TDS_TYPE_STRING {
+                meta.extend((data.len() as u64).to_le_bytes());
+            }
+            meta.extend(0u32.to_le_bytes());
+            raw.extend_from_slice(data);
+        }
+
+        let mut buf = b"TDSm".to_vec();
+        buf.extend((TOC_META_DATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA).to_le_bytes());
+        buf.extend(4713u32.to_le_bytes());
+        buf.extend(((meta.len() + raw.len()) as u64).to_le_bytes());
+        buf.extend(raw_data_offset.unwrap_or(meta.len() as u64).to_le_bytes());
+        buf.extend(meta);
+        buf.extend(raw);
+        buf
+    }
+
+    #[test]
+    fn read_single_segment() {
+        let mut meta = 2u32.to_le_bytes().to_vec();
+        for (path, prop) in [("/'daq'/'t1'", 1.5f64), ("/'daq'/'t2'", 2.5f64)].iter() {
+            meta.extend(string(path));
+            meta.extend(20u32.to_le_bytes());
+            meta.extend(10u32.to_le_bytes());
+            meta.extend(1u32.to_le_bytes());
+            meta.extend(2u64.to_le_bytes());
+            meta.extend(1u32.to_le_bytes());
+            meta.extend(string("wf_increment"));
+            meta.extend(10u32.to_le_bytes());
+            meta.extend(prop.to_le_bytes());
+        }
+        let mut raw = Vec::new();
+        for v in [1f64, 2., 10., 20.].iter() {
+            raw.extend(v.to_le_bytes());
+        }
+
+        let mut buf = b"TDSm".to_vec();
+        buf.extend((TOC_META_DATA | TOC_NEW_OBJ_LIST | TOC_RAW_DATA).to_le_bytes());
+        buf.extend(4713u32.to_le_bytes());
+        buf.extend(((meta.len() + raw.len()) as u64).to_le_bytes());
+        buf.extend((meta.len() as u64).to_le_bytes());
+        buf.extend(meta);
+        buf.extend(raw);
+
+        let file = TdmsFile::from_bytes(&buf).unwrap();
+        assert_eq!(file.channels.len(), 2);
+        assert_eq!(file.channels[0].group, "daq");
+        assert_eq!(file.channels[1].name, "t2");
+        assert_eq!(file.channels[0].data, vec![1., 2.]);
+        assert_eq!(file.channels[1].data, vec![10., 20.]);
+    }
+
+    #[test]
+    fn skip_string_channel() {
+        let mut strings = Vec::new();
+        // 偏移表（各字符串的结束位置）之后是字符串内容
+        for end in [2u32, 5].iter() {
+            strings.extend(end.to_le_bytes());
+        }
+        strings.extend(b"ab");
+        strings.extend(b"cde");
+        let mut temps = Vec::new();
+        for v in [21f32, 22.].iter() {
+            temps.extend(v.to_le_bytes());
+        }
+        let buf = segment(
+            &[
+                ("/'daq'/'comment'", TDS_TYPE_STRING, strings, 2),
+                ("/'daq'/'t1'", 9, temps, 2),
+            ],
+            None,
+        );
+
+        let file = TdmsFile::from_bytes(&buf).unwrap();
+        let channels: Vec<_> = file.data_channels().collect();
+        assert_eq!(channels.len(), 1);
+        assert_eq!(channels[0].name, "t1");
+        assert_eq!(channels[0].data, vec![21., 22.]);
+    }
+
+    #[test]
+    fn truncated_file() {
+        let mut temps = Vec::new();
+        for v in [1f64, 2., 3.].iter() {
+            temps.extend(v.to_le_bytes());
+        }
+        let buf = segment(&[("/'daq'/'t1'", 10, temps.clone(), 3)], None);
+        // 写入中断，只保留完整的块
+        let file = TdmsFile::from_bytes(&buf[..buf.len() - 4]).unwrap();
+        assert!(file.channels[0].data.is_empty());
+        // 元数据不完整
+        assert!(TdmsFile::from_bytes(&buf[..LEAD_IN_LEN + 10]).is_err());
+        // 原始数据偏移超出文件
+        let buf = segment(&[("/'daq'/'t1'", 10, temps, 3)], Some(1 << 40));
+        assert!(TdmsFile::from_bytes(&buf).is_err());
+        let buf = segment(&[("/'daq'/'t1'", 10, Vec::new(), 3)], Some(u64::MAX));
+        assert!(TdmsFile::from_bytes(&buf).is_err());
+    }
+}
@@ -0,0 +1,323 @@
+//! 合成的测试工况：给定h分布，按半无限大平板的解析解生成Green值历史与数采数据，
+//! 写成视频与.lvm文件后可以走完整流程，与真值比较检查精度
+//!
+//! 只在测试或`testing`特性下编译
+
+use std::f32::consts::PI;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use ffmpeg::{codec, format};
+use ffmpeg_next as ffmpeg;
+use libm::erfcf;
+use ndarray::prelude::*;
+use serde_json::{json, Map};
+
+use super::{error::TLCResult, io::VideoWriter, TLCConfig, TLCData};
+use crate::awsl;
+
+/// 热电偶网格（行，列），均匀分布在计算区域内
+pub const TC_SHAPE: (usize, usize) = (3, 4);
+
+const FRAME_RATE: usize = 25;
+/// 计算区域四周留出的视频边缘（像素）
+const MARGIN: usize = 16;
+/// 主流温度在这一帧阶跃
+const HEATING_START: usize = 20;
+/// 初始温度（℃）
+const T0: f32 = 25.;
+/// 最左侧的主流温升（℃），向右线性增大10%，双线性插值没有误差
+const DELTA_T: f32 = 25.;
+/// h真值的范围（W/(m^2*K)）
+const H_RANGE: (f32, f32) = (60., 250.);
+/// Green值的背景、峰高与峰的半宽（帧）
+const GREEN_BASE: f32 = 40.;
+const GREEN_PEAK: f32 = 160.;
+const PEAK_WIDTH: f32 = 15.;
+
+/// 温升达到`ratio`时的β = h√(at)/k，1 - exp(β²)erfc(β)随β单调增大，二分求解
+fn beta_at(ratio: f32) -> f32 {
+    let (mut lo, mut hi) = (0_f32, 5_f32);
+    for _ in 0..40 {
+        let mid = (lo + hi) / 2.;
+        if 1. - (mid * mid).exp() * erfcf(mid) < ratio {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+
+    (lo + hi) / 2.
+}
+
+/// 与像素位置、帧数有关的确定性伪噪声，范围[-3, 3]
+fn noise(pos: usize, frame: usize) -> f32 {
+    let hash = (pos as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15) ^ (frame as u64 * 0x85eb_ca6b);
+    (hash >> 32) as f32 % 7. - 3.
+}
+
+/// 加热开始时主流温度阶跃，各像素的h平滑变化，Green值历史为叠加了伪噪声的单峰，
+/// 峰值帧即壁面温度达到峰值温度的时刻
+pub struct SyntheticCase {
+    /// 除视频、数采文件外的配置，热电偶按`TC_SHAPE`排列，双线性插值
+    pub config: TLCConfig,
+}
+
+impl SyntheticCase {
+    pub fn new(region_shape: (usize, usize), frame_num: usize) -> TLCResult<Self> {
+        let (cal_h, cal_w) = region_shape;
+        let (tc_h, tc_w) = TC_SHAPE;
+        let thermocouples: Vec<_> = (0..tc_h * tc_w)
+            .map(|i| {
+                let y = MARGIN + i / tc_w * (cal_h - 1) / (tc_h - 1);
+                let x = MARGIN + i % tc_w * (cal_w - 1) / (tc_w - 1);
+                json!({ "column_num": i, "pos": [y, x] })
+            })
+            .collect();
+        let config = TLCConfig::from_json(json!({
+            "frame_rate": FRAME_RATE,
+            "total_frames": frame_num,
+            "frame_num": frame_num,
+            "video_shape": [cal_h + 2 * MARGIN, cal_w + 2 * MARGIN],
+            "top_left_pos": [MARGIN, MARGIN],
+            "region_shape": [cal_h, cal_w],
+            "thermocouples": thermocouples,
+            "interp_method": { "Bilinear": [tc_h, tc_w] },
+        }))?;
+
+        Ok(Self { config })
+    }
+
+    /// 主流温升只随x变化
+    fn delta_t(&self, x: usize) -> f32 {
+        let cal_w = self.config.region_shape.1;
+        DELTA_T * (1. + 0.1 * x as f32 / (cal_w - 1) as f32)
+    }
+
+    /// 计算区域内`pos`处h的真值
+    pub fn h(&self, pos: usize) -> f32 {
+        let (cal_h, cal_w) = self.config.region_shape;
+        let (fy, fx) = (
+            (pos / cal_w) as f32 / cal_h as f32,
+            (pos % cal_w) as f32 / cal_w as f32,
+        );
+
+        H_RANGE.0 + (H_RANGE.1 - H_RANGE.0) * 0.5 * (1. + (2. * PI * fy).sin() * (PI * fx).cos())
+    }
+
+    /// Nu的真值，与nu2d一样上下翻转
+    pub fn nu2d(&self) -> Array2<f32> {
+        let TLCConfig {
+            region_shape: (cal_h, cal_w),
+            characteristic_length,
+            air_thermal_conductivity,
+            ..
+        } = self.config;
+        let mut nu2d = Array2::from_shape_fn((cal_h, cal_w), |(y, x)| {
+            self.h(y * cal_w + x) * characteristic_length / air_thermal_conductivity
+        });
+        nu2d.invert_axis(Axis(0));
+
+        nu2d
+    }
+
+    /// 峰值帧（非整数），即壁面温度达到峰值温度的时刻
+    fn peak_frame(&self, pos: usize) -> f32 {
+        let TLCConfig {
+            region_shape: (_, cal_w),
+            peak_temp,
+            solid_thermal_conductivity: k,
+            solid_thermal_diffusivity: a,
+            ..
+        } = self.config;
+        let beta = beta_at((peak_temp - T0) / self.delta_t(pos % cal_w));
+        let t = (beta * k / self.h(pos)).powi(2) / a;
+
+        HEATING_START as f32 + t * FRAME_RATE as f32
+    }
+
+    /// 各热电偶的温度，排列方式与数采文件一致
+    pub fn daq(&self) -> Array2<f32> {
+        let TLCConfig {
+            region_shape: (_, cal_w),
+            frame_num,
+            ..
+        } = self.config;
+        let (tc_h, tc_w) = TC_SHAPE;
+
+        Array2::from_shape_fn((frame_num, tc_h * tc_w), |(frame, i)| {
+            if frame < HEATING_START {
+                T0
+            } else {
+                T0 + self.delta_t(i % tc_w * (cal_w - 1) / (tc_w - 1))
+            }
+        })
+    }
+
+    /// 计算区域的Green值历史，排列方式与raw_g2d一致
+    pub fn raw_g2d(&self) -> Array2<u8> {
+        let TLCConfig {
+            region_shape: (cal_h, cal_w),
+            frame_num,
+            ..
+        } = self.config;
+        let peak_frames: Vec<_> = (0..cal_h * cal_w).map(|pos| self.peak_frame(pos)).collect();
+
+        Array2::from_shape_fn((frame_num, cal_h * cal_w), |(frame, pos)| {
+            let green = GREEN_BASE
+                + GREEN_PEAK * (-((frame as f32 - peak_frames[pos]) / PEAK_WIDTH).powi(2)).exp()
+                + noise(pos, frame);
+
+            green.max(0.).min(255.) as u8
+        })
+    }
+
+    /// 由`raw_g2d`的一帧合成整个画面的rgb数据，计算区域外为背景
+    pub fn rgb_frame(&self, green: ArrayView1<u8>) -> Vec<u8> {
+        let TLCConfig {
+            video_shape: (video_h, video_w),
+            region_shape: (cal_h, cal_w),
+            ..
+        } = self.config;
+        let mut rgb = vec![0; video_h * video_w * 3];
+        for (y, x) in (0..video_h).flat_map(|y| (0..video_w).map(move |x| (y, x))) {
+            let inside =
+                (MARGIN..MARGIN + cal_h).contains(&y) && (MARGIN..MARGIN + cal_w).contains(&x);
+            rgb[(y * video_w + x) * 3 + 1] = if inside {
+                green[(y - MARGIN) * cal_w + x - MARGIN]
+            } else {
+                GREEN_BASE as u8
+            };
+        }
+
+        rgb
+    }
+
+    /// 直接填入Green值历史与数采数据，不经过视频与数采文件
+    pub fn data(&self) -> TLCResult<TLCData> {
+        let mut data = TLCData::from_config(self.config.with_overrides(Map::new())?);
+        data.daq = Some(self.daq());
+        data.raw_g2d = Some(self.raw_g2d());
+
+        Ok(data)
+    }
+
+    /// 在`dir`下写入无损视频与数采文件，返回可直接求解的配置
+    ///
+    /// 视频为rawvideo编码，解码后与`raw_g2d`逐像素一致
+    pub fn write<P: AsRef<Path>>(&self, dir: P) -> TLCResult<TLCConfig> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir).map_err(|err| awsl!(CreateDirError, err, dir))?;
+        let video_path = dir.join("synthetic.nut");
+        let daq_path = dir.join("synthetic.lvm");
+        self.write_video(&video_path)?;
+        self.write_lvm(&daq_path)?;
+
+        let mut json = serde_json::to_value(&self.config).map_err(|err| awsl!(err))?;
+        json["video_path"] = json!(video_path.to_str().ok_or(awsl!(video_path))?);
+        json["daq_path"] = json!(daq_path.to_str().ok_or(awsl!(daq_path))?);
+        json["save_dir"] = json!(dir.to_str().ok_or(awsl!(dir))?);
+        // 帧数、视频尺寸由视频与数采文件重新读出
+        json["total_frames"] = json!(0);
+        json["frame_num"] = json!(0);
+        json["video_shape"] = json!([0, 0]);
+
+        TLCConfig::from_json(json)
+    }
+
+    fn write_video(&self, video_path: &Path) -> TLCResult<()> {
+        let (video_h, video_w) = self.config.video_shape;
+        let mut writer = VideoWriter::create(
+            video_path,
+            codec::Id::RAWVIDEO,
+            format::Pixel::RGB24,
+            (video_h, video_w),
+            FRAME_RATE,
+            None,
+        )?;
+        for green in self.raw_g2d().axis_iter(Axis(0)) {
+            let rgb = self.rgb_frame(green);
+            let frame = writer.frame_mut();
+            let stride = frame.stride(0);
+            for (dst, src) in frame
+                .data_mut(0)
+                .chunks_mut(stride)
+                .zip(rgb.chunks(video_w * 3))
+            {
+                dst[..video_w * 3].copy_from_slice(src);
+            }
+            writer.write_frame()?;
+        }
+
+        writer.finish()
+    }
+
+    /// 不带文件头的.lvm，每行一帧，依次为各热电偶的温度
+    fn write_lvm(&self, daq_path: &Path) -> TLCResult<()> {
+        let err = |err| awsl!(DataSaveError, err, daq_path);
+        let mut writer = BufWriter::new(File::create(daq_path).map_err(err)?);
+        for row in self.daq().axis_iter(Axis(0)) {
+            let line: Vec<_> = row.iter().map(|t| t.to_string()).collect();
+            writeln!(writer, "{}", line.join("\t")).map_err(err)?;
+        }
+
+        writer.flush().map_err(err)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// 求解结果与真值的平均、最大相对误差
+    fn relative_errors(case: &SyntheticCase, data: &mut TLCData) -> (f32, f32) {
+        data.solve().unwrap();
+        let errors: Vec<_> = data
+            .get_nu2d()
+            .unwrap()
+            .iter()
+            .zip(case.nu2d().iter())
+            .map(|(nu, truth)| (nu - truth).abs() / truth)
+            .collect();
+        let mean = errors.iter().sum::<f32>() / errors.len() as f32;
+        let max = errors.iter().fold(0_f32, |m, &e| m.max(e));
+        println!("mean relative error {}, max relative error {}", mean, max);
+
+        (mean, max)
+    }
+
+    #[test]
+    fn beta_inverse() {
+        let beta = beta_at(0.4);
+        assert!((1. - (beta * beta).exp() * erfcf(beta) - 0.4).abs() < 1e-5);
+    }
+
+    /// 滤波、峰值检测、插值、求解与真值比较
+    #[test]
+    fn accuracy() {
+        let case = SyntheticCase::new((24, 32), 800).unwrap();
+        let mut data = case.data().unwrap();
+        let (mean, max) = relative_errors(&case, &mut data);
+        assert!(mean < 0.005);
+        assert!(max < 0.02);
+
+        let stats = data.get_solve_stats().unwrap();
+        assert_eq!(stats.nan_count, 0);
+        assert!(stats.mean_iterations >= 1. && stats.mean_iterations <= 10.);
+    }
+
+    /// 从写出的视频与数采文件开始走完整流程
+    #[test]
+    fn end_to_end() {
+        let case = SyntheticCase::new((24, 32), 800).unwrap();
+        let dir = std::env::temp_dir().join("tlc_synthetic_test");
+        let mut data = TLCData::from_config(case.write(&dir).unwrap());
+        let (mean, max) = relative_errors(&case, &mut data);
+        let results_written = data.results_path().exists();
+        std::fs::remove_dir_all(&dir).ok();
+        assert!(mean < 0.005);
+        assert!(max < 0.02);
+        assert!(results_written);
+    }
+}
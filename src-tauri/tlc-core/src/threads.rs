@@ -0,0 +1,99 @@
+use std::sync::Arc;
+
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use serde::{Deserialize, Serialize};
+
+use super::{error::TLCResult, TLCData};
+use crate::awsl;
+
+/// 后台模式下工作线程的nice值
+#[cfg(unix)]
+const BACKGROUND_NICE: libc::c_int = 10;
+
+/// 读取视频、滤波与求解所用的线程池，不影响计算结果
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ThreadOptions {
+    /// 工作线程数，为0时与CPU逻辑核心数相同
+    pub num: usize,
+    /// 降低工作线程的调度优先级，长时间批量计算时界面与其他程序不卡顿
+    pub background: bool,
+}
+
+/// 降低当前线程的调度优先级，失败时只记录日志
+#[cfg(unix)]
+fn lower_priority() {
+    // Linux上nice值是线程属性，只影响当前线程
+    if unsafe { libc::setpriority(libc::PRIO_PROCESS, 0, BACKGROUND_NICE) } != 0 {
+        tracing::warn!("failed to lower worker thread priority");
+    }
+}
+
+#[cfg(windows)]
+fn lower_priority() {
+    use winapi::um::processthreadsapi::{GetCurrentThread, SetThreadPriority};
+    use winapi::um::winbase::THREAD_PRIORITY_BELOW_NORMAL;
+
+    if unsafe { SetThreadPriority(GetCurrentThread(), THREAD_PRIORITY_BELOW_NORMAL as i32) } == 0 {
+        tracing::warn!("failed to lower worker thread priority");
+    }
+}
+
+impl TLCData {
+    pub fn set_threads(&mut self, threads: ThreadOptions) -> &mut Self {
+        self.config.threads = threads;
+        self.thread_pool = None;
+        self.invalidate();
+
+        self
+    }
+
+    /// 默认设置下使用rayon的全局线程池，返回None
+    fn thread_pool(&mut self) -> TLCResult<Option<Arc<ThreadPool>>> {
+        let threads = self.config.threads;
+        if threads == ThreadOptions::default() {
+            return Ok(None);
+        }
+        if self.thread_pool.is_none() {
+            let mut builder = ThreadPoolBuilder::new()
+                .num_threads(threads.num)
+                .thread_name(|i| format!("tlc-worker-{}", i));
+            if threads.background {
+                builder = builder.start_handler(|_| lower_priority());
+            }
+            let pool = builder.build().map_err(|err| awsl!(err))?;
+            self.thread_pool = Some(Arc::new(pool));
+        }
+
+        Ok(self.thread_pool.clone())
+    }
+
+    /// 在配置的线程池中运行`op`，其中的并行迭代都使用该线程池的工作线程
+    pub(crate) fn in_pool<'a, R, F>(&'a mut self, op: F) -> TLCResult<R>
+    where
+        R: Send,
+        F: FnOnce(&'a mut Self) -> R + Send,
+    {
+        Ok(match self.thread_pool()? {
+            Some(pool) => pool.install(move || op(self)),
+            None => op(self),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::TLCConfig;
+
+    #[test]
+    fn configured_pool() {
+        let config = TLCConfig::from_json(serde_json::json!({})).unwrap();
+        let mut data = TLCData::from_config(config);
+        data.set_threads(ThreadOptions {
+            num: 2,
+            background: true,
+        });
+        let num = data.in_pool(|_| rayon::current_num_threads()).unwrap();
+        assert_eq!(num, 2);
+    }
+}
@@ -0,0 +1,93 @@
+use std::fs::{create_dir_all, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::time::Instant;
+
+use serde::Serialize;
+use tracing::span::EnteredSpan;
+
+use super::{error::TLCResult, notify::StateEvent, TLCData};
+use crate::awsl;
+
+/// 一个阶段最近一次运行的耗时
+#[derive(Debug, Clone, Serialize)]
+pub struct StageTiming {
+    /// decode、daq、filter、peak、interp、solve、plot、quick_look之一
+    pub stage: &'static str,
+    pub started_at: String,
+    pub secs: f32,
+}
+
+/// 计时的同时进入同名的tracing span，阶段内的日志都归属于该span
+pub(crate) struct StageTimer {
+    stage: &'static str,
+    started_at: String,
+    t0: Instant,
+    _span: EnteredSpan,
+}
+
+impl StageTimer {
+    pub(crate) fn start(stage: &'static str) -> Self {
+        Self {
+            stage,
+            started_at: chrono::Local::now()
+                .format("%Y-%m-%d %H:%M:%S%.3f")
+                .to_string(),
+            t0: Instant::now(),
+            _span: tracing::info_span!("stage", stage).entered(),
+        }
+    }
+}
+
+impl TLCData {
+    /// 阶段成功结束时调用，出错提前返回的阶段不计入
+    pub(crate) fn finish(&mut self, timer: StageTimer) {
+        let timing = StageTiming {
+            stage: timer.stage,
+            started_at: timer.started_at,
+            secs: timer.t0.elapsed().as_secs_f32(),
+        };
+        tracing::info!(secs = timing.secs, "stage finished");
+        self.notify(StateEvent::StageFinished {
+            stage: timing.stage,
+            secs: timing.secs,
+        });
+        if let Err(err) = self.append_timing_log(&timing) {
+            tracing::warn!(%err, "failed to write timing log");
+        }
+        self.timings.retain(|t| t.stage != timing.stage);
+        self.timings.push(timing);
+    }
+
+    /// 各阶段最近一次运行的耗时，按运行顺序排列
+    pub fn get_timings(&self) -> &[StageTiming] {
+        &self.timings
+    }
+
+    /// 每行一条json，追加到`save_dir/logs/timings.jsonl`，附带帧数与像素数便于比较不同工况
+    fn append_timing_log(&self, timing: &StageTiming) -> TLCResult<()> {
+        if self.config.save_dir.is_empty() {
+            return Ok(());
+        }
+        let log_dir = Path::new(&self.config.save_dir).join("logs");
+        create_dir_all(&log_dir).map_err(|err| awsl!(CreateDirError, err, log_dir))?;
+        let log_path = log_dir.join("timings.jsonl");
+        let (cal_h, cal_w) = self.config.region_shape;
+        let line = serde_json::json!({
+            "case_name": self.config.case_name,
+            "stage": timing.stage,
+            "started_at": timing.started_at,
+            "secs": timing.secs,
+            "frame_num": self.config.frame_num,
+            "pixels": cal_h * cal_w,
+        });
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&log_path)
+            .map_err(|err| awsl!(DataSaveError, err, log_path))?;
+        writeln!(file, "{}", line).map_err(|err| awsl!(DataSaveError, err, log_path))?;
+
+        Ok(())
+    }
+}
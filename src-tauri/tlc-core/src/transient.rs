@@ -0,0 +1,406 @@
+use std::path::Path;
+
+use ndarray::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{error::TLCResult, io, TLCData};
+use crate::awsl;
+
+/// 拟合每段h时的最大迭代步数
+const MAX_FIT_ITER: usize = 20;
+
+/// 差分网格的最大节点数
+const MAX_NODES: usize = 500;
+
+/// 时间分辨的h(t)重建参数
+///
+/// 脉动流中壁面温度反复升降，液晶会多次经过峰值温度，每次经过给出一个方程，
+/// 据此用顺序函数法逐段求出分段常数的h(t)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct TransientOptions {
+    /// 峰值相对两侧谷底的最小高度（Green值），更小的波动不视为一次经过峰值温度
+    pub min_prominence: u8,
+    /// 相邻两次峰值的最小间隔帧数
+    pub min_separation: usize,
+    /// 求解每段h时一并拟合的峰值数（顺序函数法的未来时间步），为1时逐段精确求解，越大越平滑
+    pub future_events: usize,
+}
+
+impl Default for TransientOptions {
+    fn default() -> Self {
+        Self {
+            min_prominence: 10,
+            min_separation: 10,
+            future_events: 1,
+        }
+    }
+}
+
+/// 重建h(t)的对象，坐标均为计算区域内的(y, x)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum TraceTarget {
+    Point((usize, usize)),
+    /// 左上角与尺寸，区域内的Green值与参考温度取平均后视为一个点
+    Region((usize, usize), (usize, usize)),
+}
+
+/// h(t)中的一段，h在段内为常数
+#[derive(Debug, Serialize)]
+pub struct HSegment {
+    /// 起止时间（s），从起始帧算起
+    pub start: f32,
+    pub end: f32,
+    pub h: f32,
+    pub nu: f32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HTrace {
+    pub target: TraceTarget,
+    /// 各次经过峰值温度的帧
+    pub events: Vec<usize>,
+    pub segments: Vec<HSegment>,
+}
+
+/// Green值历史中的各个峰值，峰值须为前后`min_separation`帧内的最大值，
+/// 且比同一范围内的最小值高出`min_prominence`
+pub(crate) fn find_events(green: &[u8], min_prominence: u8, min_separation: usize) -> Vec<usize> {
+    let mut events: Vec<usize> = Vec::new();
+    for i in 0..green.len() {
+        let window =
+            &green[i.saturating_sub(min_separation)..(i + min_separation + 1).min(green.len())];
+        let max = window.iter().copied().max().unwrap_or(0);
+        let min = window.iter().copied().min().unwrap_or(0);
+        // 平台上只取第一帧
+        let is_peak = green[i] == max && (i == 0 || green[i - 1] < green[i]);
+        if is_peak
+            && max - min >= min_prominence
+            && events
+                .last()
+                .map_or(true, |&last| i - last > min_separation)
+        {
+            events.push(i);
+        }
+    }
+
+    events
+}
+
+/// 一维半无限大固体的隐式差分模型，表面为对流边界，底面保持初始温度
+#[derive(Clone)]
+struct Slab {
+    temps: Vec<f64>,
+    t0: f64,
+    dx: f64,
+    dt: f64,
+    k: f64,
+    a: f64,
+}
+
+impl Slab {
+    /// `depth`应大于整个实验时长内的热穿透深度
+    fn new(t0: f32, depth: f32, dt: f32, k: f32, a: f32) -> Self {
+        let (depth, dt, k, a) = (depth as f64, dt as f64, k as f64, a as f64);
+        // 表面附近需要分辨一个时间步内的穿透深度
+        let dx = ((a * dt).sqrt() / 2.).max(depth / MAX_NODES as f64);
+        let n = ((depth / dx).ceil() as usize).max(3);
+
+        Self {
+            temps: vec![t0 as f64; n],
+            t0: t0 as f64,
+            dx,
+            dt,
+            k,
+            a,
+        }
+    }
+
+    fn surface(&self) -> f32 {
+        self.temps[0] as f32
+    }
+
+    /// 以对流换热系数`h`与参考温度`t_ref`推进一个时间步，追赶法求解三对角方程组
+    fn step(&mut self, h: f32, t_ref: f32) {
+        let Self { dx, dt, k, a, .. } = *self;
+        let (h, t_ref) = (h as f64, t_ref as f64);
+        let n = self.temps.len();
+        let r = a / (dx * dx);
+        // 第i行为lower[i]·T[i-1] + diag[i]·T[i] + upper[i]·T[i+1] = rhs[i]
+        let mut diag = vec![1. / dt + 2. * r; n];
+        let mut upper = vec![-r; n];
+        let lower = vec![-r; n];
+        let mut rhs: Vec<_> = self.temps.iter().map(|t| t / dt).collect();
+        // 表面半个控制体的能量平衡
+        diag[0] = dx / (2. * dt) + a * h / k + a / dx;
+        upper[0] = -a / dx;
+        rhs[0] = dx / (2. * dt) * self.temps[0] + a * h * t_ref / k;
+        // 底面温度不变
+        diag[n - 1] = 1.;
+        rhs[n - 1] = self.t0;
+
+        for i in 1..n - 1 {
+            let m = lower[i] / diag[i - 1];
+            diag[i] -= m * upper[i - 1];
+            rhs[i] -= m * rhs[i - 1];
+        }
+        self.temps[n - 1] = rhs[n - 1];
+        for i in (0..n - 1).rev() {
+            self.temps[i] = (rhs[i] - upper[i] * self.temps[i + 1]) / diag[i];
+        }
+    }
+
+    /// 从`from`帧推进到`to`帧
+    fn advance(&mut self, from: usize, to: usize, h: f32, t_refs: &[f32]) {
+        for &t_ref in &t_refs[from + 1..=to] {
+            self.step(h, t_ref);
+        }
+    }
+}
+
+/// 从`slab`所处的`start`帧起h保持为常数，各峰值处表面温度与峰值温度之差
+fn residuals(
+    slab: &Slab,
+    start: usize,
+    events: &[usize],
+    t_refs: &[f32],
+    peak_temp: f32,
+    h: f32,
+) -> Vec<f32> {
+    let mut slab = slab.clone();
+    let mut from = start;
+    events
+        .iter()
+        .map(|&event| {
+            slab.advance(from, event, h, t_refs);
+            from = event;
+            slab.surface() - peak_temp
+        })
+        .collect()
+}
+
+/// 高斯-牛顿法求使`residuals`平方和最小的h，导数用差分近似
+fn fit(
+    slab: &Slab,
+    start: usize,
+    events: &[usize],
+    t_refs: &[f32],
+    peak_temp: f32,
+    h0: f32,
+) -> f32 {
+    let mut h = h0;
+    for _ in 0..MAX_FIT_ITER {
+        let dh = 1e-3 * h.abs().max(1.);
+        let r = residuals(slab, start, events, t_refs, peak_temp, h);
+        let r_dh = residuals(slab, start, events, t_refs, peak_temp, h + dh);
+        let jac: Vec<_> = r
+            .iter()
+            .zip(&r_dh)
+            .map(|(r, r_dh)| (r_dh - r) / dh)
+            .collect();
+        let jtj: f32 = jac.iter().map(|j| j * j).sum();
+        if jtj == 0. || !jtj.is_finite() {
+            return f32::NAN;
+        }
+        let step = -jac.iter().zip(&r).map(|(j, r)| j * r).sum::<f32>() / jtj;
+        // h不能为负
+        let next_h = (h + step).max(0.);
+        if (next_h - h).abs() < 1e-3 {
+            return next_h;
+        }
+        h = next_h;
+    }
+
+    h
+}
+
+/// 顺序函数法：按时间顺序逐段拟合h，每段以前面各段的结果为已知条件，返回各段的h
+///
+/// `t0`与`peak_temp`为初始壁面温度与液晶峰值温度
+fn reconstruct(
+    t_refs: &[f32],
+    events: &[usize],
+    (t0, peak_temp): (f32, f32),
+    dt: f32,
+    (k, a): (f32, f32),
+    h0: f32,
+    future_events: usize,
+) -> Vec<f32> {
+    let depth = 4. * (a * dt * t_refs.len() as f32).sqrt();
+    let mut slab = Slab::new(t0, depth, dt, k, a);
+
+    let mut hs = Vec::with_capacity(events.len());
+    let (mut start, mut h) = (0, h0);
+    for (i, &event) in events.iter().enumerate() {
+        let window = &events[i..(i + future_events.max(1)).min(events.len())];
+        h = fit(&slab, start, window, t_refs, peak_temp, h);
+        if h.is_nan() {
+            // 之后的各段都以这一段为已知条件，无法继续
+            hs.resize(events.len(), f32::NAN);
+            break;
+        }
+        slab.advance(start, event, h, t_refs);
+        hs.push(h);
+        start = event;
+    }
+
+    hs
+}
+
+impl TLCData {
+    /// 重建单个点或区域的h(t)
+    pub fn reconstruct_h_trace(
+        &mut self,
+        target: TraceTarget,
+        options: TransientOptions,
+    ) -> TLCResult<HTrace> {
+        if self.filtered_g2d.is_none() {
+            self.filtering()?;
+        }
+        if self.interp.is_none() {
+            self.interp()?;
+        }
+        let (cal_h, cal_w) = self.config.region_shape;
+        let ((y0, x0), (h, w)) = match target {
+            TraceTarget::Point(pos) => (pos, (1, 1)),
+            TraceTarget::Region(top_left, shape) => (top_left, shape),
+        };
+        if h == 0 || w == 0 || y0 + h > cal_h || x0 + w > cal_w {
+            return Err(awsl!(
+                HandleError,
+                format!(
+                    "区域(左上角({}, {})，尺寸{}×{})超出计算区域{}×{}",
+                    y0, x0, h, w, cal_h, cal_w
+                )
+            ));
+        }
+        let positions: Vec<_> = (y0..y0 + h)
+            .flat_map(|y| (x0..x0 + w).map(move |x| y * cal_w + x))
+            .collect();
+
+        // 区域内的Green值与参考温度取平均
+        let filtered_g2d = self.get_filtered_g2d()?;
+        let mut green = Array1::<f32>::zeros(filtered_g2d.nrows());
+        let interp = self.get_interp()?;
+        let mut t_refs = Array1::<f32>::zeros(filtered_g2d.nrows());
+        for &pos in &positions {
+            green += &filtered_g2d.column(pos).mapv(|g| g as f32);
+            t_refs += &interp.interp_single_point(pos, self.config.region_shape);
+        }
+        let n = positions.len() as f32;
+        let green: Vec<_> = green.iter().map(|g| (g / n).round() as u8).collect();
+        let t_refs: Vec<_> = t_refs.iter().map(|t| t / n).collect();
+
+        let events: Vec<_> = find_events(&green, options.min_prominence, options.min_separation)
+            .into_iter()
+            .filter(|&event| event > self.config.t0_estimation.frames())
+            .collect();
+        let c = &self.config;
+        let t0 = c.t0_estimation.estimate(&t_refs);
+        let dt = 1. / c.frame_rate as f32;
+        let h0 = c.iteration_method.h0();
+        let properties = (c.solid_thermal_conductivity, c.solid_thermal_diffusivity);
+        let hs = reconstruct(
+            &t_refs,
+            &events,
+            (t0, c.peak_temp),
+            dt,
+            properties,
+            h0,
+            options.future_events,
+        );
+
+        let segments = hs
+            .iter()
+            .enumerate()
+            .map(|(i, &h)| HSegment {
+                start: if i == 0 {
+                    0.
+                } else {
+                    events[i - 1] as f32 * dt
+                },
+                end: events[i] as f32 * dt,
+                h,
+                nu: h * c.characteristic_length / c.air_thermal_conductivity,
+            })
+            .collect();
+
+        Ok(HTrace {
+            target,
+            events,
+            segments,
+        })
+    }
+
+    /// 重建多个点或区域的h(t)，保存为一张.csv，`trace`列为对象在`targets`中的序号
+    pub fn export_h_traces(
+        &mut self,
+        targets: Vec<TraceTarget>,
+        options: TransientOptions,
+    ) -> TLCResult<String> {
+        let mut columns = vec![Vec::new(); 5];
+        for (i, target) in targets.into_iter().enumerate() {
+            for s in self.reconstruct_h_trace(target, options)?.segments {
+                for (column, v) in columns
+                    .iter_mut()
+                    .zip(&[i as f32, s.start, s.end, s.h, s.nu])
+                {
+                    column.push(*v);
+                }
+            }
+        }
+
+        let trace_path = Path::new(&self.config.data_path)
+            .with_file_name(format!("{}_h_traces.csv", self.config.case_name));
+        let columns: Vec<_> = columns.iter().map(|c| c.as_slice()).collect();
+        io::save_table(
+            &trace_path,
+            &["trace", "start_s", "end_s", "h", "nu"],
+            &columns,
+        )?;
+
+        Ok(trace_path.to_str().ok_or(awsl!(trace_path))?.to_owned())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn events_in_green_history() {
+        let mut green = vec![0u8; 100];
+        for (i, g) in green.iter_mut().enumerate() {
+            let d = |c: usize| 50 - 5 * (i as i32 - c as i32).abs().min(10);
+            *g = d(30).max(d(70)) as u8;
+        }
+        assert_eq!(find_events(&green, 10, 10), vec![30, 70]);
+    }
+
+    #[test]
+    fn piecewise_h_roundtrip() {
+        let (dt, k, a) = (0.04, 0.19, 1.091e-7);
+        // 加热、冷却、再加热
+        let t_refs: Vec<f32> = (0..300)
+            .map(|i| match i {
+                0..=4 => 20.,
+                5..=100 => 40.,
+                101..=200 => 10.,
+                _ => 40.,
+            })
+            .collect();
+        let depth = 4. * (a * dt * t_refs.len() as f32).sqrt();
+        let mut slab = Slab::new(20., depth, dt, k, a);
+        slab.advance(0, 100, 150., &t_refs);
+        let peak_temp = slab.surface();
+        let mut second = 100;
+        while second < 200 || slab.surface() < peak_temp {
+            second += 1;
+            slab.step(300., t_refs[second]);
+        }
+
+        let temps = (20., peak_temp);
+        let hs = reconstruct(&t_refs, &[100, second], temps, dt, (k, a), 50., 1);
+        assert!((hs[0] - 150.).abs() < 1.);
+        assert!((hs[1] - 300.).abs() < 30.);
+    }
+}
@@ -0,0 +1,196 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use ndarray::prelude::*;
+use rayon::prelude::*;
+use serde::Serialize;
+
+use super::preprocess::{noise_level, FilterMethod, PeakSignal};
+use super::{error::TLCResult, TLCConfig, TLCData};
+use crate::awsl;
+
+/// 参与评估的像素数，计算区域较小时取全部像素
+const TUNING_SAMPLES: usize = 500;
+
+/// 固定的随机种子，同一数据多次评估的结果相同
+const TUNING_SEED: u64 = 0x2545_f491_4f6c_dd1d;
+
+/// 信噪比不低于最佳候选的该比例时才参与推荐，避免过度平滑削弱峰值
+const SNR_RETENTION: f32 = 0.8;
+
+/// 一种候选滤波方法在抽样像素上的表现
+#[derive(Debug, Serialize)]
+pub struct FilterScore {
+    pub filter_method: FilterMethod,
+    /// 峰值帧与右侧、下方相邻像素峰值帧均值之差的中位数（帧），越小峰值时刻越稳定
+    pub peak_jitter: f32,
+    /// 峰值升幅与原始信号噪声之比的中位数，过度平滑时下降
+    pub snr: f32,
+}
+
+/// `tune_filter`的结果
+#[derive(Debug, Serialize)]
+pub struct FilterTuning {
+    /// 抽样的像素数
+    pub samples: usize,
+    pub scores: Vec<FilterScore>,
+    /// 信噪比接近最佳的候选中峰值时刻最稳定的一个
+    pub recommended: FilterMethod,
+}
+
+/// splitmix64
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+/// 从`candidates`中不重复地随机取出至多`n`个
+fn sample(mut candidates: Vec<usize>, n: usize) -> Vec<usize> {
+    let n = n.min(candidates.len());
+    let mut rng = Rng(TUNING_SEED);
+    for i in 0..n {
+        let j = i + (rng.next() % (candidates.len() - i) as u64) as usize;
+        candidates.swap(i, j);
+    }
+    candidates.truncate(n);
+
+    candidates
+}
+
+fn median(mut values: Vec<f32>) -> f32 {
+    values.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    values.get(values.len() / 2).copied().unwrap_or(f32::NAN)
+}
+
+/// 信噪比不低于最佳候选`SNR_RETENTION`倍的候选中，峰值抖动最小的一个
+fn recommend(scores: &[FilterScore]) -> Option<&FilterScore> {
+    let best_snr = scores.iter().map(|s| s.snr).fold(f32::NAN, f32::max);
+    scores
+        .iter()
+        .filter(|s| s.snr >= SNR_RETENTION * best_snr)
+        .min_by(|a, b| {
+            a.peak_jitter
+                .partial_cmp(&b.peak_jitter)
+                .unwrap_or(Ordering::Equal)
+                .then(b.snr.partial_cmp(&a.snr).unwrap_or(Ordering::Equal))
+        })
+}
+
+impl TLCData {
+    /// 在随机抽取的像素上比较`candidates`，按峰值时刻的空间稳定性与信噪比推荐一种滤波方法，
+    /// 不修改配置
+    ///
+    /// 相邻像素的换热系数相近，峰值时刻也应相近，噪声使峰值帧随机跳动，过度平滑则削弱峰值
+    pub fn tune_filter(&mut self, candidates: &[FilterMethod]) -> TLCResult<FilterTuning> {
+        if candidates.is_empty() {
+            return Err(awsl!(HandleError, "候选滤波方法为空"));
+        }
+        let (start, end) = self.event_window()?;
+        let TLCConfig {
+            region_shape: (cal_h, cal_w),
+            frame_num,
+            frame_rate,
+            peak_method,
+            ..
+        } = self.config;
+
+        // 只抽取右侧与下方都有参与处理的相邻像素的点
+        let mask = self.config.mask_pixels()?;
+        let included = |pos: usize| mask.as_ref().map_or(true, |mask| mask[pos]);
+        let candidate_pixels: Vec<_> = (0..cal_h.saturating_sub(1) * cal_w)
+            .filter(|&pos| pos % cal_w + 1 < cal_w)
+            .filter(|&pos| included(pos) && included(pos + 1) && included(pos + cal_w))
+            .collect();
+        let samples = sample(candidate_pixels, TUNING_SAMPLES);
+        if samples.is_empty() {
+            return Err(awsl!(HandleError, "没有可用于评估的像素"));
+        }
+
+        let mut positions: Vec<_> = samples
+            .iter()
+            .flat_map(|&pos| [pos, pos + 1, pos + cal_w])
+            .collect();
+        positions.sort_unstable();
+        positions.dedup();
+        let index: HashMap<_, _> = positions.iter().enumerate().map(|(i, &p)| (p, i)).collect();
+        let unfiltered = self.unfiltered_points(&positions)?;
+        let noises: Vec<_> = unfiltered.iter().map(|g| noise_level(g.view())).collect();
+
+        let scores = candidates
+            .iter()
+            .map(|filter_method| {
+                // 各像素的（峰值帧，信噪比）
+                let peaks: Vec<_> = unfiltered
+                    .par_iter()
+                    .zip(&noises)
+                    .map(|(g, &noise)| {
+                        let mut g = g.clone();
+                        filter_method.apply(g.view_mut(), frame_num, frame_rate);
+                        let window = g.slice(s![start..end]);
+                        let peak = peak_method.locate(window).unwrap_or(0);
+                        (peak as f32, PeakSignal::of(window, peak).amplitude / noise)
+                    })
+                    .collect();
+                let peak = |pos: usize| peaks[index[&pos]].0;
+                let jitters = samples
+                    .iter()
+                    .map(|&pos| (peak(pos) - (peak(pos + 1) + peak(pos + cal_w)) / 2.).abs())
+                    .collect();
+                let snrs = samples.iter().map(|pos| peaks[index[pos]].1).collect();
+
+                FilterScore {
+                    filter_method: filter_method.clone(),
+                    peak_jitter: median(jitters),
+                    snr: median(snrs),
+                }
+            })
+            .collect::<Vec<_>>();
+        let recommended = recommend(&scores)
+            .unwrap_or(&scores[0])
+            .filter_method
+            .clone();
+
+        Ok(FilterTuning {
+            samples: samples.len(),
+            scores,
+            recommended,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sample_without_replacement() {
+        let mut picked = sample((0..100).collect(), 30);
+        assert_eq!(picked, sample((0..100).collect(), 30));
+        picked.sort_unstable();
+        picked.dedup();
+        assert_eq!(picked.len(), 30);
+        assert_eq!(sample((0..10).collect(), 30).len(), 10);
+    }
+
+    #[test]
+    fn recommend_stable_without_losing_peak() {
+        let score = |window, peak_jitter, snr| FilterScore {
+            filter_method: FilterMethod::Median(window),
+            peak_jitter,
+            snr,
+        };
+        // 窗口过大时峰值最稳定但信噪比下降过多
+        let scores = [score(1, 4., 20.), score(10, 1., 19.), score(80, 0.5, 8.)];
+        match recommend(&scores).unwrap().filter_method {
+            FilterMethod::Median(window) => assert_eq!(window, 10),
+            _ => unreachable!(),
+        }
+    }
+}
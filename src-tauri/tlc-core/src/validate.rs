@@ -0,0 +1,331 @@
+use serde::Serialize;
+
+use super::preprocess::{grid_shape, notch_coefs, FilterMethod, InterpMethod};
+use super::script::valid_name;
+use super::solve::{PropertyCurve, T0Estimation};
+use super::{TLCConfig, TLCData};
+
+/// 一条不合法的配置
+#[derive(Debug, Serialize)]
+pub struct Violation {
+    /// 出错的配置项
+    pub field: &'static str,
+    /// 错误原因及修改建议
+    pub message: String,
+}
+
+/// 逐条收集，而不是遇到第一条就返回
+struct Violations(Vec<Violation>);
+
+impl Violations {
+    fn push(&mut self, field: &'static str, message: String) {
+        self.0.push(Violation { field, message });
+    }
+
+    fn check(&mut self, ok: bool, field: &'static str, message: impl FnOnce() -> String) {
+        if !ok {
+            self.push(field, message());
+        }
+    }
+}
+
+impl TLCConfig {
+    /// 检查配置之间的一致性，返回所有不合法的配置项，为空时表示通过
+    ///
+    /// `daq_columns`为数采文件的列数，未读取数采文件时传入None，跳过热电偶列数的检查
+    pub fn validate(&self, daq_columns: Option<usize>) -> Vec<Violation> {
+        let mut v = Violations(Vec::new());
+        self.validate_region(&mut v);
+        self.validate_frames(&mut v);
+        self.validate_thermocouples(&mut v, daq_columns);
+        self.validate_properties(&mut v);
+
+        v.0
+    }
+
+    fn validate_region(&self, v: &mut Violations) {
+        let (tl_y, tl_x) = self.top_left_pos;
+        let (cal_h, cal_w) = self.region_shape;
+        let (video_h, video_w) = self.video_shape;
+        v.check(cal_h > 0 && cal_w > 0, "region_shape", || {
+            "计算区域尺寸为0，请重新框选计算区域".to_owned()
+        });
+        // 视频尺寸未知时无法检查
+        if self.video_shape != (0, 0) {
+            v.check(
+                tl_y + cal_h <= video_h && tl_x + cal_w <= video_w,
+                "region_shape",
+                || {
+                    format!(
+                        "计算区域(左上角({}, {})，尺寸{}×{})超出视频画面{}×{}，请重新框选",
+                        tl_y, tl_x, cal_h, cal_w, video_h, video_w
+                    )
+                },
+            );
+        }
+        if let Some(((ref_y, ref_x), (ref_h, ref_w))) = self.reference_patch {
+            v.check(ref_h > 0 && ref_w > 0, "reference_patch", || {
+                "参考区域尺寸为0，请重新框选参考区域".to_owned()
+            });
+            if self.video_shape != (0, 0) {
+                v.check(
+                    ref_y + ref_h <= video_h && ref_x + ref_w <= video_w,
+                    "reference_patch",
+                    || {
+                        format!(
+                            "参考区域(左上角({}, {})，尺寸{}×{})超出视频画面{}×{}，请重新框选",
+                            ref_y, ref_x, ref_h, ref_w, video_h, video_w
+                        )
+                    },
+                );
+            }
+        }
+    }
+
+    fn validate_frames(&self, v: &mut Violations) {
+        v.check(self.frame_rate > 0, "frame_rate", || {
+            "视频帧率为0，请检查视频文件".to_owned()
+        });
+        v.check(self.start_frame < self.total_frames, "start_frame", || {
+            format!(
+                "起始帧数{}超过视频总帧数{}",
+                self.start_frame, self.total_frames
+            )
+        });
+        v.check(self.start_row < self.total_rows, "start_row", || {
+            format!(
+                "起始行数{}超过数采文件总行数{}",
+                self.start_row, self.total_rows
+            )
+        });
+        v.check(self.frame_num > 0, "frame_num", || {
+            "处理帧数为0，请检查起始帧数、起始行数与数采频率".to_owned()
+        });
+        v.check(
+            self.start_frame + self.frame_num <= self.total_frames,
+            "frame_num",
+            || {
+                format!(
+                    "起始帧数{}加处理帧数{}超过视频总帧数{}",
+                    self.start_frame, self.frame_num, self.total_frames
+                )
+            },
+        );
+        if let FilterMethod::Median(window_size) = self.filter_method {
+            v.check(
+                window_size > 0 && window_size < self.frame_num,
+                "filter_method",
+                || format!("中值滤波窗口{}应大于0且小于处理帧数", window_size),
+            );
+        }
+        if let FilterMethod::Wavelet {
+            threshold_ratio, ..
+        } = self.filter_method
+        {
+            v.check(
+                threshold_ratio > 0. && threshold_ratio < 1.,
+                "filter_method",
+                || format!("小波滤波阈值{}应在0与1之间", threshold_ratio),
+            );
+        }
+        if let FilterMethod::Notch { freq, q } = self.filter_method {
+            v.check(freq > 0. && q > 0., "filter_method", || {
+                format!("陷波频率{}与品质因数{}应大于0", freq, q)
+            });
+            // 帧率未知时无法检查混叠
+            if freq > 0. && q > 0. && self.frame_rate > 0 {
+                v.check(
+                    notch_coefs(freq, q, self.frame_rate).is_some(),
+                    "filter_method",
+                    || {
+                        format!(
+                            "闪烁频率{}Hz接近帧率{}的整数倍，混叠到直流附近，无法陷波滤除",
+                            freq, self.frame_rate
+                        )
+                    },
+                );
+            }
+        }
+    }
+
+    fn validate_thermocouples(&self, v: &mut Violations, daq_columns: Option<usize>) {
+        let tcs = &self.thermocouples;
+        if let Some(daq_columns) = daq_columns {
+            for (i, tc) in tcs.iter().enumerate() {
+                v.check(tc.column_num < daq_columns, "thermocouples", || {
+                    format!(
+                        "{}的列数{}超出数采文件列数{}",
+                        tc.describe(i),
+                        tc.column_num,
+                        daq_columns
+                    )
+                });
+            }
+        }
+        v.check(self.regulator.len() == tcs.len(), "regulator", || {
+            format!(
+                "调节系数数量{}与热电偶数量{}不一致",
+                self.regulator.len(),
+                tcs.len()
+            )
+        });
+
+        use InterpMethod::*;
+        let enabled: Vec<_> = tcs.iter().filter(|tc| tc.enabled).collect();
+        // 一维插值时热电偶沿插值方向的坐标不能重复，否则插值时除以0
+        let distinct = |coord: fn(&(i32, i32)) -> i32| {
+            let mut coords: Vec<_> = enabled.iter().map(|tc| coord(&tc.pos)).collect();
+            coords.sort_unstable();
+            coords.windows(2).all(|w| w[0] != w[1])
+        };
+        match self.interp_method {
+            Horizontal | HorizontalExtra | HorizontalSpline => {
+                v.check(enabled.len() >= 2, "thermocouples", || {
+                    "一维插值至少需要两个可用热电偶".to_owned()
+                });
+                v.check(distinct(|pos| pos.1), "thermocouples", || {
+                    "水平插值时各热电偶的x坐标不能相同".to_owned()
+                });
+            }
+            Vertical | VerticalExtra | VerticalSpline => {
+                v.check(enabled.len() >= 2, "thermocouples", || {
+                    "一维插值至少需要两个可用热电偶".to_owned()
+                });
+                v.check(distinct(|pos| pos.0), "thermocouples", || {
+                    "竖直插值时各热电偶的y坐标不能相同".to_owned()
+                });
+            }
+            Bilinear((h, w)) | BilinearExtra((h, w)) => {
+                if h * w != tcs.len() {
+                    let message = format!("热电偶行数({})列数({})之积不等于热电偶数量", h, w);
+                    v.push("interp_method", message);
+                } else if h < 2 || w < 2 {
+                    let message = "双线性插值的热电偶行数与列数都不能少于2".to_owned();
+                    v.push("interp_method", message);
+                } else {
+                    let pos: Vec<_> = tcs.iter().map(|tc| tc.pos).collect();
+                    v.check(grid_shape(&pos) == Some((h, w)), "thermocouples", || {
+                        format!(
+                            "热电偶位置未构成{}行{}列的规则网格，请检查坐标，按位置推断的插值方法为{:?}",
+                            h,
+                            w,
+                            self.interp_method.infer(&pos)
+                        )
+                    });
+                }
+            }
+            Idw { power } => {
+                v.check(!enabled.is_empty(), "thermocouples", || {
+                    "没有可用热电偶".to_owned()
+                });
+                v.check(power > 0., "interp_method", || {
+                    format!("反距离加权的幂次{}应为正数", power)
+                });
+            }
+            ThinPlateSpline => {
+                v.check(enabled.len() >= 3, "thermocouples", || {
+                    "薄板样条至少需要三个可用热电偶".to_owned()
+                });
+            }
+        }
+    }
+
+    fn validate_properties(&self, v: &mut Violations) {
+        let properties = [
+            (
+                "solid_thermal_conductivity",
+                "固体导热系数",
+                self.solid_thermal_conductivity,
+            ),
+            (
+                "solid_thermal_diffusivity",
+                "固体热扩散系数",
+                self.solid_thermal_diffusivity,
+            ),
+            (
+                "characteristic_length",
+                "特征长度",
+                self.characteristic_length,
+            ),
+            (
+                "air_thermal_conductivity",
+                "空气导热系数",
+                self.air_thermal_conductivity,
+            ),
+        ];
+        for &(field, name, value) in properties.iter() {
+            v.check(value > 0., field, || {
+                format!("{}应为正数，当前为{}", name, value)
+            });
+        }
+        let curves = [
+            (
+                "solid_thermal_conductivity_curve",
+                "固体导热系数",
+                &self.solid_thermal_conductivity_curve,
+            ),
+            (
+                "solid_thermal_diffusivity_curve",
+                "固体热扩散系数",
+                &self.solid_thermal_diffusivity_curve,
+            ),
+        ];
+        for &(field, name, curve) in curves.iter() {
+            let curve = match curve {
+                Some(curve) => curve,
+                None => continue,
+            };
+            match curve {
+                PropertyCurve::Table(table) => {
+                    v.check(!table.is_empty(), field, || format!("{}温度表为空", name));
+                    v.check(table.windows(2).all(|w| w[0].0 < w[1].0), field, || {
+                        format!("{}温度表中的温度应严格递增", name)
+                    });
+                    v.check(table.iter().all(|&(_, value)| value > 0.), field, || {
+                        format!("{}温度表中的物性值应为正数", name)
+                    });
+                }
+                PropertyCurve::Polynomial(_) => {
+                    let value = curve.eval(self.peak_temp);
+                    v.check(value > 0., field, || {
+                        format!("{}在峰值温度下应为正数，当前为{}", name, value)
+                    });
+                }
+            }
+        }
+        match self.t0_estimation {
+            T0Estimation::Mean(n) | T0Estimation::Median(n) => {
+                v.check(n > 0 && n < self.frame_num.max(1), "t0_estimation", || {
+                    format!(
+                        "估计初始温度的帧数{}应为正数且小于总帧数{}",
+                        n, self.frame_num
+                    )
+                });
+            }
+            T0Estimation::Uniform(_) => {}
+        }
+        v.check(self.model_thickness >= 0., "model_thickness", || {
+            format!("模型厚度{}不能为负数", self.model_thickness)
+        });
+        v.check(self.pixel_size >= 0., "pixel_size", || {
+            format!("空间标定{}不能为负数", self.pixel_size)
+        });
+        for script in &self.post_scripts {
+            v.check(valid_name(&script.name), "post_scripts", || {
+                format!(
+                    "后处理脚本名称{}只能包含字母、数字、_与-，否则无法作为文件名",
+                    script.name
+                )
+            });
+        }
+    }
+}
+
+impl TLCData {
+    /// 检查当前配置，已读取数采文件时同时检查热电偶列数
+    pub fn validate(&self) -> Vec<Violation> {
+        let daq_columns = self.daq.as_ref().map(|daq| daq.ncols());
+
+        self.config.validate(daq_columns)
+    }
+}
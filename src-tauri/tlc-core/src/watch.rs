@@ -0,0 +1,146 @@
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::channel;
+use std::thread;
+use std::time::Duration;
+
+use notify::{watcher, DebouncedEvent, RecommendedWatcher, RecursiveMode, Watcher};
+
+use super::{error::TLCResult, Stage, TLCData};
+use crate::awsl;
+
+/// 数采软件导出时会分多次写入，等待文件1秒内不再变化后才通知
+const DEBOUNCE: Duration = Duration::from_secs(1);
+
+/// 监视视频与数采文件，析构时停止监视
+pub struct InputWatcher {
+    _watcher: RecommendedWatcher,
+}
+
+/// 规范化所在目录，与事件中的路径比较
+fn canonical(path: &Path) -> Option<PathBuf> {
+    let dir = path.parent()?.canonicalize().ok()?;
+    Some(dir.join(path.file_name()?))
+}
+
+impl InputWatcher {
+    /// 监视文件所在的目录而不是文件本身，覆盖写入（先删除再创建）的文件同样能被发现
+    ///
+    /// `on_change`在监视线程中调用，参数为`Stage::Video`或`Stage::Daq`
+    fn new(
+        inputs: &[(Stage, &str)],
+        on_change: impl Fn(Stage) + Send + 'static,
+    ) -> TLCResult<Self> {
+        let (tx, rx) = channel();
+        let mut w = watcher(tx, DEBOUNCE).map_err(|err| awsl!(err))?;
+
+        let mut targets = Vec::new();
+        for &(stage, path) in inputs {
+            let path = match canonical(Path::new(path)) {
+                Some(path) => path,
+                None => continue,
+            };
+            let dir = path.parent().ok_or(awsl!(path))?;
+            if targets
+                .iter()
+                .all(|(_, p): &(Stage, PathBuf)| p.parent() != Some(dir))
+            {
+                w.watch(dir, RecursiveMode::NonRecursive)
+                    .map_err(|err| awsl!(DataReadError, err, dir))?;
+            }
+            targets.push((stage, path));
+        }
+        if targets.is_empty() {
+            return Err(awsl!(HandleError, "视频与数采文件均不存在，无法监视"));
+        }
+
+        // 监视器析构后发送端关闭，线程随之退出
+        thread::spawn(move || {
+            for event in rx {
+                let path = match event {
+                    DebouncedEvent::Write(path)
+                    | DebouncedEvent::Create(path)
+                    | DebouncedEvent::Rename(_, path) => path,
+                    _ => continue,
+                };
+                let path = canonical(&path).unwrap_or(path);
+                for (stage, target) in &targets {
+                    if path == *target {
+                        tracing::info!(?stage, ?path, "input file changed");
+                        on_change(*stage);
+                    }
+                }
+            }
+        });
+
+        Ok(Self { _watcher: w })
+    }
+}
+
+impl Stage {
+    /// 该阶段及所有直接或间接依赖它的阶段
+    pub fn dependents(self) -> Vec<Stage> {
+        let mut stages = vec![self];
+        for &stage in Stage::ALL.iter() {
+            if stage.deps().iter().any(|dep| stages.contains(dep)) && !stages.contains(&stage) {
+                stages.push(stage);
+            }
+        }
+
+        stages
+    }
+}
+
+impl TLCData {
+    /// 开始监视当前配置的视频与数采文件，返回的监视器析构时停止
+    pub fn watch_inputs(
+        &self,
+        on_change: impl Fn(Stage) + Send + 'static,
+    ) -> TLCResult<InputWatcher> {
+        InputWatcher::new(
+            &[
+                (Stage::Video, &self.config.video_path),
+                (Stage::Daq, &self.config.daq_path),
+            ],
+            on_change,
+        )
+    }
+
+    /// 输入文件在外部被更新后调用：重新读取文件信息，清空该输入及所有依赖它的阶段，
+    /// 返回其中已经计算过、需要重新计算的阶段
+    ///
+    /// 路径没有变化，已缓存数据的配置哈希不会变，所以不能依赖`invalidate`
+    pub fn input_changed(&mut self, input: Stage) -> TLCResult<Vec<Stage>> {
+        match input {
+            Stage::Video => self.config.init_video_metadata()?,
+            Stage::Daq => self.config.init_daq_metadata()?,
+            _ => return Err(awsl!(HandleError, "只有视频与数采文件可以监视")),
+        }
+        .init_frame_num();
+
+        // 文件长度变化时帧数等配置随之变化，这部分阶段同样需要清空
+        let mut stages = input.dependents();
+        for stage in self.plan(&self.config) {
+            if !stages.contains(&stage) {
+                stages.push(stage);
+            }
+        }
+        stages.retain(|stage| self.stage_hashes.contains_key(stage));
+        self.drop_stages(&stages);
+        // 文件信息的变化不是用户的设置，不进入修改记录
+        self.history.resync(&self.config);
+
+        Ok(stages)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn daq_dependents() {
+        use Stage::*;
+        assert_eq!(Daq.dependents(), vec![Daq, T2d, Interp, Nu, SmoothedNu]);
+        assert_eq!(PeakFrames.dependents(), vec![PeakFrames, Nu, SmoothedNu]);
+    }
+}
@@ -6,7 +6,7 @@ mod cal {
 
     use plotters::prelude::*;
 
-    use tlc::cal::*;
+    use tlc_core::*;
 
     const CONFIG_PATH: &str = "./config/default_config.json";
 
@@ -0,0 +1,196 @@
+//! 计算核心的Python绑定
+//!
+//! ```python
+//! import tlc
+//!
+//! data = tlc.TLCData("config.json", {"peak_temp": 35.48})
+//! data.solve()
+//! nu2d = data.nu2d()  # 只读的numpy数组，与内部数据共享内存
+//! ```
+
+use std::path::PathBuf;
+
+use numpy::{PyArray2, PyArrayMethods};
+use pyo3::exceptions::PyRuntimeError;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyWeakrefMethods, PyWeakrefReference};
+use serde_json::{Map, Value as Json};
+
+use tlc_core::error::{self, Lang, TLCError};
+use tlc_core::{TLCConfig, TLCData};
+
+fn py_err(err: TLCError) -> PyErr {
+    PyRuntimeError::new_err(err.to_string())
+}
+
+/// Python对象与json之间经由json字符串转换
+fn to_json(obj: &Bound<'_, PyAny>) -> PyResult<Json> {
+    let json = obj.py().import("json")?;
+    let s: String = json.call_method1("dumps", (obj,))?.extract()?;
+    serde_json::from_str(&s).map_err(|err| PyRuntimeError::new_err(err.to_string()))
+}
+
+fn from_json<'py>(py: Python<'py>, value: &Json) -> PyResult<Bound<'py, PyAny>> {
+    let json = py.import("json")?;
+    json.call_method1("loads", (value.to_string(),))
+}
+
+#[pyclass(name = "TLCData", unsendable)]
+struct PyTLCData {
+    data: TLCData,
+    /// 已返回的numpy视图的弱引用，视图存活时不允许修改或释放内部数据
+    views: Vec<Py<PyWeakrefReference>>,
+}
+
+impl PyTLCData {
+    /// 各阶段的计算可能释放已缓存的数组，调用前确认没有存活的视图
+    fn check_views(&mut self, py: Python<'_>) -> PyResult<()> {
+        self.views.retain(|view| view.bind(py).upgrade().is_some());
+        if self.views.is_empty() {
+            Ok(())
+        } else {
+            Err(PyRuntimeError::new_err(
+                "仍有nu2d/t2d等数组视图未释放，请先del或改用.copy()",
+            ))
+        }
+    }
+
+    fn run(
+        &mut self,
+        py: Python<'_>,
+        f: impl FnOnce(&mut TLCData) -> Result<&mut TLCData, TLCError>,
+    ) -> PyResult<()> {
+        self.check_views(py)?;
+        f(&mut self.data).map_err(py_err)?;
+
+        Ok(())
+    }
+
+    fn view<'py>(
+        slf: Bound<'py, Self>,
+        get: impl FnOnce(&TLCData) -> Result<numpy::ndarray::ArrayView2<f32>, TLCError>,
+    ) -> PyResult<Bound<'py, PyArray2<f32>>> {
+        let array = {
+            let this = slf.borrow();
+            let view = get(&this.data).map_err(py_err)?;
+            // SAFETY: 视图存活期间`check_views`会拒绝所有可能修改或释放该数组的调用，
+            // 且视图以本对象为base，本对象不会先于视图析构
+            unsafe { PyArray2::borrow_from_array(&view, slf.clone().into_any()) }
+        };
+        array.readwrite().make_nonwriteable();
+        let weakref = PyWeakrefReference::new(&array)?;
+        slf.borrow_mut().views.push(weakref.unbind());
+
+        Ok(array)
+    }
+}
+
+#[pymethods]
+impl PyTLCData {
+    /// 读取配置文件，`overrides`覆盖其中部分配置项，键与配置文件一致
+    #[new]
+    #[pyo3(signature = (config_path, overrides = None))]
+    fn new(config_path: PathBuf, overrides: Option<&Bound<'_, PyDict>>) -> PyResult<Self> {
+        let overrides = match overrides {
+            Some(overrides) => match to_json(overrides.as_any())? {
+                Json::Object(map) => map,
+                _ => unreachable!(),
+            },
+            None => Map::new(),
+        };
+        let config = TLCConfig::from_path_with_overrides(config_path, overrides).map_err(py_err)?;
+
+        Ok(Self {
+            data: TLCData::from_config(config),
+            views: Vec::new(),
+        })
+    }
+
+    /// 当前配置，dict
+    #[getter]
+    fn config<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let config = serde_json::to_value(self.data.get_config())
+            .map_err(|err| PyRuntimeError::new_err(err.to_string()))?;
+        from_json(py, &config)
+    }
+
+    fn read_video(&mut self, py: Python<'_>) -> PyResult<()> {
+        self.run(py, TLCData::read_video)
+    }
+
+    fn read_daq(&mut self, py: Python<'_>) -> PyResult<()> {
+        self.run(py, TLCData::read_daq)
+    }
+
+    fn filtering(&mut self, py: Python<'_>) -> PyResult<()> {
+        self.run(py, TLCData::filtering)
+    }
+
+    fn detect_peak(&mut self, py: Python<'_>) -> PyResult<()> {
+        self.run(py, TLCData::detect_peak)
+    }
+
+    fn interp(&mut self, py: Python<'_>) -> PyResult<()> {
+        self.run(py, TLCData::interp)
+    }
+
+    /// 依次完成尚未进行的各阶段并求解
+    fn solve(&mut self, py: Python<'_>) -> PyResult<()> {
+        self.run(py, TLCData::solve)
+    }
+
+    fn save_nu(&mut self, py: Python<'_>) -> PyResult<()> {
+        self.run(py, TLCData::save_nu)
+    }
+
+    fn save_config(&self) -> PyResult<()> {
+        self.data.save_config().map_err(py_err)?;
+
+        Ok(())
+    }
+
+    fn nu_nan_mean(&self) -> PyResult<f32> {
+        self.data.get_nu_nan_mean().map_err(py_err)
+    }
+
+    /// 各像素峰值对应的帧数
+    fn peak_frames(&self) -> PyResult<Vec<usize>> {
+        Ok(self.data.get_peak_frames().map_err(py_err)?.clone())
+    }
+
+    /// 努塞尔数，(高, 宽)
+    fn nu2d(slf: Bound<'_, Self>) -> PyResult<Bound<'_, PyArray2<f32>>> {
+        Self::view(slf, TLCData::get_nu2d)
+    }
+
+    /// 各热电偶温度，(热电偶, 帧)
+    fn t2d(slf: Bound<'_, Self>) -> PyResult<Bound<'_, PyArray2<f32>>> {
+        Self::view(slf, TLCData::get_t2d)
+    }
+}
+
+/// 错误信息的语言，"zh"或"en"
+#[pyfunction]
+fn set_lang(lang: &str) -> PyResult<()> {
+    let lang = match lang {
+        "zh" => Lang::Zh,
+        "en" => Lang::En,
+        _ => {
+            return Err(PyRuntimeError::new_err(format!(
+                "unsupported language {}",
+                lang
+            )))
+        }
+    };
+    error::set_lang(lang);
+
+    Ok(())
+}
+
+#[pymodule]
+fn tlc(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyTLCData>()?;
+    m.add_function(wrap_pyfunction!(set_lang, m)?)?;
+
+    Ok(())
+}
@@ -0,0 +1,171 @@
+//! 只支持本服务所需的HTTP/1.1子集：每个连接一个请求，请求体由Content-Length给出
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+/// 请求体的上限，配置文件远小于此
+const MAX_BODY_LEN: usize = 16 << 20;
+
+/// 请求行与每个请求头的长度上限
+const MAX_LINE_LEN: usize = 8 << 10;
+
+/// 请求头数量的上限
+const MAX_HEADERS: usize = 100;
+
+/// 客户端迟迟不发完请求时断开，不长期占用线程
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+pub struct HttpRequest {
+    pub method: String,
+    pub path: String,
+    pub body: Vec<u8>,
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_owned())
+}
+
+pub fn read_request(stream: &TcpStream) -> io::Result<HttpRequest> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+
+    parse_request(BufReader::new(stream))
+}
+
+/// 读取一行，超过`MAX_LINE_LEN`时报错，而不是无限制地缓存
+fn read_line<R: BufRead>(reader: &mut R, line: &mut String) -> io::Result<usize> {
+    line.clear();
+    let len = reader.take(MAX_LINE_LEN as u64).read_line(line)?;
+    if len == MAX_LINE_LEN && !line.ends_with('\n') {
+        return Err(invalid("line too long"));
+    }
+
+    Ok(len)
+}
+
+fn parse_request<R: BufRead>(mut reader: R) -> io::Result<HttpRequest> {
+    let mut line = String::new();
+    read_line(&mut reader, &mut line)?;
+    let mut parts = line.split_whitespace();
+    let method = parts
+        .next()
+        .ok_or_else(|| invalid("empty request"))?
+        .to_owned();
+    let path = parts
+        .next()
+        .ok_or_else(|| invalid("missing path"))?
+        .to_owned();
+
+    let mut content_len = 0;
+    let mut headers = 0;
+    loop {
+        if read_line(&mut reader, &mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        headers += 1;
+        if headers > MAX_HEADERS {
+            return Err(invalid("too many headers"));
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_len = value
+                    .trim()
+                    .parse()
+                    .map_err(|_| invalid("bad content-length"))?;
+            }
+        }
+    }
+    if content_len > MAX_BODY_LEN {
+        return Err(invalid("request body too large"));
+    }
+    let mut body = vec![0; content_len];
+    reader.read_exact(&mut body)?;
+
+    Ok(HttpRequest { method, path, body })
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Internal Server Error",
+    }
+}
+
+pub fn respond(
+    mut stream: &TcpStream,
+    status: u16,
+    content_type: &str,
+    body: &[u8],
+) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        reason(status),
+        content_type,
+        body.len()
+    )?;
+    stream.write_all(body)?;
+    stream.flush()
+}
+
+/// 开始Server-Sent Events响应，之后逐条调用`send_event`
+pub fn start_events(mut stream: &TcpStream) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\n\r\n"
+    )?;
+    stream.flush()
+}
+
+pub fn send_event(mut stream: &TcpStream, data: &str) -> io::Result<()> {
+    write!(stream, "data: {}\n\n", data)?;
+    stream.flush()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse(raw: &[u8]) -> io::Result<HttpRequest> {
+        parse_request(raw)
+    }
+
+    #[test]
+    fn request_with_body() {
+        let req = parse(b"PUT /config HTTP/1.1\r\nHost: x\r\ncontent-length: 4\r\n\r\n{}\r\nextra")
+            .unwrap();
+        assert_eq!(req.method, "PUT");
+        assert_eq!(req.path, "/config");
+        assert_eq!(req.body, b"{}\r\n");
+
+        let req = parse(b"GET /events HTTP/1.1\r\n\r\n").unwrap();
+        assert_eq!((req.method.as_str(), req.path.as_str()), ("GET", "/events"));
+        assert!(req.body.is_empty());
+    }
+
+    #[test]
+    fn malformed_requests() {
+        assert!(parse(b"").is_err());
+        assert!(parse(b"GET\r\n\r\n").is_err());
+        assert!(parse(b"PUT /config HTTP/1.1\r\nContent-Length: x\r\n\r\n").is_err());
+        // 请求体不完整
+        assert!(parse(b"PUT /config HTTP/1.1\r\nContent-Length: 10\r\n\r\n{}").is_err());
+
+        let too_large = format!(
+            "PUT / HTTP/1.1\r\nContent-Length: {}\r\n\r\n",
+            MAX_BODY_LEN + 1
+        );
+        assert!(parse(too_large.as_bytes()).is_err());
+        let long_line = format!("GET /{} HTTP/1.1\r\n\r\n", "a".repeat(MAX_LINE_LEN));
+        assert!(parse(long_line.as_bytes()).is_err());
+        let many_headers = format!(
+            "GET / HTTP/1.1\r\n{}\r\n",
+            "X: y\r\n".repeat(MAX_HEADERS + 1)
+        );
+        assert!(parse(many_headers.as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,228 @@
+//! 通过HTTP远程处理，重计算在实验室工作站上进行，界面或脚本从其他机器连接
+//!
+//! ```text
+//! tlc-server --addr 0.0.0.0:8017
+//!
+//! GET  /config            当前配置
+//! PUT  /config            上传配置（json），路径为服务器上的路径
+//! POST /stages/<stage>    执行video、daq、filter、peak、interp或solve阶段
+//! POST /save              保存Nu矩阵
+//! GET  /results/nu        下载最近保存的Nu矩阵
+//! GET  /events            以Server-Sent Events推送状态变化，用于显示进度
+//! ```
+//!
+//! 同一时间只处理一个工况，各请求依次访问同一份`TLCData`，不做身份验证，只应在可信网络中开放。
+
+mod http;
+
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+
+use serde_json::{json, Value as Json};
+use structopt::StructOpt;
+
+use http::HttpRequest;
+use tlc_core::{error::TLCResult, notify::StateEvent, TLCConfig, TLCData};
+
+#[derive(Debug, StructOpt)]
+#[structopt(name = "tlc-server", about = "瞬态液晶实验数据处理的HTTP服务")]
+struct Args {
+    /// 监听地址，远程连接时改为0.0.0.0
+    #[structopt(long, default_value = "127.0.0.1:8017")]
+    addr: SocketAddr,
+}
+
+struct Server {
+    data: Mutex<TLCData>,
+    /// 各`TLCData`的状态变化都发送到这里，由`broadcast`转发给订阅者
+    events: Sender<StateEvent>,
+    subscribers: Mutex<Vec<Sender<StateEvent>>>,
+    /// 最近保存的Nu矩阵路径
+    saved: Mutex<Option<String>>,
+}
+
+/// 服务器的响应：状态码，Content-Type，响应体
+type Response = (u16, &'static str, Vec<u8>);
+
+fn json_response(status: u16, value: &Json) -> Response {
+    (status, "application/json", value.to_string().into_bytes())
+}
+
+fn result_response(res: TLCResult<Json>) -> Response {
+    match res {
+        Ok(value) => json_response(200, &value),
+        Err(err) => json_response(500, &json!(err.report())),
+    }
+}
+
+impl Server {
+    fn new() -> TLCResult<Arc<Self>> {
+        let (events, rx) = channel();
+        let mut data = TLCData::new()?;
+        data.set_listener(events.clone());
+        let server = Arc::new(Self {
+            data: Mutex::new(data),
+            events,
+            subscribers: Mutex::new(Vec::new()),
+            saved: Mutex::new(None),
+        });
+        let broadcaster = server.clone();
+        thread::spawn(move || broadcaster.broadcast(rx));
+
+        Ok(server)
+    }
+
+    /// 计算中途panic不影响之后的请求
+    fn data(&self) -> MutexGuard<'_, TLCData> {
+        self.data.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn broadcast(&self, rx: Receiver<StateEvent>) {
+        for event in rx {
+            let mut subscribers = self
+                .subscribers
+                .lock()
+                .unwrap_or_else(|err| err.into_inner());
+            subscribers.retain(|subscriber| subscriber.send(event.clone()).is_ok());
+        }
+    }
+
+    fn load_config(&self, body: &[u8]) -> Response {
+        let json = match serde_json::from_slice(body) {
+            Ok(json) => json,
+            Err(err) => return json_response(400, &json!({ "message": err.to_string() })),
+        };
+        result_response(TLCConfig::from_json(json).map(|config| {
+            let mut data = TLCData::from_config(config);
+            data.set_listener(self.events.clone());
+            let mut current = self.data();
+            *current = data;
+            json!(current.get_config())
+        }))
+    }
+
+    fn run_stage(&self, stage: &str) -> Response {
+        let mut data = self.data();
+        let res = match stage {
+            "video" => data.read_video().map(|_| Json::Null),
+            "daq" => data.read_daq().map(|_| Json::Null),
+            "filter" => data.filtering().map(|_| Json::Null),
+            "peak" => data.detect_peak().map(|_| Json::Null),
+            "interp" => data.interp().map(|_| Json::Null),
+            "solve" => data
+                .solve()
+                .and_then(|data| data.get_nu_nan_mean())
+                .map(|nu_nan_mean| json!({ "nu_nan_mean": nu_nan_mean })),
+            _ => return json_response(404, &json!({ "message": "unknown stage" })),
+        };
+
+        result_response(res)
+    }
+
+    /// 在响应之前记录保存路径，紧接着的下载请求不会读到旧的结果
+    fn save_nu(&self) -> Response {
+        let mut data = self.data();
+        let res = data.save_nu().map(|data| {
+            let data_path = data.get_config().data_path().to_owned();
+            *self.saved.lock().unwrap_or_else(|err| err.into_inner()) = Some(data_path);
+            Json::Null
+        });
+
+        result_response(res)
+    }
+
+    fn download_nu(&self) -> Response {
+        let saved = self
+            .saved
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .clone();
+        let data_path = match saved {
+            Some(data_path) => data_path,
+            None => return json_response(404, &json!({ "message": "Nu矩阵尚未保存" })),
+        };
+        match std::fs::read(&data_path) {
+            Ok(bytes) => (200, "application/octet-stream", bytes),
+            Err(err) => json_response(404, &json!({ "message": err.to_string() })),
+        }
+    }
+
+    fn route(&self, req: &HttpRequest) -> Response {
+        match (req.method.as_str(), req.path.as_str()) {
+            ("GET", "/config") => json_response(200, &json!(self.data().get_config())),
+            ("PUT", "/config") => self.load_config(&req.body),
+            ("POST", "/save") => self.save_nu(),
+            ("GET", "/results/nu") => self.download_nu(),
+            ("POST", path) if path.starts_with("/stages/") => {
+                self.run_stage(&path["/stages/".len()..])
+            }
+            _ => json_response(404, &json!({ "message": "not found" })),
+        }
+    }
+
+    /// 持续推送状态变化，直到客户端断开
+    fn stream_events(&self, stream: &TcpStream) {
+        let (tx, rx) = channel();
+        self.subscribers
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .push(tx);
+        if http::start_events(stream).is_err() {
+            return;
+        }
+        for event in rx {
+            let data = serde_json::to_string(&event).unwrap_or_default();
+            if http::send_event(stream, &data).is_err() {
+                break;
+            }
+        }
+    }
+
+    fn handle(&self, stream: TcpStream) {
+        let req = match http::read_request(&stream) {
+            Ok(req) => req,
+            Err(err) => {
+                let body = json!({ "message": err.to_string() });
+                let _ = http::respond(
+                    &stream,
+                    400,
+                    "application/json",
+                    body.to_string().as_bytes(),
+                );
+                return;
+            }
+        };
+        tracing::info!(method = %req.method, path = %req.path, "request");
+        if req.method == "GET" && req.path == "/events" {
+            return self.stream_events(&stream);
+        }
+        let (status, content_type, body) = self.route(&req);
+        if let Err(err) = http::respond(&stream, status, content_type, &body) {
+            tracing::warn!(%err, "failed to respond");
+        }
+    }
+}
+
+fn main() {
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .init();
+    let args = Args::from_args();
+    let serve = || -> Result<(), String> {
+        let server = Server::new().map_err(|err| err.to_string())?;
+        let listener = TcpListener::bind(args.addr).map_err(|err| err.to_string())?;
+        eprintln!("正在监听{}", args.addr);
+        for stream in listener.incoming().flatten() {
+            let server = server.clone();
+            thread::spawn(move || server.handle(stream));
+        }
+
+        Ok(())
+    };
+    if let Err(err) = serve() {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
+}
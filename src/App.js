@@ -20,6 +20,13 @@ function App() {
   const [errMsg, setErrMsg] = useState("");
   const [config, setConfig] = useState("");
 
+  useEffect(() => {
+    tauri.promisified({
+      cmd: "setLang",
+      body: { Lang: navigator.language.startsWith("zh") ? "Zh" : "En" },
+    });
+  }, []);
+
   useEffect(() => {
     if (appState > 0) {
       tauri.promisified({ cmd: "tryDropVideo" });
@@ -6,6 +6,45 @@ import {
   CloseButton,
 } from "@chakra-ui/react";
 
+const zh = navigator.language.startsWith("zh");
+
+// 根据后端返回的错误码给出进一步的操作提示
+const GUIDANCE = {
+  config_io: [
+    "请确认配置文件存在且未被其他程序占用",
+    "Make sure the config file exists and is not locked by another program",
+  ],
+  video_io: [
+    "请在基础配置中重新选择视频文件",
+    "Please select the video file again in the basic settings",
+  ],
+  daq_io: [
+    "请在基础配置中重新选择数采文件",
+    "Please select the DAQ file again in the basic settings",
+  ],
+  frame: [
+    "请检查起始帧与处理帧数",
+    "Please check the start frame and the number of frames",
+  ],
+  thermocouple: [
+    "请检查热电偶的列数设置",
+    "Please check the thermocouple columns",
+  ],
+  data_save: [
+    "请关闭占用结果文件的程序后重试",
+    "Close any program using the result files and retry",
+  ],
+};
+
+// 后端错误为{ code, message, path, frame, thermocouple }，前端自身的错误为字符串
+function describe(errMsg) {
+  if (typeof errMsg === "string") {
+    return errMsg;
+  }
+  const guidance = GUIDANCE[errMsg.code];
+  return guidance ? `${errMsg.message} (${guidance[zh ? 0 : 1]})` : errMsg.message;
+}
+
 function IAlert({ errMsg, onClose }) {
   return (
     <Alert
@@ -15,7 +54,7 @@ function IAlert({ errMsg, onClose }) {
       bg="#f38019"
     >
       <AlertIcon color="#cc241d" />
-      <AlertDescription color="#1d2021">{errMsg}</AlertDescription>
+      <AlertDescription color="#1d2021">{describe(errMsg)}</AlertDescription>
       <CloseButton position="absolute" right="8px" top="8px" onClick={onClose} />
     </Alert>
   )
@@ -9,7 +9,7 @@ function SelectFilter({ value, onSubmit, setErrMsg }) {
   const onSelectChange = v => {
     switch (v.target.value) {
       case "Median": setInnerValue({ Median: 20 }); break;
-      case "Wavelet": setInnerValue({ Wavelet: 0.5 }); break;
+      case "Wavelet": setInnerValue({ Wavelet: { threshold_ratio: 0.5, boundary: "Symmetric" } }); break;
       default: setInnerValue({ No: null });
     }
   }
@@ -50,20 +50,35 @@ function SelectFilter({ value, onSubmit, setErrMsg }) {
         <Box w="300px" marginRight="9px">
           <IInput
             leftTag="滤波阈值"
-            value={innerValue.Wavelet.toPrecision(2)}
+            value={innerValue.Wavelet.threshold_ratio.toPrecision(2)}
             onBlur={v => {
               const vv = parseFloat(v);
               if (isNaN(vv) || vv <= 0 || vv >= 1) {
                 setErrMsg(`不合法的滤波阈值：${v}`);
                 return;
               }
-              setInnerValue({ Wavelet: vv });
+              setInnerValue({ Wavelet: { ...innerValue.Wavelet, threshold_ratio: vv } });
             }}
             mutable
             rightTag="(0, 1)"
           />
         </Box>
       }
+      {!!innerValue && !!innerValue.Wavelet &&
+        <Select
+          w="110px"
+          value={innerValue.Wavelet.boundary}
+          bg="#689d6a"
+          color="#32302f"
+          border="unset"
+          fontWeight="bold"
+          onChange={v => setInnerValue({ Wavelet: { ...innerValue.Wavelet, boundary: v.target.value } })}
+          marginRight="9px"
+        >
+          <option value="Symmetric">镜像延拓</option>
+          <option value="Periodic">周期延拓</option>
+        </Select>
+      }
       <IButton
         text="滤波"
         hover="由于对全部数据点滤波耗时较长，此处仅对当前数据点进行滤波，完整滤波在求解时进行"
@@ -0,0 +1,27 @@
+import * as tauri from "tauri/api/tauri";
+
+// 在后台执行耗时的请求，后端立即返回任务编号，任务完成时通过"tlc-job"事件返回结果
+// 用法与tauri.promisified相同
+export function background(args) {
+  return tauri.promisified({ ...args, background: true }).then(
+    ({ id }) =>
+      new Promise((resolve, reject) => {
+        function onJob({ detail }) {
+          if (detail.id !== id) return;
+          window.removeEventListener("tlc-job", onJob);
+          detail.ok ? resolve(detail.result) : reject(detail.error);
+        }
+        window.addEventListener("tlc-job", onJob);
+      })
+  );
+}
+
+// 所有后台任务的编号、命令、状态（Queued/Running/Done/Failed/Cancelled）与耗时
+export function listJobs() {
+  return tauri.promisified({ cmd: "listJobs" });
+}
+
+// 只能取消排队中的任务，被取消任务的Promise以错误结束
+export function cancelJob(id) {
+  return tauri.promisified({ cmd: "cancelJob", body: { Uint: id } });
+}
@@ -30,7 +30,16 @@ function BasicSettings({ config, setConfig, setErrMsg }) {
         cmd: "loadConfig",
         body: { String: path },
       })
-        .then(ok => setConfig(ok))
+        .then(ok => {
+          setConfig(ok);
+          return tauri.promisified({ cmd: "verifyInputs" });
+        })
+        .then(mismatches => {
+          if (mismatches.length > 0) {
+            const paths = mismatches.map(m => m.path).join("、");
+            setErrMsg(`${paths}与上次求解时的文件不一致，已有结果可能来自不同的输入`);
+          }
+        })
         .catch(err => setErrMsg(err));
     });
   }
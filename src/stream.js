@@ -0,0 +1,27 @@
+import * as tauri from "tauri/api/tauri";
+
+// 实验过程中实时接收数采数据，source为{ Tcp: "host:port" }或{ Serial: "COM3" }
+export function startDaqStream(source) {
+  return tauri.promisified({ cmd: "startDaqStream", body: { DaqSource: source } });
+}
+
+export function stopDaqStream() {
+  return tauri.promisified({ cmd: "stopDaqStream" });
+}
+
+// 每隔interval毫秒取回新数据，handler收到{ total_rows, temps, skipped, closed }，
+// 连接断开或出错后停止，返回停止轮询的函数
+export function pollDaqStream(handler, onError, interval = 500) {
+  const timer = setInterval(() => {
+    tauri.promisified({ cmd: "pollDaqStream" })
+      .then(live => {
+        handler(live);
+        if (live.closed !== null) clearInterval(timer);
+      })
+      .catch(err => {
+        clearInterval(timer);
+        onError(err);
+      });
+  }, interval);
+  return () => clearInterval(timer);
+}
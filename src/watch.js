@@ -0,0 +1,17 @@
+import * as tauri from "tauri/api/tauri";
+
+// 监视视频与数采文件，更新后后端清空受影响的缓存，autoRerun时自动重新求解
+export function watchInputs(autoRerun) {
+  return tauri.promisified({ cmd: "watchInputs", body: { Bool: autoRerun } });
+}
+
+export function unwatchInputs() {
+  return tauri.promisified({ cmd: "unwatchInputs" });
+}
+
+// handler收到{ input, stages, error, rerun }，返回取消订阅的函数
+export function onInputsChanged(handler) {
+  const listener = e => handler(e.detail);
+  window.addEventListener("tlc-inputs-changed", listener);
+  return () => window.removeEventListener("tlc-inputs-changed", listener);
+}